@@ -0,0 +1,82 @@
+//! A cache for decoded assets that outlives the editor's window, so reopening the editor doesn't
+//! decode everything again.
+
+use parking_lot::Mutex;
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Decoded assets like images, fonts, and shader sources, indexed by a key of your choosing. This
+/// belongs to the [`BaseviewState`][crate::BaseviewState], so it survives closing and reopening
+/// the editor and rebuilding its window. Get it using
+/// [`BaseviewState::asset_cache()`][crate::BaseviewState::asset_cache()].
+///
+/// Only cache data that doesn't depend on the window. OpenGL objects like textures belong to the
+/// window's context, so cache the decoded pixels instead and upload them again when the window is
+/// opened.
+#[derive(Default)]
+pub struct AssetCache {
+    assets: Mutex<HashMap<String, Arc<dyn Any + Send + Sync>>>,
+}
+
+impl fmt::Debug for AssetCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AssetCache")
+            .field("keys", &self.assets.lock().keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl AssetCache {
+    /// Get the asset stored under `key`. Returns `None` if there is no asset with that key, or if
+    /// the asset is not a `T`.
+    pub fn get<T: Any + Send + Sync>(&self, key: &str) -> Option<Arc<T>> {
+        let asset = self.assets.lock().get(key)?.clone();
+        asset.downcast().ok()
+    }
+
+    /// Store `value` under `key`, replacing any existing asset with that key.
+    pub fn insert<T: Any + Send + Sync>(&self, key: impl Into<String>, value: T) -> Arc<T> {
+        let value = Arc::new(value);
+        self.assets.lock().insert(key.into(), value.clone());
+
+        value
+    }
+
+    /// Get the asset stored under `key`, or decode and store it using `decode` if it isn't cached
+    /// yet. The cache isn't locked while `decode` runs, so other assets can be used in the
+    /// meantime. If two threads decode the same asset at the same time, the first result is kept.
+    pub fn get_or_insert_with<T: Any + Send + Sync>(
+        &self,
+        key: &str,
+        decode: impl FnOnce() -> T,
+    ) -> Arc<T> {
+        if let Some(value) = self.get(key) {
+            return value;
+        }
+
+        let value = Arc::new(decode());
+        let mut assets = self.assets.lock();
+        let existing = assets
+            .get(key)
+            .and_then(|existing| existing.clone().downcast::<T>().ok());
+        match existing {
+            Some(existing) => existing,
+            None => {
+                assets.insert(key.to_owned(), value.clone());
+                value
+            }
+        }
+    }
+
+    /// Remove the asset stored under `key`.
+    pub fn remove(&self, key: &str) {
+        self.assets.lock().remove(key);
+    }
+
+    /// Remove all assets.
+    pub fn clear(&self) {
+        self.assets.lock().clear();
+    }
+}
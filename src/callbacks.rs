@@ -0,0 +1,25 @@
+//! Callbacks the plugin can register to find out when its editor opens and closes.
+
+use std::fmt;
+use std::sync::Arc;
+
+/// A callback registered through
+/// [`BaseviewState::set_on_editor_open()`][crate::BaseviewState::set_on_editor_open()] or
+/// [`BaseviewState::set_on_editor_close()`][crate::BaseviewState::set_on_editor_close()].
+pub(crate) type EditorCallback = Arc<dyn Fn() + Send + Sync>;
+
+/// The plugin's open and close callbacks, if any.
+#[derive(Default, Clone)]
+pub(crate) struct EditorCallbacks {
+    pub on_open: Option<EditorCallback>,
+    pub on_close: Option<EditorCallback>,
+}
+
+impl fmt::Debug for EditorCallbacks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EditorCallbacks")
+            .field("on_open", &self.on_open.is_some())
+            .field("on_close", &self.on_close.is_some())
+            .finish()
+    }
+}
@@ -0,0 +1,37 @@
+//! Mouse capture, so drags keep receiving mouse events after the cursor leaves the editor and moves
+//! over the host's UI.
+
+use baseview::Window;
+
+/// Capture or release the mouse for the window. While the mouse is captured, the window receives
+/// all mouse movement and button releases, even when the cursor is outside of the window.
+#[cfg(target_os = "windows")]
+pub(crate) fn set_mouse_capture(window: &Window, captured: bool) {
+    use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+    use winapi::shared::windef::HWND;
+    use winapi::um::winuser::{GetCapture, ReleaseCapture, SetCapture};
+
+    let hwnd = match window.raw_window_handle() {
+        RawWindowHandle::Win32(handle) => handle.hwnd as HWND,
+        _ => return,
+    };
+
+    unsafe {
+        if captured {
+            SetCapture(hwnd);
+        } else if GetCapture() == hwnd {
+            // Don't release a capture some other window holds
+            ReleaseCapture();
+        }
+    }
+}
+
+/// AppKit already keeps sending mouse dragged events to the view a drag started in, and a view
+/// cannot capture the mouse without a button being held.
+#[cfg(target_os = "macos")]
+pub(crate) fn set_mouse_capture(_window: &Window, _captured: bool) {}
+
+/// The X server already grabs the pointer for the window while a button is held. An explicit grab
+/// would need to be made on baseview's own display connection, which we don't have access to.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub(crate) fn set_mouse_capture(_window: &Window, _captured: bool) {}
@@ -0,0 +1,96 @@
+//! Double-click detection, so every window handler doesn't need to track click times itself.
+
+use baseview::{MouseButton, Point};
+use std::time::{Duration, Instant};
+
+/// Decides when two clicks count as a double-click. Set this using
+/// [`BaseviewState::set_double_click_threshold()`][crate::BaseviewState::set_double_click_threshold()].
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum DoubleClickThreshold {
+    /// Use the double-click time configured in the OS. This is the default.
+    #[default]
+    System,
+    /// Use a fixed threshold.
+    Custom {
+        /// The maximum time between two clicks.
+        interval: Duration,
+        /// The maximum distance in logical pixels the cursor may move between two clicks.
+        distance: f64,
+    },
+}
+
+impl DoubleClickThreshold {
+    /// The maximum time and distance between two clicks.
+    fn resolve(self) -> (Duration, f64) {
+        match self {
+            DoubleClickThreshold::System => system_double_click_threshold(),
+            DoubleClickThreshold::Custom { interval, distance } => (interval, distance),
+        }
+    }
+}
+
+/// The double-click time configured in the control panel, and the double-click rectangle's size.
+#[cfg(target_os = "windows")]
+fn system_double_click_threshold() -> (Duration, f64) {
+    use winapi::um::winuser::{GetDoubleClickTime, GetSystemMetrics, SM_CXDOUBLECLK};
+
+    unsafe {
+        let interval = Duration::from_millis(GetDoubleClickTime() as u64);
+        // The rectangle is centered on the first click
+        let distance = GetSystemMetrics(SM_CXDOUBLECLK) as f64 / 2.0;
+
+        (interval, distance)
+    }
+}
+
+/// The double-click speed configured in System Settings. macOS doesn't have a setting for the
+/// distance.
+#[cfg(target_os = "macos")]
+fn system_double_click_threshold() -> (Duration, f64) {
+    use objc::{class, msg_send, sel, sel_impl};
+
+    let interval: f64 = unsafe { msg_send![class!(NSEvent), doubleClickInterval] };
+    (Duration::from_secs_f64(interval), 4.0)
+}
+
+/// X11 doesn't have a system-wide setting for this, so these are GTK's defaults.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn system_double_click_threshold() -> (Duration, f64) {
+    (Duration::from_millis(400), 5.0)
+}
+
+/// Counts consecutive clicks of the same mouse button.
+#[derive(Debug, Default)]
+pub(crate) struct ClickTracker {
+    /// The last button that was pressed, when and where it was pressed, and how many clicks in a
+    /// row that was.
+    last_click: Option<(MouseButton, Instant, Point, u32)>,
+}
+
+impl ClickTracker {
+    /// Register a button press. Returns the number of consecutive clicks, so 1 for a single click
+    /// and 2 for a double-click.
+    pub fn press(
+        &mut self,
+        button: MouseButton,
+        position: Point,
+        threshold: DoubleClickThreshold,
+    ) -> u32 {
+        let (interval, distance) = threshold.resolve();
+        let now = Instant::now();
+        let click_count = match self.last_click {
+            Some((last_button, last_time, last_position, last_count))
+                if last_button == button
+                    && now.duration_since(last_time) <= interval
+                    && (position.x - last_position.x).abs() <= distance
+                    && (position.y - last_position.y).abs() <= distance =>
+            {
+                last_count + 1
+            }
+            _ => 1,
+        };
+
+        self.last_click = Some((button, now, position, click_count));
+        click_count
+    }
+}
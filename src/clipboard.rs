@@ -0,0 +1,384 @@
+//! Reading and writing text from and to the system clipboard. These functions should be called
+//! from the GUI thread, for instance from the window handler.
+
+/// Get the text currently stored on the clipboard, if there is any.
+#[cfg(target_os = "windows")]
+pub fn get_text() -> Option<String> {
+    use winapi::um::winbase::{GlobalLock, GlobalUnlock};
+    use winapi::um::winuser::{CloseClipboard, GetClipboardData, OpenClipboard, CF_UNICODETEXT};
+
+    unsafe {
+        if OpenClipboard(std::ptr::null_mut()) == 0 {
+            return None;
+        }
+
+        let text = (|| {
+            let handle = GetClipboardData(CF_UNICODETEXT);
+            if handle.is_null() {
+                return None;
+            }
+
+            let data = GlobalLock(handle) as *const u16;
+            if data.is_null() {
+                return None;
+            }
+
+            let len = (0..).take_while(|&i| *data.add(i) != 0).count();
+            let text = String::from_utf16_lossy(std::slice::from_raw_parts(data, len));
+            GlobalUnlock(handle);
+
+            Some(text)
+        })();
+        CloseClipboard();
+
+        text
+    }
+}
+
+/// Replace the clipboard's contents with `text`. Returns `false` if the clipboard could not be
+/// written to.
+#[cfg(target_os = "windows")]
+pub fn set_text(text: &str) -> bool {
+    use winapi::um::winbase::{GlobalAlloc, GlobalFree, GlobalLock, GlobalUnlock, GMEM_MOVEABLE};
+    use winapi::um::winuser::{
+        CloseClipboard, EmptyClipboard, OpenClipboard, SetClipboardData, CF_UNICODETEXT,
+    };
+
+    let wide_text: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+    unsafe {
+        if OpenClipboard(std::ptr::null_mut()) == 0 {
+            return false;
+        }
+
+        let success = (|| {
+            if EmptyClipboard() == 0 {
+                return false;
+            }
+
+            let handle = GlobalAlloc(GMEM_MOVEABLE, wide_text.len() * std::mem::size_of::<u16>());
+            if handle.is_null() {
+                return false;
+            }
+
+            let data = GlobalLock(handle) as *mut u16;
+            if data.is_null() {
+                GlobalFree(handle);
+                return false;
+            }
+            std::ptr::copy_nonoverlapping(wide_text.as_ptr(), data, wide_text.len());
+            GlobalUnlock(handle);
+
+            // The clipboard owns the memory if this succeeds
+            if SetClipboardData(CF_UNICODETEXT, handle).is_null() {
+                GlobalFree(handle);
+                return false;
+            }
+
+            true
+        })();
+        CloseClipboard();
+
+        success
+    }
+}
+
+/// Get the text currently stored on the clipboard, if there is any.
+#[cfg(target_os = "macos")]
+pub fn get_text() -> Option<String> {
+    use cocoa::appkit::{NSPasteboard, NSPasteboardTypeString};
+    use cocoa::base::nil;
+    use cocoa::foundation::NSString;
+    use std::ffi::CStr;
+
+    unsafe {
+        let pasteboard = NSPasteboard::generalPasteboard(nil);
+        let string = pasteboard.stringForType(NSPasteboardTypeString);
+        if string == nil {
+            return None;
+        }
+
+        let bytes = string.UTF8String();
+        if bytes.is_null() {
+            return None;
+        }
+
+        Some(CStr::from_ptr(bytes).to_string_lossy().into_owned())
+    }
+}
+
+/// Replace the clipboard's contents with `text`. Returns `false` if the clipboard could not be
+/// written to.
+#[cfg(target_os = "macos")]
+pub fn set_text(text: &str) -> bool {
+    use cocoa::appkit::{NSPasteboard, NSPasteboardTypeString};
+    use cocoa::base::{nil, NO};
+    use cocoa::foundation::NSString;
+    use objc::{msg_send, sel, sel_impl};
+
+    unsafe {
+        let pasteboard = NSPasteboard::generalPasteboard(nil);
+        pasteboard.clearContents();
+
+        let string = NSString::alloc(nil).init_str(text);
+        let success = pasteboard.setString_forType(string, NSPasteboardTypeString) != NO;
+        let () = msg_send![string, release];
+
+        success
+    }
+}
+
+/// Get the text currently stored on the clipboard, if there is any. This waits for up to half a
+/// second for the clipboard's owner to respond. Incremental transfers for very large selections
+/// are not supported.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub fn get_text() -> Option<String> {
+    use std::os::raw::{c_int, c_uchar, c_ulong};
+    use std::time::{Duration, Instant};
+    use x11::xlib;
+
+    const TIMEOUT: Duration = Duration::from_millis(500);
+
+    unsafe {
+        let connection = selection::Connection::open()?;
+        let display = connection.display;
+        if xlib::XGetSelectionOwner(display, connection.clipboard) == 0 {
+            return None;
+        }
+
+        xlib::XConvertSelection(
+            display,
+            connection.clipboard,
+            connection.utf8_string,
+            connection.property,
+            connection.window,
+            xlib::CurrentTime,
+        );
+        xlib::XFlush(display);
+
+        let start = Instant::now();
+        let mut event: xlib::XEvent = std::mem::zeroed();
+        loop {
+            if xlib::XCheckTypedWindowEvent(
+                display,
+                connection.window,
+                xlib::SelectionNotify,
+                &mut event,
+            ) != 0
+            {
+                break;
+            }
+            if start.elapsed() > TIMEOUT {
+                return None;
+            }
+
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        // The owner sets the property to `None` if it could not convert the selection
+        if event.selection.property == 0 {
+            return None;
+        }
+
+        let mut actual_type = 0;
+        let mut actual_format: c_int = 0;
+        let mut num_items: c_ulong = 0;
+        let mut bytes_after: c_ulong = 0;
+        let mut data: *mut c_uchar = std::ptr::null_mut();
+        let status = xlib::XGetWindowProperty(
+            display,
+            connection.window,
+            connection.property,
+            0,
+            c_int::MAX as _,
+            xlib::True,
+            xlib::AnyPropertyType as c_ulong,
+            &mut actual_type,
+            &mut actual_format,
+            &mut num_items,
+            &mut bytes_after,
+            &mut data,
+        );
+        if status != xlib::Success as c_int || data.is_null() {
+            return None;
+        }
+
+        let text = if actual_format == 8 {
+            let bytes = std::slice::from_raw_parts(data, num_items as usize);
+            Some(String::from_utf8_lossy(bytes).into_owned())
+        } else {
+            None
+        };
+        xlib::XFree(data as *mut _);
+
+        text
+    }
+}
+
+/// Replace the clipboard's contents with `text`. Returns `false` if the clipboard could not be
+/// written to.
+///
+/// On X11 the clipboard's contents are owned by a window, so this spawns a small thread with its
+/// own hidden window that hands out the text until another application takes over the clipboard.
+/// This avoids interfering with the host's or the editor's own windows.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub fn set_text(text: &str) -> bool {
+    use std::sync::mpsc;
+
+    let text = text.to_owned();
+    let (owned_sender, owned_receiver) = mpsc::channel();
+    let spawn_result = std::thread::Builder::new()
+        .name(String::from("clipboard-owner"))
+        .spawn(move || unsafe { selection::serve_selection(text, owned_sender) });
+    if spawn_result.is_err() {
+        return false;
+    }
+
+    owned_receiver.recv().unwrap_or(false)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod selection {
+    use std::ffi::CString;
+    use std::os::raw::{c_int, c_uchar, c_ulong};
+    use std::sync::mpsc::Sender;
+    use x11::xlib;
+
+    /// A display connection with a hidden window for exchanging selections.
+    pub(super) struct Connection {
+        pub display: *mut xlib::Display,
+        pub window: xlib::Window,
+        pub clipboard: xlib::Atom,
+        pub utf8_string: xlib::Atom,
+        pub targets: xlib::Atom,
+        /// The property on our window the clipboard's contents are written to.
+        pub property: xlib::Atom,
+    }
+
+    impl Connection {
+        pub unsafe fn open() -> Option<Self> {
+            let display = crate::x11_isolation::open_display();
+            if display.is_null() {
+                return None;
+            }
+
+            let root = xlib::XDefaultRootWindow(display);
+            let window = xlib::XCreateSimpleWindow(display, root, 0, 0, 1, 1, 0, 0, 0);
+            let intern_atom = |name: &str| {
+                let name = CString::new(name).unwrap();
+                xlib::XInternAtom(display, name.as_ptr(), xlib::False)
+            };
+
+            Some(Self {
+                display,
+                window,
+                clipboard: intern_atom("CLIPBOARD"),
+                utf8_string: intern_atom("UTF8_STRING"),
+                targets: intern_atom("TARGETS"),
+                property: intern_atom("NIH_PLUG_BASEVIEW_CLIPBOARD"),
+            })
+        }
+    }
+
+    impl Drop for Connection {
+        fn drop(&mut self) {
+            unsafe {
+                xlib::XDestroyWindow(self.display, self.window);
+                crate::x11_isolation::close_display(self.display);
+            }
+        }
+    }
+
+    /// Take ownership of the clipboard and hand out `text` until another client takes it over.
+    /// Whether the ownership could be acquired is sent through `owned_sender`.
+    pub(super) unsafe fn serve_selection(text: String, owned_sender: Sender<bool>) {
+        let connection = match Connection::open() {
+            Some(connection) => connection,
+            None => {
+                let _ = owned_sender.send(false);
+                return;
+            }
+        };
+        let display = connection.display;
+
+        xlib::XSetSelectionOwner(
+            display,
+            connection.clipboard,
+            connection.window,
+            xlib::CurrentTime,
+        );
+        let owned = xlib::XGetSelectionOwner(display, connection.clipboard) == connection.window;
+        let _ = owned_sender.send(owned);
+        if !owned {
+            return;
+        }
+
+        let mut event: xlib::XEvent = std::mem::zeroed();
+        loop {
+            xlib::XNextEvent(display, &mut event);
+            match event.get_type() {
+                xlib::SelectionClear => break,
+                xlib::SelectionRequest => {
+                    let request = event.selection_request;
+                    let string_atom = xlib::XA_STRING;
+                    let property = if request.property == 0 {
+                        request.target
+                    } else {
+                        request.property
+                    };
+
+                    let converted = if request.target == connection.targets {
+                        let targets = [
+                            connection.targets as c_ulong,
+                            connection.utf8_string as c_ulong,
+                            string_atom as c_ulong,
+                        ];
+                        xlib::XChangeProperty(
+                            display,
+                            request.requestor,
+                            property,
+                            xlib::XA_ATOM,
+                            32,
+                            xlib::PropModeReplace,
+                            targets.as_ptr() as *const c_uchar,
+                            targets.len() as c_int,
+                        );
+                        true
+                    } else if request.target == connection.utf8_string
+                        || request.target == string_atom
+                    {
+                        xlib::XChangeProperty(
+                            display,
+                            request.requestor,
+                            property,
+                            request.target,
+                            8,
+                            xlib::PropModeReplace,
+                            text.as_ptr(),
+                            text.len() as c_int,
+                        );
+                        true
+                    } else {
+                        false
+                    };
+
+                    let mut notify = xlib::XEvent {
+                        selection: xlib::XSelectionEvent {
+                            type_: xlib::SelectionNotify,
+                            serial: 0,
+                            send_event: xlib::True,
+                            display,
+                            requestor: request.requestor,
+                            selection: request.selection,
+                            target: request.target,
+                            property: if converted { property } else { 0 },
+                            time: request.time,
+                        },
+                    };
+                    xlib::XSendEvent(display, request.requestor, xlib::False, 0, &mut notify);
+                    xlib::XFlush(display);
+                }
+                _ => (),
+            }
+        }
+    }
+}
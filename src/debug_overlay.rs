@@ -0,0 +1,238 @@
+//! A minimal diagnostics overlay for the built-in OpenGL editors, enabled by setting the
+//! `NIH_PLUG_BASEVIEW_DEBUG_OVERLAY` environment variable to anything other than `0`. The text is
+//! drawn using scissored clears with a tiny built-in bitmap font, so it doesn't need shaders or
+//! buffers and it can't interfere with the renderer's own GL state beyond what it restores.
+
+use baseview::gl::GlContext;
+use std::cell::RefCell;
+use std::ffi::c_void;
+
+use crate::Diagnostics;
+
+const GL_COLOR_BUFFER_BIT: u32 = 0x0000_4000;
+const GL_SCISSOR_TEST: u32 = 0x0C11;
+const GL_SCISSOR_BOX: u32 = 0x0C10;
+const GL_VIEWPORT: u32 = 0x0BA2;
+const GL_COLOR_CLEAR_VALUE: u32 = 0x0C22;
+
+/// The width of a glyph in font pixels, including the spacing after it.
+const GLYPH_ADVANCE: i32 = 6;
+/// The height of a line in font pixels, including the spacing below it.
+const LINE_HEIGHT: i32 = 9;
+/// The space around the text in font pixels.
+const PADDING: i32 = 2;
+
+type Enable = unsafe extern "system" fn(u32);
+type IsEnabled = unsafe extern "system" fn(u32) -> u8;
+type Scissor = unsafe extern "system" fn(i32, i32, i32, i32);
+type ClearColor = unsafe extern "system" fn(f32, f32, f32, f32);
+type Clear = unsafe extern "system" fn(u32);
+type GetIntegerv = unsafe extern "system" fn(u32, *mut i32);
+type GetFloatv = unsafe extern "system" fn(u32, *mut f32);
+
+thread_local! {
+    /// The text the next buffer swap on this thread should draw, set by the window handler
+    /// while the overlay is enabled.
+    static QUEUED_TEXT: RefCell<Option<String>> = RefCell::new(None);
+}
+
+/// Whether the overlay was enabled through the environment variable. This is only checked once.
+pub(crate) fn enabled() -> bool {
+    lazy_static::lazy_static! {
+        static ref ENABLED: bool = std::env::var_os("NIH_PLUG_BASEVIEW_DEBUG_OVERLAY")
+            .map_or(false, |value| !value.is_empty() && value != "0");
+    }
+
+    *ENABLED
+}
+
+/// Set or clear the text the built-in editors draw before swapping their buffers.
+pub(crate) fn queue(text: Option<String>) {
+    QUEUED_TEXT.with(|queued_text| *queued_text.borrow_mut() = text);
+}
+
+/// Draw the queued text, if any. The context needs to be current.
+pub(crate) fn draw_queued(context: &GlContext) {
+    QUEUED_TEXT.with(|queued_text| {
+        if let Some(text) = queued_text.borrow_mut().take() {
+            draw_text(context, &text, 2);
+        }
+    });
+}
+
+/// Draw `diagnostics` in the top left corner of the current viewport, for window handlers that
+/// render with OpenGL themselves. The context needs to be current. Call this after drawing the
+/// GUI and before swapping the buffers.
+pub fn draw_diagnostics_overlay(context: &GlContext, diagnostics: &Diagnostics) {
+    let pixel_size = (diagnostics.scale.round() as i32).max(1) * 2;
+    draw_text(context, &diagnostics.to_string(), pixel_size);
+}
+
+/// Draw light text on a dark background in the top left corner of the current viewport. Every
+/// font pixel is `pixel_size` physical pixels wide.
+fn draw_text(context: &GlContext, text: &str, pixel_size: i32) {
+    let load = |name| {
+        let ptr = context.get_proc_address(name);
+        (!ptr.is_null()).then_some(ptr)
+    };
+    let (
+        Some(enable),
+        Some(disable),
+        Some(is_enabled),
+        Some(scissor),
+        Some(clear_color),
+        Some(clear),
+        Some(get_integerv),
+        Some(get_floatv),
+    ) = (
+        load("glEnable"),
+        load("glDisable"),
+        load("glIsEnabled"),
+        load("glScissor"),
+        load("glClearColor"),
+        load("glClear"),
+        load("glGetIntegerv"),
+        load("glGetFloatv"),
+    )
+    else {
+        return;
+    };
+
+    unsafe {
+        let enable = std::mem::transmute::<*const c_void, Enable>(enable);
+        let disable = std::mem::transmute::<*const c_void, Enable>(disable);
+        let is_enabled = std::mem::transmute::<*const c_void, IsEnabled>(is_enabled);
+        let scissor = std::mem::transmute::<*const c_void, Scissor>(scissor);
+        let clear_color = std::mem::transmute::<*const c_void, ClearColor>(clear_color);
+        let clear = std::mem::transmute::<*const c_void, Clear>(clear);
+        let get_integerv = std::mem::transmute::<*const c_void, GetIntegerv>(get_integerv);
+        let get_floatv = std::mem::transmute::<*const c_void, GetFloatv>(get_floatv);
+
+        let scissor_enabled = is_enabled(GL_SCISSOR_TEST) != 0;
+        let mut old_scissor_box = [0i32; 4];
+        get_integerv(GL_SCISSOR_BOX, old_scissor_box.as_mut_ptr());
+        let mut old_clear_color = [0.0f32; 4];
+        get_floatv(GL_COLOR_CLEAR_VALUE, old_clear_color.as_mut_ptr());
+        let mut viewport = [0i32; 4];
+        get_integerv(GL_VIEWPORT, viewport.as_mut_ptr());
+
+        let fill = |x: i32, y: i32, width: i32, height: i32| {
+            scissor(x, y, width, height);
+            clear(GL_COLOR_BUFFER_BIT);
+        };
+
+        // Scissor rectangles start at the bottom left, the text starts at the top left
+        let left = viewport[0];
+        let top = viewport[1] + viewport[3];
+        let columns = text.lines().map(|line| line.chars().count()).max();
+        let rows = text.lines().count() as i32;
+        let width = (columns.unwrap_or(0) as i32 * GLYPH_ADVANCE + PADDING * 2) * pixel_size;
+        let height = (rows * LINE_HEIGHT + PADDING * 2) * pixel_size;
+
+        enable(GL_SCISSOR_TEST);
+        clear_color(0.0, 0.0, 0.0, 1.0);
+        fill(left, top - height, width, height);
+
+        clear_color(0.6, 1.0, 0.6, 1.0);
+        for (row, line) in text.lines().enumerate() {
+            for (column, c) in line.chars().enumerate() {
+                let glyph_left = left + (PADDING + column as i32 * GLYPH_ADVANCE) * pixel_size;
+                let glyph_top = top - (PADDING + row as i32 * LINE_HEIGHT) * pixel_size;
+                for (glyph_row, bits) in glyph(c).into_iter().enumerate() {
+                    let y = glyph_top - (glyph_row as i32 + 1) * pixel_size;
+
+                    // Runs of set pixels are filled at once, the leftmost pixel is bit 4
+                    let mut run_start = None;
+                    for bit in 0..=5 {
+                        let set = bit < 5 && bits & (0x10 >> bit) != 0;
+                        match (set, run_start) {
+                            (true, None) => run_start = Some(bit),
+                            (false, Some(start)) => {
+                                fill(
+                                    glyph_left + start * pixel_size,
+                                    y,
+                                    (bit - start) * pixel_size,
+                                    pixel_size,
+                                );
+                                run_start = None;
+                            }
+                            _ => (),
+                        }
+                    }
+                }
+            }
+        }
+
+        clear_color(
+            old_clear_color[0],
+            old_clear_color[1],
+            old_clear_color[2],
+            old_clear_color[3],
+        );
+        scissor(
+            old_scissor_box[0],
+            old_scissor_box[1],
+            old_scissor_box[2],
+            old_scissor_box[3],
+        );
+        if !scissor_enabled {
+            disable(GL_SCISSOR_TEST);
+        }
+    }
+}
+
+/// The rows of a character in a 5x7 pixel font, from top to bottom. Lowercase letters are drawn
+/// as uppercase letters, and unknown characters are drawn as question marks.
+fn glyph(c: char) -> [u8; 7] {
+    match c.to_ascii_uppercase() {
+        ' ' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        'A' => [0x0E, 0x11, 0x11, 0x11, 0x1F, 0x11, 0x11],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'D' => [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        'H' => [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'I' => [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'P' => [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+        'Q' => [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+        'R' => [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'T' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        'X' => [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04],
+        'Z' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        ',' => [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08],
+        ':' => [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        '+' => [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00],
+        '=' => [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00],
+        '_' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F],
+        '/' => [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00],
+        '(' => [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02],
+        ')' => [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08],
+        '%' => [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03],
+        _ => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04],
+    }
+}
@@ -0,0 +1,187 @@
+//! A snapshot of the editor's vital signs, updated every frame, so handlers can show a debug
+//! readout in whatever renderer they use. Get it using
+//! [`BaseviewState::diagnostics()`][crate::BaseviewState::diagnostics()]. The built-in editors
+//! also draw it on top of the GUI when the `NIH_PLUG_BASEVIEW_DEBUG_OVERLAY` environment variable
+//! is set.
+
+use baseview::gl::GlContext;
+use std::ffi::{c_void, CStr};
+use std::fmt;
+use std::os::raw::c_char;
+use std::time::{Duration, Instant};
+
+use crate::FrameStats;
+
+const GL_VENDOR: u32 = 0x1F00;
+const GL_RENDERER: u32 = 0x1F01;
+const GL_VERSION: u32 = 0x1F02;
+
+type GetString = unsafe extern "system" fn(u32) -> *const u8;
+
+/// Diagnostics for the editor's window. The [`Display`][fmt::Display] implementation formats
+/// these as a few short lines of text.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Diagnostics {
+    /// The number of frames drawn during the last second.
+    pub fps: f32,
+    /// Statistics about the time spent drawing recent frames.
+    pub frame_stats: FrameStats,
+    /// The number of events baseview sent to the window during the last second.
+    pub events_per_second: u32,
+    /// The number of events baseview sent to the window since it was opened.
+    pub total_events: u64,
+    /// The window's logical size.
+    pub size: (f64, f64),
+    /// The factor the logical size is multiplied by to get the window's physical size. This
+    /// includes the user's zoom factor.
+    pub scale: f64,
+    /// Information about the window's OpenGL context, if it has one.
+    pub gl_info: Option<GlInfo>,
+}
+
+/// Information about an OpenGL context, as reported by the driver.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GlInfo {
+    /// The `GL_VERSION` string.
+    pub version: String,
+    /// The `GL_RENDERER` string, usually the GPU's name.
+    pub renderer: String,
+    /// The `GL_VENDOR` string.
+    pub vendor: String,
+}
+
+impl GlInfo {
+    /// Query the context's version and renderer. This leaves no context current.
+    pub(crate) fn query(context: &GlContext) -> Option<Self> {
+        let get_string = context.get_proc_address("glGetString");
+        if get_string.is_null() {
+            return None;
+        }
+
+        unsafe {
+            let get_string = std::mem::transmute::<*const c_void, GetString>(get_string);
+            let string = |name| {
+                let string = get_string(name);
+                if string.is_null() {
+                    String::new()
+                } else {
+                    CStr::from_ptr(string as *const c_char)
+                        .to_string_lossy()
+                        .into_owned()
+                }
+            };
+
+            context.make_current();
+            let info = Self {
+                version: string(GL_VERSION),
+                renderer: string(GL_RENDERER),
+                vendor: string(GL_VENDOR),
+            };
+            context.make_not_current();
+
+            Some(info)
+        }
+    }
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let millis = |duration: Duration| duration.as_secs_f64() * 1000.0;
+
+        writeln!(
+            f,
+            "{:.1} fps, frame {:.2} ms (p95 {:.2}, worst {:.2}), {} dropped",
+            self.fps,
+            millis(self.frame_stats.mean),
+            millis(self.frame_stats.p95),
+            millis(self.frame_stats.worst),
+            self.frame_stats.dropped_frames
+        )?;
+        writeln!(
+            f,
+            "{} events/s, {} total",
+            self.events_per_second, self.total_events
+        )?;
+        write!(
+            f,
+            "{:.0}x{:.0} at {:.2}x ({:.0}x{:.0} px)",
+            self.size.0,
+            self.size.1,
+            self.scale,
+            self.size.0 * self.scale,
+            self.size.1 * self.scale
+        )?;
+        if let Some(gl_info) = &self.gl_info {
+            write!(f, "\nGL {}, {}", gl_info.version, gl_info.renderer)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Counts the frames and events for [`Diagnostics`] over one second periods.
+pub(crate) struct DiagnosticsCounter {
+    period_start: Instant,
+    frames: u32,
+    events: u32,
+    total_events: u64,
+    fps: f32,
+    events_per_second: u32,
+    gl_info: Option<Option<GlInfo>>,
+}
+
+impl Default for DiagnosticsCounter {
+    fn default() -> Self {
+        Self {
+            period_start: Instant::now(),
+            frames: 0,
+            events: 0,
+            total_events: 0,
+            fps: 0.0,
+            events_per_second: 0,
+            gl_info: None,
+        }
+    }
+}
+
+impl DiagnosticsCounter {
+    pub fn count_event(&mut self) {
+        self.events += 1;
+        self.total_events += 1;
+    }
+
+    /// Count a drawn frame and produce the diagnostics for it. The context's information is only
+    /// queried the first time.
+    pub fn count_frame(
+        &mut self,
+        context: Option<&GlContext>,
+        frame_stats: FrameStats,
+        size: (f64, f64),
+        scale: f64,
+    ) -> Diagnostics {
+        self.frames += 1;
+        let elapsed = self.period_start.elapsed();
+        if elapsed >= Duration::from_secs(1) {
+            self.fps = self.frames as f32 / elapsed.as_secs_f32();
+            self.events_per_second = (self.events as f32 / elapsed.as_secs_f32()).round() as u32;
+            self.period_start = Instant::now();
+            self.frames = 0;
+            self.events = 0;
+        }
+
+        let gl_info = self
+            .gl_info
+            .get_or_insert_with(|| context.and_then(GlInfo::query))
+            .clone();
+
+        Diagnostics {
+            fps: self.fps,
+            frame_stats,
+            events_per_second: self.events_per_second,
+            total_events: self.total_events,
+            size,
+            scale,
+            gl_info,
+        }
+    }
+}
@@ -0,0 +1,112 @@
+//! Noticing when the editor's window moves to a monitor with a different DPI on Windows. Top level
+//! windows receive `WM_DPICHANGED`, but embedded child windows only receive
+//! `WM_DPICHANGED_AFTERPARENT` after the host's window has been rescaled, which baseview ignores.
+//! Not every host calls the editor's `set_scale_factor()` when this happens, so the bridge rebuilds
+//! the window at the new scale itself. On macOS the window system handles this transparently, and
+//! X11 doesn't have per-monitor scaling factors.
+
+use baseview::Window;
+
+/// Watches a window for DPI changes.
+pub(crate) struct DpiWatcher {
+    #[cfg(target_os = "windows")]
+    hwnd: winapi::shared::windef::HWND,
+    /// The window's new DPI, set by the window's subclass procedure. This is boxed so it has a
+    /// stable address.
+    #[cfg(target_os = "windows")]
+    new_dpi: Box<std::cell::Cell<Option<u32>>>,
+}
+
+/// Identifies our subclass procedure on the window.
+#[cfg(target_os = "windows")]
+const SUBCLASS_ID: usize = 0x6e69_6864;
+
+/// The DPI Windows considers to be a scaling factor of 1.
+#[cfg(target_os = "windows")]
+const USER_DEFAULT_SCREEN_DPI: u32 = 96;
+
+/// `WM_DPICHANGED_AFTERPARENT`, which is missing from winapi.
+#[cfg(target_os = "windows")]
+const WM_DPICHANGED_AFTERPARENT: u32 = 0x02e3;
+
+#[cfg(target_os = "windows")]
+impl DpiWatcher {
+    pub fn new(window: &Window) -> Option<Self> {
+        use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+        use std::cell::Cell;
+        use winapi::shared::windef::HWND;
+        use winapi::um::commctrl::SetWindowSubclass;
+
+        let hwnd = match window.raw_window_handle() {
+            RawWindowHandle::Win32(handle) => handle.hwnd as HWND,
+            _ => return None,
+        };
+
+        let new_dpi = Box::new(Cell::new(None));
+        let subclassed = unsafe {
+            SetWindowSubclass(
+                hwnd,
+                Some(subclass_proc),
+                SUBCLASS_ID,
+                &*new_dpi as *const Cell<Option<u32>> as usize,
+            )
+        };
+        if subclassed == 0 {
+            return None;
+        }
+
+        Some(Self { hwnd, new_dpi })
+    }
+
+    /// The window's new scaling factor if its DPI changed since the last call.
+    pub fn take_scale_factor(&mut self) -> Option<f32> {
+        self.new_dpi
+            .take()
+            .map(|dpi| dpi as f32 / USER_DEFAULT_SCREEN_DPI as f32)
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Drop for DpiWatcher {
+    fn drop(&mut self) {
+        unsafe {
+            winapi::um::commctrl::RemoveWindowSubclass(self.hwnd, Some(subclass_proc), SUBCLASS_ID)
+        };
+    }
+}
+
+/// Records DPI changes before passing the messages on to baseview's window procedure.
+#[cfg(target_os = "windows")]
+unsafe extern "system" fn subclass_proc(
+    hwnd: winapi::shared::windef::HWND,
+    msg: winapi::shared::minwindef::UINT,
+    wparam: winapi::shared::minwindef::WPARAM,
+    lparam: winapi::shared::minwindef::LPARAM,
+    _id: winapi::shared::basetsd::UINT_PTR,
+    ref_data: winapi::shared::basetsd::DWORD_PTR,
+) -> winapi::shared::minwindef::LRESULT {
+    use std::cell::Cell;
+    use winapi::um::commctrl::DefSubclassProc;
+
+    if msg == WM_DPICHANGED_AFTERPARENT {
+        // This message is only sent on Windows versions that have `GetDpiForWindow()`
+        let dpi = crate::scale::get_dpi_for_window().map_or(0, |get_dpi| get_dpi(hwnd));
+        if dpi != 0 {
+            let new_dpi = &*(ref_data as *const Cell<Option<u32>>);
+            new_dpi.set(Some(dpi));
+        }
+    }
+
+    DefSubclassProc(hwnd, msg, wparam, lparam)
+}
+
+#[cfg(not(target_os = "windows"))]
+impl DpiWatcher {
+    pub fn new(_window: &Window) -> Option<Self> {
+        None
+    }
+
+    pub fn take_scale_factor(&mut self) -> Option<f32> {
+        None
+    }
+}
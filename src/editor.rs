@@ -1,40 +1,252 @@
-//! An [`Editor`] implementation for egui.
+//! The [`Editor`] implementation that opens baseview windows for the plugin's window handler.
 
 use baseview::gl::GlConfig;
-use baseview::{Size, WindowHandle, WindowHandler, WindowOpenOptions, WindowScalePolicy};
+use baseview::{
+    Size, WindowHandle, WindowHandler, WindowInfo, WindowOpenOptions, WindowScalePolicy,
+};
 use crossbeam::atomic::AtomicCell;
-use nih_plug::prelude::{Editor, GuiContext, ParamSetter, ParentWindowHandle};
+use crossbeam::channel::{Receiver, Sender};
+use nih_plug::prelude::{nih_log, Editor, GuiContext, ParamSetter, ParentWindowHandle};
 use parking_lot::RwLock;
 use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
-use std::sync::atomic::Ordering;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use crate::BaseviewState;
+use crate::event_trace::trace_event;
+use crate::handle::HandleRegistration;
+use crate::profile::profile_scope;
+use crate::window::{panic_message, BaseviewWindowHandler, FrameUpdate};
+use crate::{monitor, scale, splash, transparency, BaseviewState, ScalePolicy};
 
-/// An [`Editor`] implementation that calls an egui draw loop.
+/// An [`Editor`] implementation that opens a baseview window for the user's window handler.
 pub(crate) struct BaseviewEditor<T, H> {
     pub(crate) baseview_state: Arc<BaseviewState>,
-    /// The plugin's state. This is kept in between editor openenings.
-    pub(crate) user_state: Arc<RwLock<T>>,
+    /// The plugin's state, or the function that creates it when the window is opened.
+    pub(crate) user_state: UserState<T>,
 
     /// The user's build function. Applied once at the start of the application.
-    pub(crate) build:
-        Arc<dyn Fn(&baseview::Window, Arc<dyn GuiContext>, &mut T) -> H + 'static + Send + Sync>,
-    /// The user's update function.
-    // pub(crate) render: Arc<dyn Fn(&ParamSetter, &mut T) + 'static + Send + Sync>,
+    pub(crate) build: Arc<
+        dyn Fn(&baseview::Window, WindowInfo, Arc<dyn GuiContext>, &mut T) -> H
+            + 'static
+            + Send
+            + Sync,
+    >,
+    /// The user's update function, called before every frame. This is `None` for editors created
+    /// using [`create_baseview_editor()`][crate::create_baseview_editor()].
+    pub(crate) update: Option<Arc<dyn Fn(&ParamSetter, &mut T) + 'static + Send + Sync>>,
 
-    /// The scaling factor reported by the host, if any. On macOS this will never be set and we
-    /// should use the system scaling factor instead.
-    pub(crate) scaling_factor: AtomicCell<Option<f32>>,
+    /// The scaling factor reported by the host, if any. If the host never sets this, then
+    /// [`scale::fallback_scale_factor()`] is used instead. On macOS this is only set when
+    /// [`BaseviewState::set_macos_host_scaling()`] is enabled, and otherwise we should use the
+    /// system scaling factor instead.
+    pub(crate) scaling_factor: Arc<AtomicCell<Option<f32>>>,
+
+    /// The ID of the last spawn, or 0 if the editor has never been spawned. This is shared with
+    /// the [`BaseviewEditorHandle`]s so a handle can tell whether the window it spawned has since
+    /// been replaced. The window itself is stored in [`OPEN_WINDOWS`].
+    pub(crate) current_spawn_id: Arc<AtomicU64>,
+}
+
+/// The user state passed to the build function.
+pub(crate) enum UserState<T> {
+    /// A single state that is kept in between editor openings.
+    Shared(Arc<RwLock<T>>),
+    /// Creates a fresh state every time the window is opened. The state is dropped along with the
+    /// window.
+    Factory(Arc<dyn Fn() -> T + Send + Sync>),
+}
+
+impl<T> Clone for UserState<T> {
+    fn clone(&self) -> Self {
+        match self {
+            UserState::Shared(state) => UserState::Shared(state.clone()),
+            UserState::Factory(factory) => UserState::Factory(factory.clone()),
+        }
+    }
+}
+
+// The editor only consists of shared state, so a clone opens the same windows as the original
+impl<T, H> Clone for BaseviewEditor<T, H> {
+    fn clone(&self) -> Self {
+        Self {
+            baseview_state: self.baseview_state.clone(),
+            user_state: self.user_state.clone(),
+            build: self.build.clone(),
+            update: self.update.clone(),
+            scaling_factor: self.scaling_factor.clone(),
+            current_spawn_id: self.current_spawn_id.clone(),
+        }
+    }
+}
+
+/// Used to give every spawned window a unique ID across all editor instances.
+static NEXT_SPAWN_ID: AtomicU64 = AtomicU64::new(1);
+
+/// How long dropping the editor handle waits for the window to be torn down on its own thread.
+const TEARDOWN_TIMEOUT: Duration = Duration::from_millis(500);
+
+thread_local! {
+    /// The windows opened on this thread, indexed by their spawn IDs. Both the parent handle and
+    /// the window handle contain raw pointers that should only be used on the GUI thread, so they
+    /// never leave the thread that opened the window.
+    static OPEN_WINDOWS: RefCell<HashMap<u64, OpenWindow>> = RefCell::new(HashMap::new());
+}
+
+/// An open editor window, along with everything needed to open it again.
+struct OpenWindow {
+    parent: ParentWindowHandle,
+    context: Arc<dyn GuiContext>,
+    handle: WindowHandle,
+    /// Passed to the window handler so it can close the window itself when the
+    /// [`BaseviewEditorHandle`] is dropped on another thread.
+    close_receiver: Receiver<()>,
+    /// The number of window handlers for this spawn that are still alive. This is shared with
+    /// the [`BaseviewEditorHandle`].
+    alive_handlers: Arc<AtomicUsize>,
+    /// The editor that opened the window, so the window can be rebuilt when its scaling factor
+    /// changes.
+    editor: Rc<dyn ReopenWindow>,
+}
+
+/// The parts of a [`BaseviewEditor`] needed to rebuild its window without knowing the editor's
+/// type.
+trait ReopenWindow {
+    fn baseview_state(&self) -> &BaseviewState;
+    /// Only used by [`rebuild_window()`], which does nothing on X11.
+    #[cfg_attr(not(any(target_os = "windows", target_os = "macos")), allow(dead_code))]
+    fn current_spawn_id(&self) -> u64;
+    /// Override the scaling factor last reported by the host.
+    fn set_scaling_factor(&self, factor: Option<f32>);
+    /// Open a new window in place of `open_window`'s.
+    fn reopen(&self, open_window: &OpenWindow) -> WindowHandle;
+}
+
+impl<T, H> ReopenWindow for BaseviewEditor<T, H>
+where
+    T: 'static + Send + Sync,
+    H: WindowHandler + 'static,
+{
+    fn baseview_state(&self) -> &BaseviewState {
+        &self.baseview_state
+    }
+
+    fn current_spawn_id(&self) -> u64 {
+        self.current_spawn_id.load(Ordering::Acquire)
+    }
+
+    fn set_scaling_factor(&self, factor: Option<f32>) {
+        self.scaling_factor.store(factor);
+    }
+
+    fn reopen(&self, open_window: &OpenWindow) -> WindowHandle {
+        self.open_window(
+            open_window.parent,
+            open_window.context.clone(),
+            open_window.close_receiver.clone(),
+            open_window.alive_handlers.clone(),
+        )
+    }
+}
+
+impl OpenWindow {
+    /// Close the window and open it again at the current scaling factor. Baseview cannot change a
+    /// window's scale after it has been created.
+    fn rebuild(&mut self) {
+        trace_event!("Rebuilding the window");
+        self.handle.close();
+
+        // This is also a good moment to apply a zoom factor change since the host's idea of the
+        // window's size needs to be updated anyways
+        let baseview_state = self.editor.baseview_state();
+        let old_size = baseview_state.zoomed_size();
+        baseview_state.apply_requested_zoom();
+        if baseview_state.zoomed_size() != old_size {
+            self.context.request_resize();
+        }
+
+        self.handle = self.editor.reopen(self);
+    }
+}
+
+/// Rebuild the window of the spawn with this ID, if it's open on this thread. The window is taken
+/// out of [`OPEN_WINDOWS`] while it's being rebuilt, since opening the new window can synchronously
+/// send messages to window handlers that look up the open windows again. Returns whether the
+/// window was rebuilt.
+fn rebuild_open_window(spawn_id: u64, factor: Option<f32>) -> bool {
+    let Some(mut open_window) = OPEN_WINDOWS.with(|windows| windows.borrow_mut().remove(&spawn_id))
+    else {
+        return false;
+    };
+
+    // A host that reports the same factor later on shouldn't cause another rebuild
+    if factor.is_some() {
+        open_window.editor.set_scaling_factor(factor);
+        open_window
+            .editor
+            .baseview_state()
+            .scale_factor
+            .store(factor);
+    }
+    open_window.rebuild();
+
+    OPEN_WINDOWS.with(|windows| windows.borrow_mut().insert(spawn_id, open_window));
+    true
+}
+
+/// Rebuild the editor's window at a new scaling factor when the scaling factor changed without the
+/// host telling the editor, for instance because the window was moved to a monitor with a
+/// different DPI. `None` keeps the current scaling factor. The old window is closed once control
+/// returns to its event loop, so this can be called from the old window's handler. Returns whether
+/// the window was rebuilt.
+///
+/// The windows are stored on the thread that spawned the editor, so this only works when called
+/// from that thread. On Windows and macOS the window's handler runs on that same thread.
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+pub(crate) fn rebuild_window(baseview_state: &BaseviewState, factor: Option<f32>) -> bool {
+    let spawn_id = OPEN_WINDOWS.with(|windows| {
+        windows.borrow().iter().find_map(|(spawn_id, open_window)| {
+            let editor = &open_window.editor;
+            (std::ptr::eq(editor.baseview_state(), baseview_state)
+                && *spawn_id == editor.current_spawn_id())
+            .then_some(*spawn_id)
+        })
+    });
+    let Some(spawn_id) = spawn_id else {
+        return false;
+    };
+
+    trace_event!("Rebuilding the window from its handler with a scaling factor of {factor:?}");
+    rebuild_open_window(spawn_id, factor)
+}
+
+/// On X11 baseview runs every window's handler on a thread of its own, while the window's handle
+/// stays in [`OPEN_WINDOWS`] on the host's GUI thread. The handler has no way to reach that thread,
+/// so the window can't be rebuilt from it and the new scaling or zoom factor is used the next time
+/// the editor opens instead.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub(crate) fn rebuild_window(_baseview_state: &BaseviewState, _factor: Option<f32>) -> bool {
+    false
 }
 
 /// This version of `baseview` uses a different version of `raw_window_handle than NIH-plug, so we
 /// need to adapt it ourselves.
+///
+/// There is no Wayland variant here. NIH-plug only hands out X11 windows on Linux and baseview
+/// does not have a Wayland backend, so Wayland-native hosts need to embed the editor through
+/// XWayland.
 struct ParentWindowHandleAdapter(nih_plug::editor::ParentWindowHandle);
 
 unsafe impl HasRawWindowHandle for ParentWindowHandleAdapter {
     fn raw_window_handle(&self) -> RawWindowHandle {
         match self.0 {
+            // NIH-plug only passes on the window's XID, which is the same for Xlib and XCB.
+            // Baseview accepts both kinds of parent handles and opens its own connection either
+            // way, so there's no separate Xlib path to take here.
             ParentWindowHandle::X11Window(window) => {
                 let mut handle = raw_window_handle::XcbWindowHandle::empty();
                 handle.window = window;
@@ -54,153 +266,490 @@ unsafe impl HasRawWindowHandle for ParentWindowHandleAdapter {
     }
 }
 
-impl<T, H> Editor for BaseviewEditor<T, H>
+/// The OpenGL context configuration used for the editor's window and its pop-out windows.
+pub(crate) fn gl_config() -> GlConfig {
+    GlConfig {
+        version: (3, 2),
+        red_bits: 8,
+        blue_bits: 8,
+        green_bits: 8,
+        alpha_bits: 8,
+        depth_bits: 24,
+        stencil_bits: 8,
+        samples: None,
+        srgb: true,
+        double_buffer: true,
+        vsync: true,
+        ..Default::default()
+    }
+}
+
+impl<T, H> BaseviewEditor<T, H>
 where
     T: 'static + Send + Sync,
-    H: WindowHandler + Send + Sync + 'static,
+    H: WindowHandler + 'static,
 {
-    fn spawn(
+    /// Open a new window using the current size, scale, and zoom factor.
+    fn open_window(
         &self,
         parent: ParentWindowHandle,
         context: Arc<dyn GuiContext>,
-    ) -> Box<dyn std::any::Any + Send> {
+        close_receiver: Receiver<()>,
+        alive_handlers: Arc<AtomicUsize>,
+    ) -> WindowHandle {
+        profile_scope!("open_window");
+
+        // A window closed through `request_close()` may be reopened before the host drops its
+        // handle, and the host never saw its pending resize. Rebuilding a window that's still open
+        // leaves the request to the new handler so it gets negotiated with the host like any other
+        // resize.
+        if !self.baseview_state.is_open() {
+            self.baseview_state.discard_requested_size();
+        }
+
+        // The new window gets a fresh handler, so a panic in the old one no longer applies
+        *self.baseview_state.panic_message.lock() = None;
+
         let build = self.build.clone();
-        let state = self.user_state.clone();
+        let state = match &self.user_state {
+            UserState::Shared(state) => state.clone(),
+            UserState::Factory(factory) => Arc::new(RwLock::new(factory())),
+        };
+        let baseview_state = self.baseview_state.clone();
+        // The window handler doesn't know about the user state's type
+        let update = self.update.clone().map(|update| {
+            let state = state.clone();
+            Arc::new(move |param_setter: &ParamSetter| update(param_setter, &mut state.write()))
+                as FrameUpdate
+        });
+
+        // Some hosts don't send the scaling factor again after restoring a plugin instance, in
+        // which case the persisted factor is better than a guess
+        let scaling_factor = match self.baseview_state.scale_policy() {
+            ScalePolicy::Host if self.baseview_state.ignores_host_scale_factor() => None,
+            ScalePolicy::Host => self
+                .scaling_factor
+                .load()
+                .or_else(|| self.baseview_state.scale_factor())
+                .or_else(|| scale::fallback_scale_factor(parent)),
+            ScalePolicy::System => None,
+            ScalePolicy::Fixed(factor) => Some(factor),
+        };
+        if scaling_factor.is_some() {
+            self.baseview_state.scale_factor.store(scaling_factor);
+        }
+        let zoom = self.baseview_state.zoom.load();
+        let window_scale = (scaling_factor.unwrap_or(1.0) * zoom) as f64;
+
+        // The size limits may have changed since this size was stored
+        let persisted_size = self.baseview_state.logical_size();
+        let (mut unscaled_width, mut unscaled_height) =
+            self.baseview_state.clamp_size(persisted_size);
+
+        // A size that was persisted on a larger screen may not fit on this one. The host is asked
+        // to resize its container to the clamped size before the first frame.
+        if let Some((work_area_width, work_area_height)) = monitor::work_area_size(parent) {
+            unscaled_width = unscaled_width.min(work_area_width / window_scale);
+            unscaled_height = unscaled_height.min(work_area_height / window_scale);
+        }
+        self.baseview_state
+            .size
+            .store((unscaled_width, unscaled_height));
+        let size_changed = (unscaled_width, unscaled_height) != persisted_size;
 
-        let (unscaled_width, unscaled_height) = self.baseview_state.size();
-        let scaling_factor = self.scaling_factor.load();
+        // When baseview uses the system scaling factor we don't know the real factor until the
+        // window sends its first resize event. On macOS logical pixels are points, so 1.0 is
+        // correct there.
+        let window_info =
+            WindowInfo::from_logical_size(Size::new(unscaled_width, unscaled_height), window_scale);
 
         let window = baseview::Window::open_parented(
             &ParentWindowHandleAdapter(parent),
             WindowOpenOptions {
                 title: String::from("baseview window"),
                 // Baseview should be doing the DPI scaling for us
-                size: Size::new(unscaled_width as f64, unscaled_height as f64),
+                size: Size::new(unscaled_width, unscaled_height),
                 // NOTE: For some reason passing 1.0 here causes the UI to be scaled on macOS but
                 //       not the mouse events.
-                scale: scaling_factor
-                    .map(|factor| WindowScalePolicy::ScaleFactor(factor as f64))
-                    .unwrap_or(WindowScalePolicy::SystemScaleFactor),
-
-                gl_config: Some(GlConfig {
-                    version: (3, 2),
-                    red_bits: 8,
-                    blue_bits: 8,
-                    green_bits: 8,
-                    alpha_bits: 8,
-                    depth_bits: 24,
-                    stencil_bits: 8,
-                    samples: None,
-                    srgb: true,
-                    double_buffer: true,
-                    vsync: true,
-                    ..Default::default()
-                }),
+                // The user's zoom factor is applied on top of the DPI scaling so the handler keeps
+                // seeing the same logical size
+                scale: match scaling_factor {
+                    Some(factor) => WindowScalePolicy::ScaleFactor((factor * zoom) as f64),
+                    None if zoom != 1.0 => WindowScalePolicy::ScaleFactor(zoom as f64),
+                    None => WindowScalePolicy::SystemScaleFactor,
+                },
+
+                gl_config: Some(gl_config()),
+            },
+            move |window| {
+                // The bridge's helpers can look up the window's handle on this thread from here on
+                let handle_registration = HandleRegistration::new(&baseview_state, window);
+
+                // Baseview still opens the window when it can't create an OpenGL context. The
+                // user's build function would most likely panic when it tries to use the context,
+                // so the window shows an error message instead.
+                let inner = if let Some(gl_context) = window.gl_context() {
+                    if baseview_state.transparent() {
+                        transparency::enable(window, context.as_ref());
+                    }
+                    if let Some(color) = baseview_state.splash_color() {
+                        splash::present(gl_context, color);
+                    }
+
+                    // A panic here would otherwise unwind into the host. The handler shows the
+                    // panic's message instead.
+                    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                        build(window, window_info, context.clone(), &mut state.write())
+                    }));
+                    match result {
+                        Ok(inner) => Some(inner),
+                        Err(payload) => {
+                            let message = panic_message(&*payload);
+                            nih_log!("The editor's build function panicked: {message}");
+                            *baseview_state.panic_message.lock() = Some(message);
+
+                            None
+                        }
+                    }
+                } else {
+                    nih_log!(
+                        "Could not create an OpenGL context, showing an error message instead"
+                    );
+                    None
+                };
+                BaseviewWindowHandler::new(
+                    window,
+                    handle_registration,
+                    baseview_state,
+                    context,
+                    close_receiver,
+                    alive_handlers,
+                    inner,
+                    update,
+                    window_info.scale(),
+                    size_changed,
+                )
             },
-            move |window| build(window, context, &mut state.write()),
         );
 
-        self.baseview_state.open.store(true, Ordering::Release);
+        window
+    }
+}
+
+impl<T, H> Editor for BaseviewEditor<T, H>
+where
+    T: 'static + Send + Sync,
+    H: WindowHandler + 'static,
+{
+    fn spawn(
+        &self,
+        parent: ParentWindowHandle,
+        context: Arc<dyn GuiContext>,
+    ) -> Box<dyn std::any::Any + Send> {
+        profile_scope!("Editor::spawn");
+        let spawn_id = NEXT_SPAWN_ID.fetch_add(1, Ordering::Relaxed);
+        let previous_spawn_id = self.current_spawn_id.swap(spawn_id, Ordering::AcqRel);
+        trace_event!("Editor::spawn() with parent {parent:?}, spawn {spawn_id}");
+
+        // Some hosts spawn the editor again without dropping the previous handle, for instance when
+        // docking or undocking the plugin window. The old window would otherwise stay open without
+        // a parent and leak its OpenGL context. The old handle won't touch the new window when it
+        // does get dropped. This can be disabled through the host quirks, in which case the old
+        // window stays open until its handle is dropped.
+        let close_stale_windows = self.baseview_state.host_quirks().close_stale_windows;
+        let old_window = OPEN_WINDOWS.with(|windows| {
+            let mut windows = windows.borrow_mut();
+
+            // Windows closed from another thread or by the plugin are only cleaned up here
+            windows.retain(|spawn_id, open_window| {
+                *spawn_id == previous_spawn_id || open_window.handle.is_open()
+            });
+            if close_stale_windows {
+                windows.remove(&previous_spawn_id)
+            } else {
+                None
+            }
+        });
+        if let Some(mut old_window) = old_window {
+            nih_log!("The editor was spawned while it was already open, closing the old window");
+            old_window.handle.close();
+        }
+
+        let (close_sender, close_receiver) = crossbeam::channel::bounded(0);
+        let alive_handlers = Arc::new(AtomicUsize::new(0));
+        let handle = self.open_window(
+            parent,
+            context.clone(),
+            close_receiver.clone(),
+            alive_handlers.clone(),
+        );
+        OPEN_WINDOWS.with(|windows| {
+            windows.borrow_mut().insert(
+                spawn_id,
+                OpenWindow {
+                    parent,
+                    context,
+                    handle,
+                    close_receiver,
+                    alive_handlers: alive_handlers.clone(),
+                    editor: Rc::new(self.clone()),
+                },
+            )
+        });
+
+        self.baseview_state
+            .close_requested
+            .store(false, Ordering::Release);
+        if !self.baseview_state.open.swap(true, Ordering::AcqRel) {
+            self.baseview_state.editor_opened();
+        }
         Box::new(BaseviewEditorHandle {
+            spawn_id,
+            current_spawn_id: self.current_spawn_id.clone(),
             baseview_state: self.baseview_state.clone(),
-            window,
+            alive_handlers,
+            close_sender: Some(close_sender),
         })
-
-        // window.
-
-        // Box::new(window);
-
-        // let window = EguiWindow::open_parented(
-        //     &ParentWindowHandleAdapter(parent),
-        //     WindowOpenOptions {
-        //         title: String::from("egui window"),
-        //         // Baseview should be doing the DPI scaling for us
-        //         size: Size::new(unscaled_width as f64, unscaled_height as f64),
-        //         // NOTE: For some reason passing 1.0 here causes the UI to be scaled on macOS but
-        //         //       not the mouse events.
-        //         scale: scaling_factor
-        //             .map(|factor| WindowScalePolicy::ScaleFactor(factor as f64))
-        //             .unwrap_or(WindowScalePolicy::SystemScaleFactor),
-
-        //         #[cfg(feature = "opengl")]
-        //         gl_config: Some(GlConfig {
-        //             version: (3, 2),
-        //             red_bits: 8,
-        //             blue_bits: 8,
-        //             green_bits: 8,
-        //             alpha_bits: 8,
-        //             depth_bits: 24,
-        //             stencil_bits: 8,
-        //             samples: None,
-        //             srgb: true,
-        //             double_buffer: true,
-        //             vsync: true,
-        //             ..Default::default()
-        //         }),
-        //     },
-        //     state,
-        //     move |egui_ctx, _queue, state| build(egui_ctx, &mut state.write()),
-        //     move |egui_ctx, _queue, state| {
-        //         let setter = ParamSetter::new(context.as_ref());
-
-        //         // For now, just always redraw. Most plugin GUIs have meters, and those almost always
-        //         // need a redraw. Later we can try to be a bit more sophisticated about this. Without
-        //         // this we would also have a blank GUI when it gets first opened because most DAWs open
-        //         // their GUI while the window is still unmapped.
-        //         egui_ctx.request_repaint();
-        //         (update)(egui_ctx, &setter, &mut state.write());
-        //     },
-        // );
-
-        // self.egui_state.open.store(true, Ordering::Release);
-        // Box::new(BaseviewEditorHandle {
-        //     baseview_state: self.egui_state.clone(),
-        //     window,
-        // })
     }
 
     fn size(&self) -> (u32, u32) {
-        self.baseview_state.size()
+        let size = self.baseview_state.zoomed_size();
+        trace_event!("Editor::size() -> {size:?}");
+
+        size
     }
 
     fn set_scale_factor(&self, factor: f32) -> bool {
-        // If the editor is currently open then the host must not change the current HiDPI scale as
-        // we don't have a way to handle that. Ableton Live does this.
-        if self.baseview_state.is_open() {
+        trace_event!("Editor::set_scale_factor({factor})");
+
+        // The plugin explicitly opted out of using the host's scaling factor, or this is macOS
+        // where the window system already takes care of scaling
+        if self.baseview_state.scale_policy() != ScalePolicy::Host
+            || self.baseview_state.ignores_host_scale_factor()
+        {
             return false;
         }
 
-        self.scaling_factor.store(Some(factor));
+        let old_factor = self.scaling_factor.swap(Some(factor));
+        self.baseview_state.scale_factor.store(Some(factor));
+        if old_factor == Some(factor)
+            || !self.baseview_state.is_open()
+            || !self.baseview_state.host_quirks().rebuild_on_scale_change
+        {
+            return true;
+        }
+
+        // Ableton Live changes the scale while the editor is open when the window gets moved to
+        // another monitor, so the window gets rebuilt at the new scale.
+        // The window can only be rebuilt on the thread it was opened on. Hosts call this from the
+        // GUI thread, but if they don't the new scale will be used the next time the editor opens.
+        let spawn_id = self.current_spawn_id.load(Ordering::Acquire);
+        rebuild_open_window(spawn_id, None);
+
         true
     }
 
-    fn param_value_changed(&self, _id: &str, _normalized_value: f32) {
+    fn param_value_changed(&self, id: &str, normalized_value: f32) {
+        trace_event!("Editor::param_value_changed({id:?}, {normalized_value})");
+
         // As mentioned above, for now we'll always force a redraw to allow meter widgets to work
         // correctly. In the future we can use an `Arc<AtomicBool>` and only force a redraw when
         // that boolean is set.
+        self.baseview_state
+            .params_changed
+            .store(true, Ordering::Release);
     }
 
-    fn param_modulation_changed(&self, _id: &str, _modulation_offset: f32) {}
+    fn param_modulation_changed(&self, id: &str, modulation_offset: f32) {
+        trace_event!("Editor::param_modulation_changed({id:?}, {modulation_offset})");
+    }
 
     fn param_values_changed(&self) {
+        trace_event!("Editor::param_values_changed()");
+
         // Same
+        self.baseview_state
+            .params_changed
+            .store(true, Ordering::Release);
     }
 }
 
-/// The window handle used for [`EguiEditor`].
-struct BaseviewEditorHandle {
+/// The handle returned from the editor's [`Editor::spawn()`]. The plugin can downcast the spawned
+/// handle to this type to query and control the window it belongs to. Once the editor has been
+/// spawned again, the old handle no longer affects the new window.
+///
+/// The window's handle stays on the thread that opened it. If the host drops this handle on that
+/// thread, the window is closed right away. Otherwise dropping `close_sender` disconnects the
+/// window handler's receiver, the handler closes the window on its own thread, and the handle
+/// waits for the handler to be destroyed so the host doesn't tear down the parent window while the
+/// editor is still using it.
+pub struct BaseviewEditorHandle {
+    /// The spawn this handle belongs to, used to look up the window in [`OPEN_WINDOWS`].
+    spawn_id: u64,
+    /// Shared with [`BaseviewEditor::current_spawn_id`].
+    current_spawn_id: Arc<AtomicU64>,
     baseview_state: Arc<BaseviewState>,
-    window: WindowHandle,
+    /// Shared with [`OpenWindow::alive_handlers`].
+    alive_handlers: Arc<AtomicUsize>,
+    /// Only an `Option` so it can be dropped before waiting for the teardown.
+    close_sender: Option<Sender<()>>,
 }
 
-/// The window handle enum stored within 'WindowHandle' contains raw pointers. Is there a way around
-/// having this requirement?
-unsafe impl Send for BaseviewEditorHandle {}
+impl BaseviewEditorHandle {
+    /// Whether this handle's window is still open. This returns `false` once the window has been
+    /// closed, or when the editor has been spawned again since.
+    pub fn is_open(&self) -> bool {
+        self.is_current() && self.alive_handlers.load(Ordering::Acquire) > 0
+    }
+
+    /// Close this handle's window. See [`BaseviewState::request_close()`].
+    pub fn request_close(&self) {
+        if self.is_current() {
+            self.baseview_state.request_close();
+        }
+    }
+
+    /// Draw the next frame even if drawing is paused. See [`BaseviewState::request_repaint()`].
+    pub fn request_repaint(&self) {
+        if self.is_current() {
+            self.baseview_state.request_repaint();
+        }
+    }
+
+    #[cfg(feature = "testing")]
+    pub(crate) fn baseview_state(&self) -> &Arc<BaseviewState> {
+        &self.baseview_state
+    }
+
+    /// Whether the editor hasn't been spawned again since this handle was created.
+    fn is_current(&self) -> bool {
+        self.current_spawn_id.load(Ordering::Acquire) == self.spawn_id
+    }
+}
 
 impl Drop for BaseviewEditorHandle {
     fn drop(&mut self) {
-        self.baseview_state.open.store(false, Ordering::Release);
+        trace_event!("Dropping the editor handle for spawn {}", self.spawn_id);
+
+        // If the editor was spawned again before this handle got dropped, then the state belongs
+        // to the newer handle
+        if self.is_current() {
+            self.baseview_state.editor_closed();
+            self.baseview_state.discard_requested_size();
+            self.baseview_state.apply_requested_zoom();
+        }
+
         // XXX: This should automatically happen when the handle gets dropped, but apparently not
-        self.window.close();
+        // `try_with()` fails if the host drops the handle while the thread is shutting down
+        let open_window = OPEN_WINDOWS
+            .try_with(|windows| windows.borrow_mut().remove(&self.spawn_id))
+            .ok()
+            .flatten();
+        if let Some(mut open_window) = open_window {
+            open_window.handle.close();
+            return;
+        }
+
+        // The window lives on another thread. Dropping the sender tells its handler to close the
+        // window, and we can't block that thread's event loop so we wait here instead.
+        self.close_sender.take();
+        let deadline = Instant::now() + TEARDOWN_TIMEOUT;
+        while self.alive_handlers.load(Ordering::Acquire) > 0 {
+            if Instant::now() >= deadline {
+                nih_log!("Timed out waiting for the editor's window to close");
+                break;
+            }
+
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use baseview::{Event, EventStatus, Window};
+
+    use super::*;
+
+    struct NoopHandler;
+
+    impl WindowHandler for NoopHandler {
+        fn on_frame(&mut self, _window: &mut Window) {}
+
+        fn on_event(&mut self, _window: &mut Window, _event: Event) -> EventStatus {
+            EventStatus::Ignored
+        }
+    }
+
+    fn editor(baseview_state: &Arc<BaseviewState>) -> Box<dyn Editor> {
+        crate::create_baseview_editor(baseview_state.clone(), (), |_, _, _, _| NoopHandler).unwrap()
+    }
+
+    /// A handle for a window that was opened on another thread and has already been torn down, so
+    /// dropping it only runs the state bookkeeping.
+    fn closed_window_handle(baseview_state: &Arc<BaseviewState>) -> BaseviewEditorHandle {
+        BaseviewEditorHandle {
+            spawn_id: 0,
+            current_spawn_id: Arc::new(AtomicU64::new(0)),
+            baseview_state: baseview_state.clone(),
+            alive_handlers: Arc::new(AtomicUsize::new(0)),
+            close_sender: None,
+        }
+    }
+
+    #[test]
+    fn set_size_while_closed_updates_editor_size() {
+        let baseview_state = BaseviewState::from_size(400, 300);
+        let editor = editor(&baseview_state);
+
+        baseview_state.set_size(640, 480);
+        assert_eq!(editor.size(), (640, 480));
+        assert_eq!(baseview_state.requested_size.load(), None);
+    }
+
+    #[test]
+    fn unnegotiated_size_is_discarded_on_drop() {
+        let baseview_state = BaseviewState::from_size(400, 300);
+        let editor = editor(&baseview_state);
+
+        baseview_state.open.store(true, Ordering::Release);
+        baseview_state.set_size(640, 480);
+        // The window handler never got to negotiate the size with the host
+        assert_eq!(editor.size(), (400, 300));
+
+        drop(closed_window_handle(&baseview_state));
+        assert!(!baseview_state.is_open());
+        assert_eq!(baseview_state.requested_size.load(), None);
+        assert_eq!(editor.size(), (400, 300));
+    }
+
+    #[test]
+    fn negotiated_size_is_reported_but_not_persisted() {
+        let baseview_state = BaseviewState::from_size(400, 300);
+        let editor = editor(&baseview_state);
+
+        baseview_state.negotiated_size.store(Some((640.0, 480.0)));
+        assert_eq!(editor.size(), (640, 480));
+        assert_eq!(baseview_state.logical_size(), (400.0, 300.0));
+
+        baseview_state.negotiated_size.store(None);
+        assert_eq!(editor.size(), (400, 300));
+    }
+
+    #[test]
+    fn set_size_while_closed_clears_stale_request() {
+        let baseview_state = BaseviewState::from_size(400, 300);
+        let editor = editor(&baseview_state);
+
+        baseview_state.open.store(true, Ordering::Release);
+        baseview_state.set_size(640, 480);
+        // The host closed the window before the handle got to apply the request
+        baseview_state.open.store(false, Ordering::Release);
+        baseview_state.set_size(800, 600);
+        assert_eq!(baseview_state.requested_size.load(), None);
+        assert_eq!(editor.size(), (800, 600));
+
+        drop(closed_window_handle(&baseview_state));
+        assert_eq!(editor.size(), (800, 600));
     }
 }
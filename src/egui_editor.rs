@@ -0,0 +1,405 @@
+//! A ready-made egui editor, available with the `egui` feature. This translates baseview's input to
+//! egui, paints egui's output to the window's OpenGL context with `egui_glow`, and calls an update
+//! function with a [`ParamSetter`] every frame.
+
+use ::egui::{self as egui_crate, Context, CursorIcon, Pos2, RawInput, Rect, Vec2};
+use baseview::{
+    Event, EventStatus, MouseButton, MouseCursor, MouseEvent, ScrollDelta, Window, WindowEvent,
+    WindowHandler, WindowInfo,
+};
+use egui_glow::Painter;
+use glow::HasContext;
+use keyboard_types::{Code, Key, KeyState, KeyboardEvent, Modifiers};
+use nih_plug::prelude::{nih_log, Editor, GuiContext, ParamSetter};
+use parking_lot::RwLock;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::{clipboard, keyboard, present, BaseviewState};
+
+/// How many points a single line of scrolling moves.
+const POINTS_PER_SCROLL_LINE: f32 = 50.0;
+
+/// Create an [`Editor`] that draws an egui GUI. `build` is called once after the window is opened
+/// to set up the egui context, for instance to load fonts. `update` is called every frame to draw
+/// the GUI. The user state is kept across reopening the editor.
+pub fn create_egui_editor<T, B, U>(
+    baseview_state: Arc<BaseviewState>,
+    user_state: T,
+    build: B,
+    update: U,
+) -> Option<Box<dyn Editor>>
+where
+    T: 'static + Send + Sync,
+    B: Fn(&Context, &mut T) + 'static + Send + Sync,
+    U: Fn(&Context, &ParamSetter, &mut T) + 'static + Send + Sync,
+{
+    let update = Arc::new(update);
+    let handler_state = baseview_state.clone();
+
+    crate::create_baseview_editor(
+        baseview_state,
+        Arc::new(RwLock::new(user_state)),
+        move |window, window_info, context, user_state| {
+            let egui_context = Context::default();
+            build(&egui_context, &mut user_state.write());
+
+            EguiHandler {
+                baseview_state: handler_state.clone(),
+                gui_context: context,
+                user_state: user_state.clone(),
+                update: update.clone(),
+
+                painter: create_painter(window),
+                egui_context,
+                raw_input: raw_input(window_info),
+                cursor_pos: Pos2::ZERO,
+                window_info,
+                start_time: Instant::now(),
+            }
+        },
+    )
+}
+
+struct EguiHandler<T, U> {
+    baseview_state: Arc<BaseviewState>,
+    gui_context: Arc<dyn GuiContext>,
+    user_state: Arc<RwLock<T>>,
+    update: Arc<U>,
+
+    /// This is `None` if the painter could not be created, in which case nothing is drawn.
+    painter: Option<Painter>,
+    egui_context: Context,
+    /// The input collected since the last frame.
+    raw_input: RawInput,
+    /// The position of the last `CursorMoved` event, for the button events. egui's own pointer
+    /// position lags a frame behind, since it's only updated when the input is processed.
+    cursor_pos: Pos2,
+    window_info: WindowInfo,
+    start_time: Instant,
+}
+
+/// Create the painter on the window's OpenGL context.
+fn create_painter(window: &Window) -> Option<Painter> {
+    let gl_context = window.gl_context()?;
+    unsafe { gl_context.make_current() };
+    let gl = unsafe {
+        glow::Context::from_loader_function(|symbol| gl_context.get_proc_address(symbol))
+    };
+    let painter = Painter::new(Arc::new(gl), "", None);
+    unsafe { gl_context.make_not_current() };
+
+    match painter {
+        Ok(painter) => Some(painter),
+        Err(err) => {
+            nih_log!("Could not create the egui painter: {err}");
+            None
+        }
+    }
+}
+
+/// The input for a window with the given size and scale, without any events.
+fn raw_input(window_info: WindowInfo) -> RawInput {
+    let logical_size = window_info.logical_size();
+
+    RawInput {
+        screen_rect: Some(Rect::from_min_size(
+            Pos2::ZERO,
+            Vec2::new(logical_size.width as f32, logical_size.height as f32),
+        )),
+        pixels_per_point: Some(window_info.scale() as f32),
+        ..Default::default()
+    }
+}
+
+impl<T, U> WindowHandler for EguiHandler<T, U>
+where
+    T: Send + Sync,
+    U: Fn(&Context, &ParamSetter, &mut T) + Send + Sync,
+{
+    fn on_frame(&mut self, window: &mut Window) {
+        let mut raw_input = std::mem::replace(&mut self.raw_input, raw_input(self.window_info));
+        raw_input.time = Some(self.start_time.elapsed().as_secs_f64());
+        raw_input.modifiers = translate_modifiers(self.baseview_state.modifiers());
+        raw_input.focused = self.baseview_state.is_focused();
+
+        let param_setter = ParamSetter::new(self.gui_context.as_ref());
+        let full_output = self.egui_context.run(raw_input, |egui_context| {
+            (self.update)(egui_context, &param_setter, &mut self.user_state.write())
+        });
+
+        let platform_output = full_output.platform_output;
+        self.baseview_state
+            .set_mouse_cursor(translate_cursor_icon(platform_output.cursor_icon));
+        if !platform_output.copied_text.is_empty() {
+            clipboard::set_text(&platform_output.copied_text);
+        }
+
+        let (Some(painter), Some(gl_context)) = (&mut self.painter, window.gl_context()) else {
+            return;
+        };
+        let clipped_primitives = self.egui_context.tessellate(full_output.shapes);
+        let physical_size = self.window_info.physical_size();
+        unsafe {
+            gl_context.make_current();
+
+            let gl = painter.gl();
+            gl.viewport(
+                0,
+                0,
+                physical_size.width as i32,
+                physical_size.height as i32,
+            );
+            gl.clear_color(0.0, 0.0, 0.0, 1.0);
+            gl.clear(glow::COLOR_BUFFER_BIT);
+            painter.paint_and_update_textures(
+                [physical_size.width, physical_size.height],
+                self.window_info.scale() as f32,
+                &clipped_primitives,
+                &full_output.textures_delta,
+            );
+
+            present::swap_buffers(gl_context);
+            gl_context.make_not_current();
+        }
+    }
+
+    fn on_event(&mut self, window: &mut Window, event: Event) -> EventStatus {
+        let modifiers = translate_modifiers(self.baseview_state.modifiers());
+        match event {
+            Event::Mouse(MouseEvent::CursorMoved { position, .. }) => {
+                self.cursor_pos = to_pos2(position);
+                self.raw_input
+                    .events
+                    .push(egui_crate::Event::PointerMoved(self.cursor_pos));
+            }
+            Event::Mouse(MouseEvent::ButtonPressed { button, .. })
+            | Event::Mouse(MouseEvent::ButtonReleased { button, .. }) => {
+                let Some(button) = translate_mouse_button(button) else {
+                    return EventStatus::Ignored;
+                };
+
+                let pressed = matches!(event, Event::Mouse(MouseEvent::ButtonPressed { .. }));
+                self.raw_input
+                    .events
+                    .push(egui_crate::Event::PointerButton {
+                        pos: self.cursor_pos,
+                        button,
+                        pressed,
+                        modifiers,
+                    });
+            }
+            Event::Mouse(MouseEvent::WheelScrolled { delta, .. }) => {
+                let delta = match delta {
+                    ScrollDelta::Lines { x, y } => Vec2::new(x, y) * POINTS_PER_SCROLL_LINE,
+                    ScrollDelta::Pixels { x, y } => {
+                        Vec2::new(x, y) / self.window_info.scale() as f32
+                    }
+                };
+                self.raw_input.events.push(egui_crate::Event::Scroll(delta));
+            }
+            Event::Mouse(MouseEvent::CursorLeft) => {
+                self.raw_input.events.push(egui_crate::Event::PointerGone);
+            }
+            Event::Keyboard(key_event) => return self.handle_key_event(key_event, modifiers),
+            Event::Window(WindowEvent::Resized(window_info)) => {
+                self.window_info = window_info;
+                let events = std::mem::take(&mut self.raw_input.events);
+                self.raw_input = raw_input(window_info);
+                self.raw_input.events = events;
+            }
+            Event::Window(WindowEvent::WillClose) => {
+                // The painter's GL objects need to be deleted while the context still exists
+                if let (Some(mut painter), Some(gl_context)) =
+                    (self.painter.take(), window.gl_context())
+                {
+                    unsafe { gl_context.make_current() };
+                    painter.destroy();
+                    unsafe { gl_context.make_not_current() };
+                }
+            }
+            _ => return EventStatus::Ignored,
+        }
+
+        EventStatus::Captured
+    }
+}
+
+impl<T, U> EguiHandler<T, U> {
+    fn handle_key_event(
+        &mut self,
+        key_event: KeyboardEvent,
+        modifiers: egui_crate::Modifiers,
+    ) -> EventStatus {
+        let pressed = key_event.state == KeyState::Down;
+        if pressed && modifiers.command {
+            match key_event.code {
+                Code::KeyC => self.raw_input.events.push(egui_crate::Event::Copy),
+                Code::KeyX => self.raw_input.events.push(egui_crate::Event::Cut),
+                Code::KeyV => {
+                    if let Some(text) = clipboard::get_text() {
+                        self.raw_input.events.push(egui_crate::Event::Paste(text));
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        if let Some(key) = translate_key(&key_event) {
+            self.raw_input.events.push(egui_crate::Event::Key {
+                key,
+                pressed,
+                repeat: key_event.repeat,
+                modifiers,
+            });
+        }
+        if let Some(text) = keyboard::text_input(&key_event) {
+            self.raw_input
+                .events
+                .push(egui_crate::Event::Text(text.to_owned()));
+        }
+
+        // Let the host handle keys like Space when no text field has keyboard focus
+        if self.egui_context.wants_keyboard_input() {
+            EventStatus::Captured
+        } else {
+            EventStatus::Ignored
+        }
+    }
+}
+
+fn to_pos2(position: baseview::Point) -> Pos2 {
+    Pos2::new(position.x as f32, position.y as f32)
+}
+
+fn translate_modifiers(modifiers: Modifiers) -> egui_crate::Modifiers {
+    let ctrl = modifiers.contains(Modifiers::CONTROL);
+    let mac_cmd = cfg!(target_os = "macos") && modifiers.contains(Modifiers::META);
+
+    egui_crate::Modifiers {
+        alt: modifiers.contains(Modifiers::ALT),
+        ctrl,
+        shift: modifiers.contains(Modifiers::SHIFT),
+        mac_cmd,
+        command: if cfg!(target_os = "macos") {
+            mac_cmd
+        } else {
+            ctrl
+        },
+    }
+}
+
+fn translate_mouse_button(button: MouseButton) -> Option<egui_crate::PointerButton> {
+    match button {
+        MouseButton::Left => Some(egui_crate::PointerButton::Primary),
+        MouseButton::Right => Some(egui_crate::PointerButton::Secondary),
+        MouseButton::Middle => Some(egui_crate::PointerButton::Middle),
+        MouseButton::Back => Some(egui_crate::PointerButton::Extra1),
+        MouseButton::Forward => Some(egui_crate::PointerButton::Extra2),
+        MouseButton::Other(_) => None,
+    }
+}
+
+fn translate_key(key_event: &KeyboardEvent) -> Option<egui_crate::Key> {
+    use egui_crate::Key as EguiKey;
+
+    let key = match &key_event.key {
+        Key::ArrowDown => EguiKey::ArrowDown,
+        Key::ArrowLeft => EguiKey::ArrowLeft,
+        Key::ArrowRight => EguiKey::ArrowRight,
+        Key::ArrowUp => EguiKey::ArrowUp,
+        Key::Escape => EguiKey::Escape,
+        Key::Tab => EguiKey::Tab,
+        Key::Backspace => EguiKey::Backspace,
+        Key::Enter => EguiKey::Enter,
+        Key::Insert => EguiKey::Insert,
+        Key::Delete => EguiKey::Delete,
+        Key::Home => EguiKey::Home,
+        Key::End => EguiKey::End,
+        Key::PageUp => EguiKey::PageUp,
+        Key::PageDown => EguiKey::PageDown,
+        // Shortcuts should work regardless of the keyboard layout, so these use the physical keys
+        _ => match key_event.code {
+            Code::Space => EguiKey::Space,
+            Code::Minus => EguiKey::Minus,
+            Code::Equal => EguiKey::PlusEquals,
+            Code::Digit0 => EguiKey::Num0,
+            Code::Digit1 => EguiKey::Num1,
+            Code::Digit2 => EguiKey::Num2,
+            Code::Digit3 => EguiKey::Num3,
+            Code::Digit4 => EguiKey::Num4,
+            Code::Digit5 => EguiKey::Num5,
+            Code::Digit6 => EguiKey::Num6,
+            Code::Digit7 => EguiKey::Num7,
+            Code::Digit8 => EguiKey::Num8,
+            Code::Digit9 => EguiKey::Num9,
+            Code::KeyA => EguiKey::A,
+            Code::KeyB => EguiKey::B,
+            Code::KeyC => EguiKey::C,
+            Code::KeyD => EguiKey::D,
+            Code::KeyE => EguiKey::E,
+            Code::KeyF => EguiKey::F,
+            Code::KeyG => EguiKey::G,
+            Code::KeyH => EguiKey::H,
+            Code::KeyI => EguiKey::I,
+            Code::KeyJ => EguiKey::J,
+            Code::KeyK => EguiKey::K,
+            Code::KeyL => EguiKey::L,
+            Code::KeyM => EguiKey::M,
+            Code::KeyN => EguiKey::N,
+            Code::KeyO => EguiKey::O,
+            Code::KeyP => EguiKey::P,
+            Code::KeyQ => EguiKey::Q,
+            Code::KeyR => EguiKey::R,
+            Code::KeyS => EguiKey::S,
+            Code::KeyT => EguiKey::T,
+            Code::KeyU => EguiKey::U,
+            Code::KeyV => EguiKey::V,
+            Code::KeyW => EguiKey::W,
+            Code::KeyX => EguiKey::X,
+            Code::KeyY => EguiKey::Y,
+            Code::KeyZ => EguiKey::Z,
+            _ => return None,
+        },
+    };
+
+    Some(key)
+}
+
+fn translate_cursor_icon(cursor_icon: CursorIcon) -> MouseCursor {
+    match cursor_icon {
+        CursorIcon::Default => MouseCursor::Default,
+        CursorIcon::None => MouseCursor::Hidden,
+        CursorIcon::Help => MouseCursor::Help,
+        CursorIcon::PointingHand => MouseCursor::Hand,
+        CursorIcon::Progress => MouseCursor::PtrWorking,
+        CursorIcon::Wait => MouseCursor::Working,
+        CursorIcon::Cell => MouseCursor::Cell,
+        CursorIcon::Crosshair => MouseCursor::Crosshair,
+        CursorIcon::Text => MouseCursor::Text,
+        CursorIcon::VerticalText => MouseCursor::VerticalText,
+        CursorIcon::Alias => MouseCursor::Alias,
+        CursorIcon::Copy => MouseCursor::Copy,
+        CursorIcon::Move => MouseCursor::Move,
+        CursorIcon::NoDrop | CursorIcon::NotAllowed => MouseCursor::NotAllowed,
+        CursorIcon::Grab => MouseCursor::Hand,
+        CursorIcon::Grabbing => MouseCursor::HandGrabbing,
+        CursorIcon::AllScroll => MouseCursor::AllScroll,
+        CursorIcon::ResizeHorizontal => MouseCursor::EwResize,
+        CursorIcon::ResizeNeSw => MouseCursor::NeswResize,
+        CursorIcon::ResizeNwSe => MouseCursor::NwseResize,
+        CursorIcon::ResizeVertical => MouseCursor::NsResize,
+        CursorIcon::ResizeEast => MouseCursor::EResize,
+        CursorIcon::ResizeSouthEast => MouseCursor::SeResize,
+        CursorIcon::ResizeSouth => MouseCursor::SResize,
+        CursorIcon::ResizeSouthWest => MouseCursor::SwResize,
+        CursorIcon::ResizeWest => MouseCursor::WResize,
+        CursorIcon::ResizeNorthWest => MouseCursor::NwResize,
+        CursorIcon::ResizeNorth => MouseCursor::NResize,
+        CursorIcon::ResizeNorthEast => MouseCursor::NeResize,
+        CursorIcon::ResizeColumn => MouseCursor::ColResize,
+        CursorIcon::ResizeRow => MouseCursor::RowResize,
+        CursorIcon::ZoomIn => MouseCursor::ZoomIn,
+        CursorIcon::ZoomOut => MouseCursor::ZoomOut,
+    }
+}
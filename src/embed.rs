@@ -0,0 +1,119 @@
+//! Hooks for hosting GUI frameworks that bring their own platform layer and renderer inside of the
+//! editor's window. Frameworks like Makepad normally create and own their top level windows and
+//! event loops. Their platform backends can instead implement [`EmbeddedApp`] to attach to the
+//! editor's native child window, receive its events and frame ticks, and follow its size and scale.
+//!
+//! This crate does not integrate Makepad itself. Makepad's platform layer currently always creates
+//! its own windows, so using it here still requires an [`EmbeddedApp`] implementation on
+//! Makepad's side.
+
+use baseview::{Event, EventStatus, Window, WindowEvent, WindowHandler, WindowInfo};
+use nih_plug::prelude::{Editor, GuiContext};
+use std::sync::Arc;
+
+use crate::{BaseviewState, WindowHandleAdapter};
+
+/// A GUI that renders into the editor's native window on its own. The editor's window is a child
+/// of the host's window, and the app will usually create its own rendering surface or child
+/// window inside of it using the handle passed to [`EmbeddedApp::attach()`]. All of these
+/// functions are called on the window's thread.
+pub trait EmbeddedApp {
+    /// Called once right after the window has been created, before any other function. The
+    /// handle is valid until [`EmbeddedApp::detach()`] is called.
+    fn attach(&mut self, window: WindowHandleAdapter, window_info: WindowInfo);
+
+    /// Called once per frame, at the display's refresh rate. This is where the app should run its
+    /// own event processing and redraw if needed.
+    fn on_frame(&mut self) {}
+
+    /// Called for every input and window event, except for the resize and close events which
+    /// have their own functions.
+    fn on_event(&mut self, event: &Event) -> EventStatus {
+        let _ = event;
+        EventStatus::Ignored
+    }
+
+    /// Called when the window's size or scale changes, with the new physical size and scale.
+    fn on_resized(&mut self, window_info: WindowInfo) {
+        let _ = window_info;
+    }
+
+    /// Called once right before the window is destroyed. The app needs to release everything it
+    /// created inside of the window here.
+    fn detach(&mut self) {}
+}
+
+/// Create an [`Editor`] that hosts an [`EmbeddedApp`]. `build` is called every time the window is
+/// opened to create the app, after which the app is attached to the new window.
+pub fn create_embedded_editor<T, B, A>(
+    baseview_state: Arc<BaseviewState>,
+    user_state: T,
+    build: B,
+) -> Option<Box<dyn Editor>>
+where
+    T: 'static + Send + Sync,
+    B: Fn(Arc<dyn GuiContext>, &mut T) -> A + 'static + Send + Sync,
+    A: EmbeddedApp + 'static,
+{
+    crate::create_baseview_editor(
+        baseview_state,
+        user_state,
+        move |window, window_info, context, user_state| {
+            let mut app = build(context, user_state);
+            app.attach(WindowHandleAdapter::new(window), window_info);
+
+            EmbeddedHandler {
+                app,
+                attached: true,
+            }
+        },
+    )
+}
+
+struct EmbeddedHandler<A: EmbeddedApp> {
+    app: A,
+    /// Whether [`EmbeddedApp::detach()`] still needs to be called.
+    attached: bool,
+}
+
+impl<A: EmbeddedApp> EmbeddedHandler<A> {
+    fn detach(&mut self) {
+        if self.attached {
+            self.attached = false;
+            self.app.detach();
+        }
+    }
+}
+
+impl<A: EmbeddedApp> WindowHandler for EmbeddedHandler<A> {
+    fn on_frame(&mut self, _window: &mut Window) {
+        if self.attached {
+            self.app.on_frame();
+        }
+    }
+
+    fn on_event(&mut self, _window: &mut Window, event: Event) -> EventStatus {
+        if !self.attached {
+            return EventStatus::Ignored;
+        }
+
+        match event {
+            Event::Window(WindowEvent::Resized(window_info)) => {
+                self.app.on_resized(window_info);
+                EventStatus::Captured
+            }
+            Event::Window(WindowEvent::WillClose) => {
+                self.detach();
+                EventStatus::Captured
+            }
+            event => self.app.on_event(&event),
+        }
+    }
+}
+
+impl<A: EmbeddedApp> Drop for EmbeddedHandler<A> {
+    fn drop(&mut self) {
+        // Not every platform sends a close event when the host destroys the parent window
+        self.detach();
+    }
+}
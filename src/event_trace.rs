@@ -0,0 +1,33 @@
+//! Logging everything that happens between the host, baseview, and the editor when the
+//! `event_tracing` feature is enabled. Host specific GUI issues usually come down to the order in
+//! which the host spawns the editor, changes its scale, and resizes it, so a log of these calls
+//! from a user's machine is often enough to reproduce them. Every message is prefixed with the time
+//! since the first traced message, in seconds. Without the feature the tracing compiles to nothing.
+
+use std::fmt;
+use std::time::Instant;
+
+/// Log a message with [`nih_log!()`][nih_plug::nih_log!()] when the `event_tracing` feature is
+/// enabled. The arguments are only evaluated when the feature is enabled.
+macro_rules! trace_event {
+    ($($arg:tt)*) => {
+        if cfg!(feature = "event_tracing") {
+            $crate::event_trace::log(format_args!($($arg)*));
+        }
+    };
+}
+
+pub(crate) use trace_event;
+
+/// Log a traced message with a timestamp. Use [`trace_event!()`] instead.
+pub(crate) fn log(message: fmt::Arguments) {
+    lazy_static::lazy_static! {
+        static ref START: Instant = Instant::now();
+    }
+
+    nih_plug::nih_log!(
+        "[trace {:>10.3}] {}",
+        START.elapsed().as_secs_f64(),
+        message
+    );
+}
@@ -0,0 +1,361 @@
+//! A native error message shown in place of the editor when its OpenGL context could not be
+//! created or when the editor's code panicked, so users see something they can act on or report
+//! instead of an empty window.
+
+use baseview::Window;
+
+/// The message shown when the window does not have an OpenGL context.
+pub(crate) const GL_ERROR_MESSAGE: &str = "This plugin's editor could not be opened because an \
+OpenGL 3.2 context could not be created.\n\nUpdating your graphics drivers usually fixes this. \
+Remote desktop sessions and virtual machines often don't support hardware accelerated OpenGL, in \
+which case the editor can only be used on the machine itself.";
+
+/// The message shown after the editor's build function or window handler panicked, followed by
+/// the panic's message.
+pub(crate) fn panic_error_message(panic_message: &str) -> String {
+    format!(
+        "This plugin's editor stopped working because of an internal error. Closing and reopening \
+         the editor may fix this. Please report this error to the plugin's developer:\n\n\
+         {panic_message}"
+    )
+}
+
+/// Shows a message on top of a window using the platform's own drawing APIs.
+pub(crate) struct ErrorView {
+    #[cfg(target_os = "windows")]
+    hwnd: winapi::shared::windef::HWND,
+    /// The message as a UTF-16 string. This is boxed so it has a stable address for the window's
+    /// subclass procedure.
+    #[cfg(target_os = "windows")]
+    _message: Box<Vec<u16>>,
+
+    #[cfg(target_os = "macos")]
+    label: cocoa::base::id,
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    display: *mut x11::xlib::Display,
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    window_id: x11::xlib::Window,
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    gc: x11::xlib::GC,
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    message: String,
+    /// Set when the window needs to be redrawn on the next call to [`ErrorView::update()`].
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    needs_redraw: bool,
+}
+
+/// Identifies our subclass procedure on the window.
+#[cfg(target_os = "windows")]
+const SUBCLASS_ID: usize = 0x6e69_6865;
+
+/// The space between the window's edges and the message, in pixels.
+const MARGIN: i32 = 16;
+
+#[cfg(target_os = "windows")]
+impl ErrorView {
+    /// Start showing `message` in the window. Returns `None` if the message can't be shown.
+    pub fn new(window: &Window, message: &str) -> Option<Self> {
+        use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+        use winapi::shared::windef::HWND;
+        use winapi::um::commctrl::SetWindowSubclass;
+        use winapi::um::winuser::InvalidateRect;
+
+        let hwnd = match window.raw_window_handle() {
+            RawWindowHandle::Win32(handle) => handle.hwnd as HWND,
+            _ => return None,
+        };
+
+        let message: Box<Vec<u16>> = Box::new(message.encode_utf16().chain([0]).collect());
+        unsafe {
+            if SetWindowSubclass(
+                hwnd,
+                Some(subclass_proc),
+                SUBCLASS_ID,
+                &*message as *const Vec<u16> as usize,
+            ) == 0
+            {
+                return None;
+            }
+
+            InvalidateRect(hwnd, std::ptr::null(), 1);
+        }
+
+        Some(Self {
+            hwnd,
+            _message: message,
+        })
+    }
+
+    /// The message is drawn in response to `WM_PAINT`, so this doesn't need to do anything.
+    pub fn update(&mut self) {}
+}
+
+#[cfg(target_os = "windows")]
+impl Drop for ErrorView {
+    fn drop(&mut self) {
+        use winapi::um::commctrl::RemoveWindowSubclass;
+
+        unsafe { RemoveWindowSubclass(self.hwnd, Some(subclass_proc), SUBCLASS_ID) };
+    }
+}
+
+/// Draws the message when the window is painted. All other messages are passed on to baseview's
+/// window procedure.
+#[cfg(target_os = "windows")]
+unsafe extern "system" fn subclass_proc(
+    hwnd: winapi::shared::windef::HWND,
+    msg: winapi::shared::minwindef::UINT,
+    wparam: winapi::shared::minwindef::WPARAM,
+    lparam: winapi::shared::minwindef::LPARAM,
+    _id: winapi::shared::basetsd::UINT_PTR,
+    ref_data: winapi::shared::basetsd::DWORD_PTR,
+) -> winapi::shared::minwindef::LRESULT {
+    use winapi::shared::windef::RECT;
+    use winapi::um::commctrl::DefSubclassProc;
+    use winapi::um::wingdi::{SetBkMode, SetTextColor, TRANSPARENT};
+    use winapi::um::winuser::{
+        BeginPaint, DrawTextW, EndPaint, FillRect, GetClientRect, GetSysColor, GetSysColorBrush,
+        InvalidateRect, COLOR_WINDOW, COLOR_WINDOWTEXT, DT_LEFT, DT_TOP, DT_WORDBREAK, PAINTSTRUCT,
+        WM_ERASEBKGND, WM_PAINT, WM_SIZE,
+    };
+
+    match msg {
+        WM_PAINT => {
+            let message = &*(ref_data as *const Vec<u16>);
+
+            let mut paint: PAINTSTRUCT = std::mem::zeroed();
+            let hdc = BeginPaint(hwnd, &mut paint);
+            let mut rect: RECT = std::mem::zeroed();
+            GetClientRect(hwnd, &mut rect);
+            FillRect(hdc, &rect, GetSysColorBrush(COLOR_WINDOW));
+
+            rect.left += MARGIN;
+            rect.top += MARGIN;
+            rect.right -= MARGIN;
+            rect.bottom -= MARGIN;
+            SetBkMode(hdc, TRANSPARENT as i32);
+            SetTextColor(hdc, GetSysColor(COLOR_WINDOWTEXT));
+            DrawTextW(
+                hdc,
+                message.as_ptr(),
+                -1,
+                &mut rect,
+                DT_LEFT | DT_TOP | DT_WORDBREAK,
+            );
+            EndPaint(hwnd, &paint);
+
+            0
+        }
+        // The background is filled while painting, erasing it separately only causes flickering
+        WM_ERASEBKGND => 1,
+        WM_SIZE => {
+            InvalidateRect(hwnd, std::ptr::null(), 1);
+            DefSubclassProc(hwnd, msg, wparam, lparam)
+        }
+        _ => DefSubclassProc(hwnd, msg, wparam, lparam),
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl ErrorView {
+    /// Start showing `message` in the window. Returns `None` if the message can't be shown.
+    pub fn new(window: &Window, message: &str) -> Option<Self> {
+        use cocoa::base::{id, nil};
+        use cocoa::foundation::{NSPoint, NSRect, NSSize, NSString};
+        use objc::{class, msg_send, sel, sel_impl};
+        use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+
+        /// `NSViewWidthSizable | NSViewHeightSizable`
+        const AUTORESIZE_WIDTH_AND_HEIGHT: u64 = 2 | 16;
+
+        let ns_view = match window.raw_window_handle() {
+            RawWindowHandle::AppKit(handle) => handle.ns_view as id,
+            _ => return None,
+        };
+
+        unsafe {
+            let string = NSString::alloc(nil).init_str(message);
+            let label: id = msg_send![class!(NSTextField), wrappingLabelWithString: string];
+            let _: () = msg_send![string, release];
+            if label == nil {
+                return None;
+            }
+
+            let bounds: NSRect = msg_send![ns_view, bounds];
+            let frame = NSRect::new(
+                NSPoint::new(MARGIN as f64, MARGIN as f64),
+                NSSize::new(
+                    (bounds.size.width - 2.0 * MARGIN as f64).max(0.0),
+                    (bounds.size.height - 2.0 * MARGIN as f64).max(0.0),
+                ),
+            );
+            let _: () = msg_send![label, setFrame: frame];
+            let _: () = msg_send![label, setAutoresizingMask: AUTORESIZE_WIDTH_AND_HEIGHT];
+            let _: () = msg_send![ns_view, addSubview: label];
+
+            // The superview retains the label, this keeps it alive until we remove it again
+            let _: id = msg_send![label, retain];
+
+            Some(Self { label })
+        }
+    }
+
+    /// AppKit draws the label, so this doesn't need to do anything.
+    pub fn update(&mut self) {}
+}
+
+#[cfg(target_os = "macos")]
+impl Drop for ErrorView {
+    fn drop(&mut self) {
+        use objc::{msg_send, sel, sel_impl};
+
+        unsafe {
+            let _: () = msg_send![self.label, removeFromSuperview];
+            let _: () = msg_send![self.label, release];
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+impl ErrorView {
+    /// Start showing `message` in the window. Returns `None` if the message can't be shown.
+    pub fn new(window: &Window, message: &str) -> Option<Self> {
+        use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+        use std::os::raw::c_ulong;
+        use x11::xlib;
+
+        let window_id = match window.raw_window_handle() {
+            RawWindowHandle::Xcb(handle) => handle.window as c_ulong,
+            RawWindowHandle::Xlib(handle) => handle.window,
+            _ => return None,
+        };
+
+        unsafe {
+            let display = crate::x11_isolation::open_display();
+            if display.is_null() {
+                return None;
+            }
+
+            // Event masks are per connection, so this doesn't interfere with baseview's events
+            xlib::XSelectInput(
+                display,
+                window_id,
+                xlib::ExposureMask | xlib::StructureNotifyMask,
+            );
+
+            // The GC uses the server's default font, which is always available
+            let screen = xlib::XDefaultScreen(display);
+            let gc = xlib::XCreateGC(display, window_id, 0, std::ptr::null_mut());
+            xlib::XSetForeground(display, gc, xlib::XBlackPixel(display, screen));
+            xlib::XSetWindowBackground(display, window_id, xlib::XWhitePixel(display, screen));
+
+            Some(Self {
+                display,
+                window_id,
+                gc,
+                message: message.to_owned(),
+                needs_redraw: true,
+            })
+        }
+    }
+
+    /// Redraw the message if the window was exposed or resized since the last call.
+    pub fn update(&mut self) {
+        use x11::xlib;
+
+        unsafe {
+            while xlib::XPending(self.display) > 0 {
+                let mut event: xlib::XEvent = std::mem::zeroed();
+                xlib::XNextEvent(self.display, &mut event);
+                if matches!(event.get_type(), xlib::Expose | xlib::ConfigureNotify) {
+                    self.needs_redraw = true;
+                }
+            }
+
+            if self.needs_redraw {
+                self.needs_redraw = false;
+                self.draw();
+            }
+        }
+    }
+
+    /// Clear the window and draw the message, wrapped to the window's width.
+    unsafe fn draw(&self) {
+        use x11::xlib;
+
+        let mut attributes: xlib::XWindowAttributes = std::mem::zeroed();
+        if xlib::XGetWindowAttributes(self.display, self.window_id, &mut attributes) == 0 {
+            return;
+        }
+
+        let font = xlib::XQueryFont(self.display, xlib::XGContextFromGC(self.gc));
+        let (char_width, line_height, ascent) = if font.is_null() {
+            (6, 13, 10)
+        } else {
+            let metrics = (
+                ((*font).max_bounds.width as i32).max(1),
+                (*font).ascent + (*font).descent,
+                (*font).ascent,
+            );
+            xlib::XFreeFontInfo(std::ptr::null_mut(), font, 0);
+            metrics
+        };
+        let max_chars = ((attributes.width - 2 * MARGIN) / char_width).max(1) as usize;
+
+        xlib::XClearWindow(self.display, self.window_id);
+        let mut y = MARGIN + ascent;
+        for line in wrap_lines(&self.message, max_chars) {
+            xlib::XDrawString(
+                self.display,
+                self.window_id,
+                self.gc,
+                MARGIN,
+                y,
+                line.as_ptr() as *const std::os::raw::c_char,
+                line.len() as i32,
+            );
+            y += line_height;
+        }
+        xlib::XFlush(self.display);
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+impl Drop for ErrorView {
+    fn drop(&mut self) {
+        use x11::xlib;
+
+        unsafe {
+            xlib::XFreeGC(self.display, self.gc);
+            crate::x11_isolation::close_display(self.display);
+        }
+    }
+}
+
+/// Split `message` into lines of at most `max_chars` characters, breaking at spaces where
+/// possible. Only ASCII is guaranteed to be in the server's default font, so other characters are
+/// replaced.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn wrap_lines(message: &str, max_chars: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in message.split('\n') {
+        let mut line = String::new();
+        for word in paragraph.split(' ') {
+            let word: String = word
+                .chars()
+                .map(|c| if c.is_ascii() { c } else { '?' })
+                .collect();
+            if !line.is_empty() && line.len() + 1 + word.len() > max_chars {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&word);
+        }
+        lines.push(line);
+    }
+
+    lines
+}
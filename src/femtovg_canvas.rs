@@ -0,0 +1,169 @@
+//! A femtovg canvas on the editor's OpenGL context, available with the `femtovg` feature. The
+//! canvas is kept in sync with the window's size and scale, and it is cleared and flushed around
+//! the handler's drawing code every frame. The `femtovg` crate is re-exported from this crate so
+//! its version always matches the canvas.
+
+use baseview::{Event, EventStatus, Window, WindowEvent, WindowHandler, WindowInfo};
+use femtovg::renderer::OpenGl;
+use femtovg::{Canvas, Color};
+use nih_plug::prelude::{nih_log, Editor, GuiContext};
+use std::sync::Arc;
+
+use crate::{present, BaseviewState};
+
+/// A handler that draws to a femtovg [`Canvas`]. The canvas is scaled so the handler can draw in
+/// logical pixels, matching the mouse events' coordinates.
+pub trait FemtovgHandler {
+    /// Draw a frame. The canvas has already been cleared to
+    /// [`FemtovgHandler::background_color()`], and it is flushed and presented afterwards.
+    fn draw(&mut self, window: &mut Window, canvas: &mut Canvas<OpenGl>);
+
+    /// Handle an event. Resize events are also handled by the canvas itself, so there's no need
+    /// to resize it here.
+    fn on_event(&mut self, window: &mut Window, event: Event) -> EventStatus {
+        let _ = (window, event);
+        EventStatus::Ignored
+    }
+
+    /// The color the canvas is cleared to before every frame.
+    fn background_color(&self) -> Color {
+        Color::black()
+    }
+}
+
+/// Create an [`Editor`] that draws to a femtovg [`Canvas`]. `build` is called every time the
+/// window is opened, and it receives the canvas so it can load fonts and images before creating
+/// the handler.
+pub fn create_femtovg_editor<T, B, H>(
+    baseview_state: Arc<BaseviewState>,
+    user_state: T,
+    build: B,
+) -> Option<Box<dyn Editor>>
+where
+    T: 'static + Send + Sync,
+    B: Fn(&Window, WindowInfo, &mut Canvas<OpenGl>, Arc<dyn GuiContext>, &mut T) -> H
+        + 'static
+        + Send
+        + Sync,
+    H: FemtovgHandler + 'static,
+{
+    crate::create_baseview_editor(
+        baseview_state,
+        user_state,
+        move |window, window_info, context, user_state| {
+            let (Some(gl_context), Some(mut canvas)) =
+                (window.gl_context(), create_canvas(window, window_info))
+            else {
+                return FemtovgWindowHandler {
+                    canvas: None,
+                    inner: None,
+                    window_info,
+                };
+            };
+
+            unsafe { gl_context.make_current() };
+            let inner = build(window, window_info, &mut canvas, context, user_state);
+            unsafe { gl_context.make_not_current() };
+
+            FemtovgWindowHandler {
+                canvas: Some(canvas),
+                inner: Some(inner),
+                window_info,
+            }
+        },
+    )
+}
+
+/// Create a canvas on the window's OpenGL context.
+fn create_canvas(window: &Window, window_info: WindowInfo) -> Option<Canvas<OpenGl>> {
+    let gl_context = window.gl_context()?;
+    unsafe { gl_context.make_current() };
+    let canvas = unsafe { OpenGl::new_from_function(|symbol| gl_context.get_proc_address(symbol)) }
+        .and_then(Canvas::new);
+    unsafe { gl_context.make_not_current() };
+
+    match canvas {
+        Ok(mut canvas) => {
+            resize_canvas(&mut canvas, window_info);
+            Some(canvas)
+        }
+        Err(err) => {
+            nih_log!("Could not create the femtovg canvas: {err:?}");
+            None
+        }
+    }
+}
+
+fn resize_canvas(canvas: &mut Canvas<OpenGl>, window_info: WindowInfo) {
+    let physical_size = window_info.physical_size();
+    canvas.set_size(
+        physical_size.width,
+        physical_size.height,
+        window_info.scale() as f32,
+    );
+}
+
+/// Owns the canvas and calls the [`FemtovgHandler`] built by the user. Both are `None` if the
+/// window doesn't have an OpenGL context.
+struct FemtovgWindowHandler<H> {
+    canvas: Option<Canvas<OpenGl>>,
+    inner: Option<H>,
+    window_info: WindowInfo,
+}
+
+impl<H: FemtovgHandler> WindowHandler for FemtovgWindowHandler<H> {
+    fn on_frame(&mut self, window: &mut Window) {
+        let (Some(canvas), Some(inner)) = (&mut self.canvas, &mut self.inner) else {
+            return;
+        };
+        match window.gl_context() {
+            Some(gl_context) => unsafe { gl_context.make_current() },
+            None => return,
+        }
+
+        canvas.clear_rect(
+            0,
+            0,
+            canvas.width(),
+            canvas.height(),
+            inner.background_color(),
+        );
+        canvas.reset();
+        let scale = self.window_info.scale() as f32;
+        canvas.scale(scale, scale);
+        inner.draw(window, canvas);
+        // The handler may have made another context current while drawing
+        if let Some(gl_context) = window.gl_context() {
+            unsafe { gl_context.make_current() };
+            canvas.flush();
+            present::swap_buffers(gl_context);
+            unsafe { gl_context.make_not_current() };
+        }
+    }
+
+    fn on_event(&mut self, window: &mut Window, event: Event) -> EventStatus {
+        if let Event::Window(WindowEvent::Resized(window_info)) = &event {
+            self.window_info = *window_info;
+            if let Some(canvas) = &mut self.canvas {
+                resize_canvas(canvas, *window_info);
+            }
+        }
+
+        let will_close = matches!(event, Event::Window(WindowEvent::WillClose));
+        let status = match &mut self.inner {
+            Some(inner) => inner.on_event(window, event),
+            None => EventStatus::Ignored,
+        };
+
+        // The canvas' GL objects need to be deleted while the context still exists
+        if will_close {
+            if let (Some(canvas), Some(gl_context)) = (self.canvas.take(), window.gl_context()) {
+                unsafe { gl_context.make_current() };
+                drop(canvas);
+                unsafe { gl_context.make_not_current() };
+            }
+        }
+
+        status
+    }
+}
@@ -0,0 +1,100 @@
+//! Native open and save dialogs that don't block the GUI. The dialogs are shown using
+//! [rfd](https://github.com/PolyMeilex/rfd) and they're parented to the editor's window so they
+//! don't end up behind the host's window. The chosen path is passed back on the GUI thread.
+
+use std::path::PathBuf;
+
+use crate::pop_out::RawParent;
+use crate::BaseviewState;
+
+/// Options for the dialogs opened using [`open_file_dialog()`], [`save_file_dialog()`], and
+/// [`pick_folder_dialog()`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileDialogOptions {
+    /// The dialog's title. The platform's default title is used when this is not set.
+    pub title: Option<String>,
+    /// The directory the dialog starts in.
+    pub directory: Option<PathBuf>,
+    /// The file name that's filled in when the dialog opens. Only used for save dialogs.
+    pub file_name: Option<String>,
+    /// Named groups of file extensions without the leading period, like
+    /// `("Audio files", vec!["wav", "flac"])`. Only files matching one of these are shown.
+    pub filters: Vec<(String, Vec<String>)>,
+}
+
+/// Let the user choose an existing file. This needs to be called from the GUI thread while the
+/// editor is open, for instance from the window handler. `on_result` is called on the GUI thread
+/// with the chosen path once the dialog closes, or with `None` if the user cancelled it. The
+/// window handler can pick the result up from there through a channel or shared state, just like
+/// with [`GuiTaskProxy::run()`][crate::GuiTaskProxy::run()].
+///
+/// If the editor isn't open the dialog is shown without a parent window.
+pub fn open_file_dialog(
+    baseview_state: &BaseviewState,
+    options: FileDialogOptions,
+    on_result: impl FnOnce(Option<PathBuf>) + Send + 'static,
+) {
+    let dialog = create_dialog(baseview_state, options);
+    let future = dialog.pick_file();
+
+    baseview_state.gui_task_proxy().spawn(async move {
+        let path = future.await.map(|handle| handle.path().to_path_buf());
+        on_result(path);
+    });
+}
+
+/// Let the user choose where to save a file. This works the same as [`open_file_dialog()`]. The
+/// chosen file does not need to exist yet.
+pub fn save_file_dialog(
+    baseview_state: &BaseviewState,
+    options: FileDialogOptions,
+    on_result: impl FnOnce(Option<PathBuf>) + Send + 'static,
+) {
+    let dialog = create_dialog(baseview_state, options);
+    let future = dialog.save_file();
+
+    baseview_state.gui_task_proxy().spawn(async move {
+        let path = future.await.map(|handle| handle.path().to_path_buf());
+        on_result(path);
+    });
+}
+
+/// Let the user choose a directory. This works the same as [`open_file_dialog()`], and the
+/// options' filters are ignored.
+pub fn pick_folder_dialog(
+    baseview_state: &BaseviewState,
+    options: FileDialogOptions,
+    on_result: impl FnOnce(Option<PathBuf>) + Send + 'static,
+) {
+    let dialog = create_dialog(baseview_state, options);
+    let future = dialog.pick_folder();
+
+    baseview_state.gui_task_proxy().spawn(async move {
+        let path = future.await.map(|handle| handle.path().to_path_buf());
+        on_result(path);
+    });
+}
+
+fn create_dialog(
+    baseview_state: &BaseviewState,
+    options: FileDialogOptions,
+) -> rfd::AsyncFileDialog {
+    let mut dialog = rfd::AsyncFileDialog::new();
+    if let Some(window_handle) = crate::handle::window_handle(baseview_state) {
+        dialog = dialog.set_parent(&RawParent(window_handle.raw()));
+    }
+    if let Some(title) = options.title {
+        dialog = dialog.set_title(&title);
+    }
+    if let Some(directory) = options.directory {
+        dialog = dialog.set_directory(directory);
+    }
+    if let Some(file_name) = options.file_name {
+        dialog = dialog.set_file_name(&file_name);
+    }
+    for (name, extensions) in &options.filters {
+        dialog = dialog.add_filter(name, extensions.as_slice());
+    }
+
+    dialog
+}
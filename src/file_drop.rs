@@ -0,0 +1,224 @@
+//! Files dragged onto the editor from the OS file manager or the host's browser, and files dragged
+//! from the editor into the host.
+
+use baseview::{DropData, Event, MouseEvent, Point, Window};
+use std::path::{Path, PathBuf};
+
+/// A file drag-and-drop event. Use [`file_drop_event()`] to get this from the events passed to
+/// the window handler.
+///
+/// The handler needs to return [`EventStatus::AcceptDrop`] from the entered and moved events to
+/// accept a drop. Otherwise the OS will show that the files cannot be dropped onto the editor.
+///
+/// [`EventStatus::AcceptDrop`]: baseview::EventStatus::AcceptDrop
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileDropEvent<'a> {
+    /// Files are being dragged onto the window.
+    Entered {
+        /// The cursor's position in logical pixels.
+        position: Point,
+        /// The paths of the files being dragged.
+        paths: &'a [PathBuf],
+    },
+    /// The files are being dragged over the window.
+    Moved {
+        /// The cursor's position in logical pixels.
+        position: Point,
+        /// The paths of the files being dragged.
+        paths: &'a [PathBuf],
+    },
+    /// The files were dragged out of the window again, or the drag was cancelled.
+    Left,
+    /// The files were dropped onto the window.
+    Dropped {
+        /// The cursor's position in logical pixels.
+        position: Point,
+        /// The paths of the dropped files.
+        paths: &'a [PathBuf],
+    },
+}
+
+/// Get the file drag-and-drop event from a baseview event, if it is one. Drags that don't contain
+/// any files are ignored.
+pub fn file_drop_event(event: &Event) -> Option<FileDropEvent<'_>> {
+    match event {
+        Event::Mouse(MouseEvent::DragEntered {
+            position,
+            data: DropData::Files(paths),
+            ..
+        }) => Some(FileDropEvent::Entered {
+            position: *position,
+            paths,
+        }),
+        Event::Mouse(MouseEvent::DragMoved {
+            position,
+            data: DropData::Files(paths),
+            ..
+        }) => Some(FileDropEvent::Moved {
+            position: *position,
+            paths,
+        }),
+        Event::Mouse(MouseEvent::DragLeft) => Some(FileDropEvent::Left),
+        Event::Mouse(MouseEvent::DragDropped {
+            position,
+            data: DropData::Files(paths),
+            ..
+        }) => Some(FileDropEvent::Dropped {
+            position: *position,
+            paths,
+        }),
+        _ => None,
+    }
+}
+
+/// Start dragging a file out of the editor, for instance to drag a rendered audio or MIDI file into
+/// the host's timeline. Call this from the window handler while the left mouse button is held down
+/// and the cursor has moved. Returns `true` if the file was dropped somewhere.
+///
+/// On Windows this blocks until the drag ends. Dragging files out of the editor is not yet
+/// supported on X11, where this always returns `false`.
+#[cfg(target_os = "windows")]
+pub fn begin_file_drag(window: &Window, path: &Path) -> bool {
+    use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+    use std::os::windows::ffi::OsStrExt;
+    use winapi::ctypes::c_void;
+    use winapi::shared::guiddef::REFIID;
+    use winapi::shared::minwindef::{DWORD, UINT};
+    use winapi::shared::ntdef::{HRESULT, PCWSTR};
+    use winapi::shared::windef::HWND;
+    use winapi::shared::winerror::{DRAGDROP_S_DROP, S_OK};
+    use winapi::um::combaseapi::CoTaskMemFree;
+    use winapi::um::objidl::IDataObject;
+    use winapi::um::oleidl::{IDropSource, DROPEFFECT_COPY};
+    use winapi::um::shtypes::{PCIDLIST_ABSOLUTE, PCUITEMID_CHILD_ARRAY, PIDLIST_ABSOLUTE};
+    use winapi::Interface;
+
+    // These are not all exposed by `winapi`
+    #[link(name = "shell32")]
+    extern "system" {
+        fn SHParseDisplayName(
+            name: PCWSTR,
+            bind_context: *mut c_void,
+            pidl: *mut PIDLIST_ABSOLUTE,
+            sfgao_in: DWORD,
+            sfgao_out: *mut DWORD,
+        ) -> HRESULT;
+        fn SHCreateDataObject(
+            pidl_folder: PCIDLIST_ABSOLUTE,
+            cidl: UINT,
+            apidl: PCUITEMID_CHILD_ARRAY,
+            inner: *mut IDataObject,
+            riid: REFIID,
+            ppv: *mut *mut c_void,
+        ) -> HRESULT;
+        fn SHDoDragDrop(
+            hwnd: HWND,
+            data_object: *mut IDataObject,
+            drop_source: *mut IDropSource,
+            ok_effects: DWORD,
+            effect: *mut DWORD,
+        ) -> HRESULT;
+    }
+
+    let hwnd = match window.raw_window_handle() {
+        RawWindowHandle::Win32(handle) => handle.hwnd as HWND,
+        _ => return false,
+    };
+    let wide_path: Vec<u16> = path
+        .as_os_str()
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+
+    unsafe {
+        let mut pidl: PIDLIST_ABSOLUTE = std::ptr::null_mut();
+        if SHParseDisplayName(
+            wide_path.as_ptr(),
+            std::ptr::null_mut(),
+            &mut pidl,
+            0,
+            std::ptr::null_mut(),
+        ) != S_OK
+        {
+            return false;
+        }
+
+        // Without a parent folder the item IDs are absolute, which lets the shell create a data
+        // object containing the file for us
+        let mut data_object: *mut IDataObject = std::ptr::null_mut();
+        let pidls = [pidl as _];
+        let result = SHCreateDataObject(
+            std::ptr::null(),
+            1,
+            pidls.as_ptr(),
+            std::ptr::null_mut(),
+            &IDataObject::uuidof(),
+            &mut data_object as *mut *mut IDataObject as *mut *mut c_void,
+        );
+        if result != S_OK || data_object.is_null() {
+            CoTaskMemFree(pidl as *mut _);
+            return false;
+        }
+
+        // The shell provides a default drop source when none is passed
+        let mut effect: DWORD = 0;
+        let result = SHDoDragDrop(
+            hwnd,
+            data_object,
+            std::ptr::null_mut(),
+            DROPEFFECT_COPY,
+            &mut effect,
+        );
+
+        (*data_object).Release();
+        CoTaskMemFree(pidl as *mut _);
+
+        result == DRAGDROP_S_DROP
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub fn begin_file_drag(window: &Window, path: &Path) -> bool {
+    use cocoa::appkit::NSApp;
+    use cocoa::base::{id, nil, NO, YES};
+    use cocoa::foundation::{NSPoint, NSRect, NSSize, NSString};
+    use objc::runtime::BOOL;
+    use objc::{msg_send, sel, sel_impl};
+    use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+
+    let ns_view = match window.raw_window_handle() {
+        RawWindowHandle::AppKit(handle) => handle.ns_view as id,
+        _ => return false,
+    };
+    let path = match path.to_str() {
+        Some(path) => path,
+        None => return false,
+    };
+
+    unsafe {
+        // This needs the mouse event that started the drag
+        let event: id = msg_send![NSApp(), currentEvent];
+        if event == nil {
+            return false;
+        }
+
+        let location: NSPoint = msg_send![event, locationInWindow];
+        let location: NSPoint = msg_send![ns_view, convertPoint: location fromView: nil];
+        let rect = NSRect::new(
+            NSPoint::new(location.x - 16.0, location.y - 16.0),
+            NSSize::new(32.0, 32.0),
+        );
+
+        let ns_path = NSString::alloc(nil).init_str(path);
+        let success: BOOL =
+            msg_send![ns_view, dragFile: ns_path fromRect: rect slideBack: YES event: event];
+        let () = msg_send![ns_path, release];
+
+        success != NO
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub fn begin_file_drag(_window: &Window, _path: &Path) -> bool {
+    false
+}
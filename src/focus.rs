@@ -0,0 +1,330 @@
+//! The keyboard focus conventions VST3 hosts expect from plugin editors. The editor takes keyboard
+//! focus when it is clicked, and it hands focus back to the host when the user presses Escape or
+//! clicks somewhere else in the host's window. Hosts generally don't take focus back themselves
+//! when their own window is clicked, so without this typing in the DAW after touching the editor
+//! would keep going to the editor. Enable or disable this using
+//! [`BaseviewState::set_focus_handshake()`][crate::BaseviewState::set_focus_handshake()].
+
+use baseview::Window;
+
+/// Moves keyboard focus between the editor's window and the host.
+pub(crate) struct FocusHandshake {
+    #[cfg(target_os = "windows")]
+    hwnd: winapi::shared::windef::HWND,
+
+    #[cfg(target_os = "macos")]
+    ns_view: cocoa::base::id,
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    display: *mut x11::xlib::Display,
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    window_id: x11::xlib::Window,
+
+    /// Whether a mouse button was held the last time [`FocusHandshake::clicked_outside()`] was
+    /// called, so a held button only counts as a single click.
+    button_held: bool,
+}
+
+#[cfg(target_os = "windows")]
+impl FocusHandshake {
+    pub fn new(window: &Window) -> Option<Self> {
+        use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+        use winapi::shared::windef::HWND;
+
+        match window.raw_window_handle() {
+            RawWindowHandle::Win32(handle) => Some(Self {
+                hwnd: handle.hwnd as HWND,
+                button_held: false,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Give the editor's window keyboard focus.
+    pub fn claim(&mut self) {
+        unsafe { winapi::um::winuser::SetFocus(self.hwnd) };
+    }
+
+    /// Give keyboard focus back to the window the editor is embedded in.
+    pub fn release(&mut self) {
+        use winapi::um::winuser::{GetParent, SetFocus};
+
+        unsafe {
+            let parent = GetParent(self.hwnd);
+            if !parent.is_null() {
+                SetFocus(parent);
+            }
+        }
+    }
+
+    /// Whether a mouse button was pressed somewhere else in the host's window since the last call.
+    /// Clicks in other top level windows, including the editor's own popups, don't count since
+    /// activating another window already moves the focus.
+    pub fn clicked_outside(&mut self) -> bool {
+        use winapi::um::winuser::{
+            GetAncestor, GetAsyncKeyState, GetCursorPos, IsChild, WindowFromPoint, GA_ROOT,
+            VK_LBUTTON, VK_MBUTTON, VK_RBUTTON,
+        };
+
+        unsafe {
+            let button_held = [VK_LBUTTON, VK_RBUTTON, VK_MBUTTON]
+                .into_iter()
+                .any(|key| GetAsyncKeyState(key) < 0);
+            let pressed = button_held && !self.button_held;
+            self.button_held = button_held;
+            if !pressed {
+                return false;
+            }
+
+            let mut point = std::mem::zeroed();
+            if GetCursorPos(&mut point) == 0 {
+                return false;
+            }
+            let window = WindowFromPoint(point);
+            if window.is_null() || window == self.hwnd || IsChild(self.hwnd, window) != 0 {
+                return false;
+            }
+
+            GetAncestor(window, GA_ROOT) == GetAncestor(self.hwnd, GA_ROOT)
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl FocusHandshake {
+    pub fn new(window: &Window) -> Option<Self> {
+        use cocoa::base::id;
+        use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+
+        match window.raw_window_handle() {
+            RawWindowHandle::AppKit(handle) => Some(Self {
+                ns_view: handle.ns_view as id,
+                button_held: false,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Make the editor's view the first responder.
+    pub fn claim(&mut self) {
+        use cocoa::base::{id, nil};
+        use objc::runtime::BOOL;
+        use objc::{msg_send, sel, sel_impl};
+
+        unsafe {
+            let ns_window: id = msg_send![self.ns_view, window];
+            if ns_window != nil {
+                let _: BOOL = msg_send![ns_window, makeFirstResponder: self.ns_view];
+            }
+        }
+    }
+
+    /// Make the host's view the first responder, or the window itself if that view doesn't accept
+    /// key events.
+    pub fn release(&mut self) {
+        use cocoa::base::{id, nil, NO};
+        use objc::runtime::BOOL;
+        use objc::{msg_send, sel, sel_impl};
+
+        unsafe {
+            let ns_window: id = msg_send![self.ns_view, window];
+            if ns_window == nil {
+                return;
+            }
+
+            let superview: id = msg_send![self.ns_view, superview];
+            let accepted: BOOL = if superview != nil {
+                msg_send![ns_window, makeFirstResponder: superview]
+            } else {
+                NO
+            };
+            if accepted == NO {
+                let _: BOOL = msg_send![ns_window, makeFirstResponder: nil];
+            }
+        }
+    }
+
+    /// Whether a mouse button was pressed somewhere else in the host's window since the last call.
+    /// Clicks in other windows, including the editor's own popups, don't count since making
+    /// another window the key window already moves the focus.
+    pub fn clicked_outside(&mut self) -> bool {
+        use cocoa::base::{id, nil};
+        use cocoa::foundation::{NSPoint, NSRect, NSUInteger};
+        use objc::{class, msg_send, sel, sel_impl};
+
+        unsafe {
+            let pressed_buttons: NSUInteger = msg_send![class!(NSEvent), pressedMouseButtons];
+            let button_held = pressed_buttons != 0;
+            let pressed = button_held && !self.button_held;
+            self.button_held = button_held;
+            if !pressed {
+                return false;
+            }
+
+            let ns_window: id = msg_send![self.ns_view, window];
+            if ns_window == nil {
+                return false;
+            }
+
+            let mouse_location: NSPoint = msg_send![class!(NSEvent), mouseLocation];
+            let window_frame: NSRect = msg_send![ns_window, frame];
+            let bounds: NSRect = msg_send![self.ns_view, bounds];
+            let view_rect: NSRect = msg_send![self.ns_view, convertRect: bounds toView: nil];
+            let view_frame: NSRect = msg_send![ns_window, convertRectToScreen: view_rect];
+
+            contains(window_frame, mouse_location) && !contains(view_frame, mouse_location)
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn contains(rect: cocoa::foundation::NSRect, point: cocoa::foundation::NSPoint) -> bool {
+    point.x >= rect.origin.x
+        && point.x < rect.origin.x + rect.size.width
+        && point.y >= rect.origin.y
+        && point.y < rect.origin.y + rect.size.height
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+impl FocusHandshake {
+    pub fn new(window: &Window) -> Option<Self> {
+        use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+        use std::os::raw::c_ulong;
+
+        let window_id = match window.raw_window_handle() {
+            RawWindowHandle::Xcb(handle) => handle.window as c_ulong,
+            RawWindowHandle::Xlib(handle) => handle.window,
+            _ => return None,
+        };
+
+        let display = unsafe { crate::x11_isolation::open_display() };
+        if display.is_null() {
+            return None;
+        }
+
+        Some(Self {
+            display,
+            window_id,
+            button_held: false,
+        })
+    }
+
+    /// Give the editor's window the input focus. XEmbed embedders also need to be asked for
+    /// focus, which the window handler takes care of.
+    pub fn claim(&mut self) {
+        use x11::xlib;
+
+        unsafe {
+            xlib::XSetInputFocus(
+                self.display,
+                self.window_id,
+                xlib::RevertToParent,
+                xlib::CurrentTime,
+            );
+            xlib::XFlush(self.display);
+        }
+    }
+
+    /// Give the input focus back to the window the editor is embedded in.
+    pub fn release(&mut self) {
+        use x11::xlib;
+
+        unsafe {
+            let parent = crate::reparent::query_parent(self.display, self.window_id);
+            if parent == 0 || parent == xlib::XDefaultRootWindow(self.display) {
+                return;
+            }
+
+            xlib::XSetInputFocus(
+                self.display,
+                parent,
+                xlib::RevertToParent,
+                xlib::CurrentTime,
+            );
+            xlib::XFlush(self.display);
+        }
+    }
+
+    /// Whether a mouse button was pressed somewhere else in the host's window since the last call.
+    /// Clicks in other top level windows, including the editor's own popups, don't count since
+    /// activating another window already moves the focus.
+    pub fn clicked_outside(&mut self) -> bool {
+        use std::os::raw::{c_int, c_uint};
+        use x11::xlib;
+
+        unsafe {
+            let root = xlib::XDefaultRootWindow(self.display);
+            let (mut root_return, mut top_level) = (0, 0);
+            let (mut root_x, mut root_y): (c_int, c_int) = (0, 0);
+            let (mut window_x, mut window_y): (c_int, c_int) = (0, 0);
+            let mut mask: c_uint = 0;
+            if xlib::XQueryPointer(
+                self.display,
+                root,
+                &mut root_return,
+                &mut top_level,
+                &mut root_x,
+                &mut root_y,
+                &mut window_x,
+                &mut window_y,
+                &mut mask,
+            ) == 0
+            {
+                return false;
+            }
+
+            let button_held =
+                mask & (xlib::Button1Mask | xlib::Button2Mask | xlib::Button3Mask) != 0;
+            let pressed = button_held && !self.button_held;
+            self.button_held = button_held;
+            if !pressed || top_level == 0 {
+                return false;
+            }
+
+            // The pointer needs to be in the same top level window as the editor
+            let mut ancestor = self.window_id;
+            loop {
+                let parent = crate::reparent::query_parent(self.display, ancestor);
+                if parent == 0 {
+                    return false;
+                }
+                if parent == root {
+                    break;
+                }
+                ancestor = parent;
+            }
+            if ancestor != top_level {
+                return false;
+            }
+
+            let mut attributes: xlib::XWindowAttributes = std::mem::zeroed();
+            let mut child = 0;
+            if xlib::XGetWindowAttributes(self.display, self.window_id, &mut attributes) == 0
+                || xlib::XTranslateCoordinates(
+                    self.display,
+                    root,
+                    self.window_id,
+                    root_x,
+                    root_y,
+                    &mut window_x,
+                    &mut window_y,
+                    &mut child,
+                ) == 0
+            {
+                return false;
+            }
+
+            window_x < 0
+                || window_y < 0
+                || window_x >= attributes.width
+                || window_y >= attributes.height
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+impl Drop for FocusHandshake {
+    fn drop(&mut self) {
+        unsafe { crate::x11_isolation::close_display(self.display) };
+    }
+}
@@ -0,0 +1,93 @@
+//! Rolling frame time statistics, so plugins can show a performance readout or log regressions
+//! without timing their own handlers. Get them using
+//! [`BaseviewState::frame_stats()`][crate::BaseviewState::frame_stats()].
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// The number of frames the statistics are computed over. This is about two seconds' worth of
+/// frames.
+const WINDOW_FRAMES: usize = 120;
+
+/// How often baseview is expected to call `on_frame()`. Baseview doesn't report the actual
+/// refresh rate, and it uses a fixed rate of about 60 Hz on every platform.
+const EXPECTED_FRAME_INTERVAL: Duration = Duration::from_micros(16_667);
+
+/// Frame time statistics over the last couple of seconds of drawing. A frame's time is the time
+/// spent in the editor's `on_frame()`, including the window handler's drawing and, for renderers
+/// that swap buffers in `on_frame()`, waiting for vsync.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct FrameStats {
+    /// The average frame time.
+    pub mean: Duration,
+    /// The 95th percentile frame time. Only 5% of the frames took longer than this.
+    pub p95: Duration,
+    /// The longest frame time.
+    pub worst: Duration,
+    /// The number of frames that were skipped since the editor was opened, because a frame took
+    /// too long or because the host's GUI thread was busy. Frames skipped while drawing was
+    /// paused don't count.
+    pub dropped_frames: u64,
+    /// The number of frames that were drawn since the editor was opened.
+    pub frames: u64,
+}
+
+/// Collects the frame times for [`FrameStats`].
+#[derive(Debug, Default)]
+pub(crate) struct FrameTimer {
+    /// The frame times of the last [`WINDOW_FRAMES`] frames, oldest first.
+    frame_times: VecDeque<Duration>,
+    /// When the last frame started, if the previous `on_frame()` call drew a frame.
+    last_frame_start: Option<Instant>,
+    dropped_frames: u64,
+    frames: u64,
+}
+
+impl FrameTimer {
+    /// Record a frame that started at `start` and just finished.
+    pub fn record(&mut self, start: Instant) {
+        let frame_time = start.elapsed();
+        if let Some(last_frame_start) = self.last_frame_start {
+            let interval = start.saturating_duration_since(last_frame_start);
+            if interval > EXPECTED_FRAME_INTERVAL * 3 / 2 {
+                let missed = interval.as_secs_f64() / EXPECTED_FRAME_INTERVAL.as_secs_f64();
+                self.dropped_frames += missed.round() as u64 - 1;
+            }
+        }
+
+        if self.frame_times.len() == WINDOW_FRAMES {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(frame_time);
+        self.last_frame_start = Some(start);
+        self.frames += 1;
+    }
+
+    /// Called when `on_frame()` didn't draw anything, so the gap until the next drawn frame isn't
+    /// counted as dropped frames.
+    pub fn skip(&mut self) {
+        self.last_frame_start = None;
+    }
+
+    pub fn stats(&self) -> FrameStats {
+        if self.frame_times.is_empty() {
+            return FrameStats {
+                dropped_frames: self.dropped_frames,
+                frames: self.frames,
+                ..FrameStats::default()
+            };
+        }
+
+        let mut sorted: Vec<Duration> = self.frame_times.iter().copied().collect();
+        sorted.sort_unstable();
+        let p95_idx = ((sorted.len() as f64 * 0.95).ceil() as usize).clamp(1, sorted.len()) - 1;
+
+        FrameStats {
+            mean: sorted.iter().sum::<Duration>() / sorted.len() as u32,
+            p95: sorted[p95_idx],
+            worst: sorted[sorted.len() - 1],
+            dropped_frames: self.dropped_frames,
+            frames: self.frames,
+        }
+    }
+}
@@ -0,0 +1,91 @@
+//! Querying the size of the window's default framebuffer from the window system. Multiplying the
+//! logical size by the scaling factor and rounding the result doesn't always match the size of the
+//! surface the window system actually allocated, most notably on macOS where the backing store of
+//! a Retina display is sized by AppKit. Using the wrong size for the OpenGL viewport leaves a
+//! one pixel seam or cuts off the edge of the GUI.
+
+use baseview::Window;
+
+/// The size of the window's drawable surface in physical pixels, as reported by the window system.
+/// Use this for `glViewport()` and for sizing offscreen framebuffers that get blitted to the
+/// window. On macOS this is the size of the view's backing store, which accounts for the
+/// display's backing scale factor. Returns `None` if the size could not be queried, in which case
+/// [`WindowInfo::physical_size()`][baseview::WindowInfo::physical_size()] is the best guess. On X11
+/// this opens a short lived connection to the X server, so the result should be reused until the
+/// window is resized.
+#[cfg(target_os = "windows")]
+pub fn framebuffer_size(window: &Window) -> Option<(u32, u32)> {
+    use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+    use winapi::shared::windef::{HWND, RECT};
+    use winapi::um::winuser::GetClientRect;
+
+    let hwnd = match window.raw_window_handle() {
+        RawWindowHandle::Win32(handle) => handle.hwnd as HWND,
+        _ => return None,
+    };
+
+    let mut rect: RECT = unsafe { std::mem::zeroed() };
+    if unsafe { GetClientRect(hwnd, &mut rect) } == 0 {
+        return None;
+    }
+
+    Some((
+        (rect.right - rect.left).max(0) as u32,
+        (rect.bottom - rect.top).max(0) as u32,
+    ))
+}
+
+#[cfg(target_os = "macos")]
+pub fn framebuffer_size(window: &Window) -> Option<(u32, u32)> {
+    use cocoa::base::id;
+    use cocoa::foundation::NSRect;
+    use objc::{msg_send, sel, sel_impl};
+    use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+
+    let ns_view = match window.raw_window_handle() {
+        RawWindowHandle::AppKit(handle) => handle.ns_view as id,
+        _ => return None,
+    };
+
+    unsafe {
+        let bounds: NSRect = msg_send![ns_view, bounds];
+        let backing: NSRect = msg_send![ns_view, convertRectToBacking: bounds];
+
+        Some((
+            backing.size.width.round().max(0.0) as u32,
+            backing.size.height.round().max(0.0) as u32,
+        ))
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub fn framebuffer_size(window: &Window) -> Option<(u32, u32)> {
+    use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+    use std::os::raw::c_ulong;
+    use x11::xlib;
+
+    let window_id = match window.raw_window_handle() {
+        RawWindowHandle::Xcb(handle) => handle.window as c_ulong,
+        RawWindowHandle::Xlib(handle) => handle.window,
+        _ => return None,
+    };
+
+    unsafe {
+        let display = crate::x11_isolation::open_display();
+        if display.is_null() {
+            return None;
+        }
+
+        let mut attributes: xlib::XWindowAttributes = std::mem::zeroed();
+        let queried = xlib::XGetWindowAttributes(display, window_id, &mut attributes);
+        crate::x11_isolation::close_display(display);
+        if queried == 0 {
+            return None;
+        }
+
+        Some((
+            attributes.width.max(0) as u32,
+            attributes.height.max(0) as u32,
+        ))
+    }
+}
@@ -0,0 +1,365 @@
+//! Pinch and rotate gestures from trackpads and touch screens. These are not reported by baseview,
+//! and on macOS they would otherwise be passed on to the host.
+
+use baseview::{Point, Window};
+
+/// A gesture reported by [`BaseviewState::take_gesture_events()`].
+///
+/// [`BaseviewState::take_gesture_events()`]: crate::BaseviewState::take_gesture_events()
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GestureEvent {
+    /// A pinch gesture, usually used for zooming.
+    Magnify {
+        /// The cursor's position in logical pixels.
+        position: Point,
+        /// The change in magnification. The content should be scaled by `1.0 + delta`, so
+        /// positive values zoom in.
+        delta: f64,
+    },
+    /// A two-finger rotation.
+    Rotate {
+        /// The cursor's position in logical pixels.
+        position: Point,
+        /// The change in rotation in degrees. Positive values are counterclockwise.
+        delta: f64,
+    },
+}
+
+/// A gesture as reported by the platform, before the cursor position is added.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum RawGesture {
+    Magnify(f64),
+    Rotate(f64),
+}
+
+impl RawGesture {
+    pub fn with_position(self, position: Point) -> GestureEvent {
+        match self {
+            RawGesture::Magnify(delta) => GestureEvent::Magnify { position, delta },
+            RawGesture::Rotate(delta) => GestureEvent::Rotate { position, delta },
+        }
+    }
+}
+
+/// Collects the gestures for a window.
+pub(crate) struct GestureInput {
+    #[cfg(target_os = "windows")]
+    hwnd: winapi::shared::windef::HWND,
+    /// The state shared with the window's subclass procedure. This is boxed so it has a stable
+    /// address.
+    #[cfg(target_os = "windows")]
+    state: Box<std::cell::RefCell<windows::SubclassState>>,
+
+    #[cfg(target_os = "macos")]
+    ns_view: usize,
+    #[cfg(target_os = "macos")]
+    gestures: std::sync::Arc<parking_lot::Mutex<Vec<RawGesture>>>,
+}
+
+#[cfg(target_os = "windows")]
+mod windows {
+    use std::cell::RefCell;
+    use std::f64::consts::PI;
+    use winapi::shared::basetsd::{DWORD_PTR, UINT_PTR};
+    use winapi::shared::minwindef::{LPARAM, LRESULT, UINT, WPARAM};
+    use winapi::shared::windef::HWND;
+    use winapi::um::commctrl::DefSubclassProc;
+    use winapi::um::winuser::{
+        CloseGestureInfoHandle, GetGestureInfo, GetKeyState, GESTUREINFO, GF_BEGIN, GID_ROTATE,
+        GID_ZOOM, HGESTUREINFO, MK_CONTROL, VK_CONTROL, WHEEL_DELTA, WM_GESTURE, WM_MOUSEWHEEL,
+    };
+
+    use super::RawGesture;
+
+    /// Identifies our subclass procedure on the window.
+    pub const SUBCLASS_ID: usize = 0x6e69_6867;
+
+    /// How much a single wheel notch's worth of pinching on a precision touchpad magnifies.
+    const MAGNIFICATION_PER_WHEEL_DELTA: f64 = 0.1;
+
+    #[derive(Default)]
+    pub struct SubclassState {
+        pub gestures: Vec<RawGesture>,
+        /// The distance between the fingers at the last zoom gesture message.
+        last_zoom_distance: u64,
+        /// The rotation in radians at the last rotate gesture message.
+        last_rotation: f64,
+    }
+
+    /// Turns `WM_GESTURE` messages from touch screens and the Ctrl+scroll messages precision
+    /// touchpads send for pinches into gestures. All other messages are passed on to baseview's
+    /// window procedure.
+    pub unsafe extern "system" fn subclass_proc(
+        hwnd: HWND,
+        msg: UINT,
+        wparam: WPARAM,
+        lparam: LPARAM,
+        _id: UINT_PTR,
+        ref_data: DWORD_PTR,
+    ) -> LRESULT {
+        let state = &*(ref_data as *const RefCell<SubclassState>);
+        match msg {
+            // Precision touchpads report pinches as Ctrl+scroll without Ctrl actually being held
+            WM_MOUSEWHEEL if (wparam & MK_CONTROL) != 0 && GetKeyState(VK_CONTROL) >= 0 => {
+                let wheel_delta = ((wparam >> 16) & 0xffff) as u16 as i16;
+                state.borrow_mut().gestures.push(RawGesture::Magnify(
+                    wheel_delta as f64 / WHEEL_DELTA as f64 * MAGNIFICATION_PER_WHEEL_DELTA,
+                ));
+
+                0
+            }
+            WM_GESTURE => {
+                let mut info: GESTUREINFO = std::mem::zeroed();
+                info.cbSize = std::mem::size_of::<GESTUREINFO>() as u32;
+                if GetGestureInfo(lparam as HGESTUREINFO, &mut info) == 0 {
+                    return DefSubclassProc(hwnd, msg, wparam, lparam);
+                }
+
+                // Both gestures report absolute values, so the deltas are computed relative to the
+                // previous message
+                let handled = match info.dwID {
+                    GID_ZOOM => {
+                        let mut state = state.borrow_mut();
+                        let distance = info.ullArguments;
+                        if info.dwFlags & GF_BEGIN == 0 && state.last_zoom_distance > 0 {
+                            let delta = distance as f64 / state.last_zoom_distance as f64 - 1.0;
+                            state.gestures.push(RawGesture::Magnify(delta));
+                        }
+                        state.last_zoom_distance = distance;
+
+                        true
+                    }
+                    GID_ROTATE => {
+                        let mut state = state.borrow_mut();
+                        let rotation =
+                            (info.ullArguments & 0xffff) as f64 / 65535.0 * 4.0 * PI - 2.0 * PI;
+                        if info.dwFlags & GF_BEGIN == 0 {
+                            let delta = rotation - state.last_rotation;
+                            state.gestures.push(RawGesture::Rotate(delta.to_degrees()));
+                        }
+                        state.last_rotation = rotation;
+
+                        true
+                    }
+                    _ => false,
+                };
+
+                if handled {
+                    CloseGestureInfoHandle(lparam as HGESTUREINFO);
+                    0
+                } else {
+                    DefSubclassProc(hwnd, msg, wparam, lparam)
+                }
+            }
+            _ => DefSubclassProc(hwnd, msg, wparam, lparam),
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl GestureInput {
+    /// Start receiving gestures for the window. Returns `None` if this is not supported.
+    pub fn new(window: &Window) -> Option<Self> {
+        use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+        use std::cell::RefCell;
+        use winapi::shared::windef::HWND;
+        use winapi::um::commctrl::SetWindowSubclass;
+        use winapi::um::winuser::{
+            SetGestureConfig, GC_ROTATE, GC_ZOOM, GESTURECONFIG, GID_ROTATE, GID_ZOOM,
+        };
+
+        let hwnd = match window.raw_window_handle() {
+            RawWindowHandle::Win32(handle) => handle.hwnd as HWND,
+            _ => return None,
+        };
+
+        unsafe {
+            let state = Box::new(RefCell::new(windows::SubclassState::default()));
+            if SetWindowSubclass(
+                hwnd,
+                Some(windows::subclass_proc),
+                windows::SUBCLASS_ID,
+                &*state as *const RefCell<_> as usize,
+            ) == 0
+            {
+                return None;
+            }
+
+            // Rotation gestures are disabled by default
+            let mut configs = [
+                GESTURECONFIG {
+                    dwID: GID_ZOOM,
+                    dwWant: GC_ZOOM,
+                    dwBlock: 0,
+                },
+                GESTURECONFIG {
+                    dwID: GID_ROTATE,
+                    dwWant: GC_ROTATE,
+                    dwBlock: 0,
+                },
+            ];
+            SetGestureConfig(
+                hwnd,
+                0,
+                configs.len() as u32,
+                configs.as_mut_ptr(),
+                std::mem::size_of::<GESTURECONFIG>() as u32,
+            );
+
+            Some(Self { hwnd, state })
+        }
+    }
+
+    /// Take the gestures received since the last call.
+    pub fn take_gestures(&mut self) -> Vec<RawGesture> {
+        std::mem::take(&mut self.state.borrow_mut().gestures)
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Drop for GestureInput {
+    fn drop(&mut self) {
+        use winapi::um::commctrl::RemoveWindowSubclass;
+
+        unsafe {
+            RemoveWindowSubclass(
+                self.hwnd,
+                Some(windows::subclass_proc),
+                windows::SUBCLASS_ID,
+            )
+        };
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use cocoa::base::id;
+    use objc::runtime::{Object, Sel};
+    use objc::{msg_send, sel, sel_impl};
+    use parking_lot::Mutex;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use super::RawGesture;
+
+    lazy_static::lazy_static! {
+        /// The gesture queues for the views that currently have gestures enabled. The methods
+        /// added to baseview's view class look up their view in here.
+        pub static ref VIEW_GESTURES: Mutex<HashMap<usize, Arc<Mutex<Vec<RawGesture>>>>> =
+            Mutex::new(HashMap::new());
+    }
+
+    pub extern "C" fn magnify_with_event(this: &Object, _sel: Sel, event: id) {
+        let gestures = VIEW_GESTURES
+            .lock()
+            .get(&(this as *const Object as usize))
+            .cloned();
+        unsafe {
+            match gestures {
+                Some(gestures) => {
+                    let magnification: f64 = msg_send![event, magnification];
+                    gestures.lock().push(RawGesture::Magnify(magnification));
+                }
+                // Let the host handle the gesture if the editor doesn't want it
+                None => {
+                    let next_responder: id = msg_send![this, nextResponder];
+                    let () = msg_send![next_responder, magnifyWithEvent: event];
+                }
+            }
+        }
+    }
+
+    pub extern "C" fn rotate_with_event(this: &Object, _sel: Sel, event: id) {
+        let gestures = VIEW_GESTURES
+            .lock()
+            .get(&(this as *const Object as usize))
+            .cloned();
+        unsafe {
+            match gestures {
+                Some(gestures) => {
+                    let rotation: f32 = msg_send![event, rotation];
+                    gestures.lock().push(RawGesture::Rotate(rotation as f64));
+                }
+                None => {
+                    let next_responder: id = msg_send![this, nextResponder];
+                    let () = msg_send![next_responder, rotateWithEvent: event];
+                }
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl GestureInput {
+    /// Start receiving gestures for the window. Returns `None` if this is not supported.
+    ///
+    /// Baseview's view class does not handle gestures, so the handlers are added to the class at
+    /// runtime. The class is only used by baseview.
+    pub fn new(window: &Window) -> Option<Self> {
+        use objc::runtime::{class_addMethod, object_getClass, Class, Imp, Object};
+        use objc::{sel, sel_impl};
+        use parking_lot::Mutex;
+        use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+        use std::ffi::CString;
+        use std::sync::Arc;
+
+        let ns_view = match window.raw_window_handle() {
+            RawWindowHandle::AppKit(handle) => handle.ns_view as *mut Object,
+            _ => return None,
+        };
+
+        unsafe {
+            // This fails harmlessly if the methods were already added for an earlier window
+            let class = object_getClass(ns_view) as *mut Class;
+            let types = CString::new("v@:@").unwrap();
+            class_addMethod(
+                class,
+                sel!(magnifyWithEvent:),
+                std::mem::transmute::<extern "C" fn(&Object, _, _), Imp>(macos::magnify_with_event),
+                types.as_ptr(),
+            );
+            class_addMethod(
+                class,
+                sel!(rotateWithEvent:),
+                std::mem::transmute::<extern "C" fn(&Object, _, _), Imp>(macos::rotate_with_event),
+                types.as_ptr(),
+            );
+        }
+
+        let gestures = Arc::new(Mutex::new(Vec::new()));
+        macos::VIEW_GESTURES
+            .lock()
+            .insert(ns_view as usize, gestures.clone());
+
+        Some(Self {
+            ns_view: ns_view as usize,
+            gestures,
+        })
+    }
+
+    /// Take the gestures received since the last call.
+    pub fn take_gestures(&mut self) -> Vec<RawGesture> {
+        std::mem::take(&mut *self.gestures.lock())
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl Drop for GestureInput {
+    fn drop(&mut self) {
+        macos::VIEW_GESTURES.lock().remove(&self.ns_view);
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+impl GestureInput {
+    /// Start receiving gestures for the window. Gestures are not yet supported on X11, so this
+    /// always returns `None`.
+    pub fn new(_window: &Window) -> Option<Self> {
+        None
+    }
+
+    /// Take the gestures received since the last call.
+    pub fn take_gestures(&mut self) -> Vec<RawGesture> {
+        Vec::new()
+    }
+}
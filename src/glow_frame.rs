@@ -0,0 +1,110 @@
+//! A minimal frame harness for drawing with raw OpenGL through `glow`, available with the `glow`
+//! feature. Every frame the window's context is made current, the viewport is set to the window's
+//! framebuffer size, the framebuffer is cleared, the draw function is called, and the frame is
+//! presented. The `glow` crate is re-exported from this crate so its version always matches.
+
+use baseview::{Event, EventStatus, Window, WindowEvent, WindowHandler, WindowInfo};
+use glow::HasContext;
+use nih_plug::prelude::{Editor, GuiContext, ParamSetter};
+use parking_lot::RwLock;
+use std::sync::Arc;
+
+use crate::{framebuffer, present, BaseviewState};
+
+/// Create an [`Editor`] that calls `draw` every frame with the window's `glow` context, the
+/// window's current size and scale, and a [`ParamSetter`]. The context is current while `draw`
+/// runs, the viewport covers the whole window, and the framebuffer has been cleared to black.
+/// The user state is kept across reopening the editor.
+pub fn create_glow_editor<T, D>(
+    baseview_state: Arc<BaseviewState>,
+    user_state: T,
+    draw: D,
+) -> Option<Box<dyn Editor>>
+where
+    T: 'static + Send + Sync,
+    D: Fn(&glow::Context, WindowInfo, &ParamSetter, &mut T) + 'static + Send + Sync,
+{
+    let draw = Arc::new(draw);
+
+    crate::create_baseview_editor(
+        baseview_state,
+        Arc::new(RwLock::new(user_state)),
+        move |window, window_info, context, user_state| {
+            let gl = window.gl_context().map(|gl_context| unsafe {
+                glow::Context::from_loader_function(|symbol| gl_context.get_proc_address(symbol))
+            });
+
+            GlowHandler {
+                gui_context: context,
+                user_state: user_state.clone(),
+                draw: draw.clone(),
+
+                gl,
+                window_info,
+                viewport_size: None,
+            }
+        },
+    )
+}
+
+struct GlowHandler<T, D> {
+    gui_context: Arc<dyn GuiContext>,
+    user_state: Arc<RwLock<T>>,
+    draw: Arc<D>,
+
+    /// This is `None` if the window doesn't have an OpenGL context, in which case nothing is
+    /// drawn.
+    gl: Option<glow::Context>,
+    window_info: WindowInfo,
+    /// The framebuffer's size in physical pixels. This is queried from the window system on the
+    /// first frame and after every resize.
+    viewport_size: Option<(u32, u32)>,
+}
+
+impl<T, D> WindowHandler for GlowHandler<T, D>
+where
+    T: Send + Sync,
+    D: Fn(&glow::Context, WindowInfo, &ParamSetter, &mut T) + Send + Sync,
+{
+    fn on_frame(&mut self, window: &mut Window) {
+        let (Some(gl), Some(gl_context)) = (&self.gl, window.gl_context()) else {
+            return;
+        };
+
+        let window_info = self.window_info;
+        let (viewport_width, viewport_height) = *self.viewport_size.get_or_insert_with(|| {
+            framebuffer::framebuffer_size(window).unwrap_or_else(|| {
+                let physical_size = window_info.physical_size();
+                (physical_size.width, physical_size.height)
+            })
+        });
+        let param_setter = ParamSetter::new(self.gui_context.as_ref());
+        unsafe {
+            gl_context.make_current();
+
+            gl.viewport(0, 0, viewport_width as i32, viewport_height as i32);
+            gl.clear_color(0.0, 0.0, 0.0, 1.0);
+            gl.clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT | glow::STENCIL_BUFFER_BIT);
+            (self.draw)(
+                gl,
+                self.window_info,
+                &param_setter,
+                &mut self.user_state.write(),
+            );
+
+            present::swap_buffers(gl_context);
+            gl_context.make_not_current();
+        }
+    }
+
+    fn on_event(&mut self, _window: &mut Window, event: Event) -> EventStatus {
+        match event {
+            Event::Window(WindowEvent::Resized(window_info)) => {
+                self.window_info = window_info;
+                self.viewport_size = None;
+                EventStatus::Captured
+            }
+            _ => EventStatus::Ignored,
+        }
+    }
+}
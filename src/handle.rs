@@ -0,0 +1,201 @@
+//! Adapters for handing out window handles to renderers that use different versions of
+//! `raw-window-handle`. Baseview uses version 0.5, and with the `rwh_06` feature the same handles
+//! can also be consumed through the version 0.6 traits.
+
+use raw_window_handle::{
+    HasRawDisplayHandle, HasRawWindowHandle, RawDisplayHandle, RawWindowHandle,
+};
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::BaseviewState;
+
+/// Used to tell the [`HandleRegistration`]s on a thread apart.
+static NEXT_REGISTRATION_ID: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    /// The handles of the editor windows whose handlers run on this thread, along with their
+    /// registration's ID and the address of their [`BaseviewState`]. The raw handles should only
+    /// be used on the window's thread, so they're never stored anywhere another thread can reach.
+    static WINDOW_HANDLES: RefCell<Vec<(u64, usize, WindowHandleAdapter)>> =
+        RefCell::new(Vec::new());
+}
+
+/// A window's raw window and display handles that can be passed to renderers expecting either
+/// version 0.5 or 0.6 of the `raw-window-handle` traits, depending on the enabled features. This
+/// is created from the [`baseview::Window`] passed to the build function or to the window
+/// handler's callbacks, so it's only ever available on the window's thread.
+///
+/// The handles are only valid for as long as the window they were created from is open. On X11
+/// the display handle is baseview's own connection for the window. The adapter isn't `Send`, since
+/// the handles should only be used on the window's thread.
+#[derive(Debug, Clone, Copy)]
+pub struct WindowHandleAdapter {
+    window: RawWindowHandle,
+    display: RawDisplayHandle,
+}
+
+impl WindowHandleAdapter {
+    /// Capture the raw window and display handles of a window, for instance the
+    /// [`baseview::Window`] passed to the build function. Create a new adapter after the window
+    /// has been rebuilt, since the old window's handles are no longer valid.
+    pub fn new<W: HasRawWindowHandle + HasRawDisplayHandle>(window: &W) -> Self {
+        Self {
+            window: window.raw_window_handle(),
+            display: window.raw_display_handle(),
+        }
+    }
+
+    /// The underlying `raw-window-handle` 0.5 window handle.
+    pub fn raw(&self) -> RawWindowHandle {
+        self.window
+    }
+
+    /// The underlying `raw-window-handle` 0.5 display handle.
+    pub fn raw_display(&self) -> RawDisplayHandle {
+        self.display
+    }
+}
+
+/// Makes an editor window's handle available to the bridge's helpers running on the window's
+/// thread through [`window_handle()`], for as long as this is alive. The window's handler holds on
+/// to this, so it's dropped on the same thread.
+pub(crate) struct HandleRegistration {
+    id: u64,
+}
+
+impl HandleRegistration {
+    pub fn new(baseview_state: &BaseviewState, window: &baseview::Window) -> Self {
+        let id = NEXT_REGISTRATION_ID.fetch_add(1, Ordering::Relaxed);
+        let state_address = baseview_state as *const BaseviewState as usize;
+        WINDOW_HANDLES.with(|handles| {
+            handles
+                .borrow_mut()
+                .push((id, state_address, WindowHandleAdapter::new(window)))
+        });
+
+        Self { id }
+    }
+}
+
+impl Drop for HandleRegistration {
+    fn drop(&mut self) {
+        // `try_with()` fails if the handler is dropped while the thread is shutting down
+        let _ = WINDOW_HANDLES.try_with(|handles| {
+            handles.borrow_mut().retain(|(id, _, _)| *id != self.id);
+        });
+    }
+}
+
+/// The handle of the editor's window if it's open and its handler runs on the current thread.
+/// When the window is being rebuilt, this is the newest window's handle. Returns `None` on any
+/// other thread.
+pub(crate) fn window_handle(baseview_state: &BaseviewState) -> Option<WindowHandleAdapter> {
+    let state_address = baseview_state as *const BaseviewState as usize;
+    WINDOW_HANDLES.with(|handles| {
+        handles
+            .borrow()
+            .iter()
+            .rev()
+            .find(|(_, address, _)| *address == state_address)
+            .map(|(_, _, handle)| *handle)
+    })
+}
+
+#[cfg(feature = "rwh_05")]
+unsafe impl HasRawWindowHandle for WindowHandleAdapter {
+    fn raw_window_handle(&self) -> RawWindowHandle {
+        self.window
+    }
+}
+
+/// This is the display handle baseview reported for the window, so on X11 it points to the
+/// connection baseview uses for the window.
+#[cfg(feature = "rwh_05")]
+unsafe impl HasRawDisplayHandle for WindowHandleAdapter {
+    fn raw_display_handle(&self) -> RawDisplayHandle {
+        self.display
+    }
+}
+
+#[cfg(feature = "rwh_06")]
+impl raw_window_handle_06::HasWindowHandle for WindowHandleAdapter {
+    fn window_handle(
+        &self,
+    ) -> Result<raw_window_handle_06::WindowHandle<'_>, raw_window_handle_06::HandleError> {
+        use raw_window_handle_06 as rwh_06;
+        use std::num::{NonZeroIsize, NonZeroU32};
+        use std::ptr::NonNull;
+
+        let unavailable = rwh_06::HandleError::Unavailable;
+        let raw_handle = match self.window {
+            RawWindowHandle::Xcb(handle) => {
+                let mut new_handle = rwh_06::XcbWindowHandle::new(
+                    NonZeroU32::new(handle.window).ok_or(unavailable)?,
+                );
+                new_handle.visual_id = NonZeroU32::new(handle.visual_id);
+                rwh_06::RawWindowHandle::Xcb(new_handle)
+            }
+            RawWindowHandle::Xlib(handle) => {
+                let mut new_handle = rwh_06::XlibWindowHandle::new(handle.window);
+                new_handle.visual_id = handle.visual_id;
+                rwh_06::RawWindowHandle::Xlib(new_handle)
+            }
+            RawWindowHandle::AppKit(handle) => rwh_06::RawWindowHandle::AppKit(
+                rwh_06::AppKitWindowHandle::new(NonNull::new(handle.ns_view).ok_or(unavailable)?),
+            ),
+            RawWindowHandle::Win32(handle) => {
+                let mut new_handle = rwh_06::Win32WindowHandle::new(
+                    NonZeroIsize::new(handle.hwnd as isize).ok_or(unavailable)?,
+                );
+                new_handle.hinstance = NonZeroIsize::new(handle.hinstance as isize);
+                rwh_06::RawWindowHandle::Win32(new_handle)
+            }
+            _ => return Err(rwh_06::HandleError::NotSupported),
+        };
+
+        // SAFETY: The handle lives as long as the window this adapter was created from, which is
+        //         the same contract the 0.5 traits have
+        Ok(unsafe { rwh_06::WindowHandle::borrow_raw(raw_handle) })
+    }
+}
+
+/// Fails with [`HandleError::Unavailable`][raw_window_handle_06::HandleError::Unavailable] if
+/// baseview reported an X11 display handle without a connection, so a null connection is never
+/// handed out.
+#[cfg(feature = "rwh_06")]
+impl raw_window_handle_06::HasDisplayHandle for WindowHandleAdapter {
+    fn display_handle(
+        &self,
+    ) -> Result<raw_window_handle_06::DisplayHandle<'_>, raw_window_handle_06::HandleError> {
+        use raw_window_handle_06 as rwh_06;
+        use std::ptr::NonNull;
+
+        let unavailable = rwh_06::HandleError::Unavailable;
+        let raw_handle = match self.display {
+            RawDisplayHandle::Xcb(handle) => {
+                rwh_06::RawDisplayHandle::Xcb(rwh_06::XcbDisplayHandle::new(
+                    Some(NonNull::new(handle.connection).ok_or(unavailable)?),
+                    handle.screen,
+                ))
+            }
+            RawDisplayHandle::Xlib(handle) => {
+                rwh_06::RawDisplayHandle::Xlib(rwh_06::XlibDisplayHandle::new(
+                    Some(NonNull::new(handle.display).ok_or(unavailable)?),
+                    handle.screen,
+                ))
+            }
+            RawDisplayHandle::AppKit(_) => {
+                rwh_06::RawDisplayHandle::AppKit(rwh_06::AppKitDisplayHandle::new())
+            }
+            RawDisplayHandle::Windows(_) => {
+                rwh_06::RawDisplayHandle::Windows(rwh_06::WindowsDisplayHandle::new())
+            }
+            _ => return Err(rwh_06::HandleError::NotSupported),
+        };
+
+        // SAFETY: The X11 connection lives as long as the window this adapter was created from,
+        //         and the other platforms' display handles don't point to anything
+        Ok(unsafe { rwh_06::DisplayHandle::borrow_raw(raw_handle) })
+    }
+}
@@ -0,0 +1,114 @@
+//! Known host behaviors that need workarounds. The host is detected from the name of the process
+//! the plugin is loaded in, and the matching [`HostQuirks`] are applied automatically. Plugins can
+//! override them using
+//! [`BaseviewState::set_host_quirks()`][crate::BaseviewState::set_host_quirks()].
+
+/// A host with known quirks. See [`Host::detect()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Host {
+    AbletonLive,
+    Bitwig,
+    FlStudio,
+    Reaper,
+    /// Any other host, or a host that could not be detected.
+    Unknown,
+}
+
+/// Workarounds for host behaviors. Get the ones for the current host using
+/// [`HostQuirks::detect()`], or start from [`HostQuirks::default()`] to only enable the
+/// workarounds that are harmless in every host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HostQuirks {
+    /// Rebuild the editor's window when the host changes the scaling factor while the editor is
+    /// open. Ableton Live does this when its window is moved to another monitor. Otherwise the
+    /// new scaling factor is only used the next time the editor opens. Enabled by default.
+    pub rebuild_on_scale_change: bool,
+    /// Close the previous window when the host spawns the editor again without dropping the
+    /// previous handle. REAPER does this when docking and undocking the plugin window, which would
+    /// otherwise leak the old window. Enabled by default.
+    pub close_stale_windows: bool,
+    /// The host hides and reparents the editor's window behind the plugin's back, so the window
+    /// may count as hidden while it's on the screen. FL Studio does this when the plugin window is
+    /// detached or docked. This keeps rendering while the window seems to be hidden, regardless of
+    /// [`BaseviewState::set_pause_when_hidden()`][crate::BaseviewState::set_pause_when_hidden()].
+    pub unreliable_visibility: bool,
+    /// The plugin runs in a separate sandbox process from the host's GUI. Bitwig does this by
+    /// default. The host's window then belongs to another process, so popups don't close
+    /// themselves when the host's window loses focus since that can't be detected reliably.
+    pub sandboxed: bool,
+}
+
+impl Default for HostQuirks {
+    fn default() -> Self {
+        Self {
+            rebuild_on_scale_change: true,
+            close_stale_windows: true,
+            unreliable_visibility: false,
+            sandboxed: false,
+        }
+    }
+}
+
+impl HostQuirks {
+    /// The quirks of the host the plugin is currently loaded in.
+    pub fn detect() -> Self {
+        Self::for_host(Host::detect())
+    }
+
+    /// The workarounds needed for `host`.
+    pub fn for_host(host: Host) -> Self {
+        let defaults = Self::default();
+        match host {
+            Host::AbletonLive | Host::Reaper | Host::Unknown => defaults,
+            Host::Bitwig => Self {
+                sandboxed: true,
+                ..defaults
+            },
+            Host::FlStudio => Self {
+                unreliable_visibility: true,
+                ..defaults
+            },
+        }
+    }
+}
+
+impl Host {
+    /// Detect the host from the name of the current process. This is cached after the first call.
+    /// Hosts that load plugins in a separate process, like Bitwig, are detected from the name of
+    /// that process.
+    pub fn detect() -> Self {
+        lazy_static::lazy_static! {
+            static ref HOST: Host = Host::from_process_name(&current_process_name());
+        }
+
+        *HOST
+    }
+
+    /// Match a process' file name without the extension against the known hosts.
+    fn from_process_name(name: &str) -> Self {
+        let name = name.to_lowercase();
+        if name.starts_with("ableton live") || name == "live" {
+            Host::AbletonLive
+        } else if name.starts_with("bitwig") {
+            Host::Bitwig
+        } else if name == "fl" || name == "fl64" || name == "flstudio" || name == "ilbridge" {
+            Host::FlStudio
+        } else if name.starts_with("reaper") {
+            Host::Reaper
+        } else {
+            Host::Unknown
+        }
+    }
+}
+
+/// The current executable's file name without the extension, or an empty string if that can't be
+/// determined.
+fn current_process_name() -> String {
+    std::env::current_exe()
+        .ok()
+        .and_then(|path| {
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,226 @@
+//! Loading the editor's build function from a dynamic library and reloading it whenever the
+//! library is rebuilt, so GUI changes can be tested without restarting the host. This is only meant
+//! for development, and it is only available with the `hot_reload` feature.
+
+use baseview::{Event, EventStatus, Window, WindowEvent, WindowHandler, WindowInfo};
+use libloading::Library;
+use nih_plug::prelude::{nih_log, Editor, GuiContext};
+use parking_lot::RwLock;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::BaseviewState;
+
+/// The signature of the build function the library exports. Define it in the library like this,
+/// with the same `T` as the user state passed to [`create_hot_reload_editor()`]:
+///
+/// ```ignore
+/// #[no_mangle]
+/// pub fn baseview_build(
+///     window: &baseview::Window,
+///     window_info: baseview::WindowInfo,
+///     context: Arc<dyn GuiContext>,
+///     user_state: &mut MyState,
+/// ) -> Box<dyn baseview::WindowHandler> {
+///     Box::new(MyHandler::new(window, window_info, context))
+/// }
+/// ```
+///
+/// The library and the plugin need to be built with the same compiler version, since this uses
+/// the Rust ABI.
+pub type HotReloadBuildFn<T> =
+    fn(&Window, WindowInfo, Arc<dyn GuiContext>, &mut T) -> Box<dyn WindowHandler>;
+
+/// How often the library's modification time is checked.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// How long the library needs to stay unchanged before it is loaded, so a library the linker is
+/// still writing doesn't get loaded.
+const SETTLE_TIME: Duration = Duration::from_millis(250);
+
+/// Used to give every copy of the library a unique file name.
+static NEXT_COPY_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Create an editor whose build function is loaded from the dynamic library at `library_path`.
+/// `symbol` is the name of the build function, which should have the [`HotReloadBuildFn`]
+/// signature. Whenever the library changes on disk the handler is dropped, the new library is
+/// loaded, and the handler is built again in the same window. The user state is kept across
+/// reloads.
+///
+/// Unloading Rust libraries is not fully supported by all platforms, so only use this during
+/// development.
+pub fn create_hot_reload_editor<T>(
+    baseview_state: Arc<BaseviewState>,
+    user_state: T,
+    library_path: impl Into<PathBuf>,
+    symbol: &str,
+) -> Option<Box<dyn Editor>>
+where
+    T: 'static + Send + Sync,
+{
+    let library_path = library_path.into();
+    let symbol = symbol.to_owned();
+
+    crate::create_baseview_editor(
+        baseview_state,
+        Arc::new(RwLock::new(user_state)),
+        move |window, window_info, context, user_state| {
+            let mut handler = HotReloadHandler {
+                library_path: library_path.clone(),
+                symbol: symbol.clone(),
+                user_state: user_state.clone(),
+                context,
+                window_info,
+
+                inner: None,
+                library: None,
+                library_modified: None,
+                last_poll: Instant::now(),
+            };
+            handler.reload(window);
+
+            handler
+        },
+    )
+}
+
+/// Forwards everything to the handler built by the library, and reloads the library when it
+/// changes.
+struct HotReloadHandler<T> {
+    library_path: PathBuf,
+    symbol: String,
+    user_state: Arc<RwLock<T>>,
+    context: Arc<dyn GuiContext>,
+    /// The window's current size and scale, passed to the build function on reloads.
+    window_info: WindowInfo,
+
+    /// The handler built by the library. This needs to be dropped before the library is unloaded.
+    inner: Option<Box<dyn WindowHandler>>,
+    /// The loaded copy of the library, and the path to that copy.
+    library: Option<(Library, PathBuf)>,
+    /// The modification time of the library that is currently loaded.
+    library_modified: Option<SystemTime>,
+    last_poll: Instant,
+}
+
+impl<T> HotReloadHandler<T> {
+    /// Drop the current handler and unload the library it came from.
+    fn unload(&mut self) {
+        // The handler's code lives in the library
+        self.inner = None;
+        if let Some((library, copy_path)) = self.library.take() {
+            drop(library);
+            let _ = std::fs::remove_file(copy_path);
+        }
+    }
+
+    /// Drop the current handler, and load the library and build a new handler.
+    fn reload(&mut self, window: &Window) {
+        self.unload();
+
+        self.library_modified = modified_time(&self.library_path);
+        match load_library(&self.library_path) {
+            Ok((library, copy_path)) => {
+                // The symbol borrows the library, so it needs to be gone before storing the library
+                self.inner = {
+                    let build =
+                        unsafe { library.get::<HotReloadBuildFn<T>>(self.symbol.as_bytes()) };
+                    match build {
+                        Ok(build) => {
+                            nih_log!("Loaded the editor from '{}'", self.library_path.display());
+                            Some(build(
+                                window,
+                                self.window_info,
+                                self.context.clone(),
+                                &mut self.user_state.write(),
+                            ))
+                        }
+                        Err(err) => {
+                            nih_log!("Could not find '{}': {err}", self.symbol);
+                            None
+                        }
+                    }
+                };
+
+                self.library = Some((library, copy_path));
+            }
+            Err(err) => nih_log!("Could not load '{}': {err}", self.library_path.display()),
+        }
+    }
+
+    /// Whether the library changed since it was loaded and has finished being written.
+    fn library_changed(&mut self) -> bool {
+        if self.last_poll.elapsed() < POLL_INTERVAL {
+            return false;
+        }
+        self.last_poll = Instant::now();
+
+        match modified_time(&self.library_path) {
+            Some(modified) => {
+                Some(modified) != self.library_modified
+                    && modified.elapsed().map_or(true, |age| age >= SETTLE_TIME)
+            }
+            None => false,
+        }
+    }
+}
+
+impl<T: Send + Sync> WindowHandler for HotReloadHandler<T> {
+    fn on_frame(&mut self, window: &mut Window) {
+        if self.library_changed() {
+            self.reload(window);
+        }
+
+        if let Some(inner) = &mut self.inner {
+            inner.on_frame(window);
+        }
+    }
+
+    fn on_event(&mut self, window: &mut Window, event: Event) -> EventStatus {
+        if let Event::Window(WindowEvent::Resized(window_info)) = &event {
+            self.window_info = *window_info;
+        }
+
+        match &mut self.inner {
+            Some(inner) => inner.on_event(window, event),
+            None => EventStatus::Ignored,
+        }
+    }
+}
+
+impl<T> Drop for HotReloadHandler<T> {
+    fn drop(&mut self) {
+        self.unload();
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Load a copy of the library. Windows doesn't allow overwriting a loaded library, and other
+/// platforms may return the already loaded library when loading the same path again.
+fn load_library(library_path: &Path) -> Result<(Library, PathBuf), Box<dyn std::error::Error>> {
+    let file_name = library_path
+        .file_name()
+        .ok_or("The library path does not contain a file name")?
+        .to_string_lossy();
+    let copy_path = std::env::temp_dir().join(format!(
+        "{}-{}-{}",
+        std::process::id(),
+        NEXT_COPY_ID.fetch_add(1, Ordering::Relaxed),
+        file_name
+    ));
+    std::fs::copy(library_path, &copy_path)?;
+
+    match unsafe { Library::new(&copy_path) } {
+        Ok(library) => Ok((library, copy_path)),
+        Err(err) => {
+            let _ = std::fs::remove_file(&copy_path);
+            Err(err.into())
+        }
+    }
+}
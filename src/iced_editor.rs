@@ -0,0 +1,560 @@
+//! A bridge for iced GUIs, available with the `iced` feature. The bridge owns iced's runtime: it
+//! translates baseview's input to iced events, runs the editor's commands on the GUI thread, and
+//! draws the editor to the window's OpenGL context with `iced_glow`. The `iced_native` and
+//! `iced_glow` crates are re-exported from this crate so their versions always match the bridge.
+
+use baseview::{
+    Event, EventStatus, MouseButton, MouseCursor, MouseEvent, ScrollDelta, Window, WindowEvent,
+    WindowHandler, WindowInfo,
+};
+use crossbeam::queue::SegQueue;
+use iced_glow::glow;
+use iced_native::command::Action;
+use iced_native::program::{Program, State};
+use iced_native::{clipboard, keyboard, mouse, renderer, window, Command, Debug, Point, Size};
+use keyboard_types::{Code, KeyState, KeyboardEvent, Modifiers};
+use nih_plug::prelude::{nih_log, Editor, GuiContext, ParamPtr};
+use std::fmt;
+use std::sync::Arc;
+
+use crate::{present, BaseviewState, GuiTaskProxy};
+
+/// The renderer used by [`IcedEditor`]s.
+pub type Renderer = iced_glow::Renderer<iced_native::Theme>;
+/// An element in an [`IcedEditor`]'s view.
+pub type Element<'a, Message> = iced_native::Element<'a, Message, Renderer>;
+
+/// An iced GUI for a plugin. This is created again every time the editor is opened.
+pub trait IcedEditor: 'static + Send + Sync + Sized {
+    type Message: 'static + Clone + fmt::Debug + Send;
+    /// Passed to [`IcedEditor::new()`] every time the editor is opened. Use this for the plugin's
+    /// parameters and any other shared state.
+    type InitializationFlags: 'static + Clone + Send + Sync;
+
+    fn new(
+        initialization_flags: Self::InitializationFlags,
+        context: Arc<dyn GuiContext>,
+    ) -> (Self, Command<Self::Message>);
+
+    /// The context passed to [`IcedEditor::new()`], used to set parameters.
+    fn context(&self) -> &dyn GuiContext;
+
+    fn update(&mut self, message: Self::Message) -> Command<Self::Message>;
+
+    fn view(&self) -> Element<'_, Self::Message>;
+
+    /// The color the window is cleared to before drawing the view.
+    fn background_color(&self) -> iced_native::Color {
+        iced_native::Color::WHITE
+    }
+
+    fn theme(&self) -> iced_native::Theme {
+        iced_native::Theme::default()
+    }
+
+    /// The message sent to [`IcedEditor::update()`] when the host changes a parameter's value,
+    /// for instance through automation. Return `None` to not be notified of these changes. The
+    /// view is redrawn every frame either way.
+    fn param_values_changed(&self) -> Option<Self::Message> {
+        None
+    }
+
+    /// Handle a [`ParamMessage`] sent by one of the editor's widgets. Call this from
+    /// [`IcedEditor::update()`] when receiving the message variant that wraps these.
+    fn handle_param_message(&self, message: ParamMessage) {
+        // We can't use the typed ParamSetter here since the messages only contain the pointers
+        let context = self.context();
+        unsafe {
+            match message {
+                ParamMessage::BeginSetParameter(p) => context.raw_begin_set_parameter(p),
+                ParamMessage::SetParameterNormalized(p, v) => {
+                    context.raw_set_parameter_normalized(p, v)
+                }
+                ParamMessage::EndSetParameter(p) => context.raw_end_set_parameter(p),
+            }
+        }
+    }
+}
+
+/// A parameter change from the editor's widgets, meant to be wrapped in the editor's message type
+/// and passed to [`IcedEditor::handle_param_message()`]. Use these the same way as the
+/// [`ParamSetter`][nih_plug::prelude::ParamSetter] methods with the same names.
+#[derive(Debug, Clone, Copy)]
+pub enum ParamMessage {
+    BeginSetParameter(ParamPtr),
+    SetParameterNormalized(ParamPtr, f32),
+    EndSetParameter(ParamPtr),
+}
+
+/// Create an [`Editor`] for an [`IcedEditor`]. The editor is created using
+/// [`IcedEditor::new()`] with `initialization_flags` every time the window is opened.
+pub fn create_iced_editor<E: IcedEditor>(
+    baseview_state: Arc<BaseviewState>,
+    initialization_flags: E::InitializationFlags,
+) -> Option<Box<dyn Editor>> {
+    let handler_state = baseview_state.clone();
+
+    crate::create_baseview_editor(
+        baseview_state,
+        initialization_flags,
+        move |window, window_info, context, initialization_flags| {
+            IcedHandler::<E>::new(
+                window,
+                window_info,
+                handler_state.clone(),
+                context,
+                initialization_flags.clone(),
+            )
+        },
+    )
+}
+
+/// Wraps the [`IcedEditor`] so it can be driven by iced's [`State`].
+struct IcedProgram<E: IcedEditor> {
+    editor: E,
+}
+
+impl<E: IcedEditor> Program for IcedProgram<E> {
+    type Renderer = Renderer;
+    type Message = E::Message;
+
+    fn update(&mut self, message: Self::Message) -> Command<Self::Message> {
+        self.editor.update(message)
+    }
+
+    fn view(&self) -> Element<'_, Self::Message> {
+        self.editor.view()
+    }
+}
+
+/// The parts of the handler that need the window's OpenGL context. This is `None` if the window
+/// doesn't have a context.
+struct IcedRenderer {
+    gl: glow::Context,
+    renderer: Renderer,
+}
+
+struct IcedHandler<E: IcedEditor> {
+    baseview_state: Arc<BaseviewState>,
+    task_proxy: GuiTaskProxy,
+    /// Messages produced by the editor's commands, queued on the GUI thread.
+    messages: Arc<SegQueue<E::Message>>,
+
+    state: Option<State<IcedProgram<E>>>,
+    renderer: Option<IcedRenderer>,
+    debug: Debug,
+    clipboard: SystemClipboard,
+
+    window_info: WindowInfo,
+    cursor_position: Point,
+}
+
+impl<E: IcedEditor> IcedHandler<E> {
+    fn new(
+        window: &Window,
+        window_info: WindowInfo,
+        baseview_state: Arc<BaseviewState>,
+        context: Arc<dyn GuiContext>,
+        initialization_flags: E::InitializationFlags,
+    ) -> Self {
+        let mut handler = Self {
+            task_proxy: baseview_state.gui_task_proxy(),
+            baseview_state,
+            messages: Arc::new(SegQueue::new()),
+
+            state: None,
+            renderer: None,
+            debug: Debug::new(),
+            clipboard: SystemClipboard,
+
+            window_info,
+            cursor_position: Point::new(-1.0, -1.0),
+        };
+
+        let Some(gl_context) = window.gl_context() else {
+            return handler;
+        };
+        let mut renderer = unsafe {
+            gl_context.make_current();
+            let gl =
+                glow::Context::from_loader_function(|symbol| gl_context.get_proc_address(symbol));
+            let renderer = Renderer::new(iced_glow::Backend::new(&gl, Default::default()));
+            gl_context.make_not_current();
+
+            IcedRenderer { gl, renderer }
+        };
+
+        let (editor, command) = E::new(initialization_flags, context);
+        handler.state = Some(State::new(
+            IcedProgram { editor },
+            handler.logical_size(),
+            &mut renderer.renderer,
+            &mut handler.debug,
+        ));
+        handler.renderer = Some(renderer);
+        handler.run_command(command);
+
+        handler
+    }
+
+    fn logical_size(&self) -> Size {
+        let logical_size = self.window_info.logical_size();
+        Size::new(logical_size.width as f32, logical_size.height as f32)
+    }
+
+    /// Run a command's futures on the GUI thread and queue the resulting messages.
+    fn run_command(&mut self, command: Command<E::Message>) {
+        for action in command.actions() {
+            match action {
+                Action::Future(future) => {
+                    let messages = self.messages.clone();
+                    self.task_proxy
+                        .spawn(async move { messages.push(future.await) });
+                }
+                Action::Clipboard(clipboard::Action::Read(read)) => {
+                    self.messages.push(read(crate::clipboard::get_text()));
+                }
+                Action::Clipboard(clipboard::Action::Write(text)) => {
+                    crate::clipboard::set_text(&text);
+                }
+                _ => nih_log!("The iced bridge does not support this command, ignoring it"),
+            }
+        }
+    }
+
+    fn queue_event(&mut self, event: iced_native::Event) {
+        if let Some(state) = &mut self.state {
+            state.queue_event(event);
+        }
+    }
+}
+
+impl<E: IcedEditor> WindowHandler for IcedHandler<E> {
+    fn on_frame(&mut self, window: &mut Window) {
+        let (Some(state), Some(renderer), Some(gl_context)) =
+            (&mut self.state, &mut self.renderer, window.gl_context())
+        else {
+            return;
+        };
+
+        if self
+            .baseview_state
+            .params_changed
+            .swap(false, std::sync::atomic::Ordering::AcqRel)
+        {
+            if let Some(message) = state.program().editor.param_values_changed() {
+                state.queue_message(message);
+            }
+        }
+        while let Some(message) = self.messages.pop() {
+            state.queue_message(message);
+        }
+
+        let logical_size = self.window_info.logical_size();
+        let bounds = Size::new(logical_size.width as f32, logical_size.height as f32);
+        let theme = state.program().editor.theme();
+        let style = renderer::Style {
+            text_color: theme.palette().text,
+        };
+        let (_, command) = state.update(
+            bounds,
+            self.cursor_position,
+            &mut renderer.renderer,
+            &theme,
+            &style,
+            &mut self.clipboard,
+            &mut self.debug,
+        );
+        self.baseview_state
+            .set_mouse_cursor(translate_mouse_interaction(state.mouse_interaction()));
+
+        let background_color = state.program().editor.background_color();
+        let physical_size = self.window_info.physical_size();
+        let viewport = iced_glow::Viewport::with_physical_size(
+            Size::new(physical_size.width, physical_size.height),
+            self.window_info.scale(),
+        );
+        let overlay = self.debug.overlay();
+        unsafe {
+            use glow::HasContext;
+
+            gl_context.make_current();
+
+            let gl = &renderer.gl;
+            gl.viewport(
+                0,
+                0,
+                physical_size.width as i32,
+                physical_size.height as i32,
+            );
+            gl.clear_color(
+                background_color.r,
+                background_color.g,
+                background_color.b,
+                background_color.a,
+            );
+            gl.clear(glow::COLOR_BUFFER_BIT);
+            renderer.renderer.with_primitives(|backend, primitives| {
+                backend.present(gl, primitives, &viewport, &overlay);
+            });
+
+            present::swap_buffers(gl_context);
+            gl_context.make_not_current();
+        }
+
+        if let Some(command) = command {
+            self.run_command(command);
+        }
+    }
+
+    fn on_event(&mut self, window: &mut Window, event: Event) -> EventStatus {
+        let modifiers = translate_modifiers(self.baseview_state.modifiers());
+        match event {
+            Event::Mouse(MouseEvent::CursorMoved { position, .. }) => {
+                self.cursor_position = Point::new(position.x as f32, position.y as f32);
+                self.queue_event(iced_native::Event::Mouse(mouse::Event::CursorMoved {
+                    position: self.cursor_position,
+                }));
+            }
+            Event::Mouse(MouseEvent::ButtonPressed { button, .. }) => {
+                self.queue_event(iced_native::Event::Mouse(mouse::Event::ButtonPressed(
+                    translate_mouse_button(button),
+                )));
+            }
+            Event::Mouse(MouseEvent::ButtonReleased { button, .. }) => {
+                self.queue_event(iced_native::Event::Mouse(mouse::Event::ButtonReleased(
+                    translate_mouse_button(button),
+                )));
+            }
+            Event::Mouse(MouseEvent::WheelScrolled { delta, .. }) => {
+                let delta = match delta {
+                    ScrollDelta::Lines { x, y } => mouse::ScrollDelta::Lines { x, y },
+                    ScrollDelta::Pixels { x, y } => mouse::ScrollDelta::Pixels { x, y },
+                };
+                self.queue_event(iced_native::Event::Mouse(mouse::Event::WheelScrolled {
+                    delta,
+                }));
+            }
+            Event::Mouse(MouseEvent::CursorEntered) => {
+                self.queue_event(iced_native::Event::Mouse(mouse::Event::CursorEntered));
+            }
+            Event::Mouse(MouseEvent::CursorLeft) => {
+                self.cursor_position = Point::new(-1.0, -1.0);
+                self.queue_event(iced_native::Event::Mouse(mouse::Event::CursorLeft));
+            }
+            Event::Keyboard(key_event) => {
+                self.handle_key_event(key_event, modifiers);
+                // iced doesn't report whether a widget used the key, so this can't let the host
+                // handle unused keys
+            }
+            Event::Window(WindowEvent::Resized(window_info)) => {
+                self.window_info = window_info;
+                let logical_size = window_info.logical_size();
+                self.queue_event(iced_native::Event::Window(window::Event::Resized {
+                    width: logical_size.width.round() as u32,
+                    height: logical_size.height.round() as u32,
+                }));
+            }
+            Event::Window(WindowEvent::Focused) => {
+                self.queue_event(iced_native::Event::Window(window::Event::Focused));
+            }
+            Event::Window(WindowEvent::Unfocused) => {
+                self.queue_event(iced_native::Event::Window(window::Event::Unfocused));
+            }
+            Event::Window(WindowEvent::WillClose) => {
+                // The renderer's GL objects need to be deleted while the context still exists
+                self.state = None;
+                if let (Some(renderer), Some(gl_context)) =
+                    (self.renderer.take(), window.gl_context())
+                {
+                    unsafe { gl_context.make_current() };
+                    drop(renderer);
+                    unsafe { gl_context.make_not_current() };
+                }
+            }
+            _ => return EventStatus::Ignored,
+        }
+
+        EventStatus::Captured
+    }
+}
+
+impl<E: IcedEditor> IcedHandler<E> {
+    fn handle_key_event(&mut self, key_event: KeyboardEvent, modifiers: keyboard::Modifiers) {
+        if let Some(key_code) = translate_key_code(key_event.code) {
+            let event = match key_event.state {
+                KeyState::Down => keyboard::Event::KeyPressed {
+                    key_code,
+                    modifiers,
+                },
+                KeyState::Up => keyboard::Event::KeyReleased {
+                    key_code,
+                    modifiers,
+                },
+            };
+            self.queue_event(iced_native::Event::Keyboard(event));
+        }
+
+        if let Some(text) = crate::keyboard::text_input(&key_event) {
+            for c in text.chars() {
+                self.queue_event(iced_native::Event::Keyboard(
+                    keyboard::Event::CharacterReceived(c),
+                ));
+            }
+        }
+        self.queue_event(iced_native::Event::Keyboard(
+            keyboard::Event::ModifiersChanged(modifiers),
+        ));
+    }
+}
+
+/// iced's clipboard, backed by [`crate::clipboard`].
+struct SystemClipboard;
+
+impl iced_native::Clipboard for SystemClipboard {
+    fn read(&self) -> Option<String> {
+        crate::clipboard::get_text()
+    }
+
+    fn write(&mut self, contents: String) {
+        crate::clipboard::set_text(&contents);
+    }
+}
+
+fn translate_modifiers(modifiers: Modifiers) -> keyboard::Modifiers {
+    let mut result = keyboard::Modifiers::empty();
+    result.set(
+        keyboard::Modifiers::SHIFT,
+        modifiers.contains(Modifiers::SHIFT),
+    );
+    result.set(
+        keyboard::Modifiers::CTRL,
+        modifiers.contains(Modifiers::CONTROL),
+    );
+    result.set(keyboard::Modifiers::ALT, modifiers.contains(Modifiers::ALT));
+    result.set(
+        keyboard::Modifiers::LOGO,
+        modifiers.contains(Modifiers::META),
+    );
+
+    result
+}
+
+fn translate_mouse_button(button: MouseButton) -> mouse::Button {
+    match button {
+        MouseButton::Left => mouse::Button::Left,
+        MouseButton::Right => mouse::Button::Right,
+        MouseButton::Middle => mouse::Button::Middle,
+        MouseButton::Back => mouse::Button::Other(4),
+        MouseButton::Forward => mouse::Button::Other(5),
+        MouseButton::Other(button) => mouse::Button::Other(button as u16),
+    }
+}
+
+fn translate_mouse_interaction(interaction: mouse::Interaction) -> MouseCursor {
+    match interaction {
+        mouse::Interaction::Idle => MouseCursor::Default,
+        mouse::Interaction::Pointer => MouseCursor::Hand,
+        mouse::Interaction::Grab => MouseCursor::Hand,
+        mouse::Interaction::Grabbing => MouseCursor::HandGrabbing,
+        mouse::Interaction::Text => MouseCursor::Text,
+        mouse::Interaction::Crosshair => MouseCursor::Crosshair,
+        mouse::Interaction::Working => MouseCursor::Working,
+        mouse::Interaction::ResizingHorizontally => MouseCursor::EwResize,
+        mouse::Interaction::ResizingVertically => MouseCursor::NsResize,
+        mouse::Interaction::NotAllowed => MouseCursor::NotAllowed,
+    }
+}
+
+/// iced uses the physical key for key codes.
+fn translate_key_code(code: Code) -> Option<keyboard::KeyCode> {
+    use keyboard::KeyCode;
+
+    let key_code = match code {
+        Code::Digit0 => KeyCode::Key0,
+        Code::Digit1 => KeyCode::Key1,
+        Code::Digit2 => KeyCode::Key2,
+        Code::Digit3 => KeyCode::Key3,
+        Code::Digit4 => KeyCode::Key4,
+        Code::Digit5 => KeyCode::Key5,
+        Code::Digit6 => KeyCode::Key6,
+        Code::Digit7 => KeyCode::Key7,
+        Code::Digit8 => KeyCode::Key8,
+        Code::Digit9 => KeyCode::Key9,
+        Code::KeyA => KeyCode::A,
+        Code::KeyB => KeyCode::B,
+        Code::KeyC => KeyCode::C,
+        Code::KeyD => KeyCode::D,
+        Code::KeyE => KeyCode::E,
+        Code::KeyF => KeyCode::F,
+        Code::KeyG => KeyCode::G,
+        Code::KeyH => KeyCode::H,
+        Code::KeyI => KeyCode::I,
+        Code::KeyJ => KeyCode::J,
+        Code::KeyK => KeyCode::K,
+        Code::KeyL => KeyCode::L,
+        Code::KeyM => KeyCode::M,
+        Code::KeyN => KeyCode::N,
+        Code::KeyO => KeyCode::O,
+        Code::KeyP => KeyCode::P,
+        Code::KeyQ => KeyCode::Q,
+        Code::KeyR => KeyCode::R,
+        Code::KeyS => KeyCode::S,
+        Code::KeyT => KeyCode::T,
+        Code::KeyU => KeyCode::U,
+        Code::KeyV => KeyCode::V,
+        Code::KeyW => KeyCode::W,
+        Code::KeyX => KeyCode::X,
+        Code::KeyY => KeyCode::Y,
+        Code::KeyZ => KeyCode::Z,
+        Code::Escape => KeyCode::Escape,
+        Code::F1 => KeyCode::F1,
+        Code::F2 => KeyCode::F2,
+        Code::F3 => KeyCode::F3,
+        Code::F4 => KeyCode::F4,
+        Code::F5 => KeyCode::F5,
+        Code::F6 => KeyCode::F6,
+        Code::F7 => KeyCode::F7,
+        Code::F8 => KeyCode::F8,
+        Code::F9 => KeyCode::F9,
+        Code::F10 => KeyCode::F10,
+        Code::F11 => KeyCode::F11,
+        Code::F12 => KeyCode::F12,
+        Code::Insert => KeyCode::Insert,
+        Code::Home => KeyCode::Home,
+        Code::Delete => KeyCode::Delete,
+        Code::End => KeyCode::End,
+        Code::PageDown => KeyCode::PageDown,
+        Code::PageUp => KeyCode::PageUp,
+        Code::ArrowLeft => KeyCode::Left,
+        Code::ArrowUp => KeyCode::Up,
+        Code::ArrowRight => KeyCode::Right,
+        Code::ArrowDown => KeyCode::Down,
+        Code::Backspace => KeyCode::Backspace,
+        Code::Enter => KeyCode::Enter,
+        Code::Space => KeyCode::Space,
+        Code::Tab => KeyCode::Tab,
+        Code::Minus => KeyCode::Minus,
+        Code::Equal => KeyCode::Equals,
+        Code::BracketLeft => KeyCode::LBracket,
+        Code::BracketRight => KeyCode::RBracket,
+        Code::Backslash => KeyCode::Backslash,
+        Code::Semicolon => KeyCode::Semicolon,
+        Code::Quote => KeyCode::Apostrophe,
+        Code::Backquote => KeyCode::Grave,
+        Code::Comma => KeyCode::Comma,
+        Code::Period => KeyCode::Period,
+        Code::Slash => KeyCode::Slash,
+        Code::ShiftLeft => KeyCode::LShift,
+        Code::ShiftRight => KeyCode::RShift,
+        Code::ControlLeft => KeyCode::LControl,
+        Code::ControlRight => KeyCode::RControl,
+        Code::AltLeft => KeyCode::LAlt,
+        Code::AltRight => KeyCode::RAlt,
+        Code::MetaLeft => KeyCode::LWin,
+        Code::MetaRight => KeyCode::RWin,
+        _ => return None,
+    };
+
+    Some(key_code)
+}
@@ -0,0 +1,413 @@
+//! A ready-made Dear ImGui editor, available with the `imgui` feature. This is meant for quick
+//! debug and development GUIs. It translates baseview's input to ImGui's IO, draws ImGui's output
+//! to the window's OpenGL context with `imgui-glow-renderer`, and calls an update function with a
+//! [`ParamSetter`] every frame.
+
+use baseview::{
+    Event, EventStatus, MouseButton, MouseCursor, MouseEvent, ScrollDelta, Window, WindowEvent,
+    WindowHandler, WindowInfo,
+};
+use glow::HasContext;
+use imgui::{ClipboardBackend, Context, Key, SuspendedContext, Ui};
+use imgui_glow_renderer::AutoRenderer;
+use keyboard_types::{Code, KeyState, KeyboardEvent, Modifiers};
+use nih_plug::prelude::{nih_log, Editor, GuiContext, ParamSetter};
+use parking_lot::RwLock;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::{clipboard, keyboard, present, BaseviewState};
+
+/// How many pixels correspond to a single line of scrolling.
+const PIXELS_PER_SCROLL_LINE: f32 = 50.0;
+
+/// Create an [`Editor`] that draws a Dear ImGui GUI. `build` is called once after the window is
+/// opened to set up the ImGui context, for instance to add fonts or change the style. `update` is
+/// called every frame to draw the GUI. The user state is kept across reopening the editor.
+pub fn create_imgui_editor<T, B, U>(
+    baseview_state: Arc<BaseviewState>,
+    user_state: T,
+    build: B,
+    update: U,
+) -> Option<Box<dyn Editor>>
+where
+    T: 'static + Send + Sync,
+    B: Fn(&mut Context, &mut T) + 'static + Send + Sync,
+    U: Fn(&Ui, &ParamSetter, &mut T) + 'static + Send + Sync,
+{
+    let user_state = Arc::new(RwLock::new(user_state));
+    let update = Arc::new(update);
+    let handler_state = baseview_state.clone();
+
+    // The user state is shared with the handler directly, so the bridge's own state is unused
+    crate::create_baseview_editor(
+        baseview_state,
+        (),
+        move |window, window_info, context, _| {
+            let mut handler = ImguiHandler {
+                baseview_state: handler_state.clone(),
+                gui_context: context,
+                user_state: user_state.clone(),
+                update: update.clone(),
+
+                imgui: Some(SuspendedContext::create()),
+                reported_inactive_context: false,
+                renderer: None,
+                window_info,
+                last_frame: Instant::now(),
+            };
+            handler.with_imgui(|handler, imgui| {
+                // The host's working directory is not a good place for `imgui.ini`
+                imgui.set_ini_filename(None);
+                imgui.set_clipboard_backend(SystemClipboard);
+                build(imgui, &mut handler.user_state.write());
+
+                handler.renderer = create_renderer(window, imgui);
+                handler.update_display_size(imgui);
+            });
+
+            handler
+        },
+    )
+}
+
+struct ImguiHandler<T, U> {
+    baseview_state: Arc<BaseviewState>,
+    gui_context: Arc<dyn GuiContext>,
+    user_state: Arc<RwLock<T>>,
+    update: Arc<U>,
+
+    /// ImGui allows only one active context per thread, and every editor window on the GUI thread
+    /// gets its own context. The context is only activated while the handler uses it, see
+    /// [`ImguiHandler::with_imgui()`]. This is only `None` while it is active.
+    imgui: Option<SuspendedContext>,
+    /// Set after failing to activate the context so the failure is only logged once.
+    reported_inactive_context: bool,
+    /// This is `None` if the renderer could not be created, in which case nothing is drawn.
+    renderer: Option<AutoRenderer>,
+    window_info: WindowInfo,
+    last_frame: Instant,
+}
+
+/// Create the renderer on the window's OpenGL context.
+fn create_renderer(window: &Window, imgui: &mut Context) -> Option<AutoRenderer> {
+    let gl_context = window.gl_context()?;
+    unsafe { gl_context.make_current() };
+    let gl = unsafe {
+        glow::Context::from_loader_function(|symbol| gl_context.get_proc_address(symbol))
+    };
+    let renderer = AutoRenderer::initialize(gl, imgui);
+    unsafe { gl_context.make_not_current() };
+
+    match renderer {
+        Ok(renderer) => Some(renderer),
+        Err(err) => {
+            nih_log!("Could not create the ImGui renderer: {err}");
+            None
+        }
+    }
+}
+
+impl<T, U> ImguiHandler<T, U> {
+    /// Activate the handler's ImGui context, call `f` with it, and suspend it again. Returns
+    /// `None` without calling `f` if some other ImGui context is active on this thread, for
+    /// instance because the plugin uses ImGui itself and didn't suspend its own context.
+    fn with_imgui<R>(&mut self, f: impl FnOnce(&mut Self, &mut Context) -> R) -> Option<R> {
+        let suspended = self.imgui.take()?;
+        match suspended.activate() {
+            Ok(mut imgui) => {
+                let result = f(self, &mut imgui);
+                self.imgui = Some(imgui.suspend());
+
+                Some(result)
+            }
+            Err(suspended) => {
+                if !self.reported_inactive_context {
+                    nih_log!(
+                        "Could not activate the ImGui context since another ImGui context is \
+                         active on this thread"
+                    );
+                    self.reported_inactive_context = true;
+                }
+                self.imgui = Some(suspended);
+
+                None
+            }
+        }
+    }
+
+    fn update_display_size(&self, imgui: &mut Context) {
+        let logical_size = self.window_info.logical_size();
+        let scale = self.window_info.scale() as f32;
+
+        let io = imgui.io_mut();
+        io.display_size = [logical_size.width as f32, logical_size.height as f32];
+        io.display_framebuffer_scale = [scale, scale];
+    }
+
+    fn handle_key_event(imgui: &mut Context, key_event: KeyboardEvent) -> EventStatus {
+        let io = imgui.io_mut();
+        if let Some(key) = translate_key(key_event.code) {
+            io.add_key_event(key, key_event.state == KeyState::Down);
+        }
+        if let Some(text) = keyboard::text_input(&key_event) {
+            for c in text.chars() {
+                io.add_input_character(c);
+            }
+        }
+
+        // Let the host handle keys like Space when no text field has keyboard focus
+        if io.want_capture_keyboard {
+            EventStatus::Captured
+        } else {
+            EventStatus::Ignored
+        }
+    }
+
+    fn draw(&mut self, window: &mut Window, imgui: &mut Context) {
+        let now = Instant::now();
+        let io = imgui.io_mut();
+        io.update_delta_time(now - self.last_frame);
+        self.last_frame = now;
+
+        let modifiers = self.baseview_state.modifiers();
+        io.add_key_event(Key::ModCtrl, modifiers.contains(Modifiers::CONTROL));
+        io.add_key_event(Key::ModShift, modifiers.contains(Modifiers::SHIFT));
+        io.add_key_event(Key::ModAlt, modifiers.contains(Modifiers::ALT));
+        io.add_key_event(Key::ModSuper, modifiers.contains(Modifiers::META));
+
+        let param_setter = ParamSetter::new(self.gui_context.as_ref());
+        let ui = imgui.new_frame();
+        (self.update)(ui, &param_setter, &mut self.user_state.write());
+        let mouse_cursor = ui.mouse_cursor();
+        let draw_data = imgui.render();
+
+        self.baseview_state
+            .set_mouse_cursor(translate_mouse_cursor(mouse_cursor));
+
+        let (Some(renderer), Some(gl_context)) = (&mut self.renderer, window.gl_context()) else {
+            return;
+        };
+        let physical_size = self.window_info.physical_size();
+        unsafe {
+            gl_context.make_current();
+
+            let gl = renderer.gl_context();
+            gl.viewport(
+                0,
+                0,
+                physical_size.width as i32,
+                physical_size.height as i32,
+            );
+            gl.clear_color(0.0, 0.0, 0.0, 1.0);
+            gl.clear(glow::COLOR_BUFFER_BIT);
+            if let Err(err) = renderer.render(draw_data) {
+                nih_log!("Could not render the ImGui frame: {err}");
+            }
+
+            present::swap_buffers(gl_context);
+            gl_context.make_not_current();
+        }
+    }
+
+    fn handle_input_event(&mut self, imgui: &mut Context, event: Event) -> EventStatus {
+        let io = imgui.io_mut();
+        match event {
+            Event::Mouse(MouseEvent::CursorMoved { position, .. }) => {
+                io.add_mouse_pos_event([position.x as f32, position.y as f32]);
+            }
+            Event::Mouse(MouseEvent::ButtonPressed { button, .. }) => {
+                let Some(button) = translate_mouse_button(button) else {
+                    return EventStatus::Ignored;
+                };
+                io.add_mouse_button_event(button, true);
+            }
+            Event::Mouse(MouseEvent::ButtonReleased { button, .. }) => {
+                let Some(button) = translate_mouse_button(button) else {
+                    return EventStatus::Ignored;
+                };
+                io.add_mouse_button_event(button, false);
+            }
+            Event::Mouse(MouseEvent::WheelScrolled { delta, .. }) => {
+                let [x, y] = match delta {
+                    ScrollDelta::Lines { x, y } => [x, y],
+                    ScrollDelta::Pixels { x, y } => [
+                        x / PIXELS_PER_SCROLL_LINE / self.window_info.scale() as f32,
+                        y / PIXELS_PER_SCROLL_LINE / self.window_info.scale() as f32,
+                    ],
+                };
+                io.add_mouse_wheel_event([x, y]);
+            }
+            Event::Mouse(MouseEvent::CursorLeft) => {
+                io.add_mouse_pos_event([f32::MIN, f32::MIN]);
+            }
+            Event::Keyboard(key_event) => return Self::handle_key_event(imgui, key_event),
+            Event::Window(WindowEvent::Resized(_)) => self.update_display_size(imgui),
+            _ => return EventStatus::Ignored,
+        }
+
+        EventStatus::Captured
+    }
+}
+
+impl<T, U> WindowHandler for ImguiHandler<T, U>
+where
+    U: Fn(&Ui, &ParamSetter, &mut T),
+{
+    fn on_frame(&mut self, window: &mut Window) {
+        self.with_imgui(|handler, imgui| handler.draw(window, imgui));
+    }
+
+    fn on_event(&mut self, window: &mut Window, event: Event) -> EventStatus {
+        match event {
+            Event::Window(WindowEvent::Resized(window_info)) => self.window_info = window_info,
+            Event::Window(WindowEvent::WillClose) => {
+                // The renderer's GL objects need to be deleted while the context still exists
+                if let (Some(renderer), Some(gl_context)) =
+                    (self.renderer.take(), window.gl_context())
+                {
+                    unsafe { gl_context.make_current() };
+                    drop(renderer);
+                    unsafe { gl_context.make_not_current() };
+                }
+
+                return EventStatus::Captured;
+            }
+            _ => (),
+        }
+
+        self.with_imgui(|handler, imgui| handler.handle_input_event(imgui, event))
+            .unwrap_or(EventStatus::Ignored)
+    }
+}
+
+/// ImGui's clipboard, backed by [`crate::clipboard`].
+struct SystemClipboard;
+
+impl ClipboardBackend for SystemClipboard {
+    fn get(&mut self) -> Option<String> {
+        clipboard::get_text()
+    }
+
+    fn set(&mut self, value: &str) {
+        clipboard::set_text(value);
+    }
+}
+
+fn translate_mouse_button(button: MouseButton) -> Option<imgui::MouseButton> {
+    match button {
+        MouseButton::Left => Some(imgui::MouseButton::Left),
+        MouseButton::Right => Some(imgui::MouseButton::Right),
+        MouseButton::Middle => Some(imgui::MouseButton::Middle),
+        MouseButton::Back => Some(imgui::MouseButton::Extra1),
+        MouseButton::Forward => Some(imgui::MouseButton::Extra2),
+        MouseButton::Other(_) => None,
+    }
+}
+
+fn translate_mouse_cursor(mouse_cursor: Option<imgui::MouseCursor>) -> MouseCursor {
+    match mouse_cursor {
+        None => MouseCursor::Hidden,
+        Some(imgui::MouseCursor::Arrow) => MouseCursor::Default,
+        Some(imgui::MouseCursor::TextInput) => MouseCursor::Text,
+        Some(imgui::MouseCursor::ResizeAll) => MouseCursor::AllScroll,
+        Some(imgui::MouseCursor::ResizeNS) => MouseCursor::NsResize,
+        Some(imgui::MouseCursor::ResizeEW) => MouseCursor::EwResize,
+        Some(imgui::MouseCursor::ResizeNESW) => MouseCursor::NeswResize,
+        Some(imgui::MouseCursor::ResizeNWSE) => MouseCursor::NwseResize,
+        Some(imgui::MouseCursor::Hand) => MouseCursor::Hand,
+        Some(imgui::MouseCursor::NotAllowed) => MouseCursor::NotAllowed,
+    }
+}
+
+/// ImGui's keys are physical keys.
+fn translate_key(code: Code) -> Option<Key> {
+    let key = match code {
+        Code::Tab => Key::Tab,
+        Code::ArrowLeft => Key::LeftArrow,
+        Code::ArrowRight => Key::RightArrow,
+        Code::ArrowUp => Key::UpArrow,
+        Code::ArrowDown => Key::DownArrow,
+        Code::PageUp => Key::PageUp,
+        Code::PageDown => Key::PageDown,
+        Code::Home => Key::Home,
+        Code::End => Key::End,
+        Code::Insert => Key::Insert,
+        Code::Delete => Key::Delete,
+        Code::Backspace => Key::Backspace,
+        Code::Space => Key::Space,
+        Code::Enter => Key::Enter,
+        Code::NumpadEnter => Key::KeypadEnter,
+        Code::Escape => Key::Escape,
+        Code::ControlLeft => Key::LeftCtrl,
+        Code::ControlRight => Key::RightCtrl,
+        Code::ShiftLeft => Key::LeftShift,
+        Code::ShiftRight => Key::RightShift,
+        Code::AltLeft => Key::LeftAlt,
+        Code::AltRight => Key::RightAlt,
+        Code::MetaLeft => Key::LeftSuper,
+        Code::MetaRight => Key::RightSuper,
+        Code::Digit0 => Key::Alpha0,
+        Code::Digit1 => Key::Alpha1,
+        Code::Digit2 => Key::Alpha2,
+        Code::Digit3 => Key::Alpha3,
+        Code::Digit4 => Key::Alpha4,
+        Code::Digit5 => Key::Alpha5,
+        Code::Digit6 => Key::Alpha6,
+        Code::Digit7 => Key::Alpha7,
+        Code::Digit8 => Key::Alpha8,
+        Code::Digit9 => Key::Alpha9,
+        Code::KeyA => Key::A,
+        Code::KeyB => Key::B,
+        Code::KeyC => Key::C,
+        Code::KeyD => Key::D,
+        Code::KeyE => Key::E,
+        Code::KeyF => Key::F,
+        Code::KeyG => Key::G,
+        Code::KeyH => Key::H,
+        Code::KeyI => Key::I,
+        Code::KeyJ => Key::J,
+        Code::KeyK => Key::K,
+        Code::KeyL => Key::L,
+        Code::KeyM => Key::M,
+        Code::KeyN => Key::N,
+        Code::KeyO => Key::O,
+        Code::KeyP => Key::P,
+        Code::KeyQ => Key::Q,
+        Code::KeyR => Key::R,
+        Code::KeyS => Key::S,
+        Code::KeyT => Key::T,
+        Code::KeyU => Key::U,
+        Code::KeyV => Key::V,
+        Code::KeyW => Key::W,
+        Code::KeyX => Key::X,
+        Code::KeyY => Key::Y,
+        Code::KeyZ => Key::Z,
+        Code::F1 => Key::F1,
+        Code::F2 => Key::F2,
+        Code::F3 => Key::F3,
+        Code::F4 => Key::F4,
+        Code::F5 => Key::F5,
+        Code::F6 => Key::F6,
+        Code::F7 => Key::F7,
+        Code::F8 => Key::F8,
+        Code::F9 => Key::F9,
+        Code::F10 => Key::F10,
+        Code::F11 => Key::F11,
+        Code::F12 => Key::F12,
+        Code::Minus => Key::Minus,
+        Code::Equal => Key::Equal,
+        Code::BracketLeft => Key::LeftBracket,
+        Code::BracketRight => Key::RightBracket,
+        Code::Backslash => Key::Backslash,
+        Code::Semicolon => Key::Semicolon,
+        Code::Quote => Key::Apostrophe,
+        Code::Backquote => Key::GraveAccent,
+        Code::Comma => Key::Comma,
+        Code::Period => Key::Period,
+        Code::Slash => Key::Slash,
+        _ => return None,
+    };
+
+    Some(key)
+}
@@ -0,0 +1,320 @@
+//! Forwarding keyboard events the editor doesn't handle to the host, so keyboard shortcuts like
+//! Space for starting playback keep working while the editor has focus.
+
+use baseview::Window;
+use keyboard_types::{Code, Key, KeyState, KeyboardEvent, Modifiers};
+
+/// Decides which key events the editor keeps to itself and which ones are passed on to the host.
+/// Set this using [`BaseviewState::set_keyboard_policy()`][crate::BaseviewState::set_keyboard_policy()].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub enum KeyboardPolicy {
+    /// The editor swallows all key events, even if the window handler ignores them.
+    CaptureAll,
+    /// Key events the window handler ignores by returning [`EventStatus::Ignored`] are passed on
+    /// to the host. This is the default.
+    ///
+    /// [`EventStatus::Ignored`]: baseview::EventStatus::Ignored
+    #[default]
+    PassthroughUnhandled,
+    /// Only the keys in this list are passed on to the host, and the window handler never
+    /// receives them. All other key events are swallowed by the editor.
+    PassthroughList(Vec<Code>),
+}
+
+/// Get the text a key event would insert into a text field, if any. This filters out key releases,
+/// control characters, and shortcuts using Ctrl, Alt, or Cmd. Characters produced through dead keys
+/// are included since baseview already combines those into a single event.
+///
+/// IME composition is not supported. Baseview handles the platform's window messages itself and
+/// doesn't report composition events, so there's no preedit text to show while composing, and CJK
+/// input methods only work to the extent the platform commits composed text as regular key events.
+/// Passing composition events on to the handler needs support for them in baseview first.
+pub fn text_input(event: &KeyboardEvent) -> Option<&str> {
+    if event.state != KeyState::Down || event.is_composing {
+        return None;
+    }
+
+    // AltGr shows up as Ctrl+Alt on Windows, so that combination can still produce text
+    let altgr = event
+        .modifiers
+        .contains(Modifiers::CONTROL | Modifiers::ALT);
+    if event.modifiers.contains(Modifiers::META)
+        || (!altgr
+            && event
+                .modifiers
+                .intersects(Modifiers::CONTROL | Modifiers::ALT))
+    {
+        return None;
+    }
+
+    match &event.key {
+        Key::Character(text) if !text.chars().any(char::is_control) => Some(text),
+        _ => None,
+    }
+}
+
+/// The modifiers that are held after a key event. A key event's modifiers don't always reflect the
+/// modifier key that is being pressed or released itself, so that key is applied on top.
+pub(crate) fn modifiers_after(event: &KeyboardEvent) -> Modifiers {
+    let key_modifier = match event.key {
+        Key::Shift => Modifiers::SHIFT,
+        Key::Control => Modifiers::CONTROL,
+        Key::Alt => Modifiers::ALT,
+        Key::AltGraph => Modifiers::ALT_GRAPH,
+        Key::Meta | Key::Super => Modifiers::META,
+        _ => return event.modifiers,
+    };
+
+    match event.state {
+        KeyState::Down => event.modifiers | key_modifier,
+        KeyState::Up => event.modifiers - key_modifier,
+    }
+}
+
+/// Forwards key events the window handler ignored to the window's parent. Baseview already passes
+/// ignored key events on to the next responder on macOS, so this only needs to do something on
+/// Windows and X11. Only keys with a known physical location can be forwarded.
+pub(crate) struct KeyForwarder {
+    /// The display connection used for sending the key events. Opening one for every keystroke
+    /// would be too slow, so this is opened when the first key is forwarded and kept until the
+    /// window closes. This is null until then.
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    display: *mut x11::xlib::Display,
+}
+
+#[cfg(target_os = "windows")]
+impl KeyForwarder {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Forward a key event to the window's parent.
+    pub fn forward(&mut self, window: &Window, event: &KeyboardEvent) {
+        use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+        use winapi::shared::minwindef::{LPARAM, WPARAM};
+        use winapi::shared::windef::HWND;
+        use winapi::um::winuser::{
+            GetParent, MapVirtualKeyW, PostMessageW, MAPVK_VK_TO_VSC, WM_KEYDOWN, WM_KEYUP,
+        };
+
+        let hwnd = match window.raw_window_handle() {
+            RawWindowHandle::Win32(handle) => handle.hwnd as HWND,
+            _ => return,
+        };
+        let virtual_key = match key_codes(event.code) {
+            Some((virtual_key, _)) => virtual_key,
+            None => return,
+        };
+
+        unsafe {
+            let parent = GetParent(hwnd);
+            if parent.is_null() {
+                return;
+            }
+
+            // See the documentation for `WM_KEYDOWN` and `WM_KEYUP` for the layout of `lParam`
+            let scan_code = MapVirtualKeyW(virtual_key, MAPVK_VK_TO_VSC) as LPARAM;
+            let (message, flags) = match event.state {
+                KeyState::Down if event.repeat => (WM_KEYDOWN, 1 << 30),
+                KeyState::Down => (WM_KEYDOWN, 0),
+                KeyState::Up => (WM_KEYUP, (1 << 30) | (1 << 31)),
+            };
+            PostMessageW(
+                parent,
+                message,
+                virtual_key as WPARAM,
+                1 | (scan_code << 16) | flags,
+            );
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl KeyForwarder {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    pub fn forward(&mut self, _window: &Window, _event: &KeyboardEvent) {}
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+impl KeyForwarder {
+    pub fn new() -> Self {
+        Self {
+            display: std::ptr::null_mut(),
+        }
+    }
+
+    /// Forward a key event to the window's parent. The parent is looked up every time since the
+    /// host may reparent the window.
+    pub fn forward(&mut self, window: &Window, event: &KeyboardEvent) {
+        use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+        use std::os::raw::{c_uint, c_ulong};
+        use x11::xlib;
+
+        /// X11 keycodes are offset by 8 from the Linux evdev scancodes.
+        const EVDEV_OFFSET: u32 = 8;
+
+        let window_id = match window.raw_window_handle() {
+            RawWindowHandle::Xcb(handle) => handle.window as c_ulong,
+            RawWindowHandle::Xlib(handle) => handle.window,
+            _ => return,
+        };
+        let scancode = match key_codes(event.code) {
+            Some((_, scancode)) => scancode,
+            None => return,
+        };
+
+        unsafe {
+            if self.display.is_null() {
+                self.display = crate::x11_isolation::open_display();
+                if self.display.is_null() {
+                    return;
+                }
+            }
+            let display = self.display;
+
+            let mut root = 0;
+            let mut parent = 0;
+            let mut children = std::ptr::null_mut();
+            let mut num_children = 0;
+            if xlib::XQueryTree(
+                display,
+                window_id,
+                &mut root,
+                &mut parent,
+                &mut children,
+                &mut num_children,
+            ) != 0
+            {
+                if !children.is_null() {
+                    xlib::XFree(children as *mut _);
+                }
+
+                let mut state: c_uint = 0;
+                if event.modifiers.contains(Modifiers::SHIFT) {
+                    state |= xlib::ShiftMask;
+                }
+                if event.modifiers.contains(Modifiers::CONTROL) {
+                    state |= xlib::ControlMask;
+                }
+                if event.modifiers.contains(Modifiers::ALT) {
+                    state |= xlib::Mod1Mask;
+                }
+                if event.modifiers.contains(Modifiers::META) {
+                    state |= xlib::Mod4Mask;
+                }
+
+                let (event_type, event_mask) = match event.state {
+                    KeyState::Down => (xlib::KeyPress, xlib::KeyPressMask),
+                    KeyState::Up => (xlib::KeyRelease, xlib::KeyReleaseMask),
+                };
+                let mut x_event = xlib::XEvent {
+                    key: xlib::XKeyEvent {
+                        type_: event_type,
+                        serial: 0,
+                        send_event: xlib::True,
+                        display,
+                        window: parent,
+                        root,
+                        subwindow: 0,
+                        time: xlib::CurrentTime,
+                        x: 0,
+                        y: 0,
+                        x_root: 0,
+                        y_root: 0,
+                        state,
+                        keycode: scancode + EVDEV_OFFSET,
+                        same_screen: xlib::True,
+                    },
+                };
+                xlib::XSendEvent(display, parent, xlib::True, event_mask, &mut x_event);
+                xlib::XFlush(display);
+            }
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+impl Drop for KeyForwarder {
+    fn drop(&mut self) {
+        if !self.display.is_null() {
+            unsafe { crate::x11_isolation::close_display(self.display) };
+        }
+    }
+}
+
+/// Map a physical key to its Windows virtual key code and its Linux evdev scancode. This only
+/// covers the keys hosts commonly use as shortcuts.
+#[cfg_attr(target_os = "macos", allow(dead_code))]
+fn key_codes(code: Code) -> Option<(u32, u32)> {
+    let codes = match code {
+        Code::KeyA => (0x41, 30),
+        Code::KeyB => (0x42, 48),
+        Code::KeyC => (0x43, 46),
+        Code::KeyD => (0x44, 32),
+        Code::KeyE => (0x45, 18),
+        Code::KeyF => (0x46, 33),
+        Code::KeyG => (0x47, 34),
+        Code::KeyH => (0x48, 35),
+        Code::KeyI => (0x49, 23),
+        Code::KeyJ => (0x4A, 36),
+        Code::KeyK => (0x4B, 37),
+        Code::KeyL => (0x4C, 38),
+        Code::KeyM => (0x4D, 50),
+        Code::KeyN => (0x4E, 49),
+        Code::KeyO => (0x4F, 24),
+        Code::KeyP => (0x50, 25),
+        Code::KeyQ => (0x51, 16),
+        Code::KeyR => (0x52, 19),
+        Code::KeyS => (0x53, 31),
+        Code::KeyT => (0x54, 20),
+        Code::KeyU => (0x55, 22),
+        Code::KeyV => (0x56, 47),
+        Code::KeyW => (0x57, 17),
+        Code::KeyX => (0x58, 45),
+        Code::KeyY => (0x59, 21),
+        Code::KeyZ => (0x5A, 44),
+        Code::Digit0 => (0x30, 11),
+        Code::Digit1 => (0x31, 2),
+        Code::Digit2 => (0x32, 3),
+        Code::Digit3 => (0x33, 4),
+        Code::Digit4 => (0x34, 5),
+        Code::Digit5 => (0x35, 6),
+        Code::Digit6 => (0x36, 7),
+        Code::Digit7 => (0x37, 8),
+        Code::Digit8 => (0x38, 9),
+        Code::Digit9 => (0x39, 10),
+        Code::Space => (0x20, 57),
+        Code::Enter => (0x0D, 28),
+        Code::Escape => (0x1B, 1),
+        Code::Tab => (0x09, 15),
+        Code::Backspace => (0x08, 14),
+        Code::Insert => (0x2D, 110),
+        Code::Delete => (0x2E, 111),
+        Code::Home => (0x24, 102),
+        Code::End => (0x23, 107),
+        Code::PageUp => (0x21, 104),
+        Code::PageDown => (0x22, 109),
+        Code::ArrowLeft => (0x25, 105),
+        Code::ArrowUp => (0x26, 103),
+        Code::ArrowRight => (0x27, 106),
+        Code::ArrowDown => (0x28, 108),
+        Code::F1 => (0x70, 59),
+        Code::F2 => (0x71, 60),
+        Code::F3 => (0x72, 61),
+        Code::F4 => (0x73, 62),
+        Code::F5 => (0x74, 63),
+        Code::F6 => (0x75, 64),
+        Code::F7 => (0x76, 65),
+        Code::F8 => (0x77, 66),
+        Code::F9 => (0x78, 67),
+        Code::F10 => (0x79, 68),
+        Code::F11 => (0x7A, 87),
+        Code::F12 => (0x7B, 88),
+        _ => return None,
+    };
+
+    Some(codes)
+}
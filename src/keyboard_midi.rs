@@ -0,0 +1,178 @@
+//! Playing notes from the computer keyboard, so instrument plugins can be auditioned from the GUI.
+
+use crossbeam::atomic::AtomicCell;
+use crossbeam::queue::ArrayQueue;
+use keyboard_types::{Code, KeyState, KeyboardEvent, Modifiers};
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+/// A note event produced by [`KeyboardMidi`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyboardNoteEvent {
+    NoteOn {
+        /// The MIDI note number.
+        note: u8,
+        /// The note's velocity, in `[0, 1]`.
+        velocity: f32,
+    },
+    NoteOff {
+        /// The MIDI note number.
+        note: u8,
+    },
+}
+
+/// Maps key presses in the editor to note events using the layout most DAWs use: the `A` row
+/// plays the white keys starting at C, the `W` row plays the black keys, and `Z` and `X` shift the
+/// octave down and up. Keys are matched by their physical location, so this works the same on
+/// every keyboard layout.
+///
+/// The events are pushed to a lock-free queue that the audio thread can drain using
+/// [`KeyboardMidi::pop()`]. Enable this with
+/// [`BaseviewState::set_keyboard_midi()`][crate::BaseviewState::set_keyboard_midi()]. Keys that
+/// play notes are not passed to the window handler.
+#[derive(Debug)]
+pub struct KeyboardMidi {
+    events: ArrayQueue<KeyboardNoteEvent>,
+    /// The octave of the `A` key, where octave 4 contains middle C.
+    octave: AtomicCell<i8>,
+    velocity: AtomicCell<f32>,
+    /// The keys that are currently held down along with the notes they started. This is needed to
+    /// stop the right note when the octave changes while a key is held.
+    held_keys: Mutex<Vec<(Code, u8)>>,
+}
+
+impl KeyboardMidi {
+    /// Create a new keyboard to MIDI bridge. The queue can hold `capacity` events. If the audio
+    /// thread doesn't drain the queue fast enough, new events are dropped.
+    pub fn new(capacity: usize) -> Arc<Self> {
+        Arc::new(Self {
+            events: ArrayQueue::new(capacity),
+            octave: AtomicCell::new(4),
+            velocity: AtomicCell::new(0.8),
+            held_keys: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Take the oldest pending note event. This is lock-free and can be called from the audio
+    /// thread.
+    pub fn pop(&self) -> Option<KeyboardNoteEvent> {
+        self.events.pop()
+    }
+
+    /// The octave played by the `A` key, where octave 4 contains middle C.
+    pub fn octave(&self) -> i8 {
+        self.octave.load()
+    }
+
+    /// Change the octave played by the `A` key. This is clamped to the MIDI note range.
+    pub fn set_octave(&self, octave: i8) {
+        self.octave.store(octave.clamp(-1, 9));
+    }
+
+    /// The velocity used for note on events.
+    pub fn velocity(&self) -> f32 {
+        self.velocity.load()
+    }
+
+    /// Change the velocity used for note on events. This is clamped to `[0, 1]`.
+    pub fn set_velocity(&self, velocity: f32) {
+        self.velocity.store(velocity.clamp(0.0, 1.0));
+    }
+
+    /// Turn a key event into note events. Returns `true` if the key is part of the keyboard
+    /// mapping, in which case it should not be passed on to the window handler.
+    pub(crate) fn handle_key_event(&self, event: &KeyboardEvent) -> bool {
+        // Don't steal shortcuts like Ctrl+S from the editor or the host
+        if event
+            .modifiers
+            .intersects(Modifiers::CONTROL | Modifiers::ALT | Modifiers::META)
+        {
+            return false;
+        }
+
+        match (event.code, event.state) {
+            (Code::KeyZ, KeyState::Down) => {
+                if !event.repeat {
+                    self.set_octave(self.octave() - 1);
+                }
+                true
+            }
+            (Code::KeyX, KeyState::Down) => {
+                if !event.repeat {
+                    self.set_octave(self.octave() + 1);
+                }
+                true
+            }
+            (Code::KeyZ | Code::KeyX, KeyState::Up) => true,
+            (code, state) => {
+                let semitone = match key_semitone(code) {
+                    Some(semitone) => semitone,
+                    None => return false,
+                };
+
+                let mut held_keys = self.held_keys.lock();
+                match state {
+                    KeyState::Down => {
+                        if held_keys.iter().any(|(held_code, _)| *held_code == code) {
+                            return true;
+                        }
+
+                        let note = (self.octave() as i32 + 1) * 12 + semitone;
+                        if (0..=127).contains(&note) {
+                            held_keys.push((code, note as u8));
+                            let _ = self.events.push(KeyboardNoteEvent::NoteOn {
+                                note: note as u8,
+                                velocity: self.velocity(),
+                            });
+                        }
+                    }
+                    KeyState::Up => {
+                        if let Some(idx) = held_keys
+                            .iter()
+                            .position(|(held_code, _)| *held_code == code)
+                        {
+                            let (_, note) = held_keys.swap_remove(idx);
+                            let _ = self.events.push(KeyboardNoteEvent::NoteOff { note });
+                        }
+                    }
+                }
+
+                true
+            }
+        }
+    }
+
+    /// Stop all notes that are currently held. Called when the window closes, since the key
+    /// release events would otherwise never arrive.
+    pub(crate) fn release_all(&self) {
+        for (_, note) in self.held_keys.lock().drain(..) {
+            let _ = self.events.push(KeyboardNoteEvent::NoteOff { note });
+        }
+    }
+}
+
+/// The offset in semitones from the octave's C for a key in the keyboard mapping.
+fn key_semitone(code: Code) -> Option<i32> {
+    let semitone = match code {
+        Code::KeyA => 0,
+        Code::KeyW => 1,
+        Code::KeyS => 2,
+        Code::KeyE => 3,
+        Code::KeyD => 4,
+        Code::KeyF => 5,
+        Code::KeyT => 6,
+        Code::KeyG => 7,
+        Code::KeyY => 8,
+        Code::KeyH => 9,
+        Code::KeyU => 10,
+        Code::KeyJ => 11,
+        Code::KeyK => 12,
+        Code::KeyO => 13,
+        Code::KeyL => 14,
+        Code::KeyP => 15,
+        Code::Semicolon => 16,
+        _ => return None,
+    };
+
+    Some(semitone)
+}
@@ -5,19 +5,182 @@
 // See the comment in the main `nih_plug` crate
 #![allow(clippy::type_complexity)]
 
-use baseview::WindowHandler;
+use baseview::{MouseCursor, Size, WindowHandler, WindowInfo};
 use crossbeam::atomic::AtomicCell;
+use keyboard_types::Modifiers;
 use nih_plug::params::persist::PersistentField;
-use nih_plug::prelude::{Editor, GuiContext, ParamSetter};
-use parking_lot::RwLock;
+use nih_plug::prelude::{nih_debug_assert, nih_log, Editor, GuiContext, ParamSetter};
+use parking_lot::{Mutex, RwLock};
 use serde::{Deserialize, Serialize};
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
 
+use crate::callbacks::EditorCallbacks;
+use crate::editor::UserState;
+use crate::frame_stats::FrameTimer;
+use crate::local_state::LocalStateHandler;
+use crate::messages::FrameHook;
+use crate::owned_state::OwnedStateWindowHandler;
+use crate::profile::profile_scope;
+use crate::recording::EventRecorder;
+use crate::tasks::GuiTaskQueue;
+use crate::texture::TextureUploads;
+use crate::timers::Timers;
+
+mod cache;
+mod callbacks;
+mod capture;
+mod click;
+pub mod clipboard;
+mod debug_overlay;
+mod diagnostics;
+mod dpi;
 mod editor;
+#[cfg(feature = "egui")]
+mod egui_editor;
+mod embed;
+mod event_trace;
+mod fallback;
+#[cfg(feature = "femtovg")]
+mod femtovg_canvas;
+#[cfg(feature = "file_dialog")]
+mod file_dialog;
+mod file_drop;
+mod focus;
+mod frame_stats;
+mod framebuffer;
+mod gesture;
+#[cfg(feature = "glow")]
+mod glow_frame;
+mod handle;
+mod host_quirks;
+#[cfg(feature = "hot_reload")]
+mod hot_reload;
+#[cfg(feature = "iced")]
+mod iced_editor;
+#[cfg(feature = "imgui")]
+mod imgui_editor;
+mod keyboard;
+mod keyboard_midi;
+mod loader;
+mod local_state;
+#[cfg(feature = "message_box")]
+mod message_box;
+mod messages;
+mod meter;
+#[cfg(feature = "testing")]
+mod mock_host;
+mod modal;
+mod monitor;
+#[cfg(feature = "nanovg")]
+mod nanovg;
+mod overlay;
+mod owned_state;
+mod pointer;
+mod pop_out;
+mod popup;
+mod present;
+mod profile;
+mod raw_mouse;
+mod recording;
+mod reparent;
+mod scale;
+mod scope;
+mod scroll;
+#[cfg(feature = "skia")]
+mod skia_surface;
+#[cfg(feature = "slint")]
+mod slint_editor;
+#[cfg(feature = "testing")]
+mod snapshot;
+mod splash;
+mod standalone;
+mod tasks;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod texture;
+mod timers;
+mod touch;
+mod transparency;
+mod visibility;
+mod visual_data;
+mod watchdog;
+mod window;
+mod x11_isolation;
+mod xembed;
 
 // export baseview for convenience
 pub use baseview;
+#[cfg(feature = "femtovg")]
+pub use femtovg;
+#[cfg(feature = "glow")]
+pub use glow;
+#[cfg(feature = "iced")]
+pub use iced_glow;
+#[cfg(feature = "iced")]
+pub use iced_native;
+pub use keyboard_types;
+#[cfg(feature = "skia")]
+pub use skia_safe;
+
+pub use cache::AssetCache;
+pub use click::DoubleClickThreshold;
+pub use debug_overlay::draw_diagnostics_overlay;
+pub use diagnostics::{Diagnostics, GlInfo};
+pub use editor::BaseviewEditorHandle;
+#[cfg(feature = "egui")]
+pub use egui_editor::create_egui_editor;
+pub use embed::{create_embedded_editor, EmbeddedApp};
+#[cfg(feature = "femtovg")]
+pub use femtovg_canvas::{create_femtovg_editor, FemtovgHandler};
+#[cfg(feature = "file_dialog")]
+pub use file_dialog::{open_file_dialog, pick_folder_dialog, save_file_dialog, FileDialogOptions};
+pub use file_drop::{begin_file_drag, file_drop_event, FileDropEvent};
+pub use frame_stats::FrameStats;
+pub use framebuffer::framebuffer_size;
+pub use gesture::GestureEvent;
+#[cfg(feature = "glow")]
+pub use glow_frame::create_glow_editor;
+pub use handle::WindowHandleAdapter;
+pub use host_quirks::{Host, HostQuirks};
+#[cfg(feature = "hot_reload")]
+pub use hot_reload::{create_hot_reload_editor, HotReloadBuildFn};
+#[cfg(feature = "iced")]
+pub use iced_editor::{create_iced_editor, Element, IcedEditor, ParamMessage, Renderer};
+#[cfg(feature = "imgui")]
+pub use imgui_editor::create_imgui_editor;
+pub use keyboard::{text_input, KeyboardPolicy};
+pub use keyboard_midi::{KeyboardMidi, KeyboardNoteEvent};
+pub use loader::{AssetLoader, PendingAsset};
+#[cfg(feature = "message_box")]
+pub use message_box::{show_confirm_box, show_message_box, MessageLevel};
+pub use messages::{AudioMessages, GuiMessages};
+pub use meter::PeakMeter;
+pub use modal::{open_modal_dialog, ModalDialog};
+pub use monitor::{monitors, Monitor};
+#[cfg(feature = "nanovg")]
+pub use nanovg::{create_nanovg_editor, NanovgHandler, Vg};
+pub use overlay::{open_overlay, OverlayOptions, OverlayWindow};
+pub use owned_state::{OwnedStateHandler, StateSnapshot};
+pub use pop_out::{open_pop_out, PopOutOptions, PopOutParent, PopOutWindow};
+pub use popup::{open_popup, PopupWindow};
+pub use recording::EventRecording;
+pub use scale::ScalePolicy;
+pub use scope::{ScopeReader, ScopeWriter};
+#[cfg(feature = "skia")]
+pub use skia_surface::{create_skia_editor, SkiaHandler};
+#[cfg(feature = "slint")]
+pub use slint_editor::create_slint_editor;
+pub use standalone::{WindowIcon, WindowStyle};
+pub use tasks::GuiTaskProxy;
+pub use texture::{GlTexture, TextureOptions};
+pub use timers::TimerId;
+pub use touch::{TouchEvent, TouchPhase};
+pub use visual_data::{VisualData, VisualDataWriter};
+pub use watchdog::WatchdogConfig;
 
 /// Create an [`Editor`] instance using an [`egui`][::egui] GUI. Using the user state parameter is
 /// optional, but it can be useful for keeping track of some temporary GUI-only settings. See the
@@ -28,48 +191,416 @@ pub use baseview;
 /// persisted when restoring a plugin instance, then you can store it in a `#[persist = "key"]`
 /// field on your parameters struct.
 ///
+/// The build function receives the [`WindowInfo`][baseview::WindowInfo] the window was opened
+/// with, so the handler can set up its pixels-per-point without duplicating the bridge's scaling
+/// logic. Whenever the window's size or scale changes afterwards, the handler receives a
+/// [`WindowEvent::Resized`][baseview::WindowEvent::Resized] event containing the new information.
+/// If the scaling factor changes while the editor is open, the window and the handler are rebuilt.
+///
 /// See [`EguiState::from_size()`].
 pub fn create_baseview_editor<T, B, H>(
     baseview_state: Arc<BaseviewState>,
     user_state: T,
     build: B,
-    // update: U,
 ) -> Option<Box<dyn Editor>>
 where
     T: 'static + Send + Sync,
-    B: Fn(&baseview::Window, Arc<dyn GuiContext>, &mut T) -> H + 'static + Send + Sync,
-    H: WindowHandler + Send + Sync + 'static, // U: Fn(&Context, &ParamSetter, &mut T) + 'static + Send + Sync,
+    B: Fn(&baseview::Window, WindowInfo, Arc<dyn GuiContext>, &mut T) -> H + 'static + Send + Sync,
+    H: WindowHandler + 'static,
+{
+    Some(Box::new(editor::BaseviewEditor {
+        baseview_state,
+        user_state: UserState::Shared(Arc::new(RwLock::new(user_state))),
+        build: Arc::new(build),
+        update: None,
+
+        // If the host never sets a scale factor, a platform specific fallback is used when the
+        // window gets opened
+        scaling_factor: Arc::new(AtomicCell::new(None)),
+
+        current_spawn_id: Arc::new(AtomicU64::new(0)),
+    }))
+}
+
+/// The same as [`create_baseview_editor()`], but with an update function that the bridge calls
+/// every frame right before the handler's [`on_frame()`][WindowHandler::on_frame()]. The update
+/// function receives a [`ParamSetter`] and the user state, so simple plugins can change their
+/// parameters from here and keep their handler focused on drawing.
+pub fn create_baseview_editor_with_update<T, B, H, U>(
+    baseview_state: Arc<BaseviewState>,
+    user_state: T,
+    build: B,
+    update: U,
+) -> Option<Box<dyn Editor>>
+where
+    T: 'static + Send + Sync,
+    B: Fn(&baseview::Window, WindowInfo, Arc<dyn GuiContext>, &mut T) -> H + 'static + Send + Sync,
+    H: WindowHandler + 'static,
+    U: Fn(&ParamSetter, &mut T) + 'static + Send + Sync,
 {
     Some(Box::new(editor::BaseviewEditor {
         baseview_state,
-        user_state: Arc::new(RwLock::new(user_state)),
+        user_state: UserState::Shared(Arc::new(RwLock::new(user_state))),
         build: Arc::new(build),
-        // render: Arc::new(update),
-
-        // TODO: We can't get the size of the window when baseview does its own scaling, so if the
-        //       host does not set a scale factor on Windows or Linux we should just use a factor of
-        //       1. That may make the GUI tiny but it also prevents it from getting cut off.
-        #[cfg(target_os = "macos")]
-        scaling_factor: AtomicCell::new(None),
-        #[cfg(not(target_os = "macos"))]
-        scaling_factor: AtomicCell::new(Some(1.0)),
+        update: Some(Arc::new(update)),
+
+        scaling_factor: Arc::new(AtomicCell::new(None)),
+
+        current_spawn_id: Arc::new(AtomicU64::new(0)),
+    }))
+}
+
+/// The same as [`create_baseview_editor()`], but the user state is created by `user_state_factory`
+/// every time the editor is opened instead of being shared between openings. The state is dropped
+/// when the window is closed, so GUI-only state like animations and scroll positions starts fresh
+/// and doesn't take up memory while the editor is closed.
+pub fn create_baseview_editor_with_factory<T, F, B, H>(
+    baseview_state: Arc<BaseviewState>,
+    user_state_factory: F,
+    build: B,
+) -> Option<Box<dyn Editor>>
+where
+    T: 'static + Send + Sync,
+    F: Fn() -> T + 'static + Send + Sync,
+    B: Fn(&baseview::Window, WindowInfo, Arc<dyn GuiContext>, &mut T) -> H + 'static + Send + Sync,
+    H: WindowHandler + 'static,
+{
+    Some(Box::new(editor::BaseviewEditor {
+        baseview_state,
+        user_state: UserState::Factory(Arc::new(user_state_factory)),
+        build: Arc::new(build),
+        update: None,
+
+        scaling_factor: Arc::new(AtomicCell::new(None)),
+
+        current_spawn_id: Arc::new(AtomicU64::new(0)),
+    }))
+}
+
+/// The same as [`create_baseview_editor()`], but for user state that can't be shared between
+/// threads, like state holding OpenGL objects or `Rc`s. Only `user_state_factory` needs to be
+/// thread safe. It is called on the window's thread every time the editor is opened, right before
+/// the build function. The state is owned by the window and dropped after the handler when the
+/// window is closed, so the state doesn't need to be `Send` or `Sync`.
+pub fn create_baseview_editor_with_local_state<S, F, B, H>(
+    baseview_state: Arc<BaseviewState>,
+    user_state_factory: F,
+    build: B,
+) -> Option<Box<dyn Editor>>
+where
+    S: 'static,
+    F: Fn() -> S + 'static + Send + Sync,
+    B: Fn(&baseview::Window, WindowInfo, Arc<dyn GuiContext>, &mut S) -> H + 'static + Send + Sync,
+    H: WindowHandler + 'static,
+{
+    // The bridge's own user state is unused, and the handler doesn't need to be thread safe since
+    // it's created on the window's thread
+    Some(Box::new(editor::BaseviewEditor {
+        baseview_state,
+        user_state: UserState::Shared(Arc::new(RwLock::new(()))),
+        build: Arc::new(
+            move |window: &baseview::Window,
+                  window_info: WindowInfo,
+                  context: Arc<dyn GuiContext>,
+                  _: &mut ()| {
+                let mut state = user_state_factory();
+                let inner = build(window, window_info, context, &mut state);
+
+                LocalStateHandler { inner, state }
+            },
+        ),
+        update: None,
+
+        scaling_factor: Arc::new(AtomicCell::new(None)),
+
+        current_spawn_id: Arc::new(AtomicU64::new(0)),
+    }))
+}
+
+/// The same as [`create_baseview_editor()`], but the user state is owned by the window while the
+/// editor is open instead of being shared behind a lock. The handler implements
+/// [`OwnedStateHandler`] and gets mutable access to the state every frame and for every event.
+/// `user_state_factory` creates the state the first time the editor is opened. The state is handed
+/// back to the editor when the window is closed and reused the next time it's opened. If the host
+/// opens the editor again before the old window has been dropped, a fresh state is created instead.
+/// Use a [`StateSnapshot`] to let other threads read parts of the state.
+pub fn create_baseview_editor_with_owned_state<T, F, B, H>(
+    baseview_state: Arc<BaseviewState>,
+    user_state_factory: F,
+    build: B,
+) -> Option<Box<dyn Editor>>
+where
+    T: 'static + Send,
+    F: Fn() -> T + 'static + Send + Sync,
+    B: Fn(&baseview::Window, WindowInfo, Arc<dyn GuiContext>, &mut T) -> H + 'static + Send + Sync,
+    H: OwnedStateHandler<T> + 'static,
+{
+    let slot: Arc<Mutex<Option<T>>> = Arc::new(Mutex::new(None));
+
+    // Like with `create_baseview_editor_with_local_state()` the bridge's own user state is unused
+    Some(Box::new(editor::BaseviewEditor {
+        baseview_state,
+        user_state: UserState::Shared(Arc::new(RwLock::new(()))),
+        build: Arc::new(
+            move |window: &baseview::Window,
+                  window_info: WindowInfo,
+                  context: Arc<dyn GuiContext>,
+                  _: &mut ()| {
+                let mut state = slot.lock().take().unwrap_or_else(&user_state_factory);
+                let inner = build(window, window_info, context, &mut state);
+
+                OwnedStateWindowHandler {
+                    inner,
+                    state: Some(state),
+                    slot: slot.clone(),
+                }
+            },
+        ),
+        update: None,
+
+        scaling_factor: Arc::new(AtomicCell::new(None)),
+
+        current_spawn_id: Arc::new(AtomicU64::new(0)),
     }))
 }
 
 /// State for an `nih_plug_egui` editor.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BaseviewState {
-    /// The window's size in logical pixels before applying `scale_factor`.
+    /// The window's size in logical pixels before applying `scale_factor`. This is stored as
+    /// floating point values so fractional scaling factors don't cause rounding errors. States
+    /// saved with integer sizes can still be restored.
     #[serde(with = "nih_plug::params::persist::serialize_atomic_cell")]
-    size: AtomicCell<(u32, u32)>,
+    size: AtomicCell<(f64, f64)>,
+    /// A size set through [`BaseviewState::set_size()`] while the editor was open. This gets
+    /// negotiated with the host on the GUI thread before the next frame.
+    #[serde(skip)]
+    requested_size: AtomicCell<Option<(f64, f64)>>,
+    /// The size being requested from the host during a call to `GuiContext::request_resize()`.
+    /// The host queries the editor's size during that call, but the size should only be stored in
+    /// `size` once the host has accepted it.
+    #[serde(skip)]
+    negotiated_size: AtomicCell<Option<(f64, f64)>>,
+    /// The smallest logical size the window may be resized to, if any.
+    #[serde(skip)]
+    min_size: AtomicCell<Option<(u32, u32)>>,
+    /// The largest logical size the window may be resized to, if any.
+    #[serde(skip)]
+    max_size: AtomicCell<Option<(u32, u32)>>,
+    /// The side length in logical pixels of the bridge's resize grip, if it is enabled.
+    #[serde(skip)]
+    resize_grip: AtomicCell<Option<u32>>,
+    /// The user's zoom factor, applied on top of the DPI scaling factor. Defaults to 1.0 for
+    /// states saved before this was added.
+    #[serde(
+        with = "nih_plug::params::persist::serialize_atomic_cell",
+        default = "default_zoom"
+    )]
+    zoom: AtomicCell<f32>,
+    /// The last known DPI scaling factor, either reported by the host or detected when the window
+    /// was opened. This is persisted so it can be used for hosts that don't send the scaling
+    /// factor again after restoring a plugin instance.
+    #[serde(with = "nih_plug::params::persist::serialize_atomic_cell", default)]
+    scale_factor: AtomicCell<Option<f32>>,
+    /// The last known position of the standalone application's window, so it can be reopened in
+    /// the same place. This is in the window system's screen coordinates, which also determine the
+    /// monitor it's on.
+    #[serde(with = "nih_plug::params::persist::serialize_atomic_cell", default)]
+    window_position: AtomicCell<Option<(i32, i32)>>,
+    /// Where the scaling factor comes from.
+    #[serde(skip)]
+    scale_policy: AtomicCell<ScalePolicy>,
+    /// Whether the host's scaling factor is used on macOS.
+    #[serde(skip)]
+    macos_host_scaling: AtomicBool,
+    /// Whether the editor's X11 errors are kept away from the host's error handler.
+    #[serde(skip)]
+    x11_isolation: AtomicBool,
+    /// A zoom factor set through [`BaseviewState::set_zoom()`] while the editor was open. The
+    /// window handler rebuilds the window to apply this where it can, and otherwise it's applied
+    /// the next time the editor gets opened.
+    #[serde(skip)]
+    requested_zoom: AtomicCell<Option<f32>>,
+    /// Decides which key events are passed on to the host.
+    #[serde(skip)]
+    keyboard_policy: RwLock<KeyboardPolicy>,
+    /// Whether the editor follows the VST3 keyboard focus conventions.
+    #[serde(skip)]
+    focus_handshake: AtomicBool,
+    /// Turns key presses into note events, if enabled.
+    #[serde(skip)]
+    keyboard_midi: RwLock<Option<Arc<KeyboardMidi>>>,
+    /// The modifier keys that are currently held down, as tracked by the editor's window.
+    #[serde(skip)]
+    modifiers: AtomicCell<Modifiers>,
+    /// The mouse cursor the window handler wants to show.
+    #[serde(skip)]
+    mouse_cursor: AtomicCell<MouseCursor>,
+    /// Whether the window handler wants the pointer to be locked in place.
+    #[serde(skip)]
+    pointer_locked: AtomicBool,
+    /// Whether the window handler wants to receive raw mouse movement.
+    #[serde(skip)]
+    raw_mouse_input: AtomicBool,
+    /// The raw mouse movement behind the cursor moved event that was handled last.
+    #[serde(skip)]
+    raw_mouse_delta: AtomicCell<Option<(f64, f64)>>,
+    /// Whether the window handler wants to receive touch input.
+    #[serde(skip)]
+    touch_input: AtomicBool,
+    /// Touch events that have not yet been taken by the window handler.
+    #[serde(skip)]
+    touch_events: Mutex<Vec<TouchEvent>>,
+    /// Whether the window handler wants to receive pinch and rotate gestures.
+    #[serde(skip)]
+    gesture_input: AtomicBool,
+    /// Gestures that have not yet been taken by the window handler.
+    #[serde(skip)]
+    gesture_events: Mutex<Vec<GestureEvent>>,
+    /// Decides when two clicks count as a double-click.
+    #[serde(skip)]
+    double_click_threshold: AtomicCell<DoubleClickThreshold>,
+    /// Whether scroll deltas are converted to lines before they reach the window handler.
+    #[serde(skip)]
+    normalize_scrolling: AtomicBool,
+    /// The number of consecutive clicks for the button press that was handled last.
+    #[serde(skip)]
+    click_count: AtomicCell<u32>,
+    /// Counts the mouse button presses in the editor's window, so popups can tell when the user
+    /// clicked outside of them.
+    #[serde(skip)]
+    button_presses: AtomicU64,
+    /// The number of open modal dialogs. The editor's window ignores mouse and keyboard input
+    /// while this is nonzero.
+    #[serde(skip)]
+    modal_dialogs: AtomicUsize,
+    /// Set when the user clicks the editor while a modal dialog is open, so the dialog can bring
+    /// itself back to the front.
+    #[serde(skip)]
+    modal_focus_requested: AtomicBool,
+    /// Whether the window handler explicitly captured the mouse.
+    #[serde(skip)]
+    mouse_captured: AtomicBool,
+    /// Whether the editor's window currently has keyboard focus.
+    #[serde(skip)]
+    focused: AtomicBool,
+    /// Whether `on_frame()` is skipped while the window is hidden.
+    #[serde(skip)]
+    pause_when_hidden: AtomicBool,
+    /// Overrides the detected host's quirks when set.
+    #[serde(skip)]
+    host_quirks: AtomicCell<Option<HostQuirks>>,
+    /// Whether the editor's window was visible at the last frame.
+    #[serde(skip)]
+    visible: AtomicBool,
     /// Whether the editor's window is currently open.
     #[serde(skip)]
     open: AtomicBool,
+    /// Set when the plugin asked to close the editor. The window is closed on the GUI thread before
+    /// the next frame.
+    #[serde(skip)]
+    close_requested: AtomicBool,
+    /// Set when the plugin asked for a frame to be drawn even if drawing is paused.
+    #[serde(skip)]
+    repaint_requested: AtomicBool,
+    /// The plugin's callbacks for when the editor opens or closes.
+    #[serde(skip)]
+    callbacks: RwLock<EditorCallbacks>,
+    /// The message of the panic that stopped the current window's handler, if it panicked.
+    #[serde(skip)]
+    panic_message: Mutex<Option<String>>,
+    /// The GUI thread watchdog's configuration, if it is enabled.
+    #[serde(skip)]
+    watchdog: AtomicCell<Option<WatchdogConfig>>,
+    /// Hooks that run on the GUI thread before every frame. These are removed once the object
+    /// they belong to is dropped.
+    #[serde(skip)]
+    frame_hooks: Mutex<Vec<Weak<dyn FrameHook>>>,
+    /// The times of the recently drawn frames, for [`BaseviewState::frame_stats()`].
+    #[serde(skip)]
+    frame_timer: Mutex<FrameTimer>,
+    /// The diagnostics for the last drawn frame.
+    #[serde(skip)]
+    diagnostics: Mutex<Diagnostics>,
+    /// Writes the window's events to a file while recording is enabled using
+    /// [`BaseviewState::start_recording()`].
+    #[serde(skip)]
+    event_recorder: Mutex<Option<EventRecorder>>,
+    /// A recording passed to [`BaseviewState::replay_recording()`] that the window handler hasn't
+    /// started replaying yet.
+    #[serde(skip)]
+    pending_replay: Mutex<Option<EventRecording>>,
+    /// Whether the window handler is currently replaying a recording.
+    #[serde(skip)]
+    replaying: AtomicBool,
+    /// Synthetic events sent using [`BaseviewState::inject_event()`] that should be handled before
+    /// the next frame.
+    #[cfg(feature = "testing")]
+    #[serde(skip)]
+    injected_events: crossbeam::queue::SegQueue<baseview::Event>,
+    /// Set by [`BaseviewState::request_frame_capture()`] until the next frame was captured.
+    #[cfg(feature = "testing")]
+    #[serde(skip)]
+    frame_capture_requested: AtomicBool,
+    /// The frame captured after the last request.
+    #[cfg(feature = "testing")]
+    #[serde(skip)]
+    captured_frame: Mutex<Option<testing::Frame>>,
+    /// Closures sent through a [`GuiTaskProxy`] that should run on the GUI thread.
+    #[serde(skip)]
+    gui_tasks: Arc<GuiTaskQueue>,
+    /// Timers started using [`BaseviewState::set_timer()`] and
+    /// [`BaseviewState::set_repeating_timer()`].
+    #[serde(skip)]
+    timers: Timers,
+    /// Textures queued using [`BaseviewState::upload_texture()`].
+    #[serde(skip)]
+    texture_uploads: TextureUploads,
+    /// Decoded assets that should survive reopening the editor.
+    #[serde(skip)]
+    asset_cache: AssetCache,
+    /// The color the window is cleared to before the user's build function runs, if any.
+    #[serde(skip)]
+    splash_color: AtomicCell<Option<[f32; 4]>>,
+    /// Whether the window's alpha channel is used to let the background show through.
+    #[serde(skip)]
+    transparent: AtomicBool,
+    /// The standalone application window's icon, if any.
+    #[serde(skip)]
+    window_icon: Mutex<Option<WindowIcon>>,
+    /// Whether the standalone application's window stays above other windows.
+    #[serde(skip)]
+    always_on_top: AtomicBool,
+    /// The standalone application window's decorations and resizability, if the plugin changed
+    /// them.
+    #[serde(skip)]
+    window_style: AtomicCell<Option<WindowStyle>>,
+    /// Set when one of the standalone application window's options changed, so the window can
+    /// apply them on the GUI thread before the next frame.
+    #[serde(skip)]
+    standalone_window_changed: AtomicBool,
+    /// Set when the host changes a parameter's value, and cleared by adapters that forward these
+    /// changes to the GUI.
+    #[serde(skip)]
+    params_changed: AtomicBool,
+}
+
+fn default_zoom() -> AtomicCell<f32> {
+    AtomicCell::new(1.0)
 }
 
 impl<'a> PersistentField<'a, BaseviewState> for Arc<BaseviewState> {
     fn set(&self, new_value: BaseviewState) {
-        self.size.store(new_value.size.load());
+        // While the editor is open this resizes the window like any other size change
+        let (width, height) = new_value.size.load();
+        self.set_logical_size(width, height);
+        self.set_zoom(new_value.zoom.load());
+        if let Some(scale_factor) = new_value.scale_factor.load() {
+            self.scale_factor.store(Some(scale_factor));
+        }
+        if let Some(window_position) = new_value.window_position.load() {
+            self.window_position.store(Some(window_position));
+        }
     }
 
     fn map<F, R>(&self, f: F) -> R
@@ -81,23 +612,974 @@ impl<'a> PersistentField<'a, BaseviewState> for Arc<BaseviewState> {
 }
 
 impl BaseviewState {
+    /// Common zoom factors that can be offered to the user, from 75% to 200%.
+    pub const ZOOM_PRESETS: [f32; 4] = [0.75, 1.0, 1.5, 2.0];
+
     /// Initialize the GUI's state. This value can be passed to [`create_egui_editor()`]. The window
     /// size is in logical pixels, so before it is multiplied by the DPI scaling factor.
     pub fn from_size(width: u32, height: u32) -> Arc<BaseviewState> {
+        Self::from_logical_size(width as f64, height as f64)
+    }
+
+    /// The same as [`BaseviewState::from_size()`], but with a fractional size in logical pixels.
+    pub fn from_logical_size(width: f64, height: f64) -> Arc<BaseviewState> {
         Arc::new(BaseviewState {
             size: AtomicCell::new((width, height)),
+            requested_size: AtomicCell::new(None),
+            negotiated_size: AtomicCell::new(None),
+            min_size: AtomicCell::new(None),
+            max_size: AtomicCell::new(None),
+            resize_grip: AtomicCell::new(None),
+            scale_factor: AtomicCell::new(None),
+            window_position: AtomicCell::new(None),
+            scale_policy: AtomicCell::new(ScalePolicy::Host),
+            macos_host_scaling: AtomicBool::new(false),
+            x11_isolation: AtomicBool::new(false),
+            zoom: AtomicCell::new(1.0),
+            requested_zoom: AtomicCell::new(None),
+            keyboard_policy: RwLock::new(KeyboardPolicy::default()),
+            focus_handshake: AtomicBool::new(true),
+            keyboard_midi: RwLock::new(None),
+            modifiers: AtomicCell::new(Modifiers::empty()),
+            mouse_cursor: AtomicCell::new(MouseCursor::Default),
+            pointer_locked: AtomicBool::new(false),
+            raw_mouse_input: AtomicBool::new(false),
+            raw_mouse_delta: AtomicCell::new(None),
+            touch_input: AtomicBool::new(false),
+            touch_events: Mutex::new(Vec::new()),
+            gesture_input: AtomicBool::new(false),
+            gesture_events: Mutex::new(Vec::new()),
+            double_click_threshold: AtomicCell::new(DoubleClickThreshold::default()),
+            normalize_scrolling: AtomicBool::new(false),
+            click_count: AtomicCell::new(0),
+            button_presses: AtomicU64::new(0),
+            modal_dialogs: AtomicUsize::new(0),
+            modal_focus_requested: AtomicBool::new(false),
+            mouse_captured: AtomicBool::new(false),
+            focused: AtomicBool::new(false),
+            pause_when_hidden: AtomicBool::new(true),
+            host_quirks: AtomicCell::new(None),
+            visible: AtomicBool::new(false),
             open: AtomicBool::new(false),
+            close_requested: AtomicBool::new(false),
+            repaint_requested: AtomicBool::new(false),
+            callbacks: RwLock::new(EditorCallbacks::default()),
+            panic_message: Mutex::new(None),
+            watchdog: AtomicCell::new(None),
+            frame_hooks: Mutex::new(Vec::new()),
+            frame_timer: Mutex::new(FrameTimer::default()),
+            diagnostics: Mutex::new(Diagnostics::default()),
+            event_recorder: Mutex::new(None),
+            pending_replay: Mutex::new(None),
+            replaying: AtomicBool::new(false),
+            #[cfg(feature = "testing")]
+            injected_events: crossbeam::queue::SegQueue::new(),
+            #[cfg(feature = "testing")]
+            frame_capture_requested: AtomicBool::new(false),
+            #[cfg(feature = "testing")]
+            captured_frame: Mutex::new(None),
+            gui_tasks: Arc::new(GuiTaskQueue::default()),
+            timers: Timers::default(),
+            texture_uploads: TextureUploads::default(),
+            asset_cache: AssetCache::default(),
+            splash_color: AtomicCell::new(Some([0.0, 0.0, 0.0, 1.0])),
+            transparent: AtomicBool::new(false),
+            window_icon: Mutex::new(None),
+            always_on_top: AtomicBool::new(false),
+            window_style: AtomicCell::new(None),
+            standalone_window_changed: AtomicBool::new(false),
+            params_changed: AtomicBool::new(false),
         })
     }
 
     /// Returns a `(width, height)` pair for the current size of the GUI in logical pixels.
     pub fn size(&self) -> (u32, u32) {
+        let (width, height) = self.size.load();
+
+        (width.round() as u32, height.round() as u32)
+    }
+
+    /// Returns a `(width, height)` pair for the current size of the GUI in logical pixels without
+    /// rounding. This can be fractional when the window has been resized under a fractional
+    /// scaling factor.
+    pub fn logical_size(&self) -> (f64, f64) {
         self.size.load()
     }
 
+    /// Returns a `(width, height)` pair for the current size of the GUI in physical pixels, after
+    /// applying the last known scaling factor and the zoom factor. This uses the same rounding as
+    /// baseview, so it can be used to size framebuffers. If no scaling factor is known yet, a
+    /// factor of 1 is assumed.
+    pub fn physical_size(&self) -> (u32, u32) {
+        let (width, height) = self.logical_size();
+        let scale = self.scale_factor().unwrap_or(1.0) * self.zoom.load();
+        let physical_size =
+            WindowInfo::from_logical_size(Size::new(width, height), scale as f64).physical_size();
+
+        (physical_size.width, physical_size.height)
+    }
+
+    /// Resize the GUI to a new size in logical pixels. If the editor is currently open, then the
+    /// new size is requested from the host on the GUI thread before the next frame. The window is
+    /// only resized and the new size is only stored if the host accepts the request, and a request
+    /// that the editor closes before negotiating is dropped. If the editor is closed, the new size
+    /// is used the next time it gets opened.
+    ///
+    /// The size is clamped to the limits set with [`BaseviewState::set_min_size()`] and
+    /// [`BaseviewState::set_max_size()`].
+    pub fn set_size(&self, width: u32, height: u32) {
+        self.set_logical_size(width as f64, height as f64)
+    }
+
+    /// The same as [`BaseviewState::set_size()`], but with a fractional size in logical pixels.
+    pub fn set_logical_size(&self, width: f64, height: f64) {
+        let (width, height) = self.clamp_size((width, height));
+        if self.is_open() {
+            self.requested_size.store(Some((width, height)));
+        } else {
+            // A request left over from when the editor was open would otherwise override this
+            self.requested_size.store(None);
+            self.size.store((width, height));
+        }
+    }
+
+    /// Set the smallest logical size the GUI can be resized to, or `None` to remove the limit.
+    /// Resizes below this size, whether they come from [`BaseviewState::set_size()`] or from the
+    /// host, are clamped to this size.
+    pub fn set_min_size(&self, min_size: Option<(u32, u32)>) {
+        self.min_size.store(min_size);
+    }
+
+    /// Set the largest logical size the GUI can be resized to, or `None` to remove the limit.
+    pub fn set_max_size(&self, max_size: Option<(u32, u32)>) {
+        self.max_size.store(max_size);
+    }
+
+    /// Returns the minimum size set with [`BaseviewState::set_min_size()`], if any.
+    pub fn min_size(&self) -> Option<(u32, u32)> {
+        self.min_size.load()
+    }
+
+    /// Returns the maximum size set with [`BaseviewState::set_max_size()`], if any.
+    pub fn max_size(&self) -> Option<(u32, u32)> {
+        self.max_size.load()
+    }
+
+    /// Returns the last known DPI scaling factor, either reported by the host or detected when the
+    /// window was last opened. This can be used to size framebuffers before the window opens. This
+    /// does not include the zoom factor. Returns `None` if no scaling factor is known yet, or on
+    /// macOS where the system's scaling factor is used. See
+    /// [`BaseviewState::set_macos_host_scaling()`].
+    pub fn scale_factor(&self) -> Option<f32> {
+        self.scale_factor.load()
+    }
+
+    /// Choose where the scaling factor comes from. This takes effect the next time the editor
+    /// gets opened. Use [`ScalePolicy::per_platform()`] to use a different policy on every
+    /// operating system.
+    pub fn set_scale_policy(&self, policy: ScalePolicy) {
+        self.scale_policy.store(policy);
+    }
+
+    /// Returns the policy set with [`BaseviewState::set_scale_policy()`].
+    pub fn scale_policy(&self) -> ScalePolicy {
+        self.scale_policy.load()
+    }
+
+    /// Use the scaling factor provided by the host on macOS as well. On macOS the window system
+    /// already scales everything to the display's backing scale, so by default the host's factor
+    /// is ignored there, and so is a factor persisted on another platform. Some hosts scale the
+    /// plugin's view themselves and expect the plugin to do the same, in which case this should be
+    /// enabled. Mouse positions are then converted back from the host's scaled view to logical
+    /// pixels. This only applies with [`ScalePolicy::Host`], and it takes effect the next time the
+    /// editor gets opened. Disabled by default.
+    pub fn set_macos_host_scaling(&self, enabled: bool) {
+        self.macos_host_scaling.store(enabled, Ordering::Release);
+    }
+
+    /// Whether the host's scaling factor is used on macOS. See
+    /// [`BaseviewState::set_macos_host_scaling()`].
+    pub fn macos_host_scaling(&self) -> bool {
+        self.macos_host_scaling.load(Ordering::Acquire)
+    }
+
+    /// Keep the editor's X11 connections isolated from the host's Xlib error handling. The error
+    /// handler is shared by the entire process, and both hosts and other plugins install handlers
+    /// that abort on any error. With this enabled, X11 errors caused by the editor are logged and
+    /// ignored instead, while errors on the host's connections still reach the host's handler. The
+    /// editor always runs its event loop on its own thread with its own connection on X11. This
+    /// takes effect the next time the editor gets opened, and it does nothing on Windows and
+    /// macOS. Disabled by default.
+    pub fn set_x11_isolation(&self, enabled: bool) {
+        self.x11_isolation.store(enabled, Ordering::Release);
+    }
+
+    /// Whether the editor's X11 errors are isolated from the host. See
+    /// [`BaseviewState::set_x11_isolation()`].
+    pub fn x11_isolation(&self) -> bool {
+        self.x11_isolation.load(Ordering::Acquire)
+    }
+
+    /// Whether the host's scaling factor should be ignored on this platform.
+    fn ignores_host_scale_factor(&self) -> bool {
+        cfg!(target_os = "macos") && !self.macos_host_scaling()
+    }
+
+    /// Returns the user's zoom factor. This is applied on top of the DPI scaling factor, so the
+    /// window's size as seen by the host is [`BaseviewState::size()`] multiplied by this factor
+    /// while the window handler still sees the same logical size. This is the factor the window
+    /// currently uses, the same one [`BaseviewState::zoomed_size()`] uses, so a factor set while
+    /// the editor is open only shows up here once it has been applied, which on Linux means once
+    /// the editor has been closed. See [`BaseviewState::set_zoom()`].
+    pub fn zoom(&self) -> f32 {
+        self.zoom.load()
+    }
+
+    /// Set the user's zoom factor, for instance to one of [`BaseviewState::ZOOM_PRESETS`]. This is
+    /// stored alongside the size so it can be persisted. Baseview can't change an open window's
+    /// scale, so if the editor is currently open the window is rebuilt at the new zoom factor
+    /// before the next frame and the host is asked to resize it to the new zoomed size.
+    ///
+    /// Zooming an open editor is not supported on Linux. Baseview runs the window's handler on a
+    /// thread of its own there, and the window can't be rebuilt from that thread. The new zoom
+    /// factor is stored when the editor closes and takes effect the next time it's opened, so the
+    /// GUI should tell the user to reopen the editor. The same happens on the other platforms when
+    /// the window can't be rebuilt.
+    pub fn set_zoom(&self, zoom: f32) {
+        nih_debug_assert!(zoom > 0.0, "Zoom factors must be positive");
+        if zoom <= 0.0 {
+            return;
+        }
+
+        if self.is_open() {
+            self.requested_zoom.store(Some(zoom));
+        } else {
+            self.zoom.store(zoom);
+        }
+    }
+
+    /// Returns a `(width, height)` pair for the size of the window as seen by the host, in logical
+    /// pixels. This is the GUI's size multiplied by the active zoom factor.
+    pub fn zoomed_size(&self) -> (u32, u32) {
+        let (width, height) = self
+            .negotiated_size
+            .load()
+            .unwrap_or_else(|| self.size.load());
+        let zoom = self.zoom.load() as f64;
+
+        (
+            (width * zoom).round() as u32,
+            (height * zoom).round() as u32,
+        )
+    }
+
+    /// Drop a size requested while the editor was open that the window didn't get to negotiate
+    /// with the host anymore. The host never accepted that size, so it must not be persisted.
+    /// Called when the editor's handle is dropped, and again before a window opens since a window
+    /// closed through [`BaseviewState::request_close()`] keeps its handle until the host drops it.
+    fn discard_requested_size(&self) {
+        if let Some((width, height)) = self.requested_size.take() {
+            nih_log!("Discarding the resize to {width}x{height} since the editor was closed");
+        }
+    }
+
+    /// Apply a zoom factor set while the editor was open. Called when the editor closes so the
+    /// host sees the new size before it opens the editor again.
+    fn apply_requested_zoom(&self) {
+        if let Some(zoom) = self.requested_zoom.take() {
+            self.zoom.store(zoom);
+        }
+    }
+
+    /// Let the bridge handle resizing the window by dragging its bottom right corner. `size` is the
+    /// side length in logical pixels of the square in that corner that acts as the grip, or `None`
+    /// to disable the grip. Mouse events handled by the grip are not passed on to the window
+    /// handler. The resize requests go through [`BaseviewState::set_size()`], so they respect the
+    /// size limits and the host can still refuse them.
+    pub fn set_resize_grip(&self, size: Option<u32>) {
+        self.resize_grip.store(size);
+    }
+
+    /// Returns the size of the resize grip set with [`BaseviewState::set_resize_grip()`], if any.
+    pub fn resize_grip(&self) -> Option<u32> {
+        self.resize_grip.load()
+    }
+
+    /// Clamp a logical size to the minimum and maximum sizes. The minimum size wins if the two
+    /// limits overlap.
+    fn clamp_size(&self, (mut width, mut height): (f64, f64)) -> (f64, f64) {
+        if let Some((max_width, max_height)) = self.max_size.load() {
+            width = width.min(max_width as f64);
+            height = height.min(max_height as f64);
+        }
+        if let Some((min_width, min_height)) = self.min_size.load() {
+            width = width.max(min_width as f64);
+            height = height.max(min_height as f64);
+        }
+
+        (width, height)
+    }
+
+    /// Choose which key events the editor keeps to itself and which ones are passed on to the host.
+    /// By default, key events the window handler ignores are passed on to the host.
+    pub fn set_keyboard_policy(&self, policy: KeyboardPolicy) {
+        *self.keyboard_policy.write() = policy;
+    }
+
+    /// Returns the policy set with [`BaseviewState::set_keyboard_policy()`].
+    pub fn keyboard_policy(&self) -> KeyboardPolicy {
+        self.keyboard_policy.read().clone()
+    }
+
+    /// Follow the keyboard focus conventions VST3 hosts expect. The editor then takes keyboard
+    /// focus when it's clicked, and it gives focus back to the host when the user clicks somewhere
+    /// else in the host's window or presses Escape. Escape only does this when the window handler
+    /// ignores the key, so it can still be used to cancel text entry. Enabled by default.
+    pub fn set_focus_handshake(&self, enabled: bool) {
+        self.focus_handshake.store(enabled, Ordering::Release);
+    }
+
+    /// Whether the editor follows the keyboard focus conventions. See
+    /// [`BaseviewState::set_focus_handshake()`].
+    pub fn focus_handshake(&self) -> bool {
+        self.focus_handshake.load(Ordering::Acquire)
+    }
+
+    /// Play notes from the computer keyboard while the editor has focus. Pass the same
+    /// [`KeyboardMidi`] to the audio thread so it can drain the note events, or pass `None` to
+    /// disable this again. Keys that play notes take precedence over the keyboard policy.
+    pub fn set_keyboard_midi(&self, keyboard_midi: Option<Arc<KeyboardMidi>>) {
+        *self.keyboard_midi.write() = keyboard_midi;
+    }
+
+    /// Returns the keyboard to MIDI bridge set with [`BaseviewState::set_keyboard_midi()`], if
+    /// any.
+    pub fn keyboard_midi(&self) -> Option<Arc<KeyboardMidi>> {
+        self.keyboard_midi.read().clone()
+    }
+
+    /// Returns the modifier keys (Shift, Ctrl, Alt, Cmd) that are currently held down. This is
+    /// updated from every keyboard and mouse event the editor receives, and it is reset when the
+    /// window loses focus so modifiers can't get stuck when focus is lost in the middle of a key
+    /// press.
+    pub fn modifiers(&self) -> Modifiers {
+        self.modifiers.load()
+    }
+
+    /// Change the mouse cursor shown while the cursor is over the editor, for instance to show a
+    /// hand over buttons or a resize cursor while dragging a knob. Use [`MouseCursor::Hidden`] to
+    /// hide the cursor. The cursor is updated on the GUI thread before the next frame or mouse
+    /// event, and the bridge's own resize grip takes precedence.
+    pub fn set_mouse_cursor(&self, mouse_cursor: MouseCursor) {
+        self.mouse_cursor.store(mouse_cursor);
+    }
+
+    /// Returns the cursor set with [`BaseviewState::set_mouse_cursor()`].
+    pub fn mouse_cursor(&self) -> MouseCursor {
+        self.mouse_cursor.load()
+    }
+
+    /// Lock the pointer in place for infinite drags, for instance when the user starts dragging a
+    /// knob. While the pointer is locked the cursor is hidden and kept where the drag started, and
+    /// the window handler receives cursor moved events with a virtual position that accumulates
+    /// the relative movement without stopping at the edge of the screen. The lock is released
+    /// again when a mouse button is released, when the window loses focus, or when this is called
+    /// with `false`, and the cursor then reappears where the drag started.
+    ///
+    /// The lock takes effect on the GUI thread before the next frame or mouse event, so this is
+    /// usually called when handling a button press.
+    pub fn set_pointer_locked(&self, locked: bool) {
+        self.pointer_locked.store(locked, Ordering::Release);
+    }
+
+    /// Whether the pointer is locked using [`BaseviewState::set_pointer_locked()`].
+    pub fn pointer_locked(&self) -> bool {
+        self.pointer_locked.load(Ordering::Acquire)
+    }
+
+    /// Enable or disable raw mouse input. Regular cursor positions are rounded to whole pixels and
+    /// go through the OS' pointer acceleration, which is too coarse for fine parameter adjustments.
+    /// With raw mouse input enabled, [`BaseviewState::raw_mouse_delta()`] returns the unaccelerated
+    /// movement behind every cursor moved event. This uses `WM_INPUT` on Windows, the mouse event's
+    /// sub-pixel deltas on macOS, and XInput 2 raw motion events on X11.
+    ///
+    /// Windows only lets one window per process receive raw mouse input, so this should only be
+    /// enabled for the duration of a drag to avoid taking it away from the host.
+    pub fn set_raw_mouse_input(&self, enabled: bool) {
+        self.raw_mouse_input.store(enabled, Ordering::Release);
+    }
+
+    /// Whether raw mouse input is enabled using [`BaseviewState::set_raw_mouse_input()`].
+    pub fn raw_mouse_input(&self) -> bool {
+        self.raw_mouse_input.load(Ordering::Acquire)
+    }
+
+    /// The raw mouse movement since the previous cursor moved event, if raw mouse input is enabled.
+    /// Call this while handling a cursor moved event. The movement is in the mouse's own units,
+    /// which are usually much finer than logical pixels and don't scale with the window, so a
+    /// drag's sensitivity should be tuned separately for this.
+    pub fn raw_mouse_delta(&self) -> Option<(f64, f64)> {
+        self.raw_mouse_delta.load()
+    }
+
+    /// Enable or disable touch input. Touch screens on Windows and X11 normally only produce the
+    /// mouse events the OS emulates for the first finger. With touch input enabled, every touch
+    /// point is also reported through [`BaseviewState::take_touch_events()`], with an ID to tell
+    /// multiple fingers apart. Macs don't have touch screens, so this does nothing on macOS. On
+    /// X11 this needs XInput 2.2.
+    pub fn set_touch_input(&self, enabled: bool) {
+        self.touch_input.store(enabled, Ordering::Release);
+    }
+
+    /// Whether touch input is enabled using [`BaseviewState::set_touch_input()`].
+    pub fn touch_input(&self) -> bool {
+        self.touch_input.load(Ordering::Acquire)
+    }
+
+    /// Take the touch events received since the last call. The bridge collects these before every
+    /// frame and event, so the window handler should call this from `on_frame()` or while handling
+    /// the mouse events emulated for the touches. The events are discarded when touch input is
+    /// disabled.
+    pub fn take_touch_events(&self) -> Vec<TouchEvent> {
+        std::mem::take(&mut *self.touch_events.lock())
+    }
+
+    /// Enable or disable pinch and rotate gestures, for instance to zoom a waveform view. These
+    /// gestures are reported through [`BaseviewState::take_gesture_events()`]. This covers
+    /// trackpads on macOS, and precision touchpads and touch screens on Windows. Gestures are not
+    /// yet supported on X11.
+    ///
+    /// On Windows, precision touchpads report pinches as Ctrl+scroll events, which are turned into
+    /// gestures instead while this is enabled. Touch screen gestures are only reported while touch
+    /// input is disabled, since Windows sends either touches or gestures.
+    pub fn set_gesture_input(&self, enabled: bool) {
+        self.gesture_input.store(enabled, Ordering::Release);
+    }
+
+    /// Whether gestures are enabled using [`BaseviewState::set_gesture_input()`].
+    pub fn gesture_input(&self) -> bool {
+        self.gesture_input.load(Ordering::Acquire)
+    }
+
+    /// Take the gestures received since the last call. Like with
+    /// [`BaseviewState::take_touch_events()`], the window handler should call this from
+    /// `on_frame()`. The events are discarded when gestures are disabled.
+    pub fn take_gesture_events(&self) -> Vec<GestureEvent> {
+        std::mem::take(&mut *self.gesture_events.lock())
+    }
+
+    /// Change when two clicks count as a double-click for [`BaseviewState::click_count()`]. By
+    /// default the OS' double-click time is used.
+    pub fn set_double_click_threshold(&self, threshold: DoubleClickThreshold) {
+        self.double_click_threshold.store(threshold);
+    }
+
+    /// Returns the threshold set with [`BaseviewState::set_double_click_threshold()`].
+    pub fn double_click_threshold(&self) -> DoubleClickThreshold {
+        self.double_click_threshold.load()
+    }
+
+    /// Convert scroll events to a consistent unit before they reach the window handler. The
+    /// handler then only receives [`ScrollDelta::Lines`], with fractional lines for trackpads,
+    /// positive values scrolling up and to the right on every platform, and Shift turning a
+    /// vertical wheel into a horizontal one on Windows and Linux like it does on macOS. Disabled
+    /// by default, in which case the deltas are passed on as reported by baseview.
+    ///
+    /// [`ScrollDelta::Lines`]: baseview::ScrollDelta::Lines
+    pub fn set_normalize_scrolling(&self, enabled: bool) {
+        self.normalize_scrolling.store(enabled, Ordering::Release);
+    }
+
+    /// Whether scroll events are normalized. See [`BaseviewState::set_normalize_scrolling()`].
+    pub fn normalize_scrolling(&self) -> bool {
+        self.normalize_scrolling.load(Ordering::Acquire)
+    }
+
+    /// The number of consecutive clicks of the same button, so 1 for a single click, 2 for a
+    /// double-click, and 3 for a triple-click. Call this while handling a button pressed event.
+    /// Clicks only count as consecutive if they are within the
+    /// [double-click threshold][BaseviewState::set_double_click_threshold()] of each other.
+    pub fn click_count(&self) -> u32 {
+        self.click_count.load()
+    }
+
+    /// Capture the mouse so the window keeps receiving mouse movement and button releases after
+    /// the cursor leaves the window, until [`BaseviewState::release_mouse()`] is called. The bridge
+    /// already captures the mouse while a button is held down, so drags don't get stuck when the
+    /// cursor moves over the host's UI. This is only needed to keep receiving events without a
+    /// button being held.
+    ///
+    /// Explicitly capturing the mouse is only supported on Windows. On macOS and X11 the OS already
+    /// captures the mouse while a button is held down.
+    pub fn capture_mouse(&self) {
+        self.mouse_captured.store(true, Ordering::Release);
+    }
+
+    /// Release the mouse after capturing it with [`BaseviewState::capture_mouse()`].
+    pub fn release_mouse(&self) {
+        self.mouse_captured.store(false, Ordering::Release);
+    }
+
+    /// Whether the mouse is captured using [`BaseviewState::capture_mouse()`].
+    pub fn is_mouse_captured(&self) -> bool {
+        self.mouse_captured.load(Ordering::Acquire)
+    }
+
+    /// Whether the editor's window currently has keyboard focus. The window handler also receives
+    /// [`WindowEvent::Focused`] and [`WindowEvent::Unfocused`] events when this changes, which can
+    /// be used to commit a text field's edits when the user clicks back into the host.
+    ///
+    /// [`WindowEvent::Focused`]: baseview::WindowEvent::Focused
+    /// [`WindowEvent::Unfocused`]: baseview::WindowEvent::Unfocused
+    pub fn is_focused(&self) -> bool {
+        self.focused.load(Ordering::Acquire)
+    }
+
+    /// Whether a dialog opened using [`open_modal_dialog()`] is currently open. The editor's
+    /// window doesn't receive mouse and keyboard events while this is the case.
+    pub fn has_modal_dialog(&self) -> bool {
+        self.modal_dialogs.load(Ordering::Acquire) > 0
+    }
+
+    /// Whether the window handler's `on_frame()` should be skipped while the editor's window is
+    /// hidden, for instance because the host's window is minimized or the editor is in a
+    /// background tab. This avoids rendering frames nobody can see. Rendering resumes as soon as
+    /// the window is shown again. This is enabled by default.
+    pub fn set_pause_when_hidden(&self, pause: bool) {
+        self.pause_when_hidden.store(pause, Ordering::Release);
+    }
+
+    /// Whether rendering is paused while the window is hidden. See
+    /// [`BaseviewState::set_pause_when_hidden()`].
+    pub fn pause_when_hidden(&self) -> bool {
+        self.pause_when_hidden.load(Ordering::Acquire)
+    }
+
+    /// Override the workarounds for host behaviors, for instance to disable one that misbehaves
+    /// in a host version the bridge doesn't know about yet. Passing `None` goes back to the
+    /// workarounds for the detected host. See [`HostQuirks`].
+    pub fn set_host_quirks(&self, quirks: Option<HostQuirks>) {
+        self.host_quirks.store(quirks);
+    }
+
+    /// The workarounds currently in use. These are the detected host's unless they were
+    /// overridden using [`BaseviewState::set_host_quirks()`].
+    pub fn host_quirks(&self) -> HostQuirks {
+        self.host_quirks.load().unwrap_or_else(HostQuirks::detect)
+    }
+
+    /// Whether the editor's window is open and visible on the screen. On macOS this also detects
+    /// windows that are fully covered by other windows. On Windows and X11 the window only counts
+    /// as hidden if it or one of its parents is hidden or minimized.
+    pub fn is_visible(&self) -> bool {
+        self.visible.load(Ordering::Acquire)
+    }
+
+    /// Frame time statistics for the last couple of seconds of drawing, for showing a performance
+    /// readout or logging slow frames. These are reset every time the editor is opened. This can
+    /// be called from any thread.
+    pub fn frame_stats(&self) -> FrameStats {
+        self.frame_timer.lock().stats()
+    }
+
+    /// The frame rate, event counts, size, scale, and OpenGL context of the editor's window as of
+    /// the current frame, for showing a debug readout. The diagnostics are formatted as a few
+    /// lines of text by their [`Display`][std::fmt::Display] implementation. Setting the
+    /// `NIH_PLUG_BASEVIEW_DEBUG_OVERLAY` environment variable makes the built-in OpenGL editors
+    /// draw this on top of the GUI, and custom OpenGL handlers can do the same using
+    /// [`draw_diagnostics_overlay()`].
+    pub fn diagnostics(&self) -> Diagnostics {
+        self.diagnostics.lock().clone()
+    }
+
+    /// Send a synthetic event to the window handler as if it came from baseview, for testing
+    /// widget interactions. The [`testing`] module has constructors for common events. This can be
+    /// called from any thread. The event is handled on the GUI thread right before the next frame,
+    /// so it still needs an open window, but that window can live on a virtual display like Xvfb.
+    /// Events injected while the editor is closed are handled once it opens. Use
+    /// [`testing::EventHarness`] to test input handling without a window.
+    #[cfg(feature = "testing")]
+    pub fn inject_event(&self, event: baseview::Event) {
+        self.injected_events.push(event);
+    }
+
+    /// Send several synthetic events in order. See [`BaseviewState::inject_event()`].
+    #[cfg(feature = "testing")]
+    pub fn inject_events(&self, events: impl IntoIterator<Item = baseview::Event>) {
+        for event in events {
+            self.injected_events.push(event);
+        }
+    }
+
+    /// Capture the next frame the editor draws, for comparing it against a golden image. Get the
+    /// frame using [`BaseviewState::take_captured_frame()`] once it's drawn. This can be called
+    /// from any thread. The built-in OpenGL editors capture the frame right before swapping their
+    /// buffers. Custom handlers need to capture it themselves using
+    /// [`testing::capture_current_frame()`] or [`testing::render_offscreen()`], and hand it over
+    /// using [`testing::submit_frame()`].
+    #[cfg(feature = "testing")]
+    pub fn request_frame_capture(&self) {
+        *self.captured_frame.lock() = None;
+        self.frame_capture_requested.store(true, Ordering::Release);
+    }
+
+    /// The frame captured after the last call to [`BaseviewState::request_frame_capture()`], if
+    /// it has been drawn yet.
+    #[cfg(feature = "testing")]
+    pub fn take_captured_frame(&self) -> Option<testing::Frame> {
+        self.captured_frame.lock().take()
+    }
+
+    /// Start writing the events baseview sends to the editor's window to a file, along with the
+    /// times they arrived at, so a user can send in a recording of the steps that lead to a bug.
+    /// Load the file using [`EventRecording::load()`] to replay it. The recording continues while
+    /// the editor is closed and reopened until [`BaseviewState::stop_recording()`] is called, and
+    /// starting a new recording ends the previous one. This can be called from any thread.
+    pub fn start_recording(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let recorder = EventRecorder::create(path.as_ref())?;
+        *self.event_recorder.lock() = Some(recorder);
+
+        Ok(())
+    }
+
+    /// Stop the recording started using [`BaseviewState::start_recording()`] and write the
+    /// remaining events to the file. This does nothing if nothing is being recorded.
+    pub fn stop_recording(&self) -> io::Result<()> {
+        match self.event_recorder.lock().take() {
+            Some(mut recorder) => recorder.flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether events are currently being recorded. See [`BaseviewState::start_recording()`].
+    pub fn is_recording(&self) -> bool {
+        self.event_recorder.lock().is_some()
+    }
+
+    /// Replay a recording's events in the editor's window with their original timing, starting
+    /// with the next frame. The events take the same path through the bridge as baseview's own
+    /// events. Mouse and keyboard input from the actual window is ignored until the replay
+    /// finishes so the user can't interfere with it. Recorded resize events only change the size
+    /// the handler sees, so replays are most faithful with the editor at the size and scale it was
+    /// recorded at. Replaying a new recording replaces the one that's currently being replayed.
+    /// This can be called from any thread.
+    pub fn replay_recording(&self, recording: EventRecording) {
+        *self.pending_replay.lock() = Some(recording);
+    }
+
+    /// Whether a recording is being replayed or waiting to be replayed. See
+    /// [`BaseviewState::replay_recording()`].
+    pub fn is_replaying(&self) -> bool {
+        self.replaying.load(Ordering::Acquire) || self.pending_replay.lock().is_some()
+    }
+
     /// Whether the GUI is currently visible.
     // Called `is_open()` instead of `open()` to avoid the ambiguity.
     pub fn is_open(&self) -> bool {
         self.open.load(Ordering::Acquire)
     }
+
+    /// Close the editor's window from the plugin's side, for instance after a fatal GUI error. This
+    /// can be called from any thread. The window is closed on the GUI thread before the next frame,
+    /// after which [`BaseviewState::is_open()`] returns `false` and the close callback is called.
+    ///
+    /// Plugins cannot close the host's window, so the host keeps showing an empty plugin window
+    /// until the user closes it. Reopening the editor from the host opens a new window.
+    pub fn request_close(&self) {
+        if self.is_open() {
+            self.close_requested.store(true, Ordering::Release);
+        }
+    }
+
+    /// Draw the next frame even if drawing is currently paused because the window is hidden, for
+    /// instance so a thumbnail or a host's preview shows the editor's latest state. This can be
+    /// called from any thread. Frames are otherwise drawn continuously, so this does nothing while
+    /// the window is visible.
+    pub fn request_repaint(&self) {
+        if self.is_open() {
+            self.repaint_requested.store(true, Ordering::Release);
+        }
+    }
+
+    /// If the build function, the window handler, or one of the plugin's GUI tasks, timers, frame
+    /// hooks, or texture upload callbacks panicked, returns the panic's message. The panic is
+    /// caught so it doesn't unwind into the host, but none of these are called afterwards and the
+    /// window shows the message until the editor is reopened. This can be used to show the error
+    /// somewhere else, or to close the editor using [`BaseviewState::request_close()`].
+    pub fn panic_message(&self) -> Option<String> {
+        self.panic_message.lock().clone()
+    }
+
+    /// Enable or disable the GUI thread watchdog. While enabled, a separate thread logs a message
+    /// naming the callback when the window handler's `on_frame()` or `on_event()`, or one of the
+    /// plugin's GUI tasks or timers, takes longer than the configured timeout. How long it took is
+    /// logged once the callback returns. The watchdog can't interrupt the callback, so one that
+    /// never returns keeps blocking the host's GUI thread. This is disabled by default.
+    pub fn set_watchdog(&self, config: Option<WatchdogConfig>) {
+        self.watchdog.store(config);
+    }
+
+    /// The GUI thread watchdog's configuration, if it is enabled. See
+    /// [`BaseviewState::set_watchdog()`].
+    pub fn watchdog(&self) -> Option<WatchdogConfig> {
+        self.watchdog.load()
+    }
+
+    /// Create a pair of bounded lock-free message queues between the editor and the audio thread,
+    /// with room for `capacity` messages in each direction. Move the [`AudioMessages`] side to the
+    /// plugin's `process()` function and the [`GuiMessages`] side to the editor. The messages for
+    /// the GUI are received automatically before every frame while the editor is open.
+    pub fn message_channel<ToAudio, ToGui>(
+        &self,
+        capacity: usize,
+    ) -> (GuiMessages<ToAudio, ToGui>, AudioMessages<ToAudio, ToGui>)
+    where
+        ToAudio: Send + 'static,
+        ToGui: Send + 'static,
+    {
+        let (gui_messages, audio_messages) = messages::channel(capacity);
+        self.add_frame_hook(&gui_messages.frame_hook());
+
+        (gui_messages, audio_messages)
+    }
+
+    /// Create a triple buffer for passing visualization data like spectra and envelopes from the
+    /// audio thread to the editor, with every buffer set to `initial`. Move the
+    /// [`VisualDataWriter`] to the plugin's `process()` function and the [`VisualData`] to the
+    /// editor. Writes are skipped while the editor is closed.
+    pub fn visual_data<T: Clone + Send>(
+        self: &Arc<Self>,
+        initial: T,
+    ) -> (VisualDataWriter<T>, VisualData<T>) {
+        visual_data::visual_data(initial, self.clone())
+    }
+
+    /// Create a ring buffer that holds `capacity` samples for streaming audio from the audio thread
+    /// to the editor, for waveform and oscilloscope displays. Move the [`ScopeWriter`] to the
+    /// plugin's `process()` function and the [`ScopeReader`] to the editor. Writes are skipped
+    /// while the editor is closed.
+    pub fn scope_buffer(self: &Arc<Self>, capacity: usize) -> (ScopeWriter, ScopeReader) {
+        scope::scope_buffer(capacity, self.clone())
+    }
+
+    /// Get a handle for running closures on the editor's GUI thread before the next frame. This
+    /// lets background threads safely hand their results to the GUI.
+    pub fn gui_task_proxy(&self) -> GuiTaskProxy {
+        GuiTaskProxy::new(self.gui_tasks.clone())
+    }
+
+    /// Call `callback` on the GUI thread once `delay` has passed. Timers are checked before every
+    /// frame, so they are only as precise as the frame rate. Timers that expire while the editor
+    /// is closed fire once it is opened again. This can be called from any thread.
+    pub fn set_timer(&self, delay: Duration, callback: impl FnOnce() + Send + 'static) -> TimerId {
+        self.timers.add_once(delay, callback)
+    }
+
+    /// Call `callback` on the GUI thread every `interval` until the timer is cancelled using
+    /// [`BaseviewState::cancel_timer()`]. See [`BaseviewState::set_timer()`].
+    pub fn set_repeating_timer(
+        &self,
+        interval: Duration,
+        callback: impl FnMut() + Send + 'static,
+    ) -> TimerId {
+        self.timers.add_repeating(interval, callback)
+    }
+
+    /// Stop a timer started using [`BaseviewState::set_timer()`] or
+    /// [`BaseviewState::set_repeating_timer()`]. Cancelling a timer that already fired does
+    /// nothing.
+    pub fn cancel_timer(&self, id: TimerId) {
+        self.timers.cancel(id);
+    }
+
+    /// Upload tightly packed 8-bit RGBA pixels to a texture on the editor's OpenGL context before
+    /// the next frame, and then call `on_uploaded` with the texture on the GUI thread. This can be
+    /// called from any thread, for instance from an [`AssetLoader`] callback. `on_uploaded`
+    /// receives `None` if the upload failed. Uploads queued while the editor is closed happen once
+    /// it is opened again. Textures belong to the window's context, so they need to be uploaded
+    /// again when the window is rebuilt.
+    pub fn upload_texture(
+        &self,
+        rgba: Vec<u8>,
+        width: u32,
+        height: u32,
+        options: TextureOptions,
+        on_uploaded: impl FnOnce(Option<GlTexture>) + Send + 'static,
+    ) {
+        self.texture_uploads
+            .push(rgba, width, height, options, on_uploaded);
+    }
+
+    /// The editor's asset cache. Assets stored here survive closing and reopening the editor, so
+    /// they only need to be decoded once. This can be used from any thread.
+    pub fn asset_cache(&self) -> &AssetCache {
+        &self.asset_cache
+    }
+
+    /// Set the RGBA color the window is cleared to right after it is created, before the build
+    /// function runs. Without this, hosts show an empty white or garbage-filled rectangle while a
+    /// slow build function compiles shaders or loads assets. This defaults to black. Pass `None` to
+    /// disable it, for instance when the build function is fast and draws its own first frame.
+    pub fn set_splash_color(&self, color: Option<[f32; 4]>) {
+        self.splash_color.store(color);
+    }
+
+    /// The color set with [`BaseviewState::set_splash_color()`].
+    pub fn splash_color(&self) -> Option<[f32; 4]> {
+        self.splash_color.load()
+    }
+
+    /// Use the alpha channel of what the editor draws to let whatever is behind the window show
+    /// through, for non-rectangular or partially transparent GUIs. The window's framebuffer always
+    /// has an alpha channel. This only works where the platform and the host allow it: on macOS
+    /// the host's window shows through, on Windows this only works for the standalone
+    /// application's window, and on X11 it requires a compositor and a window with an ARGB visual.
+    /// This takes effect the next time the editor opens. The splash color should be transparent
+    /// as well, see [`BaseviewState::set_splash_color()`].
+    pub fn set_transparent(&self, transparent: bool) {
+        self.transparent.store(transparent, Ordering::Release);
+    }
+
+    /// Whether the window is made transparent when it opens.
+    pub fn transparent(&self) -> bool {
+        self.transparent.load(Ordering::Acquire)
+    }
+
+    /// Set the icon of the application's window when the plugin runs as a standalone application,
+    /// or `None` to use the default icon. On macOS this sets the application's Dock icon instead.
+    /// Plugin hosts own the windows the editor is embedded in, so this does nothing inside of a
+    /// host. This can be called at any time, and it takes effect before the next frame.
+    pub fn set_window_icon(&self, icon: Option<WindowIcon>) {
+        *self.window_icon.lock() = icon;
+        self.standalone_window_changed
+            .store(true, Ordering::Release);
+    }
+
+    /// Keep the standalone application's window above other windows, for instance so a meter stays
+    /// visible while arranging in a DAW. Like [`BaseviewState::set_window_icon()`] this does
+    /// nothing inside of a plugin host, since the host owns the windows the editor is embedded in.
+    pub fn set_always_on_top(&self, always_on_top: bool) {
+        self.always_on_top.store(always_on_top, Ordering::Release);
+        self.standalone_window_changed
+            .store(true, Ordering::Release);
+    }
+
+    /// Whether the standalone application's window is set to stay above other windows.
+    pub fn always_on_top(&self) -> bool {
+        self.always_on_top.load(Ordering::Acquire)
+    }
+
+    /// Choose whether the standalone application's window has a title bar and borders, and
+    /// whether the user can resize it. `None` keeps the style the standalone wrapper created the
+    /// window with. Like [`BaseviewState::set_window_icon()`] this does nothing inside of a plugin
+    /// host. The editor's own size is not affected, so adding or removing decorations changes the
+    /// window's outer size.
+    pub fn set_window_style(&self, style: Option<WindowStyle>) {
+        self.window_style.store(style);
+        self.standalone_window_changed
+            .store(true, Ordering::Release);
+    }
+
+    /// The style set with [`BaseviewState::set_window_style()`], if any.
+    pub fn window_style(&self) -> Option<WindowStyle> {
+        self.window_style.load()
+    }
+
+    /// The last known position of the standalone application's window, if the plugin has run as a
+    /// standalone application. This is tracked and persisted automatically, and the window is
+    /// moved back to this position when the editor opens as long as that's still on one of the
+    /// connected monitors. The position is in physical pixels on Windows and X11, and in points
+    /// with the y-axis pointing up on macOS.
+    pub fn window_position(&self) -> Option<(i32, i32)> {
+        self.window_position.load()
+    }
+
+    /// Call `callback` whenever the editor opens, right after [`BaseviewState::is_open()`] starts
+    /// returning `true`. This can be used to tell the DSP side to start producing data for meters
+    /// and visualizations. The callback is called from the thread the host opens the editor on and
+    /// no locks are held while it runs, so it's safe to send messages from it.
+    pub fn set_on_editor_open(&self, callback: impl Fn() + Send + Sync + 'static) {
+        self.callbacks.write().on_open = Some(Arc::new(callback));
+    }
+
+    /// Call `callback` whenever the editor closes, right after [`BaseviewState::is_open()`] starts
+    /// returning `false`. See [`BaseviewState::set_on_editor_open()`].
+    pub fn set_on_editor_close(&self, callback: impl Fn() + Send + Sync + 'static) {
+        self.callbacks.write().on_close = Some(Arc::new(callback));
+    }
+
+    /// Remove the callbacks set with [`BaseviewState::set_on_editor_open()`] and
+    /// [`BaseviewState::set_on_editor_close()`].
+    pub fn clear_editor_callbacks(&self) {
+        *self.callbacks.write() = EditorCallbacks::default();
+    }
+
+    /// Run `hook` before every frame until the hook is dropped.
+    fn add_frame_hook(&self, hook: &Arc<dyn FrameHook>) {
+        self.frame_hooks.lock().push(Arc::downgrade(hook));
+    }
+
+    /// Run the hooks added with [`BaseviewState::add_frame_hook()`]. Called by the window handler
+    /// before every frame.
+    fn run_frame_hooks(&self) {
+        profile_scope!("frame_hooks");
+
+        // The hooks may create new channels, so they can't be run while holding the lock
+        let hooks: Vec<_> = {
+            let mut frame_hooks = self.frame_hooks.lock();
+            frame_hooks.retain(|hook| hook.strong_count() > 0);
+            frame_hooks.iter().filter_map(Weak::upgrade).collect()
+        };
+
+        for hook in hooks {
+            hook.before_frame();
+        }
+    }
+
+    /// Add an event baseview sent to the window to the recording, if there is one. The recording
+    /// is stopped if the file can't be written to.
+    fn record_event(&self, event: &baseview::Event) {
+        let mut event_recorder = self.event_recorder.lock();
+        if let Some(Err(err)) = event_recorder
+            .as_mut()
+            .map(|recorder| recorder.record(event))
+        {
+            nih_log!("Could not write to the event recording, stopping the recording: {err}");
+            *event_recorder = None;
+        }
+    }
+
+    /// Write the recorded events to the file. Called by the window handler after every frame.
+    fn flush_recording(&self) {
+        let mut event_recorder = self.event_recorder.lock();
+        if let Some(Err(err)) = event_recorder.as_mut().map(EventRecorder::flush) {
+            nih_log!("Could not write to the event recording, stopping the recording: {err}");
+            *event_recorder = None;
+        }
+    }
+
+    /// Called by the editor after it opened the window.
+    fn editor_opened(&self) {
+        *self.frame_timer.lock() = FrameTimer::default();
+
+        // The callback may replace the callbacks, so it can't be called while holding the lock
+        let on_open = self.callbacks.read().on_open.clone();
+        if let Some(on_open) = on_open {
+            on_open();
+        }
+    }
+
+    /// Called after the window was closed, either by the host or through
+    /// [`BaseviewState::request_close()`]. This only calls the close callback once.
+    fn editor_closed(&self) {
+        if !self.open.swap(false, Ordering::AcqRel) {
+            return;
+        }
+        self.replaying.store(false, Ordering::Release);
+
+        let on_close = self.callbacks.read().on_close.clone();
+        if let Some(on_close) = on_close {
+            on_close();
+        }
+    }
 }
@@ -0,0 +1,154 @@
+//! Loading and decoding assets like images, fonts, and impulse responses on background threads, so
+//! the editor stays responsive while they load.
+
+use crossbeam::channel::{self, Receiver, Sender};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use crate::GuiTaskProxy;
+
+/// A job sent to the loader's worker threads.
+type Job = Box<dyn FnOnce() + Send>;
+
+/// The most threads [`AssetLoader::new()`] starts, so the editor doesn't compete with the host's
+/// audio threads.
+const MAX_DEFAULT_THREADS: usize = 4;
+
+/// A small thread pool for loading assets. Results are either delivered to a callback on the GUI
+/// thread using [`AssetLoader::load_then()`], or polled by the window handler using the
+/// [`PendingAsset`] returned by [`AssetLoader::load()`]. Dropping the loader discards the jobs that
+/// haven't started yet and waits for the running ones to finish.
+pub struct AssetLoader {
+    /// Dropping this stops the worker threads once the queue is empty.
+    job_sender: Option<Sender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+    /// Set when the loader is dropped, so the workers skip the remaining jobs.
+    cancelled: Arc<AtomicBool>,
+    gui_task_proxy: GuiTaskProxy,
+}
+
+/// An asset that is being loaded by an [`AssetLoader`].
+pub struct PendingAsset<T> {
+    receiver: Receiver<T>,
+    value: Option<T>,
+}
+
+impl AssetLoader {
+    /// Start a loader with one thread per CPU core, up to four threads. Callbacks passed to
+    /// [`AssetLoader::load_then()`] run through `gui_task_proxy`, which can be obtained using
+    /// [`BaseviewState::gui_task_proxy()`][crate::BaseviewState::gui_task_proxy()].
+    pub fn new(gui_task_proxy: GuiTaskProxy) -> Self {
+        let num_threads = thread::available_parallelism()
+            .map_or(1, |threads| threads.get())
+            .min(MAX_DEFAULT_THREADS);
+
+        Self::with_threads(gui_task_proxy, num_threads)
+    }
+
+    /// The same as [`AssetLoader::new()`], but with a fixed number of threads.
+    pub fn with_threads(gui_task_proxy: GuiTaskProxy, num_threads: usize) -> Self {
+        let (job_sender, job_receiver) = channel::unbounded::<Job>();
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        let workers = (0..num_threads.max(1))
+            .filter_map(|i| {
+                let job_receiver = job_receiver.clone();
+                let cancelled = cancelled.clone();
+                thread::Builder::new()
+                    .name(format!("baseview-loader-{i}"))
+                    .spawn(move || {
+                        for job in job_receiver {
+                            if cancelled.load(Ordering::Acquire) {
+                                break;
+                            }
+
+                            job();
+                        }
+                    })
+                    .ok()
+            })
+            .collect();
+
+        Self {
+            job_sender: Some(job_sender),
+            workers,
+            cancelled,
+            gui_task_proxy,
+        }
+    }
+
+    /// Run `load` on one of the loader's threads. The window handler can check whether the
+    /// result is ready using [`PendingAsset::get()`].
+    pub fn load<T, F>(&self, load: F) -> PendingAsset<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let (sender, receiver) = channel::bounded(1);
+        self.spawn(move || {
+            let _ = sender.send(load());
+        });
+
+        PendingAsset {
+            receiver,
+            value: None,
+        }
+    }
+
+    /// Run `load` on one of the loader's threads, and then call `on_loaded` with the result on the
+    /// GUI thread before the next frame.
+    pub fn load_then<T, F, C>(&self, load: F, on_loaded: C)
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+        C: FnOnce(T) + Send + 'static,
+    {
+        let gui_task_proxy = self.gui_task_proxy.clone();
+        self.spawn(move || {
+            let value = load();
+            gui_task_proxy.run(move || on_loaded(value));
+        });
+    }
+
+    fn spawn(&self, job: impl FnOnce() + Send + 'static) {
+        if let Some(job_sender) = &self.job_sender {
+            let _ = job_sender.send(Box::new(job));
+        }
+    }
+}
+
+impl Drop for AssetLoader {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Release);
+        self.job_sender.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl<T> PendingAsset<T> {
+    /// The loaded asset, or `None` if it is still loading. This never blocks.
+    pub fn get(&mut self) -> Option<&T> {
+        if self.value.is_none() {
+            self.value = self.receiver.try_recv().ok();
+        }
+
+        self.value.as_ref()
+    }
+
+    /// Take the loaded asset out, or get the pending asset back if it is still loading.
+    pub fn try_take(mut self) -> Result<T, Self> {
+        self.get();
+        match self.value.take() {
+            Some(value) => Ok(value),
+            None => Err(self),
+        }
+    }
+
+    /// Whether the asset has finished loading.
+    pub fn is_loaded(&mut self) -> bool {
+        self.get().is_some()
+    }
+}
@@ -0,0 +1,21 @@
+//! Support for user state that can't be sent between threads, like state holding OpenGL objects
+//! or `Rc`s. The state is created on the window's thread and lives next to the handler.
+
+use baseview::{Event, EventStatus, Window, WindowHandler};
+
+/// Keeps the user state created for a window alive alongside the handler built from it.
+pub(crate) struct LocalStateHandler<S, H> {
+    /// This is dropped before the state, since the handler may refer to resources it owns.
+    pub inner: H,
+    pub state: S,
+}
+
+impl<S, H: WindowHandler> WindowHandler for LocalStateHandler<S, H> {
+    fn on_frame(&mut self, window: &mut Window) {
+        self.inner.on_frame(window);
+    }
+
+    fn on_event(&mut self, window: &mut Window, event: Event) -> EventStatus {
+        self.inner.on_event(window, event)
+    }
+}
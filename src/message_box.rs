@@ -0,0 +1,72 @@
+//! Native message boxes for reporting errors and asking for simple confirmations, so plugins don't
+//! need to draw their own alerts for trivial cases. Like the file dialogs, these are shown using
+//! [rfd](https://github.com/PolyMeilex/rfd) and they don't block the GUI.
+
+use crate::pop_out::RawParent;
+use crate::BaseviewState;
+
+/// The kind of message shown in a message box. This decides the message box's icon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MessageLevel {
+    #[default]
+    Info,
+    Warning,
+    Error,
+}
+
+/// Show a message box with an OK button, for instance to report that a sample failed to load.
+/// This needs to be called from the GUI thread. The message box is parented to the editor's window
+/// if the editor is open.
+pub fn show_message_box(
+    baseview_state: &BaseviewState,
+    level: MessageLevel,
+    title: &str,
+    message: &str,
+) {
+    let future = create_dialog(baseview_state, level, title, message)
+        .set_buttons(rfd::MessageButtons::Ok)
+        .show();
+
+    baseview_state.gui_task_proxy().spawn(async move {
+        future.await;
+    });
+}
+
+/// Show a message box with OK and Cancel buttons. `on_result` is called on the GUI thread with
+/// `true` if the user pressed OK, and with `false` if they cancelled or closed the message box.
+pub fn show_confirm_box(
+    baseview_state: &BaseviewState,
+    level: MessageLevel,
+    title: &str,
+    message: &str,
+    on_result: impl FnOnce(bool) + Send + 'static,
+) {
+    let future = create_dialog(baseview_state, level, title, message)
+        .set_buttons(rfd::MessageButtons::OkCancel)
+        .show();
+
+    baseview_state.gui_task_proxy().spawn(async move {
+        on_result(future.await);
+    });
+}
+
+fn create_dialog(
+    baseview_state: &BaseviewState,
+    level: MessageLevel,
+    title: &str,
+    message: &str,
+) -> rfd::AsyncMessageDialog {
+    let mut dialog = rfd::AsyncMessageDialog::new()
+        .set_level(match level {
+            MessageLevel::Info => rfd::MessageLevel::Info,
+            MessageLevel::Warning => rfd::MessageLevel::Warning,
+            MessageLevel::Error => rfd::MessageLevel::Error,
+        })
+        .set_title(title)
+        .set_description(message);
+    if let Some(window_handle) = crate::handle::window_handle(baseview_state) {
+        dialog = dialog.set_parent(&RawParent(window_handle.raw()));
+    }
+
+    dialog
+}
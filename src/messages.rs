@@ -0,0 +1,219 @@
+//! Typed message queues between the editor and the audio thread. Both directions use bounded
+//! lock-free queues, so neither side ever blocks or allocates when sending a message.
+
+use crossbeam::queue::ArrayQueue;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Something that needs to run on the GUI thread before every frame, registered using
+/// [`BaseviewState::add_frame_hook()`][crate::BaseviewState::add_frame_hook()].
+pub(crate) trait FrameHook: Send + Sync {
+    fn before_frame(&self);
+}
+
+/// The GUI side of a message channel created using
+/// [`BaseviewState::message_channel()`][crate::BaseviewState::message_channel()]. Messages from the
+/// audio thread are moved out of the queue before every frame, so the audio thread doesn't run out
+/// of space while the handler is busy.
+pub struct GuiMessages<ToAudio, ToGui> {
+    channel: Arc<Channel<ToAudio, ToGui>>,
+}
+
+/// The audio thread's side of a message channel created using
+/// [`BaseviewState::message_channel()`][crate::BaseviewState::message_channel()].
+pub struct AudioMessages<ToAudio, ToGui> {
+    channel: Arc<Channel<ToAudio, ToGui>>,
+}
+
+struct Channel<ToAudio, ToGui> {
+    to_audio: ArrayQueue<ToAudio>,
+    to_gui: ArrayQueue<ToGui>,
+    /// The messages for the GUI that were moved out of `to_gui` but have not been taken by the
+    /// handler yet. Only the GUI thread uses this.
+    received: Mutex<VecDeque<ToGui>>,
+    /// The number of messages for the GUI that were dropped because the handler didn't take them
+    /// in time, since the last call to [`GuiMessages::take_dropped()`].
+    dropped: AtomicUsize,
+    capacity: usize,
+}
+
+/// Create both sides of a message channel. `capacity` is the number of messages each queue can
+/// hold.
+pub(crate) fn channel<ToAudio, ToGui>(
+    capacity: usize,
+) -> (GuiMessages<ToAudio, ToGui>, AudioMessages<ToAudio, ToGui>) {
+    let capacity = capacity.max(1);
+    let channel = Arc::new(Channel {
+        to_audio: ArrayQueue::new(capacity),
+        to_gui: ArrayQueue::new(capacity),
+        received: Mutex::new(VecDeque::with_capacity(capacity)),
+        dropped: AtomicUsize::new(0),
+        capacity,
+    });
+
+    (
+        GuiMessages {
+            channel: channel.clone(),
+        },
+        AudioMessages { channel },
+    )
+}
+
+impl<ToAudio, ToGui> GuiMessages<ToAudio, ToGui> {
+    /// Send a message to the audio thread. If the queue is full the message is returned.
+    pub fn send(&self, message: ToAudio) -> Result<(), ToAudio> {
+        self.channel.to_audio.push(message)
+    }
+
+    /// Take the messages the audio thread sent since the last call, oldest first. If the handler
+    /// doesn't take the messages often enough, the oldest ones are dropped and counted in
+    /// [`GuiMessages::take_dropped()`].
+    pub fn take(&self) -> Vec<ToGui> {
+        self.channel.receive();
+        self.channel.received.lock().drain(..).collect()
+    }
+
+    /// The number of messages from the audio thread that were dropped since the last call because
+    /// the handler didn't take them in time. Messages the audio thread couldn't send because the
+    /// queue was full are returned by [`AudioMessages::send()`] instead.
+    pub fn take_dropped(&self) -> usize {
+        self.channel.dropped.swap(0, Ordering::Relaxed)
+    }
+
+    /// The hook that moves the audio thread's messages out of the queue before every frame.
+    pub(crate) fn frame_hook(&self) -> Arc<dyn FrameHook>
+    where
+        ToAudio: Send + 'static,
+        ToGui: Send + 'static,
+    {
+        self.channel.clone()
+    }
+}
+
+impl<ToAudio, ToGui> AudioMessages<ToAudio, ToGui> {
+    /// Send a message to the GUI. If the queue is full the message is returned. This is
+    /// realtime-safe.
+    pub fn send(&self, message: ToGui) -> Result<(), ToGui> {
+        self.channel.to_gui.push(message)
+    }
+
+    /// Receive the next message from the GUI, if there is one. This is realtime-safe.
+    pub fn try_recv(&self) -> Option<ToAudio> {
+        self.channel.to_audio.pop()
+    }
+
+    /// Iterate over all messages the GUI has sent so far. This is realtime-safe.
+    pub fn iter(&self) -> impl Iterator<Item = ToAudio> + '_ {
+        std::iter::from_fn(|| self.channel.to_audio.pop())
+    }
+}
+
+impl<ToAudio, ToGui> Clone for GuiMessages<ToAudio, ToGui> {
+    fn clone(&self) -> Self {
+        Self {
+            channel: self.channel.clone(),
+        }
+    }
+}
+
+impl<ToAudio, ToGui> Channel<ToAudio, ToGui> {
+    /// Move the audio thread's messages out of the queue.
+    fn receive(&self) {
+        let mut received = self.received.lock();
+        while let Some(message) = self.to_gui.pop() {
+            if received.len() == self.capacity {
+                received.pop_front();
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+            received.push_back(message);
+        }
+    }
+}
+
+impl<ToAudio: Send, ToGui: Send> FrameHook for Channel<ToAudio, ToGui> {
+    fn before_frame(&self) {
+        self.receive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn messages_arrive_in_order() {
+        let (gui, audio) = channel::<u32, u32>(4);
+
+        gui.send(1).unwrap();
+        gui.send(2).unwrap();
+        assert_eq!(audio.iter().collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(audio.try_recv(), None);
+
+        audio.send(3).unwrap();
+        audio.send(4).unwrap();
+        assert_eq!(gui.take(), [3, 4]);
+        assert!(gui.take().is_empty());
+    }
+
+    #[test]
+    fn full_queues_return_the_message() {
+        let (gui, audio) = channel::<u32, u32>(2);
+
+        gui.send(1).unwrap();
+        gui.send(2).unwrap();
+        assert_eq!(gui.send(3), Err(3));
+
+        audio.send(1).unwrap();
+        audio.send(2).unwrap();
+        assert_eq!(audio.send(3), Err(3));
+        assert_eq!(gui.take_dropped(), 0);
+    }
+
+    #[test]
+    fn overflowing_messages_are_dropped_and_counted() {
+        let (gui, audio) = channel::<(), u32>(2);
+        let frame_hook = gui.frame_hook();
+
+        // The frame hook empties the queue every frame, but the handler never takes the messages
+        for message in 0..5 {
+            audio.send(message).unwrap();
+            frame_hook.before_frame();
+        }
+        assert_eq!(gui.take(), [3, 4]);
+        assert_eq!(gui.take_dropped(), 3);
+        assert_eq!(gui.take_dropped(), 0);
+    }
+
+    #[test]
+    fn concurrent_messages_are_received_in_order_or_counted() {
+        const MESSAGES: u32 = 100_000;
+
+        let (gui, audio) = channel::<(), u32>(64);
+        let audio_thread = thread::spawn(move || {
+            let mut message = 0;
+            while message < MESSAGES {
+                if audio.send(message).is_ok() {
+                    message += 1;
+                }
+            }
+        });
+
+        // A batch can overflow if the audio thread keeps refilling the queue while it's being
+        // emptied, in which case the oldest messages of that batch are dropped
+        let mut expected = 0;
+        while expected < MESSAGES {
+            let messages = gui.take();
+            expected += gui.take_dropped() as u32;
+            for message in messages {
+                assert_eq!(message, expected);
+                expected += 1;
+            }
+        }
+        audio_thread.join().unwrap();
+        assert_eq!(expected, MESSAGES);
+    }
+}
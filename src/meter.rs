@@ -0,0 +1,113 @@
+//! A peak and RMS meter the audio thread updates and the editor reads, with the usual attack and
+//! release ballistics.
+
+use crossbeam::atomic::AtomicCell;
+use nih_plug::util;
+
+/// A level meter that can be shared between the audio thread and the editor, usually through an
+/// `Arc`. The audio thread feeds it samples using [`PeakMeter::process()`], and the editor reads
+/// the smoothed levels using [`PeakMeter::peak()`] and [`PeakMeter::rms()`]. All operations are
+/// lock-free and realtime-safe.
+#[derive(Debug)]
+pub struct PeakMeter {
+    /// The attack and release times in milliseconds.
+    ballistics: AtomicCell<(f32, f32)>,
+    sample_rate: AtomicCell<f32>,
+
+    /// The smoothed peak level as a linear gain value.
+    peak: AtomicCell<f32>,
+    /// The smoothed mean square. The RMS level is the square root of this.
+    mean_square: AtomicCell<f32>,
+}
+
+impl PeakMeter {
+    /// Create a meter with the given attack and release times in milliseconds. An attack time of
+    /// zero makes the meter jump to new peaks immediately.
+    pub fn new(attack_ms: f32, release_ms: f32) -> Self {
+        Self {
+            ballistics: AtomicCell::new((attack_ms, release_ms)),
+            sample_rate: AtomicCell::new(44100.0),
+
+            peak: AtomicCell::new(0.0),
+            mean_square: AtomicCell::new(0.0),
+        }
+    }
+
+    /// Change the attack and release times in milliseconds.
+    pub fn set_ballistics(&self, attack_ms: f32, release_ms: f32) {
+        self.ballistics.store((attack_ms, release_ms));
+    }
+
+    /// Set the sample rate the meter is fed at. Call this from the plugin's `initialize()`
+    /// function.
+    pub fn set_sample_rate(&self, sample_rate: f32) {
+        self.sample_rate.store(sample_rate);
+    }
+
+    /// Update the meter with a block of samples. For multichannel audio, pass the loudest sample
+    /// of every frame or use one meter per channel. Only one thread should call this at a time.
+    pub fn process(&self, samples: impl IntoIterator<Item = f32>) {
+        let (attack_ms, release_ms) = self.ballistics.load();
+        let sample_rate = self.sample_rate.load();
+        let attack_coefficient = smoothing_coefficient(attack_ms, sample_rate);
+        let release_coefficient = smoothing_coefficient(release_ms, sample_rate);
+
+        let mut peak = self.peak.load();
+        let mut mean_square = self.mean_square.load();
+        for sample in samples {
+            let magnitude = sample.abs();
+            let coefficient = if magnitude > peak {
+                attack_coefficient
+            } else {
+                release_coefficient
+            };
+            peak = magnitude + coefficient * (peak - magnitude);
+
+            let square = sample * sample;
+            let coefficient = if square > mean_square {
+                attack_coefficient
+            } else {
+                release_coefficient
+            };
+            mean_square = square + coefficient * (mean_square - square);
+        }
+
+        self.peak.store(peak);
+        self.mean_square.store(mean_square);
+    }
+
+    /// The smoothed peak level as a linear gain value.
+    pub fn peak(&self) -> f32 {
+        self.peak.load()
+    }
+
+    /// The smoothed peak level in decibels.
+    pub fn peak_db(&self) -> f32 {
+        util::gain_to_db(self.peak())
+    }
+
+    /// The smoothed RMS level as a linear gain value.
+    pub fn rms(&self) -> f32 {
+        self.mean_square.load().sqrt()
+    }
+
+    /// The smoothed RMS level in decibels.
+    pub fn rms_db(&self) -> f32 {
+        util::gain_to_db(self.rms())
+    }
+
+    /// Drop the meter back to silence, for instance when the plugin is reset.
+    pub fn reset(&self) {
+        self.peak.store(0.0);
+        self.mean_square.store(0.0);
+    }
+}
+
+/// The coefficient for a one-pole filter that reaches about 63% of a step within `time_ms`.
+fn smoothing_coefficient(time_ms: f32, sample_rate: f32) -> f32 {
+    if time_ms <= 0.0 || sample_rate <= 0.0 {
+        0.0
+    } else {
+        (-1.0 / (time_ms / 1000.0 * sample_rate)).exp()
+    }
+}
@@ -0,0 +1,1061 @@
+//! A scripted stand-in for a plugin host, so the bridge and a plugin's editor can be tested in
+//! `cargo test`. [`MockHost`] owns a native window to embed the editor in, and it calls the
+//! editor the same way hosts do, including in the odd orders some hosts use. The editor still
+//! opens a real window. On Linux that needs an X server, which can be a virtual one like Xvfb.
+//! AppKit only works on the main thread, so on macOS the tests need to run there, for instance
+//! in an integration test with `harness = false`.
+
+use nih_plug::prelude::{Editor, GuiContext, ParamPtr, ParentWindowHandle, PluginApi};
+use nih_plug::wrapper::state::PluginState;
+use parking_lot::Mutex;
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::snapshot::Frame;
+use crate::BaseviewEditorHandle;
+
+/// How long [`MockHost::capture_frame()`] waits for the editor to draw a frame.
+const CAPTURE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A call from the host to the editor. Scripts made out of these can be run using
+/// [`MockHost::run()`], and [`scripts`] contains the orders real hosts use.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HostCall {
+    /// Call [`Editor::spawn()`] with the host's window as the parent. The handles of editors that
+    /// are already open are kept, like REAPER does when docking the plugin window.
+    Spawn,
+    /// Drop the handle returned by the most recent [`Editor::spawn()`], which closes the editor.
+    Close,
+    /// Drop the oldest handle that's still around. This is what hosts that spawn the editor again
+    /// before closing the old one do afterwards.
+    CloseStale,
+    /// Call [`Editor::set_scale_factor()`].
+    SetScaleFactor(f32),
+    /// Call [`Editor::size()`] and resize the host's window to match.
+    Resize,
+    /// Call [`Editor::param_value_changed()`] with a parameter ID and a normalized value.
+    ParamValueChanged(String, f32),
+    /// Call [`Editor::param_modulation_changed()`] with a parameter ID and a modulation offset.
+    ParamModulationChanged(String, f32),
+    /// Call [`Editor::param_values_changed()`].
+    ParamValuesChanged,
+    /// Run the host's event loop for a while, so the editor can draw frames and handle its
+    /// requests. Resize requests the [`MockGuiContext`] accepts are carried out here.
+    Idle(Duration),
+}
+
+/// Calls an editor the way a plugin host would. The editor is spawned in a window owned by the
+/// host, and the host calls are made on the thread that created the `MockHost`. Assert on the
+/// [`BaseviewState`][crate::BaseviewState] the editor was created with, on the
+/// [`MockGuiContext`], and on the editor handles in between calls.
+pub struct MockHost {
+    editor: Box<dyn Editor>,
+    context: Arc<MockGuiContext>,
+    /// The handles returned by [`Editor::spawn()`] that haven't been dropped yet, oldest first.
+    /// These are dropped before the host's window.
+    handles: Vec<Box<dyn Any + Send>>,
+    window: HostWindow,
+    /// The calls made so far, for printing when an assertion fails.
+    calls: Vec<HostCall>,
+}
+
+impl MockHost {
+    /// Create a host with a window of the editor's current size. Returns `None` if the window
+    /// can't be created, for instance because there's no X server to connect to.
+    pub fn new(editor: Box<dyn Editor>) -> Option<Self> {
+        Self::with_context(editor, Arc::new(MockGuiContext::default()))
+    }
+
+    /// Create a host whose window is never shown, so tests don't flash windows on the screen. The
+    /// editor still gets an OpenGL context, but its window's framebuffer has no contents. Frames
+    /// can only be captured from handlers that draw them using
+    /// [`render_offscreen()`][crate::testing::render_offscreen()].
+    pub fn new_hidden(editor: Box<dyn Editor>) -> Option<Self> {
+        Self::create(editor, Arc::new(MockGuiContext::default()), false)
+    }
+
+    /// Create a host that passes `context` to the editor when spawning it.
+    pub fn with_context(editor: Box<dyn Editor>, context: Arc<MockGuiContext>) -> Option<Self> {
+        Self::create(editor, context, true)
+    }
+
+    fn create(
+        editor: Box<dyn Editor>,
+        context: Arc<MockGuiContext>,
+        visible: bool,
+    ) -> Option<Self> {
+        let (width, height) = editor.size();
+        let window = HostWindow::new(width, height, visible)?;
+
+        Some(Self {
+            editor,
+            context,
+            handles: Vec::new(),
+            window,
+            calls: Vec::new(),
+        })
+    }
+
+    /// Make a single call to the editor.
+    pub fn call(&mut self, call: HostCall) {
+        match &call {
+            HostCall::Spawn => {
+                let handle = self
+                    .editor
+                    .spawn(self.window.parent_handle(), self.context.clone());
+                self.handles.push(handle);
+            }
+            HostCall::Close => {
+                self.handles.pop();
+            }
+            HostCall::CloseStale => {
+                if !self.handles.is_empty() {
+                    self.handles.remove(0);
+                }
+            }
+            HostCall::SetScaleFactor(factor) => {
+                self.editor.set_scale_factor(*factor);
+            }
+            HostCall::Resize => self.resize_window(),
+            HostCall::ParamValueChanged(id, normalized_value) => {
+                self.editor.param_value_changed(id, *normalized_value)
+            }
+            HostCall::ParamModulationChanged(id, modulation_offset) => {
+                self.editor.param_modulation_changed(id, *modulation_offset)
+            }
+            HostCall::ParamValuesChanged => self.editor.param_values_changed(),
+            HostCall::Idle(duration) => self.idle(*duration),
+        }
+
+        self.calls.push(call);
+    }
+
+    /// Make a sequence of calls to the editor, in order.
+    pub fn run(&mut self, script: impl IntoIterator<Item = HostCall>) {
+        for call in script {
+            self.call(call);
+        }
+    }
+
+    /// The calls made so far.
+    pub fn calls(&self) -> &[HostCall] {
+        &self.calls
+    }
+
+    /// The context passed to the editor.
+    pub fn context(&self) -> &Arc<MockGuiContext> {
+        &self.context
+    }
+
+    pub fn editor(&self) -> &dyn Editor {
+        self.editor.as_ref()
+    }
+
+    /// The number of handles returned by [`Editor::spawn()`] that haven't been dropped yet.
+    pub fn open_handles(&self) -> usize {
+        self.handles.len()
+    }
+
+    /// The handle returned by the most recent [`Editor::spawn()`], if it hasn't been dropped and
+    /// the editor was created by this crate.
+    pub fn editor_handle(&self) -> Option<&BaseviewEditorHandle> {
+        self.handles.last()?.downcast_ref()
+    }
+
+    /// The size of the host's window, in the same units as [`Editor::size()`].
+    pub fn window_size(&self) -> (u32, u32) {
+        self.window.size
+    }
+
+    /// Capture the next frame the editor draws, waiting up to a second for it. Returns `None` if
+    /// the editor isn't open or if it didn't draw a frame in time. Only the built-in OpenGL
+    /// editors capture their frames on their own, see
+    /// [`BaseviewState::request_frame_capture()`][crate::BaseviewState::request_frame_capture()].
+    /// Hidden hosts only get frames from handlers that use
+    /// [`render_offscreen()`][crate::testing::render_offscreen()].
+    pub fn capture_frame(&mut self) -> Option<Frame> {
+        let baseview_state = self.editor_handle()?.baseview_state().clone();
+        baseview_state.request_frame_capture();
+
+        let deadline = Instant::now() + CAPTURE_TIMEOUT;
+        while Instant::now() < deadline {
+            self.idle(Duration::from_millis(5));
+            if let Some(frame) = baseview_state.take_captured_frame() {
+                return Some(frame);
+            }
+        }
+
+        None
+    }
+
+    /// Capture the next frame the editor draws and compare it against a golden image, panicking if
+    /// they don't match or if no frame could be captured. See [`Frame::assert_matches()`].
+    pub fn assert_frame_matches(&mut self, golden: impl AsRef<Path>) {
+        match self.capture_frame() {
+            Some(frame) => frame.assert_matches(golden),
+            None => panic!(
+                "Could not capture a frame from the editor after {:?}",
+                self.calls
+            ),
+        }
+    }
+
+    fn resize_window(&mut self) {
+        let (width, height) = self.editor.size();
+        self.window.resize(width, height);
+    }
+
+    fn idle(&mut self, duration: Duration) {
+        let deadline = Instant::now() + duration;
+        loop {
+            self.window.pump_events();
+            // Hosts query the editor's new size after accepting a resize request
+            if self.context.pending_resize.swap(false, Ordering::AcqRel) {
+                self.resize_window();
+            }
+
+            if Instant::now() >= deadline {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+}
+
+/// A call an editor made to a [`MockGuiContext`]. Compare parameters against the widget's
+/// parameter using [`Param::as_ptr()`][nih_plug::prelude::Param::as_ptr()].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContextCall {
+    BeginSetParameter(ParamPtr),
+    SetParameterNormalized(ParamPtr, f32),
+    EndSetParameter(ParamPtr),
+    /// A call to [`GuiContext::request_resize()`], and whether the request was accepted.
+    RequestResize {
+        accepted: bool,
+    },
+}
+
+/// A [`GuiContext`] for testing editors and widgets outside of a host. It records every parameter
+/// change and resize request in order, so tests can assert that a widget emitted exactly the
+/// automation gestures it should have. Pass it to
+/// [`ParamSetter::new()`][nih_plug::prelude::ParamSetter::new()] to test widgets and drag helpers
+/// on their own. The parameters' values aren't changed, since that's up to the plugin's wrapper.
+/// The plugin state passed to [`GuiContext::set_state()`] is kept and returned from
+/// [`GuiContext::get_state()`].
+pub struct MockGuiContext {
+    plugin_api: PluginApi,
+    /// Every call made to this context, oldest first.
+    calls: Mutex<Vec<ContextCall>>,
+    accept_resize: AtomicBool,
+    resize_requests: AtomicUsize,
+    /// Set when a resize request was accepted, so [`MockHost`] resizes its window.
+    pending_resize: AtomicBool,
+    state: Mutex<PluginState>,
+}
+
+impl Default for MockGuiContext {
+    fn default() -> Self {
+        Self::new(PluginApi::Clap)
+    }
+}
+
+impl MockGuiContext {
+    /// Create a context that reports `plugin_api` as the plugin's API, since some of the editor's
+    /// behavior depends on it.
+    pub fn new(plugin_api: PluginApi) -> Self {
+        Self {
+            plugin_api,
+            calls: Mutex::new(Vec::new()),
+            accept_resize: AtomicBool::new(true),
+            resize_requests: AtomicUsize::new(0),
+            pending_resize: AtomicBool::new(false),
+            state: Mutex::new(PluginState {
+                version: String::new(),
+                params: BTreeMap::new(),
+                fields: BTreeMap::new(),
+            }),
+        }
+    }
+
+    /// Whether [`GuiContext::request_resize()`] accepts the editor's resize requests. Hosts may
+    /// reject them, for instance when the plugin window is docked. Enabled by default.
+    pub fn set_accept_resize(&self, accept: bool) {
+        self.accept_resize.store(accept, Ordering::Release);
+    }
+
+    /// The number of times the editor called [`GuiContext::request_resize()`].
+    pub fn resize_requests(&self) -> usize {
+        self.resize_requests.load(Ordering::Acquire)
+    }
+
+    /// The calls made to this context so far, oldest first.
+    pub fn calls(&self) -> Vec<ContextCall> {
+        self.calls.lock().clone()
+    }
+
+    /// Take the calls made to this context so far, so the next assertion only sees newer calls.
+    pub fn take_calls(&self) -> Vec<ContextCall> {
+        std::mem::take(&mut *self.calls.lock())
+    }
+
+    /// The normalized values `param` was set to, in order, regardless of the gestures they were
+    /// part of.
+    pub fn parameter_values(&self, param: ParamPtr) -> Vec<f32> {
+        self.calls
+            .lock()
+            .iter()
+            .filter_map(|call| match call {
+                ContextCall::SetParameterNormalized(call_param, normalized)
+                    if *call_param == param =>
+                {
+                    Some(*normalized)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Check that every parameter change was part of a gesture and that every gesture was ended,
+    /// like hosts expect. Returns a description of the first violation.
+    pub fn check_gestures(&self) -> Result<(), String> {
+        let mut open_gestures: Vec<ParamPtr> = Vec::new();
+        for (idx, call) in self.calls.lock().iter().enumerate() {
+            match *call {
+                ContextCall::BeginSetParameter(param) => {
+                    if open_gestures.contains(&param) {
+                        return Err(format!(
+                            "Call {idx} began a gesture that was already active"
+                        ));
+                    }
+                    open_gestures.push(param);
+                }
+                ContextCall::SetParameterNormalized(param, _) => {
+                    if !open_gestures.contains(&param) {
+                        return Err(format!("Call {idx} set a parameter outside of a gesture"));
+                    }
+                }
+                ContextCall::EndSetParameter(param) => {
+                    if !open_gestures.contains(&param) {
+                        return Err(format!("Call {idx} ended a gesture that wasn't active"));
+                    }
+                    open_gestures.retain(|open_param| *open_param != param);
+                }
+                ContextCall::RequestResize { .. } => (),
+            }
+        }
+
+        match open_gestures.len() {
+            0 => Ok(()),
+            n => Err(format!("{n} gestures were never ended")),
+        }
+    }
+}
+
+impl GuiContext for MockGuiContext {
+    fn plugin_api(&self) -> PluginApi {
+        self.plugin_api
+    }
+
+    fn request_resize(&self) -> bool {
+        self.resize_requests.fetch_add(1, Ordering::AcqRel);
+        let accepted = self.accept_resize.load(Ordering::Acquire);
+        if accepted {
+            self.pending_resize.store(true, Ordering::Release);
+        }
+        self.calls
+            .lock()
+            .push(ContextCall::RequestResize { accepted });
+
+        accepted
+    }
+
+    unsafe fn raw_begin_set_parameter(&self, param: ParamPtr) {
+        self.calls
+            .lock()
+            .push(ContextCall::BeginSetParameter(param));
+    }
+
+    unsafe fn raw_set_parameter_normalized(&self, param: ParamPtr, normalized: f32) {
+        self.calls
+            .lock()
+            .push(ContextCall::SetParameterNormalized(param, normalized));
+    }
+
+    unsafe fn raw_end_set_parameter(&self, param: ParamPtr) {
+        self.calls.lock().push(ContextCall::EndSetParameter(param));
+    }
+
+    fn get_state(&self) -> PluginState {
+        self.state.lock().clone()
+    }
+
+    fn set_state(&self, state: PluginState) {
+        *self.state.lock() = state;
+    }
+}
+
+/// Host call orders taken from real hosts, for [`MockHost::run()`]. Each script closes every
+/// editor it opens.
+pub mod scripts {
+    use std::time::Duration;
+
+    use super::HostCall;
+
+    /// How long the editor gets to draw a couple of frames in between calls.
+    const IDLE: Duration = Duration::from_millis(100);
+
+    /// Size the window, open the editor, and close it again.
+    pub fn open_close() -> Vec<HostCall> {
+        vec![
+            HostCall::Resize,
+            HostCall::Spawn,
+            HostCall::Idle(IDLE),
+            HostCall::Close,
+        ]
+    }
+
+    /// Open and close the editor twice in a row, like when the user toggles the plugin window.
+    pub fn reopen() -> Vec<HostCall> {
+        let mut script = open_close();
+        script.extend(open_close());
+        script
+    }
+
+    /// Set the scaling factor before opening the editor. CLAP hosts do this on Windows and Linux.
+    pub fn scale_before_spawn(factor: f32) -> Vec<HostCall> {
+        let mut script = vec![HostCall::SetScaleFactor(factor)];
+        script.extend(open_close());
+        script
+    }
+
+    /// Change the scaling factor while the editor is open and resize the window afterwards. VST3
+    /// hosts only set the scale after the editor is attached, and Ableton Live also does this
+    /// when its window moves to another monitor.
+    pub fn scale_while_open(factor: f32) -> Vec<HostCall> {
+        vec![
+            HostCall::Spawn,
+            HostCall::Idle(IDLE),
+            HostCall::SetScaleFactor(factor),
+            HostCall::Resize,
+            HostCall::Idle(IDLE),
+            HostCall::Close,
+        ]
+    }
+
+    /// Set the same scaling factor several times while the editor is open, which many hosts do
+    /// whenever their window is moved.
+    pub fn repeated_scale_factor(factor: f32) -> Vec<HostCall> {
+        vec![
+            HostCall::SetScaleFactor(factor),
+            HostCall::Spawn,
+            HostCall::SetScaleFactor(factor),
+            HostCall::Idle(IDLE),
+            HostCall::SetScaleFactor(factor),
+            HostCall::Idle(IDLE),
+            HostCall::Close,
+        ]
+    }
+
+    /// Spawn the editor again before dropping the previous handle, and drop the stale handle
+    /// afterwards. REAPER does this when docking and undocking the plugin window.
+    pub fn spawn_before_close() -> Vec<HostCall> {
+        vec![
+            HostCall::Spawn,
+            HostCall::Idle(IDLE),
+            HostCall::Spawn,
+            HostCall::CloseStale,
+            HostCall::Idle(IDLE),
+            HostCall::Close,
+        ]
+    }
+
+    /// Change a parameter while the editor is closed and then open it, like when a host restores
+    /// a project or plays back automation before the plugin window is opened.
+    pub fn params_while_closed(id: &str, normalized_value: f32) -> Vec<HostCall> {
+        let mut script = vec![
+            HostCall::ParamValueChanged(id.to_owned(), normalized_value),
+            HostCall::ParamValuesChanged,
+        ];
+        script.extend(open_close());
+        script
+    }
+
+    /// Change a parameter and its modulation while the editor is open.
+    pub fn params_while_open(id: &str, normalized_value: f32) -> Vec<HostCall> {
+        vec![
+            HostCall::Spawn,
+            HostCall::ParamValueChanged(id.to_owned(), normalized_value),
+            HostCall::ParamModulationChanged(id.to_owned(), 0.0),
+            HostCall::Idle(IDLE),
+            HostCall::ParamValuesChanged,
+            HostCall::Idle(IDLE),
+            HostCall::Close,
+        ]
+    }
+}
+
+/// The host's window the editor is embedded in.
+struct HostWindow {
+    #[cfg(target_os = "windows")]
+    hwnd: winapi::shared::windef::HWND,
+
+    #[cfg(target_os = "macos")]
+    ns_window: cocoa::base::id,
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    display: *mut x11::xlib::Display,
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    window_id: x11::xlib::Window,
+
+    size: (u32, u32),
+}
+
+#[cfg(target_os = "windows")]
+impl HostWindow {
+    fn new(width: u32, height: u32, visible: bool) -> Option<Self> {
+        use std::os::windows::ffi::OsStrExt;
+        use winapi::um::libloaderapi::GetModuleHandleW;
+        use winapi::um::winuser::{
+            CreateWindowExW, DefWindowProcW, RegisterClassW, WNDCLASSW, WS_CLIPCHILDREN, WS_POPUP,
+            WS_VISIBLE,
+        };
+
+        let class_name: Vec<u16> = std::ffi::OsStr::new("nih_plug_baseview_mock_host")
+            .encode_wide()
+            .chain(std::iter::once(0))
+            .collect();
+
+        unsafe {
+            let instance = GetModuleHandleW(std::ptr::null());
+
+            // This fails if another host already registered the class, which is fine
+            let mut class: WNDCLASSW = std::mem::zeroed();
+            class.lpfnWndProc = Some(DefWindowProcW);
+            class.hInstance = instance;
+            class.lpszClassName = class_name.as_ptr();
+            RegisterClassW(&class);
+
+            // Popup windows don't have a frame, so the window's size is the client area's size
+            let hwnd = CreateWindowExW(
+                0,
+                class_name.as_ptr(),
+                std::ptr::null(),
+                WS_POPUP | WS_CLIPCHILDREN | if visible { WS_VISIBLE } else { 0 },
+                0,
+                0,
+                width as i32,
+                height as i32,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                instance,
+                std::ptr::null_mut(),
+            );
+            if hwnd.is_null() {
+                return None;
+            }
+
+            Some(Self {
+                hwnd,
+                size: (width, height),
+            })
+        }
+    }
+
+    fn parent_handle(&self) -> ParentWindowHandle {
+        ParentWindowHandle::Win32Hwnd(self.hwnd as *mut _)
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        use winapi::um::winuser::{SetWindowPos, SWP_NOACTIVATE, SWP_NOMOVE, SWP_NOZORDER};
+
+        unsafe {
+            SetWindowPos(
+                self.hwnd,
+                std::ptr::null_mut(),
+                0,
+                0,
+                width as i32,
+                height as i32,
+                SWP_NOACTIVATE | SWP_NOMOVE | SWP_NOZORDER,
+            );
+        }
+        self.size = (width, height);
+    }
+
+    /// The editor's window is created on the host's thread, so its messages, including the timer
+    /// that draws frames, are only handled while the host's thread pumps them.
+    fn pump_events(&mut self) {
+        use winapi::um::winuser::{DispatchMessageW, PeekMessageW, TranslateMessage, PM_REMOVE};
+
+        unsafe {
+            let mut message = std::mem::zeroed();
+            while PeekMessageW(&mut message, std::ptr::null_mut(), 0, 0, PM_REMOVE) != 0 {
+                TranslateMessage(&message);
+                DispatchMessageW(&message);
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Drop for HostWindow {
+    fn drop(&mut self) {
+        unsafe { winapi::um::winuser::DestroyWindow(self.hwnd) };
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl HostWindow {
+    fn new(width: u32, height: u32, visible: bool) -> Option<Self> {
+        use cocoa::base::{id, nil, NO};
+        use cocoa::foundation::{NSPoint, NSRect, NSSize, NSUInteger};
+        use objc::{class, msg_send, sel, sel_impl};
+
+        /// `NSWindowStyleMaskTitled` and `NSBackingStoreBuffered`
+        const STYLE_TITLED: NSUInteger = 1 << 0;
+        const BACKING_BUFFERED: NSUInteger = 2;
+
+        unsafe {
+            let frame = NSRect::new(
+                NSPoint::new(0.0, 0.0),
+                NSSize::new(width as f64, height as f64),
+            );
+            let ns_window: id = msg_send![class!(NSWindow), alloc];
+            let ns_window: id = msg_send![ns_window,
+                initWithContentRect: frame
+                styleMask: STYLE_TITLED
+                backing: BACKING_BUFFERED
+                defer: NO];
+            if ns_window == nil {
+                return None;
+            }
+            let () = msg_send![ns_window, setReleasedWhenClosed: NO];
+            if visible {
+                let () = msg_send![ns_window, makeKeyAndOrderFront: nil];
+            }
+
+            Some(Self {
+                ns_window,
+                size: (width, height),
+            })
+        }
+    }
+
+    fn parent_handle(&self) -> ParentWindowHandle {
+        use cocoa::base::id;
+        use objc::{msg_send, sel, sel_impl};
+
+        let content_view: id = unsafe { msg_send![self.ns_window, contentView] };
+        ParentWindowHandle::AppKitNsView(content_view as *mut _)
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        use cocoa::foundation::NSSize;
+        use objc::{msg_send, sel, sel_impl};
+
+        unsafe {
+            let () = msg_send![self.ns_window,
+                setContentSize: NSSize::new(width as f64, height as f64)];
+        }
+        self.size = (width, height);
+    }
+
+    /// The editor's frame timer runs on the main run loop, which only runs while the host's
+    /// thread runs it.
+    fn pump_events(&mut self) {
+        use cocoa::base::id;
+        use objc::{class, msg_send, sel, sel_impl};
+
+        unsafe {
+            let date: id = msg_send![class!(NSDate), dateWithTimeIntervalSinceNow: 0.001f64];
+            let run_loop: id = msg_send![class!(NSRunLoop), currentRunLoop];
+            let () = msg_send![run_loop, runUntilDate: date];
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl Drop for HostWindow {
+    fn drop(&mut self) {
+        use cocoa::base::nil;
+        use objc::{msg_send, sel, sel_impl};
+
+        unsafe {
+            let () = msg_send![self.ns_window, orderOut: nil];
+            let () = msg_send![self.ns_window, close];
+            let () = msg_send![self.ns_window, release];
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+impl HostWindow {
+    fn new(width: u32, height: u32, visible: bool) -> Option<Self> {
+        use x11::xlib;
+
+        unsafe {
+            let display = crate::x11_isolation::open_display();
+            if display.is_null() {
+                return None;
+            }
+
+            let window_id = xlib::XCreateSimpleWindow(
+                display,
+                xlib::XDefaultRootWindow(display),
+                0,
+                0,
+                width.max(1),
+                height.max(1),
+                0,
+                0,
+                0,
+            );
+            if window_id == 0 {
+                crate::x11_isolation::close_display(display);
+                return None;
+            }
+            if visible {
+                xlib::XMapWindow(display, window_id);
+            }
+            xlib::XFlush(display);
+
+            Some(Self {
+                display,
+                window_id,
+                size: (width, height),
+            })
+        }
+    }
+
+    fn parent_handle(&self) -> ParentWindowHandle {
+        ParentWindowHandle::X11Window(self.window_id as u32)
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        use x11::xlib;
+
+        unsafe {
+            xlib::XResizeWindow(self.display, self.window_id, width.max(1), height.max(1));
+            xlib::XFlush(self.display);
+        }
+        self.size = (width, height);
+    }
+
+    /// Baseview runs the editor's event loop on its own thread on X11, and the host's window
+    /// doesn't listen to any events, so there's nothing to pump.
+    fn pump_events(&mut self) {}
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+impl Drop for HostWindow {
+    fn drop(&mut self) {
+        use x11::xlib;
+
+        unsafe {
+            xlib::XDestroyWindow(self.display, self.window_id);
+            crate::x11_isolation::close_display(self.display);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use baseview::{Event, EventStatus, Window, WindowHandler};
+    use nih_plug::prelude::{FloatParam, FloatRange, Param, ParamSetter};
+
+    use super::*;
+    use crate::{BaseviewState, HostQuirks};
+
+    #[cfg_attr(target_os = "macos", allow(dead_code))]
+    struct NoopHandler;
+
+    impl WindowHandler for NoopHandler {
+        fn on_frame(&mut self, _window: &mut Window) {}
+
+        fn on_event(&mut self, _window: &mut Window, _event: Event) -> EventStatus {
+            EventStatus::Ignored
+        }
+    }
+
+    /// A host for an editor with an empty window, and the number of times the editor's handler
+    /// was built. The host quirks are fixed so the tests don't depend on the process running them.
+    /// Panics if the host's window can't be created.
+    #[cfg_attr(target_os = "macos", allow(dead_code))]
+    fn host(baseview_state: &Arc<BaseviewState>) -> (MockHost, Arc<AtomicUsize>) {
+        baseview_state.set_host_quirks(Some(HostQuirks::default()));
+
+        let builds = Arc::new(AtomicUsize::new(0));
+        let editor = crate::create_baseview_editor(baseview_state.clone(), (), {
+            let builds = builds.clone();
+            move |_, _, _, _| {
+                builds.fetch_add(1, Ordering::AcqRel);
+                NoopHandler
+            }
+        })
+        .unwrap();
+
+        let host = MockHost::new(editor)
+            .expect("Could not create the host's window, these tests need a display to run on");
+
+        (host, builds)
+    }
+
+    #[cfg_attr(target_os = "macos", allow(dead_code))]
+    fn assert_closed(host: &MockHost, baseview_state: &BaseviewState) {
+        assert_eq!(host.open_handles(), 0, "after {:?}", host.calls());
+        assert!(!baseview_state.is_open(), "after {:?}", host.calls());
+    }
+
+    // AppKit only works on the main thread, which `cargo test` doesn't run tests on
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    #[ignore = "opens real windows, run with `--ignored` on a display (Xvfb works on Linux)"]
+    fn open_close() {
+        let baseview_state = BaseviewState::from_size(400, 300);
+        let (mut host, builds) = host(&baseview_state);
+
+        host.run(scripts::open_close());
+        assert_closed(&host, &baseview_state);
+        assert_eq!(builds.load(Ordering::Acquire), 1);
+    }
+
+    // AppKit only works on the main thread, which `cargo test` doesn't run tests on
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    #[ignore = "opens real windows, run with `--ignored` on a display (Xvfb works on Linux)"]
+    fn scale_while_open() {
+        let baseview_state = BaseviewState::from_size(400, 300);
+        let (mut host, builds) = host(&baseview_state);
+
+        host.run(scripts::scale_while_open(2.0));
+        assert_closed(&host, &baseview_state);
+        // The window is rebuilt at the new scale, since that's what the default quirks ask for
+        assert_eq!(baseview_state.scale_factor(), Some(2.0));
+        assert_eq!(builds.load(Ordering::Acquire), 2);
+    }
+
+    // AppKit only works on the main thread, which `cargo test` doesn't run tests on
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    #[ignore = "opens real windows, run with `--ignored` on a display (Xvfb works on Linux)"]
+    fn spawn_before_close() {
+        let baseview_state = BaseviewState::from_size(400, 300);
+        let (mut host, builds) = host(&baseview_state);
+
+        // Dropping the stale handle must not close the newer editor
+        let mut script = scripts::spawn_before_close();
+        let rest = script.split_off(
+            script
+                .iter()
+                .position(|call| *call == HostCall::CloseStale)
+                .unwrap()
+                + 1,
+        );
+        host.run(script);
+        assert_eq!(host.open_handles(), 1);
+        assert!(baseview_state.is_open(), "after {:?}", host.calls());
+
+        host.run(rest);
+        assert_closed(&host, &baseview_state);
+        assert_eq!(builds.load(Ordering::Acquire), 2);
+    }
+
+    /// Fills an offscreen frame with a single color whenever a capture is requested.
+    #[cfg_attr(target_os = "macos", allow(dead_code))]
+    struct OffscreenHandler {
+        color: [f32; 4],
+    }
+
+    impl WindowHandler for OffscreenHandler {
+        fn on_frame(&mut self, window: &mut Window) {
+            type ClearColor = unsafe extern "system" fn(f32, f32, f32, f32);
+            type Clear = unsafe extern "system" fn(u32);
+            const GL_COLOR_BUFFER_BIT: u32 = 0x4000;
+
+            let Some(gl_context) = window.gl_context() else {
+                return;
+            };
+            if !crate::testing::capture_requested() {
+                return;
+            }
+
+            unsafe {
+                gl_context.make_current();
+                let clear_color = std::mem::transmute::<*const std::ffi::c_void, ClearColor>(
+                    gl_context.get_proc_address("glClearColor"),
+                );
+                let clear = std::mem::transmute::<*const std::ffi::c_void, Clear>(
+                    gl_context.get_proc_address("glClear"),
+                );
+                let [r, g, b, a] = self.color;
+                let frame = crate::testing::render_offscreen(gl_context, 4, 3, || {
+                    clear_color(r, g, b, a);
+                    clear(GL_COLOR_BUFFER_BIT);
+                });
+                if let Some(frame) = frame {
+                    crate::testing::submit_frame(frame);
+                }
+                gl_context.make_not_current();
+            }
+        }
+
+        fn on_event(&mut self, _window: &mut Window, _event: Event) -> EventStatus {
+            EventStatus::Ignored
+        }
+    }
+
+    // AppKit only works on the main thread, which `cargo test` doesn't run tests on
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    #[ignore = "opens real windows, run with `--ignored` on a display (Xvfb works on Linux)"]
+    fn hidden_host_captures_offscreen_frames() {
+        let baseview_state = BaseviewState::from_size(400, 300);
+        let editor =
+            crate::create_baseview_editor(baseview_state, (), |_, _, _, _| OffscreenHandler {
+                color: [1.0, 0.0, 0.0, 1.0],
+            })
+            .unwrap();
+        let mut host = MockHost::new_hidden(editor)
+            .expect("Could not create the host's window, these tests need a display to run on");
+
+        host.call(HostCall::Spawn);
+        let frame = host.capture_frame().expect("No frame was captured");
+        host.call(HostCall::Close);
+
+        assert_eq!((frame.width(), frame.height()), (4, 3));
+        assert_eq!(frame.pixel(3, 2), Some([255, 0, 0, 255]));
+    }
+
+    fn param(name: &str) -> FloatParam {
+        FloatParam::new(name, 0.0, FloatRange::Linear { min: 0.0, max: 1.0 })
+    }
+
+    #[test]
+    fn param_setter_gestures_are_recorded() {
+        let context = MockGuiContext::default();
+        let setter = ParamSetter::new(&context);
+        let param = param("Gain");
+
+        setter.begin_set_parameter(&param);
+        setter.set_parameter(&param, 0.25);
+        setter.set_parameter(&param, 0.5);
+        setter.end_set_parameter(&param);
+
+        let ptr = param.as_ptr();
+        assert_eq!(
+            context.calls(),
+            vec![
+                ContextCall::BeginSetParameter(ptr),
+                ContextCall::SetParameterNormalized(ptr, 0.25),
+                ContextCall::SetParameterNormalized(ptr, 0.5),
+                ContextCall::EndSetParameter(ptr),
+            ]
+        );
+        assert_eq!(context.parameter_values(ptr), vec![0.25, 0.5]);
+        assert_eq!(context.check_gestures(), Ok(()));
+
+        assert_eq!(context.take_calls().len(), 4);
+        assert!(context.calls().is_empty());
+    }
+
+    #[test]
+    fn interleaved_gestures_on_different_params_are_fine() {
+        let context = MockGuiContext::default();
+        let setter = ParamSetter::new(&context);
+        let (gain, pan) = (param("Gain"), param("Pan"));
+
+        setter.begin_set_parameter(&gain);
+        setter.begin_set_parameter(&pan);
+        setter.set_parameter(&gain, 1.0);
+        setter.end_set_parameter(&gain);
+        setter.set_parameter(&pan, 0.5);
+        setter.end_set_parameter(&pan);
+
+        assert_eq!(context.parameter_values(pan.as_ptr()), vec![0.5]);
+        assert_eq!(context.check_gestures(), Ok(()));
+    }
+
+    #[test]
+    fn gesture_began_twice_is_reported() {
+        let context = MockGuiContext::default();
+        let setter = ParamSetter::new(&context);
+        let param = param("Gain");
+
+        setter.begin_set_parameter(&param);
+        setter.begin_set_parameter(&param);
+        setter.end_set_parameter(&param);
+
+        assert_eq!(
+            context.check_gestures(),
+            Err("Call 1 began a gesture that was already active".to_owned())
+        );
+    }
+
+    #[test]
+    fn set_outside_of_a_gesture_is_reported() {
+        let context = MockGuiContext::default();
+        let setter = ParamSetter::new(&context);
+        let param = param("Gain");
+
+        setter.set_parameter(&param, 0.5);
+
+        assert_eq!(
+            context.check_gestures(),
+            Err("Call 0 set a parameter outside of a gesture".to_owned())
+        );
+    }
+
+    #[test]
+    fn ending_an_inactive_gesture_is_reported() {
+        let context = MockGuiContext::default();
+        let setter = ParamSetter::new(&context);
+        let param = param("Gain");
+
+        setter.begin_set_parameter(&param);
+        setter.end_set_parameter(&param);
+        setter.end_set_parameter(&param);
+
+        assert_eq!(
+            context.check_gestures(),
+            Err("Call 2 ended a gesture that wasn't active".to_owned())
+        );
+    }
+
+    #[test]
+    fn unended_gestures_are_reported() {
+        let context = MockGuiContext::default();
+        let setter = ParamSetter::new(&context);
+        let (gain, pan) = (param("Gain"), param("Pan"));
+
+        setter.begin_set_parameter(&gain);
+        setter.begin_set_parameter(&pan);
+        setter.set_parameter(&gain, 0.5);
+
+        assert_eq!(
+            context.check_gestures(),
+            Err("2 gestures were never ended".to_owned())
+        );
+    }
+
+    #[test]
+    fn resize_requests_are_recorded_but_not_gestures() {
+        let context = MockGuiContext::default();
+        assert!(context.request_resize());
+        context.set_accept_resize(false);
+        assert!(!context.request_resize());
+
+        assert_eq!(context.resize_requests(), 2);
+        assert_eq!(
+            context.calls(),
+            vec![
+                ContextCall::RequestResize { accepted: true },
+                ContextCall::RequestResize { accepted: false },
+            ]
+        );
+        assert_eq!(context.check_gestures(), Ok(()));
+    }
+}
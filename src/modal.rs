@@ -0,0 +1,167 @@
+//! Modal dialogs for confirmations and small settings panels. While a modal dialog is open the
+//! editor's window ignores mouse and keyboard input, but the host keeps working normally. The
+//! dialog is an overlay window centered over the editor that can take keyboard focus.
+
+use baseview::{Event, EventStatus, Window, WindowHandler};
+use nih_plug::prelude::GuiContext;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use crate::overlay::{self, OverlayOptions, OverlayWindow};
+use crate::BaseviewState;
+
+/// A dialog opened using [`open_modal_dialog()`]. The dialog is closed when this is dropped.
+pub struct ModalDialog {
+    overlay: OverlayWindow,
+}
+
+impl ModalDialog {
+    /// Whether the dialog is still open.
+    pub fn is_open(&self) -> bool {
+        self.overlay.is_open()
+    }
+
+    /// Close the dialog, for instance after the user confirmed or cancelled it. The dialog's
+    /// handler can also close it by calling [`Window::close()`]. The editor receives input again
+    /// once the dialog's handler has been dropped.
+    pub fn close(&mut self) {
+        self.overlay.close();
+    }
+}
+
+/// Open a modal dialog of `size` logical pixels centered over the editor. Until the dialog is
+/// closed the editor's window doesn't receive mouse and keyboard events, and clicking the editor
+/// brings the dialog back to the front. Keyboard events go to the host instead, so its shortcuts
+/// keep working. The dialog closes itself when the editor closes. `build` and the handler it
+/// returns work the same as for [`open_overlay()`][crate::open_overlay()], except that the dialog
+/// can take keyboard focus. On X11 the dialog cannot take keyboard focus, since overlays there are
+/// not managed by the window manager.
+///
+/// Returns `None` if the editor isn't open or if the dialog could not be created.
+pub fn open_modal_dialog<B, H>(
+    baseview_state: &Arc<BaseviewState>,
+    context: Arc<dyn GuiContext>,
+    size: (f64, f64),
+    build: B,
+) -> Option<ModalDialog>
+where
+    B: FnOnce(&mut Window, Arc<dyn GuiContext>) -> H + Send + 'static,
+    H: WindowHandler + 'static,
+{
+    let (editor_width, editor_height) = baseview_state.logical_size();
+    let position = (
+        (editor_width - size.0) / 2.0,
+        (editor_height - size.1) / 2.0,
+    );
+
+    // The editor is blocked from here on so no input slips through before the dialog's window has
+    // been created. If that fails the guard is dropped again right away.
+    let guard = ModalGuard::new(baseview_state.clone());
+    let overlay = overlay::open(
+        baseview_state,
+        context,
+        OverlayOptions { position, size },
+        true,
+        move |window, context| ModalHandler {
+            inner: build(window, context),
+            guard,
+        },
+    )?;
+
+    Some(ModalDialog { overlay })
+}
+
+/// Blocks input to the editor for as long as it's alive.
+struct ModalGuard(Arc<BaseviewState>);
+
+impl ModalGuard {
+    fn new(baseview_state: Arc<BaseviewState>) -> Self {
+        baseview_state.modal_dialogs.fetch_add(1, Ordering::AcqRel);
+        baseview_state
+            .modal_focus_requested
+            .store(false, Ordering::Release);
+
+        Self(baseview_state)
+    }
+}
+
+impl Drop for ModalGuard {
+    fn drop(&mut self) {
+        self.0.modal_dialogs.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Wraps the dialog's handler, keeps the dialog in front of the editor, and closes it together
+/// with the editor.
+struct ModalHandler<H> {
+    inner: H,
+    /// This needs to be the last field so the editor is only unblocked after the user's handler
+    /// has been dropped.
+    guard: ModalGuard,
+}
+
+impl<H: WindowHandler> WindowHandler for ModalHandler<H> {
+    fn on_frame(&mut self, window: &mut Window) {
+        let baseview_state = &self.guard.0;
+        if !baseview_state.is_open() {
+            window.close();
+            return;
+        }
+        if baseview_state
+            .modal_focus_requested
+            .swap(false, Ordering::AcqRel)
+        {
+            bring_to_front(window);
+        }
+
+        self.inner.on_frame(window);
+    }
+
+    fn on_event(&mut self, window: &mut Window, event: Event) -> EventStatus {
+        self.inner.on_event(window, event)
+    }
+}
+
+/// Activate the dialog's window and give it keyboard focus.
+#[cfg(target_os = "windows")]
+fn bring_to_front(window: &Window) {
+    use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+    use winapi::shared::windef::HWND;
+    use winapi::um::winuser::{GetAncestor, SetFocus, SetForegroundWindow, GA_ROOT};
+
+    let hwnd = match window.raw_window_handle() {
+        RawWindowHandle::Win32(handle) => handle.hwnd as HWND,
+        _ => return,
+    };
+
+    unsafe {
+        SetForegroundWindow(GetAncestor(hwnd, GA_ROOT));
+        SetFocus(hwnd);
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn bring_to_front(window: &Window) {
+    use cocoa::base::{id, nil};
+    use objc::runtime::BOOL;
+    use objc::{msg_send, sel, sel_impl};
+    use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+
+    let ns_view = match window.raw_window_handle() {
+        RawWindowHandle::AppKit(handle) => handle.ns_view as id,
+        _ => return,
+    };
+
+    unsafe {
+        let ns_window: id = msg_send![ns_view, window];
+        if ns_window != nil {
+            let () = msg_send![ns_window, makeKeyAndOrderFront: nil];
+            let _: BOOL = msg_send![ns_window, makeFirstResponder: ns_view];
+        }
+    }
+}
+
+/// Overlays on X11 are override-redirect windows that always stay on top and that the window
+/// manager doesn't give focus to, so there's nothing to do here.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn bring_to_front(_window: &Window) {}
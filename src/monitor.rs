@@ -0,0 +1,339 @@
+//! Querying the monitors the editor can be shown on.
+
+use nih_plug::prelude::ParentWindowHandle;
+
+/// Information about a connected monitor, as returned by [`monitors()`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Monitor {
+    /// The monitor's resolution in physical pixels.
+    pub size: (u32, u32),
+    /// The size of the monitor's work area in physical pixels, so the screen minus task bars and
+    /// docks. On X11 this is limited by the desktop's work area, which may not account for panels
+    /// on other monitors.
+    pub work_area_size: (u32, u32),
+    /// The monitor's DPI scaling factor. On X11 this is the same for every monitor.
+    pub scale_factor: f32,
+    /// Whether this is the primary monitor.
+    pub is_primary: bool,
+}
+
+impl Monitor {
+    /// The size of the work area in logical pixels. An editor with this size and the monitor's
+    /// scaling factor would exactly fit on the screen, which can be used to implement a "fit to
+    /// screen" option.
+    pub fn logical_work_area_size(&self) -> (f64, f64) {
+        (
+            self.work_area_size.0 as f64 / self.scale_factor as f64,
+            self.work_area_size.1 as f64 / self.scale_factor as f64,
+        )
+    }
+}
+
+/// Enumerate the connected monitors. This should be called from the GUI thread. The list is empty
+/// if the monitors cannot be queried.
+#[cfg(target_os = "windows")]
+pub fn monitors() -> Vec<Monitor> {
+    use winapi::shared::minwindef::{BOOL, LPARAM, TRUE};
+    use winapi::shared::windef::{HDC, HMONITOR, LPRECT};
+    use winapi::shared::winerror::S_OK;
+    use winapi::um::shellscalingapi::{GetDpiForMonitor, MDT_EFFECTIVE_DPI};
+    use winapi::um::winuser::{
+        EnumDisplayMonitors, GetMonitorInfoW, MONITORINFO, MONITORINFOF_PRIMARY,
+    };
+
+    unsafe extern "system" fn callback(
+        monitor: HMONITOR,
+        _hdc: HDC,
+        _rect: LPRECT,
+        monitors: LPARAM,
+    ) -> BOOL {
+        let monitors = &mut *(monitors as *mut Vec<Monitor>);
+
+        let mut info: MONITORINFO = std::mem::zeroed();
+        info.cbSize = std::mem::size_of::<MONITORINFO>() as u32;
+        if GetMonitorInfoW(monitor, &mut info) == 0 {
+            return TRUE;
+        }
+
+        let (mut dpi_x, mut dpi_y) = (0, 0);
+        let scale_factor =
+            if GetDpiForMonitor(monitor, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y) == S_OK {
+                dpi_x as f32 / 96.0
+            } else {
+                1.0
+            };
+
+        let (bounds, work_area) = (info.rcMonitor, info.rcWork);
+        monitors.push(Monitor {
+            size: (
+                (bounds.right - bounds.left) as u32,
+                (bounds.bottom - bounds.top) as u32,
+            ),
+            work_area_size: (
+                (work_area.right - work_area.left) as u32,
+                (work_area.bottom - work_area.top) as u32,
+            ),
+            scale_factor,
+            is_primary: info.dwFlags & MONITORINFOF_PRIMARY != 0,
+        });
+
+        TRUE
+    }
+
+    let mut monitors: Vec<Monitor> = Vec::new();
+    unsafe {
+        EnumDisplayMonitors(
+            std::ptr::null_mut(),
+            std::ptr::null(),
+            Some(callback),
+            &mut monitors as *mut Vec<Monitor> as LPARAM,
+        );
+    }
+
+    monitors
+}
+
+#[cfg(target_os = "macos")]
+pub fn monitors() -> Vec<Monitor> {
+    use cocoa::appkit::NSScreen;
+    use cocoa::base::nil;
+    use cocoa::foundation::NSArray;
+
+    let mut monitors = Vec::new();
+    unsafe {
+        // The first screen is always the one containing the menu bar
+        let screens = NSScreen::screens(nil);
+        if screens == nil {
+            return monitors;
+        }
+
+        for i in 0..NSArray::count(screens) {
+            let screen = NSArray::objectAtIndex(screens, i);
+            let scale_factor = NSScreen::backingScaleFactor(screen);
+            let frame = NSScreen::frame(screen);
+            let visible_frame = NSScreen::visibleFrame(screen);
+
+            monitors.push(Monitor {
+                size: (
+                    (frame.size.width * scale_factor).round() as u32,
+                    (frame.size.height * scale_factor).round() as u32,
+                ),
+                work_area_size: (
+                    (visible_frame.size.width * scale_factor).round() as u32,
+                    (visible_frame.size.height * scale_factor).round() as u32,
+                ),
+                scale_factor: scale_factor as f32,
+                is_primary: i == 0,
+            });
+        }
+    }
+
+    monitors
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub fn monitors() -> Vec<Monitor> {
+    use std::os::raw::c_int;
+    use x11::{xlib, xrandr};
+
+    let scale_factor = crate::scale::system_scale_factor().unwrap_or(1.0);
+
+    let mut monitors = Vec::new();
+    unsafe {
+        let display = crate::x11_isolation::open_display();
+        if display.is_null() {
+            return monitors;
+        }
+
+        let screen = xlib::XDefaultScreen(display);
+        let root = xlib::XRootWindow(display, screen);
+        let desktop_work_area = net_work_area(display, root);
+
+        let mut num_monitors: c_int = 0;
+        let monitor_infos = xrandr::XRRGetMonitors(display, root, xlib::True, &mut num_monitors);
+        if !monitor_infos.is_null() {
+            for info in std::slice::from_raw_parts(monitor_infos, num_monitors.max(0) as usize) {
+                let size = (info.width.max(0) as u32, info.height.max(0) as u32);
+
+                // The work area is the intersection between the monitor and the desktop's work
+                // area
+                let work_area_size = match desktop_work_area {
+                    Some((x, y, width, height)) => {
+                        let left = info.x.max(x);
+                        let top = info.y.max(y);
+                        let right = (info.x + info.width).min(x + width);
+                        let bottom = (info.y + info.height).min(y + height);
+
+                        ((right - left).max(0) as u32, (bottom - top).max(0) as u32)
+                    }
+                    None => size,
+                };
+
+                monitors.push(Monitor {
+                    size,
+                    work_area_size,
+                    scale_factor,
+                    is_primary: info.primary != 0,
+                });
+            }
+
+            xrandr::XRRFreeMonitors(monitor_infos);
+        }
+
+        crate::x11_isolation::close_display(display);
+    }
+
+    monitors
+}
+
+/// Get the size of the work area of the monitor containing `parent`, so the screen minus task bars
+/// and docks. The size is in the window system's units, which means physical pixels on Windows and
+/// X11 and points on macOS. These are the units baseview uses for a scaling factor of 1.
+#[cfg(target_os = "windows")]
+pub(crate) fn work_area_size(parent: ParentWindowHandle) -> Option<(f64, f64)> {
+    use winapi::shared::windef::HWND;
+    use winapi::um::winuser::{
+        GetMonitorInfoW, MonitorFromWindow, MONITORINFO, MONITOR_DEFAULTTONEAREST,
+    };
+
+    let hwnd = match parent {
+        ParentWindowHandle::Win32Hwnd(hwnd) => hwnd as HWND,
+        _ => return None,
+    };
+
+    unsafe {
+        let monitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+        if monitor.is_null() {
+            return None;
+        }
+
+        let mut info: MONITORINFO = std::mem::zeroed();
+        info.cbSize = std::mem::size_of::<MONITORINFO>() as u32;
+        if GetMonitorInfoW(monitor, &mut info) == 0 {
+            return None;
+        }
+
+        let work_area = info.rcWork;
+        Some((
+            (work_area.right - work_area.left) as f64,
+            (work_area.bottom - work_area.top) as f64,
+        ))
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub(crate) fn work_area_size(parent: ParentWindowHandle) -> Option<(f64, f64)> {
+    use cocoa::appkit::NSScreen;
+    use cocoa::base::{id, nil};
+    use objc::{msg_send, sel, sel_impl};
+
+    let ns_view = match parent {
+        ParentWindowHandle::AppKitNsView(ns_view) => ns_view as id,
+        _ => return None,
+    };
+
+    unsafe {
+        // The parent view may not be in a window yet, in which case the main screen is our best
+        // guess
+        let ns_window: id = msg_send![ns_view, window];
+        let mut screen: id = if ns_window != nil {
+            msg_send![ns_window, screen]
+        } else {
+            nil
+        };
+        if screen == nil {
+            screen = NSScreen::mainScreen(nil);
+        }
+        if screen == nil {
+            return None;
+        }
+
+        let visible_frame = NSScreen::visibleFrame(screen);
+        Some((visible_frame.size.width, visible_frame.size.height))
+    }
+}
+
+/// This uses the `_NET_WORKAREA` property set by most window managers, which covers the entire
+/// desktop rather than a single monitor. If that's not available, the screen's size is used.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub(crate) fn work_area_size(_parent: ParentWindowHandle) -> Option<(f64, f64)> {
+    use x11::xlib;
+
+    unsafe {
+        let display = crate::x11_isolation::open_display();
+        if display.is_null() {
+            return None;
+        }
+
+        let screen = xlib::XDefaultScreen(display);
+        let root = xlib::XRootWindow(display, screen);
+        let work_area = match net_work_area(display, root) {
+            Some((_, _, width, height)) => (width as f64, height as f64),
+            None => (
+                xlib::XDisplayWidth(display, screen) as f64,
+                xlib::XDisplayHeight(display, screen) as f64,
+            ),
+        };
+        crate::x11_isolation::close_display(display);
+
+        Some(work_area)
+    }
+}
+
+/// Read the desktop's work area from the root window's `_NET_WORKAREA` property. Returns the x and
+/// y coordinates, width, and height of the work area in physical pixels.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+unsafe fn net_work_area(
+    display: *mut x11::xlib::Display,
+    root: x11::xlib::Window,
+) -> Option<(i32, i32, i32, i32)> {
+    use std::ffi::CString;
+    use std::os::raw::{c_int, c_uchar, c_ulong};
+    use x11::xlib;
+
+    let atom_name = CString::new("_NET_WORKAREA").unwrap();
+    let work_area_atom = xlib::XInternAtom(display, atom_name.as_ptr(), xlib::True);
+    if work_area_atom == 0 {
+        return None;
+    }
+
+    let mut actual_type = 0;
+    let mut actual_format: c_int = 0;
+    let mut num_items: c_ulong = 0;
+    let mut bytes_after: c_ulong = 0;
+    let mut data: *mut c_uchar = std::ptr::null_mut();
+    let status = xlib::XGetWindowProperty(
+        display,
+        root,
+        work_area_atom,
+        0,
+        4,
+        xlib::False,
+        xlib::XA_CARDINAL,
+        &mut actual_type,
+        &mut actual_format,
+        &mut num_items,
+        &mut bytes_after,
+        &mut data,
+    );
+    if status != xlib::Success as c_int || data.is_null() {
+        return None;
+    }
+
+    // Format 32 properties are returned as an array of C longs, containing the x, y, width, and
+    // height of the work area
+    let work_area = if actual_format == 32 && num_items >= 4 {
+        let values = std::slice::from_raw_parts(data as *const c_ulong, 4);
+        Some((
+            values[0] as i32,
+            values[1] as i32,
+            values[2] as i32,
+            values[3] as i32,
+        ))
+    } else {
+        None
+    };
+    xlib::XFree(data as *mut _);
+
+    work_area
+}
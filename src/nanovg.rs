@@ -0,0 +1,317 @@
+//! A nanovg-style immediate mode drawing API, available with the `nanovg` feature. This is a thin
+//! layer over the femtovg canvas from the `femtovg` feature that keeps nanovg's function names and
+//! its state model, for handlers that only need to draw some paths and text and don't want to
+//! pull in a full widget framework.
+
+use baseview::{Event, EventStatus, Window, WindowEvent, WindowInfo};
+use femtovg::renderer::OpenGl;
+use femtovg::{Align, Baseline, Canvas, Color, FontId, LineCap, LineJoin, Paint, Path, Solidity};
+use nih_plug::prelude::{Editor, GuiContext};
+use std::sync::Arc;
+
+use crate::femtovg_canvas::{self, FemtovgHandler};
+use crate::BaseviewState;
+
+/// A handler that draws using the nanovg-style [`Vg`] API. Coordinates are in logical pixels,
+/// matching the mouse events' coordinates.
+pub trait NanovgHandler {
+    /// Draw a frame. The window has already been cleared to
+    /// [`NanovgHandler::background_color()`], and the frame is presented afterwards.
+    fn draw(&mut self, window: &mut Window, vg: &mut Vg);
+
+    fn on_event(&mut self, window: &mut Window, event: Event) -> EventStatus {
+        let _ = (window, event);
+        EventStatus::Ignored
+    }
+
+    /// The color the window is cleared to before every frame.
+    fn background_color(&self) -> Color {
+        Color::black()
+    }
+}
+
+/// Create an [`Editor`] that draws using the nanovg-style [`Vg`] API. `build` is called every
+/// time the window is opened, and it receives a [`Vg`] so it can load fonts using
+/// [`Vg::create_font_mem()`] before creating the handler.
+pub fn create_nanovg_editor<T, B, H>(
+    baseview_state: Arc<BaseviewState>,
+    user_state: T,
+    build: B,
+) -> Option<Box<dyn Editor>>
+where
+    T: 'static + Send + Sync,
+    B: Fn(&Window, WindowInfo, &mut Vg, Arc<dyn GuiContext>, &mut T) -> H + 'static + Send + Sync,
+    H: NanovgHandler + 'static,
+{
+    femtovg_canvas::create_femtovg_editor(
+        baseview_state,
+        user_state,
+        move |window, window_info, canvas, context, user_state| {
+            let scale = window_info.scale() as f32;
+            let inner = build(
+                window,
+                window_info,
+                &mut Vg::new(canvas, scale),
+                context,
+                user_state,
+            );
+
+            NanovgAdapter { inner, scale }
+        },
+    )
+}
+
+/// Runs a [`NanovgHandler`] as a [`FemtovgHandler`].
+struct NanovgAdapter<H> {
+    inner: H,
+    /// The window's scale, which [`Vg::reset()`] needs to restore the canvas' base transform.
+    scale: f32,
+}
+
+impl<H: NanovgHandler> FemtovgHandler for NanovgAdapter<H> {
+    fn draw(&mut self, window: &mut Window, canvas: &mut Canvas<OpenGl>) {
+        self.inner.draw(window, &mut Vg::new(canvas, self.scale));
+    }
+
+    fn on_event(&mut self, window: &mut Window, event: Event) -> EventStatus {
+        if let Event::Window(WindowEvent::Resized(window_info)) = &event {
+            self.scale = window_info.scale() as f32;
+        }
+
+        self.inner.on_event(window, event)
+    }
+
+    fn background_color(&self) -> Color {
+        self.inner.background_color()
+    }
+}
+
+/// The render state that [`Vg::save()`] and [`Vg::restore()`] push and pop, on top of the
+/// canvas' own transform and scissor state.
+#[derive(Clone)]
+struct VgState {
+    fill: Paint,
+    stroke: Paint,
+    text: Paint,
+}
+
+impl Default for VgState {
+    fn default() -> Self {
+        let mut stroke = Paint::color(Color::black());
+        stroke.set_line_width(1.0);
+        let mut text = Paint::color(Color::white());
+        text.set_font_size(16.0);
+
+        Self {
+            fill: Paint::color(Color::white()),
+            stroke,
+            text,
+        }
+    }
+}
+
+/// A nanovg-style drawing context for a single frame. The functions have the same names and
+/// behavior as their nanovg counterparts, so nanovg examples translate almost line by line.
+pub struct Vg<'a> {
+    canvas: &'a mut Canvas<OpenGl>,
+    /// The canvas' scale at the start of the frame.
+    scale: f32,
+    path: Path,
+    state: VgState,
+    saved_states: Vec<VgState>,
+}
+
+impl<'a> Vg<'a> {
+    fn new(canvas: &'a mut Canvas<OpenGl>, scale: f32) -> Self {
+        Self {
+            canvas,
+            scale,
+            path: Path::new(),
+            state: VgState::default(),
+            saved_states: Vec::new(),
+        }
+    }
+
+    /// The underlying femtovg canvas, for anything this API doesn't cover.
+    pub fn canvas(&mut self) -> &mut Canvas<OpenGl> {
+        self.canvas
+    }
+
+    /// Push the current render state, including the transform, onto the state stack.
+    pub fn save(&mut self) {
+        self.saved_states.push(self.state.clone());
+        self.canvas.save();
+    }
+
+    /// Pop the render state pushed by the last [`Vg::save()`].
+    pub fn restore(&mut self) {
+        if let Some(state) = self.saved_states.pop() {
+            self.state = state;
+            self.canvas.restore();
+        }
+    }
+
+    /// Reset the current render state to its defaults. This does not affect the state stack.
+    pub fn reset(&mut self) {
+        self.state = VgState::default();
+        self.canvas.reset();
+        self.canvas.scale(self.scale, self.scale);
+    }
+
+    pub fn translate(&mut self, x: f32, y: f32) {
+        self.canvas.translate(x, y);
+    }
+
+    /// Rotate the coordinate system by `angle` radians.
+    pub fn rotate(&mut self, angle: f32) {
+        self.canvas.rotate(angle);
+    }
+
+    pub fn scale(&mut self, x: f32, y: f32) {
+        self.canvas.scale(x, y);
+    }
+
+    /// Only draw inside of the rectangle, in the current coordinate system.
+    pub fn scissor(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        self.canvas.scissor(x, y, width, height);
+    }
+
+    pub fn reset_scissor(&mut self) {
+        self.canvas.reset_scissor();
+    }
+
+    pub fn fill_color(&mut self, color: Color) {
+        self.state.fill = Paint::color(color);
+    }
+
+    /// Fill with a paint, for instance a gradient or an image pattern.
+    pub fn fill_paint(&mut self, paint: Paint) {
+        self.state.fill = paint;
+    }
+
+    pub fn stroke_color(&mut self, color: Color) {
+        self.state.stroke.set_color(color);
+    }
+
+    pub fn stroke_width(&mut self, width: f32) {
+        self.state.stroke.set_line_width(width);
+    }
+
+    pub fn line_cap(&mut self, cap: LineCap) {
+        self.state.stroke.set_line_cap(cap);
+    }
+
+    pub fn line_join(&mut self, join: LineJoin) {
+        self.state.stroke.set_line_join(join);
+    }
+
+    /// Clear the current path and start a new one.
+    pub fn begin_path(&mut self) {
+        self.path = Path::new();
+    }
+
+    pub fn move_to(&mut self, x: f32, y: f32) {
+        self.path.move_to(x, y);
+    }
+
+    pub fn line_to(&mut self, x: f32, y: f32) {
+        self.path.line_to(x, y);
+    }
+
+    pub fn bezier_to(&mut self, c1x: f32, c1y: f32, c2x: f32, c2y: f32, x: f32, y: f32) {
+        self.path.bezier_to(c1x, c1y, c2x, c2y, x, y);
+    }
+
+    pub fn quad_to(&mut self, cx: f32, cy: f32, x: f32, y: f32) {
+        self.path.quad_to(cx, cy, x, y);
+    }
+
+    pub fn arc_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, radius: f32) {
+        self.path.arc_to(x1, y1, x2, y2, radius);
+    }
+
+    pub fn close_path(&mut self) {
+        self.path.close();
+    }
+
+    /// Add an arc from `a0` to `a1` radians around `(cx, cy)`.
+    pub fn arc(&mut self, cx: f32, cy: f32, radius: f32, a0: f32, a1: f32, direction: Solidity) {
+        self.path.arc(cx, cy, radius, a0, a1, direction);
+    }
+
+    pub fn rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        self.path.rect(x, y, width, height);
+    }
+
+    pub fn rounded_rect(&mut self, x: f32, y: f32, width: f32, height: f32, radius: f32) {
+        self.path.rounded_rect(x, y, width, height, radius);
+    }
+
+    pub fn ellipse(&mut self, cx: f32, cy: f32, rx: f32, ry: f32) {
+        self.path.ellipse(cx, cy, rx, ry);
+    }
+
+    pub fn circle(&mut self, cx: f32, cy: f32, radius: f32) {
+        self.path.circle(cx, cy, radius);
+    }
+
+    /// Fill the current path with the fill paint.
+    pub fn fill(&mut self) {
+        self.canvas.fill_path(&self.path, &self.state.fill);
+    }
+
+    /// Stroke the current path with the stroke paint.
+    pub fn stroke(&mut self) {
+        self.canvas.stroke_path(&self.path, &self.state.stroke);
+    }
+
+    /// Load a TrueType or OpenType font. Fonts belong to the canvas, so they only need to be
+    /// loaded once, for instance in the build function. Returns `None` if the font could not be
+    /// parsed.
+    pub fn create_font_mem(&mut self, data: &[u8]) -> Option<FontId> {
+        self.canvas.add_font_mem(data).ok()
+    }
+
+    /// Use `font` for drawing text.
+    pub fn font_face(&mut self, font: FontId) {
+        self.state.text.set_font(&[font]);
+    }
+
+    pub fn font_size(&mut self, size: f32) {
+        self.state.text.set_font_size(size);
+    }
+
+    pub fn text_color(&mut self, color: Color) {
+        self.state.text.set_color(color);
+    }
+
+    pub fn text_align(&mut self, align: Align, baseline: Baseline) {
+        self.state.text.set_text_align(align);
+        self.state.text.set_text_baseline(baseline);
+    }
+
+    /// Draw `text` at `(x, y)` using the current font settings. Returns the horizontal position
+    /// where the next piece of text would start.
+    pub fn text(&mut self, x: f32, y: f32, text: &str) -> f32 {
+        match self.canvas.fill_text(x, y, text, &self.state.text) {
+            Ok(metrics) => x + metrics.width(),
+            Err(_) => x,
+        }
+    }
+
+    /// The width `text` would take up when drawn with the current font settings.
+    pub fn text_width(&mut self, text: &str) -> f32 {
+        self.canvas
+            .measure_text(0.0, 0.0, text, &self.state.text)
+            .map_or(0.0, |metrics| metrics.width())
+    }
+}
+
+impl Drop for Vg<'_> {
+    fn drop(&mut self) {
+        // Unbalanced saves would otherwise pile up on the canvas' state stack across frames
+        for _ in self.saved_states.drain(..) {
+            self.canvas.restore();
+        }
+    }
+}
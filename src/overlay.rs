@@ -0,0 +1,559 @@
+//! Small borderless windows positioned relative to the editor, for tooltips and dropdowns that need
+//! to extend past the editor's bounds. The editor's window is clipped to the host's window, so
+//! these are separate top level windows that float above the host's window without taking focus
+//! away from it. Each overlay is a native popup window with a baseview window inside of it.
+
+use baseview::{Size, Window, WindowHandle, WindowHandler, WindowOpenOptions, WindowScalePolicy};
+use nih_plug::prelude::{nih_log, GuiContext};
+use raw_window_handle::RawWindowHandle;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::pop_out::{PopOutHandler, RawParent};
+use crate::{editor, BaseviewState};
+
+/// Options for a window opened using [`open_overlay()`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OverlayOptions {
+    /// The position of the overlay's top left corner in logical pixels, relative to the editor's
+    /// top left corner. This may be outside of the editor.
+    pub position: (f64, f64),
+    /// The overlay's size in logical pixels.
+    pub size: (f64, f64),
+}
+
+/// A window opened using [`open_overlay()`]. The window is closed when this is dropped.
+pub struct OverlayWindow {
+    /// Only an `Option` so the baseview window can be closed before the native window is
+    /// destroyed.
+    handle: Option<WindowHandle>,
+    native: NativeOverlay,
+    close_requested: Arc<AtomicBool>,
+    open: Arc<AtomicBool>,
+    /// The editor's window, which the overlay is positioned relative to.
+    editor_window: RawWindowHandle,
+    /// The ratio between the window system's units and logical pixels.
+    scale: f64,
+}
+
+impl OverlayWindow {
+    /// Whether the overlay is still open.
+    pub fn is_open(&self) -> bool {
+        self.open.load(Ordering::Acquire)
+    }
+
+    /// Move the overlay's top left corner to `position` in logical pixels, relative to the
+    /// editor's top left corner.
+    pub fn set_position(&mut self, position: (f64, f64)) {
+        if let Some(origin) = editor_origin(self.editor_window) {
+            self.native
+                .set_position(overlay_position(origin, position, self.scale));
+        }
+    }
+
+    /// Close the overlay. This is also done when the handle is dropped.
+    pub fn close(&mut self) {
+        self.close_requested.store(true, Ordering::Release);
+        if let Some(mut handle) = self.handle.take() {
+            handle.close();
+        }
+    }
+}
+
+impl Drop for OverlayWindow {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// Open a borderless overlay window positioned relative to the editor, for instance for a tooltip
+/// or a dropdown list. This needs to be called from the GUI thread while the editor is open.
+/// `build` is called with the overlay's baseview window and the editor's [`GuiContext`], and the
+/// handler it returns receives the overlay's events. Overlays don't take keyboard focus away from
+/// the editor, and they don't move along with the host's window, so they should be closed when
+/// the editor loses focus.
+///
+/// Returns `None` if the editor isn't open or if the overlay could not be created.
+pub fn open_overlay<B, H>(
+    baseview_state: &BaseviewState,
+    context: Arc<dyn GuiContext>,
+    options: OverlayOptions,
+    build: B,
+) -> Option<OverlayWindow>
+where
+    B: FnOnce(&mut Window, Arc<dyn GuiContext>) -> H + Send + 'static,
+    H: WindowHandler + 'static,
+{
+    open(baseview_state, context, options, false, build)
+}
+
+/// Open an overlay. `focusable` overlays can become the key window, which is used for modal
+/// dialogs that need keyboard input.
+pub(crate) fn open<B, H>(
+    baseview_state: &BaseviewState,
+    context: Arc<dyn GuiContext>,
+    options: OverlayOptions,
+    focusable: bool,
+    build: B,
+) -> Option<OverlayWindow>
+where
+    B: FnOnce(&mut Window, Arc<dyn GuiContext>) -> H + Send + 'static,
+    H: WindowHandler + 'static,
+{
+    let editor_window = crate::handle::window_handle(baseview_state)?.raw();
+
+    // Windows and X11 position windows in physical pixels, while macOS uses points
+    let zoom = baseview_state.zoom() as f64;
+    let scale_factor = baseview_state.scale_factor().map(|factor| factor as f64);
+    let scale = if cfg!(target_os = "macos") {
+        zoom
+    } else {
+        scale_factor.unwrap_or(1.0) * zoom
+    };
+
+    let origin = editor_origin(editor_window)?;
+    let position = overlay_position(origin, options.position, scale);
+    let size = (options.size.0 * scale, options.size.1 * scale);
+    let Some(native) = NativeOverlay::new(editor_window, position, size, focusable) else {
+        nih_log!("Could not create the overlay's native window");
+        return None;
+    };
+
+    let close_requested = Arc::new(AtomicBool::new(false));
+    let open = Arc::new(AtomicBool::new(true));
+    let handle = Window::open_parented(
+        &RawParent(native.raw_window_handle()),
+        WindowOpenOptions {
+            title: String::from("overlay"),
+            size: Size::new(options.size.0, options.size.1),
+            scale: match scale_factor {
+                Some(factor) => WindowScalePolicy::ScaleFactor(factor * zoom),
+                None => WindowScalePolicy::SystemScaleFactor,
+            },
+            gl_config: Some(editor::gl_config()),
+        },
+        {
+            let close_requested = close_requested.clone();
+            let open = open.clone();
+            move |window: &mut Window| {
+                PopOutHandler::new(build(window, context), close_requested, open)
+            }
+        },
+    );
+
+    Some(OverlayWindow {
+        handle: Some(handle),
+        native,
+        close_requested,
+        open,
+        editor_window,
+        scale,
+    })
+}
+
+/// Convert a position relative to the editor in logical pixels to the window system's screen
+/// coordinates.
+fn overlay_position(origin: (f64, f64), position: (f64, f64), scale: f64) -> (f64, f64) {
+    // macOS' y-axis points up
+    if cfg!(target_os = "macos") {
+        (origin.0 + position.0 * scale, origin.1 - position.1 * scale)
+    } else {
+        (origin.0 + position.0 * scale, origin.1 + position.1 * scale)
+    }
+}
+
+/// The native popup window containing an overlay's baseview window.
+struct NativeOverlay {
+    #[cfg(target_os = "windows")]
+    hwnd: winapi::shared::windef::HWND,
+
+    #[cfg(target_os = "macos")]
+    ns_window: cocoa::base::id,
+    /// The editor's window, which the overlay is attached to as a child window.
+    #[cfg(target_os = "macos")]
+    parent_ns_window: cocoa::base::id,
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    display: *mut x11::xlib::Display,
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    window_id: x11::xlib::Window,
+}
+
+/// The screen position of the editor's top left corner in the window system's units.
+#[cfg(target_os = "windows")]
+fn editor_origin(editor_window: RawWindowHandle) -> Option<(f64, f64)> {
+    use winapi::shared::windef::{HWND, POINT};
+    use winapi::um::winuser::ClientToScreen;
+
+    let hwnd = match editor_window {
+        RawWindowHandle::Win32(handle) => handle.hwnd as HWND,
+        _ => return None,
+    };
+
+    let mut point = POINT { x: 0, y: 0 };
+    if unsafe { ClientToScreen(hwnd, &mut point) } == 0 {
+        return None;
+    }
+
+    Some((point.x as f64, point.y as f64))
+}
+
+#[cfg(target_os = "windows")]
+impl NativeOverlay {
+    fn new(
+        editor_window: RawWindowHandle,
+        position: (f64, f64),
+        size: (f64, f64),
+        focusable: bool,
+    ) -> Option<Self> {
+        use std::os::windows::ffi::OsStrExt;
+        use winapi::shared::windef::HWND;
+        use winapi::um::libloaderapi::GetModuleHandleW;
+        use winapi::um::winuser::{
+            CreateWindowExW, DefWindowProcW, GetAncestor, RegisterClassW, GA_ROOT, WNDCLASSW,
+            WS_CLIPCHILDREN, WS_EX_NOACTIVATE, WS_EX_TOOLWINDOW, WS_POPUP, WS_VISIBLE,
+        };
+
+        let editor_hwnd = match editor_window {
+            RawWindowHandle::Win32(handle) => handle.hwnd as HWND,
+            _ => return None,
+        };
+        let class_name: Vec<u16> = std::ffi::OsStr::new("nih_plug_baseview_overlay")
+            .encode_wide()
+            .chain(std::iter::once(0))
+            .collect();
+
+        unsafe {
+            let instance = GetModuleHandleW(std::ptr::null());
+
+            // This fails if the class already exists, which is fine since every overlay uses the
+            // same class
+            let mut class: WNDCLASSW = std::mem::zeroed();
+            class.lpfnWndProc = Some(DefWindowProcW);
+            class.hInstance = instance;
+            class.lpszClassName = class_name.as_ptr();
+            RegisterClassW(&class);
+
+            // Owned popup windows always stay above their owner, which is the host's top level
+            // window
+            let ex_style = if focusable {
+                WS_EX_TOOLWINDOW
+            } else {
+                WS_EX_NOACTIVATE | WS_EX_TOOLWINDOW
+            };
+            let hwnd = CreateWindowExW(
+                ex_style,
+                class_name.as_ptr(),
+                std::ptr::null(),
+                WS_POPUP | WS_VISIBLE | WS_CLIPCHILDREN,
+                position.0.round() as i32,
+                position.1.round() as i32,
+                size.0.round() as i32,
+                size.1.round() as i32,
+                GetAncestor(editor_hwnd, GA_ROOT),
+                std::ptr::null_mut(),
+                instance,
+                std::ptr::null_mut(),
+            );
+            if hwnd.is_null() {
+                return None;
+            }
+
+            Some(Self { hwnd })
+        }
+    }
+
+    fn raw_window_handle(&self) -> RawWindowHandle {
+        let mut handle = raw_window_handle::Win32WindowHandle::empty();
+        handle.hwnd = self.hwnd as *mut _;
+        RawWindowHandle::Win32(handle)
+    }
+
+    fn set_position(&mut self, position: (f64, f64)) {
+        use winapi::um::winuser::{SetWindowPos, SWP_NOACTIVATE, SWP_NOSIZE, SWP_NOZORDER};
+
+        unsafe {
+            SetWindowPos(
+                self.hwnd,
+                std::ptr::null_mut(),
+                position.0.round() as i32,
+                position.1.round() as i32,
+                0,
+                0,
+                SWP_NOACTIVATE | SWP_NOSIZE | SWP_NOZORDER,
+            );
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Drop for NativeOverlay {
+    fn drop(&mut self) {
+        unsafe { winapi::um::winuser::DestroyWindow(self.hwnd) };
+    }
+}
+
+/// The screen position of the editor's top left corner in points, with the y-axis pointing up.
+#[cfg(target_os = "macos")]
+fn editor_origin(editor_window: RawWindowHandle) -> Option<(f64, f64)> {
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::NSRect;
+    use objc::{msg_send, sel, sel_impl};
+
+    let ns_view = match editor_window {
+        RawWindowHandle::AppKit(handle) => handle.ns_view as id,
+        _ => return None,
+    };
+
+    unsafe {
+        let ns_window: id = msg_send![ns_view, window];
+        if ns_window == nil {
+            return None;
+        }
+
+        let bounds: NSRect = msg_send![ns_view, bounds];
+        let window_rect: NSRect = msg_send![ns_view, convertRect: bounds toView: nil];
+        let screen_rect: NSRect = msg_send![ns_window, convertRectToScreen: window_rect];
+
+        Some((
+            screen_rect.origin.x,
+            screen_rect.origin.y + screen_rect.size.height,
+        ))
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl NativeOverlay {
+    fn new(
+        editor_window: RawWindowHandle,
+        position: (f64, f64),
+        size: (f64, f64),
+        focusable: bool,
+    ) -> Option<Self> {
+        use cocoa::base::{id, nil, NO, YES};
+        use cocoa::foundation::{NSInteger, NSPoint, NSRect, NSSize, NSUInteger};
+        use objc::{class, msg_send, sel, sel_impl};
+
+        /// `NSWindowStyleMaskBorderless`, `NSWindowStyleMaskTitled`,
+        /// `NSWindowStyleMaskFullSizeContentView`, `NSBackingStoreBuffered`, `NSWindowAbove`, and
+        /// `NSWindowTitleHidden`
+        const STYLE_BORDERLESS: NSUInteger = 0;
+        const STYLE_TITLED: NSUInteger = 1 << 0;
+        const STYLE_FULL_SIZE_CONTENT_VIEW: NSUInteger = 1 << 15;
+        const BACKING_BUFFERED: NSUInteger = 2;
+        const ORDERED_ABOVE: NSInteger = 1;
+        const TITLE_HIDDEN: NSInteger = 1;
+
+        let ns_view = match editor_window {
+            RawWindowHandle::AppKit(handle) => handle.ns_view as id,
+            _ => return None,
+        };
+
+        unsafe {
+            let parent_ns_window: id = msg_send![ns_view, window];
+            if parent_ns_window == nil {
+                return None;
+            }
+
+            // `position` is the top left corner, while Cocoa uses the bottom left corner
+            let frame = NSRect::new(
+                NSPoint::new(position.0, position.1 - size.1),
+                NSSize::new(size.0, size.1),
+            );
+            // Borderless windows can't become the key window, so focusable overlays use a titled
+            // window with the title bar hidden instead
+            let style = if focusable {
+                STYLE_TITLED | STYLE_FULL_SIZE_CONTENT_VIEW
+            } else {
+                STYLE_BORDERLESS
+            };
+            let ns_window: id = msg_send![class!(NSWindow), alloc];
+            let ns_window: id = msg_send![ns_window,
+                initWithContentRect: frame
+                styleMask: style
+                backing: BACKING_BUFFERED
+                defer: NO];
+            if ns_window == nil {
+                return None;
+            }
+            let () = msg_send![ns_window, setReleasedWhenClosed: NO];
+            let () = msg_send![ns_window, setHasShadow: YES];
+            if focusable {
+                let () = msg_send![ns_window, setTitlebarAppearsTransparent: YES];
+                let () = msg_send![ns_window, setTitleVisibility: TITLE_HIDDEN];
+                // The close, minimize, and zoom buttons
+                for button in 0..3 as NSUInteger {
+                    let button: id = msg_send![ns_window, standardWindowButton: button];
+                    if button != nil {
+                        let () = msg_send![button, setHidden: YES];
+                    }
+                }
+            }
+
+            // Child windows move along with their parent and stay above it
+            let () = msg_send![parent_ns_window, addChildWindow: ns_window ordered: ORDERED_ABOVE];
+            if focusable {
+                let () = msg_send![ns_window, makeKeyAndOrderFront: nil];
+            }
+
+            Some(Self {
+                ns_window,
+                parent_ns_window,
+            })
+        }
+    }
+
+    fn raw_window_handle(&self) -> RawWindowHandle {
+        use cocoa::base::id;
+        use objc::{msg_send, sel, sel_impl};
+
+        let content_view: id = unsafe { msg_send![self.ns_window, contentView] };
+        let mut handle = raw_window_handle::AppKitWindowHandle::empty();
+        handle.ns_window = self.ns_window as *mut _;
+        handle.ns_view = content_view as *mut _;
+        RawWindowHandle::AppKit(handle)
+    }
+
+    fn set_position(&mut self, position: (f64, f64)) {
+        use cocoa::foundation::NSPoint;
+        use objc::{msg_send, sel, sel_impl};
+
+        unsafe {
+            let () = msg_send![self.ns_window,
+                setFrameTopLeftPoint: NSPoint::new(position.0, position.1)];
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl Drop for NativeOverlay {
+    fn drop(&mut self) {
+        use cocoa::base::nil;
+        use objc::{msg_send, sel, sel_impl};
+
+        unsafe {
+            let () = msg_send![self.parent_ns_window, removeChildWindow: self.ns_window];
+            let () = msg_send![self.ns_window, orderOut: nil];
+            let () = msg_send![self.ns_window, close];
+            let () = msg_send![self.ns_window, release];
+        }
+    }
+}
+
+/// The screen position of the editor's top left corner in physical pixels.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn editor_origin(editor_window: RawWindowHandle) -> Option<(f64, f64)> {
+    use std::os::raw::c_ulong;
+    use x11::xlib;
+
+    let window_id = match editor_window {
+        RawWindowHandle::Xcb(handle) => handle.window as c_ulong,
+        RawWindowHandle::Xlib(handle) => handle.window,
+        _ => return None,
+    };
+
+    unsafe {
+        let display = crate::x11_isolation::open_display();
+        if display.is_null() {
+            return None;
+        }
+
+        let (mut x, mut y, mut child) = (0, 0, 0);
+        let translated = xlib::XTranslateCoordinates(
+            display,
+            window_id,
+            xlib::XDefaultRootWindow(display),
+            0,
+            0,
+            &mut x,
+            &mut y,
+            &mut child,
+        );
+        crate::x11_isolation::close_display(display);
+
+        if translated == 0 {
+            return None;
+        }
+        Some((x as f64, y as f64))
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+impl NativeOverlay {
+    fn new(
+        _editor_window: RawWindowHandle,
+        position: (f64, f64),
+        size: (f64, f64),
+        _focusable: bool,
+    ) -> Option<Self> {
+        use std::os::raw::c_uint;
+        use x11::xlib;
+
+        unsafe {
+            let display = crate::x11_isolation::open_display();
+            if display.is_null() {
+                return None;
+            }
+
+            // Override-redirect windows are not managed by the window manager, so they don't get
+            // decorations or focus and they stay where they are put. This also means that
+            // focusable overlays don't get keyboard focus on X11.
+            let mut attributes: xlib::XSetWindowAttributes = std::mem::zeroed();
+            attributes.override_redirect = xlib::True;
+            let window_id = xlib::XCreateWindow(
+                display,
+                xlib::XDefaultRootWindow(display),
+                position.0.round() as i32,
+                position.1.round() as i32,
+                size.0.round().max(1.0) as c_uint,
+                size.1.round().max(1.0) as c_uint,
+                0,
+                xlib::CopyFromParent,
+                xlib::InputOutput as c_uint,
+                std::ptr::null_mut(),
+                xlib::CWOverrideRedirect,
+                &mut attributes,
+            );
+            if window_id == 0 {
+                crate::x11_isolation::close_display(display);
+                return None;
+            }
+            xlib::XMapRaised(display, window_id);
+            xlib::XFlush(display);
+
+            Some(Self { display, window_id })
+        }
+    }
+
+    fn raw_window_handle(&self) -> RawWindowHandle {
+        let mut handle = raw_window_handle::XcbWindowHandle::empty();
+        handle.window = self.window_id as u32;
+        RawWindowHandle::Xcb(handle)
+    }
+
+    fn set_position(&mut self, position: (f64, f64)) {
+        use x11::xlib;
+
+        unsafe {
+            xlib::XMoveWindow(
+                self.display,
+                self.window_id,
+                position.0.round() as i32,
+                position.1.round() as i32,
+            );
+            xlib::XFlush(self.display);
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+impl Drop for NativeOverlay {
+    fn drop(&mut self) {
+        use x11::xlib;
+
+        unsafe {
+            xlib::XDestroyWindow(self.display, self.window_id);
+            crate::x11_isolation::close_display(self.display);
+        }
+    }
+}
@@ -0,0 +1,88 @@
+//! Support for user state that is owned by the window instead of being shared behind a lock. Only
+//! the window's thread ever touches the state while the editor is open, so field accesses don't
+//! need to take a lock. Other threads that occasionally need to read some of it can do so through
+//! a [`StateSnapshot`] that the handler publishes to.
+
+use baseview::{Event, EventStatus, Window, WindowHandler};
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+/// A handler that gets mutable access to the user state it owns. This is the owned state version
+/// of [`WindowHandler`], and it's used with
+/// [`create_baseview_editor_with_owned_state()`][crate::create_baseview_editor_with_owned_state()].
+pub trait OwnedStateHandler<T> {
+    fn on_frame(&mut self, window: &mut Window, state: &mut T);
+
+    fn on_event(&mut self, window: &mut Window, event: Event, state: &mut T) -> EventStatus;
+}
+
+/// The most recent copy of some data published by the editor, for threads other than the window's
+/// thread. Publishing and reading both take a short lock, so this is meant for infrequent reads
+/// like showing the editor's current page in the host's generic UI, not for the audio thread.
+pub struct StateSnapshot<S> {
+    latest: Arc<Mutex<Option<Arc<S>>>>,
+}
+
+impl<S> Clone for StateSnapshot<S> {
+    fn clone(&self) -> Self {
+        Self {
+            latest: self.latest.clone(),
+        }
+    }
+}
+
+impl<S> Default for StateSnapshot<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> StateSnapshot<S> {
+    /// Create an empty snapshot. Clones of this object all share the same snapshot.
+    pub fn new() -> Self {
+        Self {
+            latest: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Replace the snapshot with `value`. Readers holding on to the old snapshot keep seeing it
+    /// until they read again.
+    pub fn publish(&self, value: S) {
+        *self.latest.lock() = Some(Arc::new(value));
+    }
+
+    /// The most recently published snapshot, or `None` if nothing has been published yet.
+    pub fn latest(&self) -> Option<Arc<S>> {
+        self.latest.lock().clone()
+    }
+}
+
+/// Owns the user state while the window is open and returns it to `slot` when the window is
+/// dropped, so the state persists across openings.
+pub(crate) struct OwnedStateWindowHandler<T, H> {
+    pub inner: H,
+    /// This is only `None` while the handler is being dropped.
+    pub state: Option<T>,
+    pub slot: Arc<Mutex<Option<T>>>,
+}
+
+impl<T, H: OwnedStateHandler<T>> WindowHandler for OwnedStateWindowHandler<T, H> {
+    fn on_frame(&mut self, window: &mut Window) {
+        if let Some(state) = &mut self.state {
+            self.inner.on_frame(window, state);
+        }
+    }
+
+    fn on_event(&mut self, window: &mut Window, event: Event) -> EventStatus {
+        match &mut self.state {
+            Some(state) => self.inner.on_event(window, event, state),
+            None => EventStatus::Ignored,
+        }
+    }
+}
+
+impl<T, H> Drop for OwnedStateWindowHandler<T, H> {
+    fn drop(&mut self) {
+        *self.slot.lock() = self.state.take();
+    }
+}
@@ -0,0 +1,185 @@
+//! Locking the pointer in place for infinite drags. The cursor is warped back to where the lock
+//! started after every movement, and the window handler receives a virtual position that keeps
+//! moving instead.
+
+/// A cursor position in the window system's global screen coordinates.
+#[derive(Debug, Clone, Copy)]
+struct ScreenPosition {
+    x: f64,
+    y: f64,
+}
+
+/// Holds the cursor's position when the pointer was locked, and warps it back there.
+pub(crate) struct PointerLock {
+    anchor: ScreenPosition,
+    /// The display connection used for warping the cursor. Opening one for every mouse movement
+    /// would be too slow.
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    display: *mut x11::xlib::Display,
+}
+
+#[cfg(target_os = "windows")]
+impl PointerLock {
+    /// Lock the pointer at its current position. Returns `None` if the cursor's position could not
+    /// be queried.
+    pub fn new() -> Option<Self> {
+        use winapi::shared::windef::POINT;
+        use winapi::um::winuser::GetCursorPos;
+
+        let mut point = POINT { x: 0, y: 0 };
+        if unsafe { GetCursorPos(&mut point) } == 0 {
+            return None;
+        }
+
+        Some(Self {
+            anchor: ScreenPosition {
+                x: point.x as f64,
+                y: point.y as f64,
+            },
+        })
+    }
+
+    /// Move the cursor back to where the pointer was locked.
+    pub fn warp_to_anchor(&self) {
+        use winapi::um::winuser::SetCursorPos;
+
+        unsafe { SetCursorPos(self.anchor.x as i32, self.anchor.y as i32) };
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod core_graphics {
+    use std::os::raw::c_void;
+
+    #[repr(C)]
+    #[derive(Debug, Clone, Copy)]
+    pub struct CGPoint {
+        pub x: f64,
+        pub y: f64,
+    }
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        pub fn CGEventCreate(source: *const c_void) -> *mut c_void;
+        pub fn CGEventGetLocation(event: *const c_void) -> CGPoint;
+        pub fn CGWarpMouseCursorPosition(new_cursor_position: CGPoint) -> i32;
+        pub fn CGAssociateMouseAndMouseCursorPosition(connected: u32) -> i32;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        pub fn CFRelease(cf: *const c_void);
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl PointerLock {
+    /// Lock the pointer at its current position. Returns `None` if the cursor's position could not
+    /// be queried.
+    pub fn new() -> Option<Self> {
+        use self::core_graphics::*;
+
+        unsafe {
+            // An empty event contains the cursor's current location
+            let event = CGEventCreate(std::ptr::null());
+            if event.is_null() {
+                return None;
+            }
+            let location = CGEventGetLocation(event);
+            CFRelease(event);
+
+            Some(Self {
+                anchor: ScreenPosition {
+                    x: location.x,
+                    y: location.y,
+                },
+            })
+        }
+    }
+
+    /// Move the cursor back to where the pointer was locked.
+    pub fn warp_to_anchor(&self) {
+        use self::core_graphics::*;
+
+        unsafe {
+            CGWarpMouseCursorPosition(CGPoint {
+                x: self.anchor.x,
+                y: self.anchor.y,
+            });
+            // Warping otherwise suppresses mouse movement for a short while
+            CGAssociateMouseAndMouseCursorPosition(1);
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+impl PointerLock {
+    /// Lock the pointer at its current position. Returns `None` if the cursor's position could not
+    /// be queried.
+    pub fn new() -> Option<Self> {
+        use x11::xlib;
+
+        unsafe {
+            let display = crate::x11_isolation::open_display();
+            if display.is_null() {
+                return None;
+            }
+
+            let root = xlib::XDefaultRootWindow(display);
+            let (mut root_return, mut child_return) = (0, 0);
+            let (mut root_x, mut root_y, mut window_x, mut window_y) = (0, 0, 0, 0);
+            let mut mask = 0;
+            if xlib::XQueryPointer(
+                display,
+                root,
+                &mut root_return,
+                &mut child_return,
+                &mut root_x,
+                &mut root_y,
+                &mut window_x,
+                &mut window_y,
+                &mut mask,
+            ) == 0
+            {
+                crate::x11_isolation::close_display(display);
+                return None;
+            }
+
+            Some(Self {
+                anchor: ScreenPosition {
+                    x: root_x as f64,
+                    y: root_y as f64,
+                },
+                display,
+            })
+        }
+    }
+
+    /// Move the cursor back to where the pointer was locked.
+    pub fn warp_to_anchor(&self) {
+        use x11::xlib;
+
+        unsafe {
+            let root = xlib::XDefaultRootWindow(self.display);
+            xlib::XWarpPointer(
+                self.display,
+                0,
+                root,
+                0,
+                0,
+                0,
+                0,
+                self.anchor.x as i32,
+                self.anchor.y as i32,
+            );
+            xlib::XFlush(self.display);
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+impl Drop for PointerLock {
+    fn drop(&mut self) {
+        unsafe { crate::x11_isolation::close_display(self.display) };
+    }
+}
@@ -0,0 +1,190 @@
+//! Additional windows opened from the editor, for instance for detachable analyzer or keyboard
+//! panels. Pop-out windows run their own window handlers but share the editor's [`GuiContext`],
+//! so they can change parameters just like the main window.
+
+use baseview::{
+    Event, EventStatus, Size, Window, WindowHandle, WindowHandler, WindowOpenOptions,
+    WindowScalePolicy,
+};
+use nih_plug::prelude::{nih_log, GuiContext};
+use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::{editor, BaseviewState};
+
+/// Where a pop-out window is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PopOutParent {
+    /// Embed the window in the editor's window, in its top left corner. The window is clipped to
+    /// the editor's bounds and it's closed together with the editor.
+    Editor,
+    /// Open a separate top level window with its own event loop thread. This is not supported on
+    /// macOS, where all windows need to be driven by the host's main thread.
+    Floating,
+}
+
+/// Options for a window opened using [`open_pop_out()`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PopOutOptions {
+    /// The window's title, for floating windows.
+    pub title: String,
+    /// The window's size in logical pixels.
+    pub size: (f64, f64),
+    pub parent: PopOutParent,
+}
+
+/// A window opened using [`open_pop_out()`]. The window is closed when this is dropped.
+pub struct PopOutWindow {
+    /// The handle for windows embedded in the editor's window. Floating windows are closed
+    /// through `close_requested` instead since they live on another thread.
+    handle: Option<WindowHandle>,
+    close_requested: Arc<AtomicBool>,
+    open: Arc<AtomicBool>,
+}
+
+impl PopOutWindow {
+    /// Whether the window is still open. Floating windows can also be closed by the user.
+    pub fn is_open(&self) -> bool {
+        self.open.load(Ordering::Acquire)
+    }
+
+    /// Close the window. This is also done when the handle is dropped.
+    pub fn close(&mut self) {
+        self.close_requested.store(true, Ordering::Release);
+        if let Some(mut handle) = self.handle.take() {
+            handle.close();
+        }
+    }
+}
+
+impl Drop for PopOutWindow {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// Open an additional window next to the editor. This needs to be called from the GUI thread while
+/// the editor is open, for instance from the window handler. `build` is called on the new window's
+/// thread with the window and the editor's [`GuiContext`], and the handler it returns receives the
+/// window's events. The window gets an OpenGL context with the same configuration as the editor's
+/// window, and it uses the editor's scaling factor.
+///
+/// Returns `None` if the editor isn't open or if the window could not be opened.
+pub fn open_pop_out<B, H>(
+    baseview_state: &BaseviewState,
+    context: Arc<dyn GuiContext>,
+    options: PopOutOptions,
+    build: B,
+) -> Option<PopOutWindow>
+where
+    B: FnOnce(&mut Window, Arc<dyn GuiContext>) -> H + Send + 'static,
+    H: WindowHandler + 'static,
+{
+    let close_requested = Arc::new(AtomicBool::new(false));
+    let open = Arc::new(AtomicBool::new(true));
+
+    let zoom = baseview_state.zoom();
+    let window_options = WindowOpenOptions {
+        title: options.title,
+        size: Size::new(options.size.0, options.size.1),
+        scale: match baseview_state.scale_factor() {
+            Some(factor) => WindowScalePolicy::ScaleFactor((factor * zoom) as f64),
+            None => WindowScalePolicy::SystemScaleFactor,
+        },
+        gl_config: Some(editor::gl_config()),
+    };
+    let build = {
+        let close_requested = close_requested.clone();
+        let open = open.clone();
+        move |window: &mut Window| PopOutHandler::new(build(window, context), close_requested, open)
+    };
+
+    let handle = match options.parent {
+        PopOutParent::Editor => {
+            let parent = crate::handle::window_handle(baseview_state)?;
+            Some(Window::open_parented(
+                &RawParent(parent.raw()),
+                window_options,
+                build,
+            ))
+        }
+        #[cfg(target_os = "macos")]
+        PopOutParent::Floating => {
+            nih_log!("Floating pop-out windows are not supported on macOS");
+            return None;
+        }
+        #[cfg(not(target_os = "macos"))]
+        PopOutParent::Floating => {
+            let spawned = std::thread::Builder::new()
+                .name(String::from("pop-out window"))
+                .spawn(move || Window::open_blocking(window_options, build));
+            if let Err(err) = spawned {
+                nih_log!("Could not spawn the pop-out window's thread: {err}");
+                return None;
+            }
+
+            None
+        }
+    };
+
+    Some(PopOutWindow {
+        handle,
+        close_requested,
+        open,
+    })
+}
+
+/// Makes a [`WindowHandleAdapter`][crate::WindowHandleAdapter]'s handle usable as a parent
+/// regardless of which `raw-window-handle` features are enabled.
+pub(crate) struct RawParent(pub RawWindowHandle);
+
+unsafe impl HasRawWindowHandle for RawParent {
+    fn raw_window_handle(&self) -> RawWindowHandle {
+        self.0
+    }
+}
+
+/// Closes the window when its [`PopOutWindow`] asks for it, and marks the window as closed once
+/// it's been dropped. This is also used for overlay windows.
+pub(crate) struct PopOutHandler<H> {
+    inner: H,
+    close_requested: Arc<AtomicBool>,
+    /// This needs to be the last field so the window only counts as closed after the user's
+    /// handler has been dropped.
+    _open_guard: OpenGuard,
+}
+
+/// Marks the pop-out window as closed when its handler is dropped.
+struct OpenGuard(Arc<AtomicBool>);
+
+impl Drop for OpenGuard {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+impl<H> PopOutHandler<H> {
+    pub fn new(inner: H, close_requested: Arc<AtomicBool>, open: Arc<AtomicBool>) -> Self {
+        Self {
+            inner,
+            close_requested,
+            _open_guard: OpenGuard(open),
+        }
+    }
+}
+
+impl<H: WindowHandler> WindowHandler for PopOutHandler<H> {
+    fn on_frame(&mut self, window: &mut Window) {
+        if self.close_requested.load(Ordering::Acquire) {
+            window.close();
+            return;
+        }
+
+        self.inner.on_frame(window);
+    }
+
+    fn on_event(&mut self, window: &mut Window, event: Event) -> EventStatus {
+        self.inner.on_event(window, event)
+    }
+}
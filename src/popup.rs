@@ -0,0 +1,156 @@
+//! Popup windows for context menus and other transient GUIs drawn by the plugin. A popup is an
+//! overlay window that dismisses itself like a native menu would, so the plugin only needs to draw
+//! the menu and handle its clicks.
+
+use baseview::{Event, EventStatus, Window, WindowHandler};
+use nih_plug::prelude::GuiContext;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use crate::overlay::{self, OverlayOptions, OverlayWindow};
+use crate::BaseviewState;
+
+/// A popup opened using [`open_popup()`]. The popup is closed when this is dropped.
+pub struct PopupWindow {
+    overlay: OverlayWindow,
+}
+
+impl PopupWindow {
+    /// Whether the popup is still open. Popups close themselves when they're dismissed.
+    pub fn is_open(&self) -> bool {
+        self.overlay.is_open()
+    }
+
+    /// Close the popup, for instance after one of its items has been selected. The popup's handler
+    /// can also close it by calling [`Window::close()`].
+    pub fn close(&mut self) {
+        self.overlay.close();
+    }
+}
+
+/// Open a popup window with its top left corner at `position`, for instance a right-click menu at
+/// the cursor's position. `position` and `size` are in logical pixels, with `position` relative to
+/// the editor's top left corner, so the cursor position from the editor's mouse events can be
+/// used directly. The popup is not clipped to the editor's bounds.
+///
+/// The popup closes itself when the user clicks anywhere in the editor outside of the popup, when
+/// the editor or the host's window loses focus, or when the editor closes. `build` and the handler
+/// it returns work the same as for [`open_overlay()`][crate::open_overlay()].
+///
+/// Returns `None` if the editor isn't open or if the popup could not be created.
+pub fn open_popup<B, H>(
+    baseview_state: &Arc<BaseviewState>,
+    context: Arc<dyn GuiContext>,
+    position: (f64, f64),
+    size: (f64, f64),
+    build: B,
+) -> Option<PopupWindow>
+where
+    B: FnOnce(&mut Window, Arc<dyn GuiContext>) -> H + Send + 'static,
+    H: WindowHandler + 'static,
+{
+    let dismissal = Dismissal {
+        baseview_state: baseview_state.clone(),
+        button_presses: baseview_state.button_presses.load(Ordering::Acquire),
+        was_focused: baseview_state.is_focused(),
+    };
+
+    let overlay = overlay::open_overlay(
+        baseview_state,
+        context,
+        OverlayOptions { position, size },
+        move |window, context| PopupHandler {
+            inner: build(window, context),
+            dismissal,
+        },
+    )?;
+
+    Some(PopupWindow { overlay })
+}
+
+/// Decides when a popup should close itself.
+struct Dismissal {
+    /// The editor's window handle is looked up through this every time the popup checks whether
+    /// it should close, so the raw handle never has to be sent to the popup.
+    baseview_state: Arc<BaseviewState>,
+    /// [`BaseviewState::button_presses`] when the popup was opened.
+    button_presses: u64,
+    /// Whether the editor had focus when the popup was opened. Otherwise only losing the host
+    /// window's focus closes the popup.
+    was_focused: bool,
+}
+
+impl Dismissal {
+    fn should_dismiss(&self) -> bool {
+        let baseview_state = &self.baseview_state;
+
+        !baseview_state.is_open()
+            || baseview_state.button_presses.load(Ordering::Acquire) != self.button_presses
+            || (self.was_focused && !baseview_state.is_focused())
+            || (!baseview_state.host_quirks().sandboxed && !host_window_active(baseview_state))
+    }
+}
+
+/// Wraps the popup's handler and closes the window once the popup has been dismissed.
+struct PopupHandler<H> {
+    inner: H,
+    dismissal: Dismissal,
+}
+
+impl<H: WindowHandler> WindowHandler for PopupHandler<H> {
+    fn on_frame(&mut self, window: &mut Window) {
+        if self.dismissal.should_dismiss() {
+            window.close();
+            return;
+        }
+
+        self.inner.on_frame(window);
+    }
+
+    fn on_event(&mut self, window: &mut Window, event: Event) -> EventStatus {
+        self.inner.on_event(window, event)
+    }
+}
+
+/// Whether the window containing the editor is the active window. Popups don't take focus, so this
+/// is still the case while the user interacts with the popup. On Windows the popup's handler runs
+/// on the editor's thread, so the editor's window handle can be looked up from here.
+#[cfg(target_os = "windows")]
+fn host_window_active(baseview_state: &BaseviewState) -> bool {
+    use raw_window_handle::RawWindowHandle;
+    use winapi::shared::windef::HWND;
+    use winapi::um::winuser::{GetAncestor, GetForegroundWindow, GA_ROOTOWNER};
+
+    let hwnd = match crate::handle::window_handle(baseview_state).map(|handle| handle.raw()) {
+        Some(RawWindowHandle::Win32(handle)) => handle.hwnd as HWND,
+        _ => return true,
+    };
+
+    // Floating plugin windows are usually owned by the host's main window
+    unsafe {
+        let foreground = GetForegroundWindow();
+        foreground.is_null()
+            || GetAncestor(foreground, GA_ROOTOWNER) == GetAncestor(hwnd, GA_ROOTOWNER)
+    }
+}
+
+/// On macOS this checks whether the host application is still the active application.
+#[cfg(target_os = "macos")]
+fn host_window_active(_baseview_state: &BaseviewState) -> bool {
+    use cocoa::base::{id, NO};
+    use objc::runtime::BOOL;
+    use objc::{class, msg_send, sel, sel_impl};
+
+    unsafe {
+        let app: id = msg_send![class!(NSApplication), sharedApplication];
+        let active: BOOL = msg_send![app, isActive];
+        active != NO
+    }
+}
+
+/// X11 has no cheap way to check this without tracking the window manager's active window, so on
+/// X11 popups are only dismissed through the editor's own focus and clicks.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn host_window_active(_baseview_state: &BaseviewState) -> bool {
+    true
+}
@@ -0,0 +1,20 @@
+//! Presenting the frames drawn by the built-in editors. Everything the bridge draws on top of the
+//! editor's GUI goes here, right before the buffers are swapped.
+
+use baseview::gl::GlContext;
+
+use crate::debug_overlay;
+use crate::profile::profile_scope;
+
+/// Draw the debug overlay if it's enabled and swap the window's buffers. The context needs to be
+/// current. Waiting for vsync shows up in the profiler's `swap_buffers` scope, so this makes it
+/// easy to tell time spent drawing apart from time spent waiting. Requested frame captures happen
+/// before the overlay is drawn.
+pub(crate) fn swap_buffers(gl_context: &GlContext) {
+    #[cfg(feature = "testing")]
+    crate::snapshot::capture_queued(gl_context);
+    debug_overlay::draw_queued(gl_context);
+
+    profile_scope!("swap_buffers");
+    gl_context.swap_buffers();
+}
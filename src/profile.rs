@@ -0,0 +1,28 @@
+//! Profiler scopes for the `profiling` feature. The scopes are recorded with the
+//! [`profiling`](https://docs.rs/profiling) crate, so enable its `profile-with-puffin` or
+//! `profile-with-tracy` feature, or this crate's `profile_with_puffin` or `profile_with_tracy`
+//! shorthands, to pick a profiler. Every window's thread ends a profiler frame after drawing, so
+//! with multiple editors open the frames of all editors get interleaved. Without the feature the
+//! scopes compile to nothing.
+
+/// Time the rest of the enclosing block as a profiler scope with a static name.
+macro_rules! profile_scope {
+    ($name:expr) => {
+        #[cfg(feature = "profiling")]
+        ::profiling::scope!($name);
+    };
+}
+
+pub(crate) use profile_scope;
+
+/// Mark the end of a frame for the profiler.
+pub(crate) fn finish_frame() {
+    #[cfg(feature = "profiling")]
+    ::profiling::finish_frame!();
+}
+
+/// Name the current thread in the profiler. Called from the window's thread when it's created.
+pub(crate) fn register_thread() {
+    #[cfg(feature = "profiling")]
+    ::profiling::register_thread!("nih_plug_baseview GUI");
+}
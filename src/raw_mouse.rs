@@ -0,0 +1,295 @@
+//! Raw, unaccelerated mouse movement for precise drags. Regular cursor positions are rounded to
+//! whole physical pixels and go through the OS' pointer acceleration, which makes fine parameter
+//! adjustments jumpy at high DPI.
+
+use baseview::Window;
+
+/// Collects the raw mouse movement for a window. The movement is reported in the device's own
+/// units, which are usually much finer than screen pixels.
+pub(crate) struct RawMouseInput {
+    #[cfg(target_os = "windows")]
+    hwnd: winapi::shared::windef::HWND,
+    /// The movement accumulated by the window's subclass procedure since the last call to
+    /// [`RawMouseInput::take_delta()`]. This is boxed so it has a stable address.
+    #[cfg(target_os = "windows")]
+    delta: Box<std::cell::Cell<(f64, f64)>>,
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    display: *mut x11::xlib::Display,
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    xinput_opcode: std::os::raw::c_int,
+}
+
+/// Identifies our subclass procedure on the window.
+#[cfg(target_os = "windows")]
+const SUBCLASS_ID: usize = 0x6e69_6872;
+
+#[cfg(target_os = "windows")]
+impl RawMouseInput {
+    /// Start receiving raw mouse input for the window. Returns `None` if this is not supported.
+    ///
+    /// Windows only allows a single window per process to receive raw mouse input, so this takes
+    /// over raw mouse input from the host for as long as it is enabled.
+    pub fn new(window: &Window) -> Option<Self> {
+        use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+        use std::cell::Cell;
+        use winapi::shared::windef::HWND;
+        use winapi::um::commctrl::SetWindowSubclass;
+        use winapi::um::winuser::{RegisterRawInputDevices, RAWINPUTDEVICE};
+
+        let hwnd = match window.raw_window_handle() {
+            RawWindowHandle::Win32(handle) => handle.hwnd as HWND,
+            _ => return None,
+        };
+
+        unsafe {
+            let delta = Box::new(Cell::new((0.0, 0.0)));
+            if SetWindowSubclass(
+                hwnd,
+                Some(subclass_proc),
+                SUBCLASS_ID,
+                &*delta as *const Cell<(f64, f64)> as usize,
+            ) == 0
+            {
+                return None;
+            }
+
+            // The generic desktop page's mouse usage
+            let device = RAWINPUTDEVICE {
+                usUsagePage: 0x01,
+                usUsage: 0x02,
+                dwFlags: 0,
+                hwndTarget: hwnd,
+            };
+            if RegisterRawInputDevices(&device, 1, std::mem::size_of::<RAWINPUTDEVICE>() as u32)
+                == 0
+            {
+                remove_subclass(hwnd);
+                return None;
+            }
+
+            Some(Self { hwnd, delta })
+        }
+    }
+
+    /// Take the raw mouse movement since the last call.
+    pub fn take_delta(&mut self) -> (f64, f64) {
+        self.delta.replace((0.0, 0.0))
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Drop for RawMouseInput {
+    fn drop(&mut self) {
+        use winapi::um::winuser::{RegisterRawInputDevices, RAWINPUTDEVICE, RIDEV_REMOVE};
+
+        unsafe {
+            let device = RAWINPUTDEVICE {
+                usUsagePage: 0x01,
+                usUsage: 0x02,
+                dwFlags: RIDEV_REMOVE,
+                hwndTarget: std::ptr::null_mut(),
+            };
+            RegisterRawInputDevices(&device, 1, std::mem::size_of::<RAWINPUTDEVICE>() as u32);
+            remove_subclass(self.hwnd);
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+unsafe fn remove_subclass(hwnd: winapi::shared::windef::HWND) {
+    winapi::um::commctrl::RemoveWindowSubclass(hwnd, Some(subclass_proc), SUBCLASS_ID);
+}
+
+/// Accumulates the relative motion from `WM_INPUT` messages before passing them on to baseview's
+/// window procedure.
+#[cfg(target_os = "windows")]
+unsafe extern "system" fn subclass_proc(
+    hwnd: winapi::shared::windef::HWND,
+    msg: winapi::shared::minwindef::UINT,
+    wparam: winapi::shared::minwindef::WPARAM,
+    lparam: winapi::shared::minwindef::LPARAM,
+    _id: winapi::shared::basetsd::UINT_PTR,
+    ref_data: winapi::shared::basetsd::DWORD_PTR,
+) -> winapi::shared::minwindef::LRESULT {
+    use std::cell::Cell;
+    use winapi::um::commctrl::DefSubclassProc;
+    use winapi::um::winuser::{
+        GetRawInputData, HRAWINPUT, MOUSE_MOVE_ABSOLUTE, RAWINPUT, RAWINPUTHEADER, RID_INPUT,
+        RIM_TYPEMOUSE, WM_INPUT,
+    };
+
+    if msg == WM_INPUT {
+        let mut raw_input: RAWINPUT = std::mem::zeroed();
+        let mut size = std::mem::size_of::<RAWINPUT>() as u32;
+        let read = GetRawInputData(
+            lparam as HRAWINPUT,
+            RID_INPUT,
+            &mut raw_input as *mut RAWINPUT as *mut _,
+            &mut size,
+            std::mem::size_of::<RAWINPUTHEADER>() as u32,
+        );
+
+        // Tablets and remote desktop sessions report absolute positions, which we can't use
+        if read != u32::MAX && raw_input.header.dwType == RIM_TYPEMOUSE {
+            let mouse = raw_input.data.mouse();
+            if mouse.usFlags & MOUSE_MOVE_ABSOLUTE == 0 {
+                let delta = &*(ref_data as *const Cell<(f64, f64)>);
+                let (delta_x, delta_y) = delta.get();
+                delta.set((delta_x + mouse.lLastX as f64, delta_y + mouse.lLastY as f64));
+            }
+        }
+    }
+
+    DefSubclassProc(hwnd, msg, wparam, lparam)
+}
+
+#[cfg(target_os = "macos")]
+impl RawMouseInput {
+    /// Start receiving raw mouse input for the window. Returns `None` if this is not supported.
+    pub fn new(_window: &Window) -> Option<Self> {
+        Some(Self {})
+    }
+
+    /// Take the raw mouse movement since the last call. On macOS this is the sub-pixel movement
+    /// stored in the mouse event that is currently being handled, so this should only be called
+    /// while handling a cursor moved event.
+    pub fn take_delta(&mut self) -> (f64, f64) {
+        use cocoa::appkit::NSApp;
+        use cocoa::base::{id, nil};
+        use cocoa::foundation::NSUInteger;
+        use objc::{msg_send, sel, sel_impl};
+
+        // `NSEventTypeMouseMoved`, and the left, right, and other mouse dragged event types
+        const MOUSE_MOVED_EVENT_TYPES: [NSUInteger; 4] = [5, 6, 7, 27];
+
+        unsafe {
+            let event: id = msg_send![NSApp(), currentEvent];
+            if event == nil {
+                return (0.0, 0.0);
+            }
+
+            let event_type: NSUInteger = msg_send![event, type];
+            if !MOUSE_MOVED_EVENT_TYPES.contains(&event_type) {
+                return (0.0, 0.0);
+            }
+
+            let delta_x: f64 = msg_send![event, deltaX];
+            let delta_y: f64 = msg_send![event, deltaY];
+            (delta_x, delta_y)
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+impl RawMouseInput {
+    /// Start receiving raw mouse input for the window. Returns `None` if this is not supported.
+    /// This needs XInput 2.0.
+    pub fn new(_window: &Window) -> Option<Self> {
+        use std::ffi::CString;
+        use std::os::raw::c_int;
+        use x11::{xinput2, xlib};
+
+        unsafe {
+            let display = crate::x11_isolation::open_display();
+            if display.is_null() {
+                return None;
+            }
+
+            let extension_name = CString::new("XInputExtension").unwrap();
+            let (mut xinput_opcode, mut first_event, mut first_error): (c_int, c_int, c_int) =
+                (0, 0, 0);
+            let (mut major_version, mut minor_version): (c_int, c_int) = (2, 0);
+            if xlib::XQueryExtension(
+                display,
+                extension_name.as_ptr(),
+                &mut xinput_opcode,
+                &mut first_event,
+                &mut first_error,
+            ) == 0
+                || xinput2::XIQueryVersion(display, &mut major_version, &mut minor_version)
+                    != xlib::Success as c_int
+            {
+                crate::x11_isolation::close_display(display);
+                return None;
+            }
+
+            // Raw events are only ever delivered to the root window
+            let mut mask = [0u8; (xinput2::XI_LASTEVENT as usize >> 3) + 1];
+            xinput2::XISetMask(&mut mask, xinput2::XI_RawMotion);
+            let mut event_mask = xinput2::XIEventMask {
+                deviceid: xinput2::XIAllMasterDevices,
+                mask_len: mask.len() as c_int,
+                mask: mask.as_mut_ptr(),
+            };
+            xinput2::XISelectEvents(
+                display,
+                xlib::XDefaultRootWindow(display),
+                &mut event_mask,
+                1,
+            );
+            xlib::XFlush(display);
+
+            Some(Self {
+                display,
+                xinput_opcode,
+            })
+        }
+    }
+
+    /// Take the raw mouse movement since the last call.
+    pub fn take_delta(&mut self) -> (f64, f64) {
+        use x11::{xinput2, xlib};
+
+        let (mut delta_x, mut delta_y) = (0.0, 0.0);
+        unsafe {
+            let mut event: xlib::XEvent = std::mem::zeroed();
+            while xlib::XPending(self.display) > 0 {
+                xlib::XNextEvent(self.display, &mut event);
+
+                let cookie = &mut event.generic_event_cookie;
+                if cookie.type_ != xlib::GenericEvent
+                    || cookie.extension != self.xinput_opcode
+                    || xlib::XGetEventData(self.display, cookie) == 0
+                {
+                    continue;
+                }
+
+                if cookie.evtype == xinput2::XI_RawMotion {
+                    // Only the valuators that changed are included in `raw_values`, so the
+                    // values need to be matched to their axes using the mask
+                    let raw_event = &*(cookie.data as *const xinput2::XIRawEvent);
+                    let mask = std::slice::from_raw_parts(
+                        raw_event.valuators.mask,
+                        raw_event.valuators.mask_len as usize,
+                    );
+                    let mut value_idx = 0;
+                    for axis in 0..(mask.len() * 8) as i32 {
+                        if !xinput2::XIMaskIsSet(mask, axis) {
+                            continue;
+                        }
+
+                        let value = *raw_event.raw_values.add(value_idx);
+                        match axis {
+                            0 => delta_x += value,
+                            1 => delta_y += value,
+                            _ => (),
+                        }
+                        value_idx += 1;
+                    }
+                }
+
+                xlib::XFreeEventData(self.display, cookie);
+            }
+        }
+
+        (delta_x, delta_y)
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+impl Drop for RawMouseInput {
+    fn drop(&mut self) {
+        unsafe { crate::x11_isolation::close_display(self.display) };
+    }
+}
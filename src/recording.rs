@@ -0,0 +1,339 @@
+//! Recording the events baseview sends to the editor's window and replaying them later, so
+//! interaction bugs users report can be reproduced step by step. Recordings are plain text files
+//! with one event per line. File drag and drop events are not recorded since the paths they carry
+//! only mean something on the machine they were recorded on.
+
+use baseview::{Event, MouseButton, MouseEvent, Point, ScrollDelta, Size, WindowEvent, WindowInfo};
+use keyboard_types::{Code, Key, KeyState, KeyboardEvent, Location, Modifiers};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::str::{FromStr, SplitAsciiWhitespace};
+use std::time::{Duration, Instant};
+
+/// The first line of every recording. Other lines starting with `#` are ignored as well, so
+/// recordings can be annotated by hand.
+const HEADER: &str = "# nih_plug_baseview event recording";
+
+/// A recorded sequence of events, each with the time it arrived at relative to the start of the
+/// recording. Record one using
+/// [`BaseviewState::start_recording()`][crate::BaseviewState::start_recording()] and replay it
+/// using [`BaseviewState::replay_recording()`][crate::BaseviewState::replay_recording()].
+#[derive(Debug, Default)]
+pub struct EventRecording {
+    events: Vec<(Duration, Event)>,
+}
+
+impl EventRecording {
+    /// Load a recording from a file. Returns an [`io::ErrorKind::InvalidData`] error mentioning the
+    /// line number if the file contains a line that isn't a valid event.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut events = Vec::new();
+        for (line_idx, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            match decode_line(line) {
+                Some(event) => events.push(event),
+                None => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Invalid event on line {}: '{line}'", line_idx + 1),
+                    ))
+                }
+            }
+        }
+
+        Ok(Self { events })
+    }
+
+    /// Write the recording to a file, in the same format
+    /// [`BaseviewState::start_recording()`][crate::BaseviewState::start_recording()] uses.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "{HEADER}")?;
+        for (time, event) in &self.events {
+            if let Some(encoded) = encode_event(event) {
+                writeln!(writer, "{} {encoded}", time.as_micros())?;
+            }
+        }
+
+        writer.flush()
+    }
+
+    /// The recorded events and the times they arrived at, in order.
+    pub fn events(&self) -> &[(Duration, Event)] {
+        &self.events
+    }
+
+    /// The time between the first and the last recorded event.
+    pub fn duration(&self) -> Duration {
+        match (self.events.first(), self.events.last()) {
+            (Some((first, _)), Some((last, _))) => last.saturating_sub(*first),
+            _ => Duration::ZERO,
+        }
+    }
+}
+
+/// Writes events to a recording file as they arrive.
+pub(crate) struct EventRecorder {
+    writer: BufWriter<File>,
+    start: Instant,
+}
+
+impl EventRecorder {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "{HEADER}")?;
+
+        Ok(Self {
+            writer,
+            start: Instant::now(),
+        })
+    }
+
+    /// Add an event to the recording. Events that can't be recorded are skipped.
+    pub fn record(&mut self, event: &Event) -> io::Result<()> {
+        match encode_event(event) {
+            Some(encoded) => writeln!(
+                self.writer,
+                "{} {encoded}",
+                self.start.elapsed().as_micros()
+            ),
+            None => Ok(()),
+        }
+    }
+
+    /// Write the buffered events to the file. Called once per frame so a recording survives the
+    /// host crashing.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// A recording that's being replayed. The events keep their original spacing, with the first
+/// event being replayed right away.
+pub(crate) struct Replay {
+    events: VecDeque<(Duration, Event)>,
+    /// The time of the first event, which is subtracted from all events' times.
+    offset: Duration,
+    start: Instant,
+}
+
+impl Replay {
+    pub fn new(recording: EventRecording) -> Self {
+        let events = VecDeque::from(recording.events);
+        let offset = events.front().map_or(Duration::ZERO, |(time, _)| *time);
+
+        Self {
+            events,
+            offset,
+            start: Instant::now(),
+        }
+    }
+
+    /// Take the next event if it's time to replay it.
+    pub fn next_due(&mut self) -> Option<Event> {
+        let (time, _) = self.events.front()?;
+        if time.saturating_sub(self.offset) <= self.start.elapsed() {
+            self.events.pop_front().map(|(_, event)| event)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+fn encode_event(event: &Event) -> Option<String> {
+    let encoded = match event {
+        Event::Mouse(MouseEvent::CursorMoved {
+            position,
+            modifiers,
+        }) => format!("move {} {} {:x}", position.x, position.y, modifiers.bits()),
+        Event::Mouse(MouseEvent::ButtonPressed { button, modifiers }) => {
+            format!("press {} {:x}", encode_button(button), modifiers.bits())
+        }
+        Event::Mouse(MouseEvent::ButtonReleased { button, modifiers }) => {
+            format!("release {} {:x}", encode_button(button), modifiers.bits())
+        }
+        Event::Mouse(MouseEvent::WheelScrolled { delta, modifiers }) => match delta {
+            ScrollDelta::Lines { x, y } => format!("scroll lines {x} {y} {:x}", modifiers.bits()),
+            ScrollDelta::Pixels { x, y } => format!("scroll pixels {x} {y} {:x}", modifiers.bits()),
+        },
+        Event::Mouse(MouseEvent::CursorEntered) => String::from("enter"),
+        Event::Mouse(MouseEvent::CursorLeft) => String::from("leave"),
+        Event::Keyboard(key_event) => format!(
+            "key {} {} {} {} {:x} {} {}",
+            match key_event.state {
+                KeyState::Down => "down",
+                KeyState::Up => "up",
+            },
+            encode_key(&key_event.key),
+            key_event.code,
+            encode_location(key_event.location),
+            key_event.modifiers.bits(),
+            key_event.repeat,
+            key_event.is_composing
+        ),
+        Event::Window(WindowEvent::Resized(window_info)) => {
+            let logical_size = window_info.logical_size();
+            format!(
+                "resize {} {} {}",
+                logical_size.width,
+                logical_size.height,
+                window_info.scale()
+            )
+        }
+        Event::Window(WindowEvent::Focused) => String::from("focus"),
+        Event::Window(WindowEvent::Unfocused) => String::from("unfocus"),
+        // Replaying the window closing would leave the handler thinking it's closed
+        _ => return None,
+    };
+
+    Some(encoded)
+}
+
+fn decode_line(line: &str) -> Option<(Duration, Event)> {
+    let mut fields = line.split_ascii_whitespace();
+    let time = Duration::from_micros(next(&mut fields)?);
+    let event = match fields.next()? {
+        "move" => Event::Mouse(MouseEvent::CursorMoved {
+            position: Point::new(next(&mut fields)?, next(&mut fields)?),
+            modifiers: next_modifiers(&mut fields)?,
+        }),
+        "press" => Event::Mouse(MouseEvent::ButtonPressed {
+            button: decode_button(fields.next()?)?,
+            modifiers: next_modifiers(&mut fields)?,
+        }),
+        "release" => Event::Mouse(MouseEvent::ButtonReleased {
+            button: decode_button(fields.next()?)?,
+            modifiers: next_modifiers(&mut fields)?,
+        }),
+        "scroll" => {
+            let unit = fields.next()?;
+            let (x, y) = (next(&mut fields)?, next(&mut fields)?);
+            let delta = match unit {
+                "lines" => ScrollDelta::Lines { x, y },
+                "pixels" => ScrollDelta::Pixels { x, y },
+                _ => return None,
+            };
+
+            Event::Mouse(MouseEvent::WheelScrolled {
+                delta,
+                modifiers: next_modifiers(&mut fields)?,
+            })
+        }
+        "enter" => Event::Mouse(MouseEvent::CursorEntered),
+        "leave" => Event::Mouse(MouseEvent::CursorLeft),
+        "key" => Event::Keyboard(KeyboardEvent {
+            state: match fields.next()? {
+                "down" => KeyState::Down,
+                "up" => KeyState::Up,
+                _ => return None,
+            },
+            key: decode_key(fields.next()?)?,
+            code: next::<Code>(&mut fields)?,
+            location: decode_location(fields.next()?)?,
+            modifiers: next_modifiers(&mut fields)?,
+            repeat: next(&mut fields)?,
+            is_composing: next(&mut fields)?,
+        }),
+        "resize" => Event::Window(WindowEvent::Resized(WindowInfo::from_logical_size(
+            Size::new(next(&mut fields)?, next(&mut fields)?),
+            next(&mut fields)?,
+        ))),
+        "focus" => Event::Window(WindowEvent::Focused),
+        "unfocus" => Event::Window(WindowEvent::Unfocused),
+        _ => return None,
+    };
+
+    // Trailing fields mean the line was written by something else
+    match fields.next() {
+        Some(_) => None,
+        None => Some((time, event)),
+    }
+}
+
+fn next<T: FromStr>(fields: &mut SplitAsciiWhitespace) -> Option<T> {
+    fields.next()?.parse().ok()
+}
+
+fn next_modifiers(fields: &mut SplitAsciiWhitespace) -> Option<Modifiers> {
+    let bits = u32::from_str_radix(fields.next()?, 16).ok()?;
+    Some(Modifiers::from_bits_truncate(bits))
+}
+
+fn encode_button(button: &MouseButton) -> String {
+    match button {
+        MouseButton::Left => String::from("left"),
+        MouseButton::Middle => String::from("middle"),
+        MouseButton::Right => String::from("right"),
+        MouseButton::Back => String::from("back"),
+        MouseButton::Forward => String::from("forward"),
+        MouseButton::Other(button) => button.to_string(),
+    }
+}
+
+fn decode_button(button: &str) -> Option<MouseButton> {
+    match button {
+        "left" => Some(MouseButton::Left),
+        "middle" => Some(MouseButton::Middle),
+        "right" => Some(MouseButton::Right),
+        "back" => Some(MouseButton::Back),
+        "forward" => Some(MouseButton::Forward),
+        button => button.parse().ok().map(MouseButton::Other),
+    }
+}
+
+/// Characters are written as their hexadecimal code points since they may be whitespace. Named
+/// keys use their names.
+fn encode_key(key: &Key) -> String {
+    match key {
+        Key::Character(characters) => {
+            let code_points: Vec<String> = characters
+                .chars()
+                .map(|c| format!("{:x}", c as u32))
+                .collect();
+            format!("char:{}", code_points.join(","))
+        }
+        key => key.to_string(),
+    }
+}
+
+fn decode_key(key: &str) -> Option<Key> {
+    match key.strip_prefix("char:") {
+        Some(code_points) => code_points
+            .split(',')
+            .map(|c| u32::from_str_radix(c, 16).ok().and_then(char::from_u32))
+            .collect::<Option<String>>()
+            .map(Key::Character),
+        None => key.parse().ok(),
+    }
+}
+
+fn encode_location(location: Location) -> &'static str {
+    match location {
+        Location::Standard => "standard",
+        Location::Left => "left",
+        Location::Right => "right",
+        Location::Numpad => "numpad",
+    }
+}
+
+fn decode_location(location: &str) -> Option<Location> {
+    match location {
+        "standard" => Some(Location::Standard),
+        "left" => Some(Location::Left),
+        "right" => Some(Location::Right),
+        "numpad" => Some(Location::Numpad),
+        _ => None,
+    }
+}
@@ -0,0 +1,260 @@
+//! Noticing when the host moves the editor's window to another parent window. FL Studio does this
+//! when the plugin window is docked or detached, and other hosts do it when moving plugins between
+//! tabs. The window and its OpenGL context survive this, but the window needs to be redrawn and
+//! on some platforms its OpenGL surface needs to be reattached.
+
+use baseview::Window;
+
+/// Tracks the parent of the editor's window.
+pub(crate) struct ParentTracker {
+    #[cfg(target_os = "windows")]
+    hwnd: winapi::shared::windef::HWND,
+    /// The window the editor was embedded in when it was opened.
+    #[cfg(target_os = "windows")]
+    original_parent: winapi::shared::windef::HWND,
+    #[cfg(target_os = "windows")]
+    parent: winapi::shared::windef::HWND,
+
+    #[cfg(target_os = "macos")]
+    ns_view: cocoa::base::id,
+    #[cfg(target_os = "macos")]
+    superview: cocoa::base::id,
+    #[cfg(target_os = "macos")]
+    ns_window: cocoa::base::id,
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    display: *mut x11::xlib::Display,
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    window_id: x11::xlib::Window,
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    parent: x11::xlib::Window,
+}
+
+#[cfg(target_os = "windows")]
+impl ParentTracker {
+    /// Returns `None` for windows without a parent, like the standalone application's window.
+    pub fn new(window: &Window) -> Option<Self> {
+        use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+        use winapi::shared::windef::HWND;
+        use winapi::um::winuser::GetParent;
+
+        let hwnd = match window.raw_window_handle() {
+            RawWindowHandle::Win32(handle) => handle.hwnd as HWND,
+            _ => return None,
+        };
+        let parent = unsafe { GetParent(hwnd) };
+        if parent.is_null() {
+            return None;
+        }
+
+        Some(Self {
+            hwnd,
+            original_parent: parent,
+            parent,
+        })
+    }
+
+    /// Check whether the window has been moved to another parent since the last call. If the host
+    /// detached the window without giving it a new parent, the window is moved back into the
+    /// window it was opened in as long as that still exists.
+    pub fn update(&mut self) -> bool {
+        use winapi::shared::minwindef::FALSE;
+        use winapi::um::winuser::{
+            GetParent, InvalidateRect, IsWindow, SetParent, SetWindowPos, SWP_FRAMECHANGED,
+            SWP_NOACTIVATE, SWP_NOSIZE, SWP_NOZORDER, SWP_SHOWWINDOW,
+        };
+
+        unsafe {
+            let mut parent = GetParent(self.hwnd);
+            if parent == self.parent {
+                return false;
+            }
+
+            if parent.is_null() && IsWindow(self.original_parent) != 0 {
+                SetParent(self.hwnd, self.original_parent);
+                parent = self.original_parent;
+            }
+            self.parent = parent;
+
+            // The window keeps its position from the old parent, and some hosts hide it while
+            // moving it
+            SetWindowPos(
+                self.hwnd,
+                std::ptr::null_mut(),
+                0,
+                0,
+                0,
+                0,
+                SWP_FRAMECHANGED | SWP_NOACTIVATE | SWP_NOSIZE | SWP_NOZORDER | SWP_SHOWWINDOW,
+            );
+            InvalidateRect(self.hwnd, std::ptr::null(), FALSE);
+        }
+
+        true
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl ParentTracker {
+    /// Returns `None` for views without a superview.
+    pub fn new(window: &Window) -> Option<Self> {
+        use cocoa::base::{id, nil};
+        use objc::{msg_send, sel, sel_impl};
+        use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+
+        let ns_view = match window.raw_window_handle() {
+            RawWindowHandle::AppKit(handle) => handle.ns_view as id,
+            _ => return None,
+        };
+
+        unsafe {
+            let superview: id = msg_send![ns_view, superview];
+            if superview == nil {
+                return None;
+            }
+            let ns_window: id = msg_send![ns_view, window];
+
+            Some(Self {
+                ns_view,
+                superview,
+                ns_window,
+            })
+        }
+    }
+
+    /// Check whether the view has been moved to another superview or window since the last call.
+    /// An OpenGL context doesn't follow its view into another window on its own, so the contexts
+    /// of the view's OpenGL subviews are updated when that happens. Views that were removed from
+    /// their superview are left alone since the old superview may no longer exist.
+    pub fn update(&mut self) -> bool {
+        use cocoa::base::{id, nil, NO, YES};
+        use cocoa::foundation::NSArray;
+        use objc::runtime::BOOL;
+        use objc::{msg_send, sel, sel_impl};
+
+        unsafe {
+            let superview: id = msg_send![self.ns_view, superview];
+            let ns_window: id = msg_send![self.ns_view, window];
+            if superview == self.superview && ns_window == self.ns_window {
+                return false;
+            }
+            self.superview = superview;
+            self.ns_window = ns_window;
+            if ns_window == nil {
+                return false;
+            }
+
+            let subviews: id = msg_send![self.ns_view, subviews];
+            if subviews != nil {
+                for i in 0..NSArray::count(subviews) {
+                    let view = NSArray::objectAtIndex(subviews, i);
+                    let is_gl_view: BOOL = msg_send![view, respondsToSelector: sel!(openGLContext)];
+                    if is_gl_view == NO {
+                        continue;
+                    }
+
+                    let gl_context: id = msg_send![view, openGLContext];
+                    if gl_context != nil {
+                        let () = msg_send![gl_context, setView: view];
+                        let () = msg_send![gl_context, update];
+                    }
+                }
+            }
+            let () = msg_send![self.ns_view, setNeedsDisplay: YES];
+        }
+
+        true
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+impl ParentTracker {
+    /// Returns `None` for top level windows.
+    pub fn new(window: &Window) -> Option<Self> {
+        use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+        use std::os::raw::c_ulong;
+        use x11::xlib;
+
+        let window_id = match window.raw_window_handle() {
+            RawWindowHandle::Xcb(handle) => handle.window as c_ulong,
+            RawWindowHandle::Xlib(handle) => handle.window,
+            _ => return None,
+        };
+
+        unsafe {
+            let display = crate::x11_isolation::open_display();
+            if display.is_null() {
+                return None;
+            }
+
+            let parent = query_parent(display, window_id);
+            if parent == 0 || parent == xlib::XDefaultRootWindow(display) {
+                crate::x11_isolation::close_display(display);
+                return None;
+            }
+
+            Some(Self {
+                display,
+                window_id,
+                parent,
+            })
+        }
+    }
+
+    /// Check whether the window has been reparented since the last call. The GLX context is bound
+    /// to the window itself so it stays valid, but the window may have been unmapped while it was
+    /// being moved. Windows that were moved to the root window are left there since there's no
+    /// safe way to tell whether the old parent still exists.
+    pub fn update(&mut self) -> bool {
+        use x11::xlib;
+
+        unsafe {
+            let parent = query_parent(self.display, self.window_id);
+            if parent == 0 || parent == self.parent {
+                return false;
+            }
+            self.parent = parent;
+
+            xlib::XMapWindow(self.display, self.window_id);
+            xlib::XFlush(self.display);
+        }
+
+        true
+    }
+}
+
+/// The parent of `window_id`, or 0 if it could not be queried.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub(crate) unsafe fn query_parent(
+    display: *mut x11::xlib::Display,
+    window_id: x11::xlib::Window,
+) -> x11::xlib::Window {
+    use x11::xlib;
+
+    let (mut root, mut parent) = (0, 0);
+    let mut children = std::ptr::null_mut();
+    let mut num_children = 0;
+    if xlib::XQueryTree(
+        display,
+        window_id,
+        &mut root,
+        &mut parent,
+        &mut children,
+        &mut num_children,
+    ) == 0
+    {
+        return 0;
+    }
+    if !children.is_null() {
+        xlib::XFree(children as *mut _);
+    }
+
+    parent
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+impl Drop for ParentTracker {
+    fn drop(&mut self) {
+        unsafe { crate::x11_isolation::close_display(self.display) };
+    }
+}
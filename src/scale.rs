@@ -0,0 +1,183 @@
+//! Fallbacks for when the host never tells us what scaling factor to use.
+
+use nih_plug::prelude::ParentWindowHandle;
+
+/// Decides where the editor's DPI scaling factor comes from. Some hosts report nonsensical scaling
+/// factors, so this can be used to ignore them. See [`ScalePolicy::per_platform()`] for
+/// configuring this separately for every operating system.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum ScalePolicy {
+    /// Use the scaling factor provided by the host. If the host doesn't provide one, then the last
+    /// known factor or a platform specific fallback is used instead. This is the default.
+    #[default]
+    Host,
+    /// Ignore the host and let baseview use the system's scaling factor.
+    System,
+    /// Ignore the host and always use this scaling factor.
+    Fixed(f32),
+}
+
+impl ScalePolicy {
+    /// Pick one of these policies depending on the operating system the plugin is running on.
+    /// The Linux policy is also used for the BSDs.
+    pub fn per_platform(windows: ScalePolicy, macos: ScalePolicy, linux: ScalePolicy) -> Self {
+        if cfg!(target_os = "windows") {
+            windows
+        } else if cfg!(target_os = "macos") {
+            macos
+        } else {
+            linux
+        }
+    }
+}
+
+/// The DPI Windows considers to be a scaling factor of 1.
+#[cfg(target_os = "windows")]
+const USER_DEFAULT_SCREEN_DPI: u32 = 96;
+
+/// Determine the scaling factor for a window embedded in `parent` when the host did not provide
+/// one. Returns `None` if baseview should use the system scaling factor instead.
+#[cfg(target_os = "windows")]
+pub(crate) fn fallback_scale_factor(parent: ParentWindowHandle) -> Option<f32> {
+    use winapi::shared::windef::HWND;
+
+    match parent {
+        ParentWindowHandle::Win32Hwnd(hwnd) => {
+            let hwnd = hwnd as HWND;
+            // SAFETY: Both functions return 0 instead of failing if the host gave us an invalid
+            //         window handle
+            let dpi = unsafe {
+                match get_dpi_for_window() {
+                    Some(get_dpi_for_window) => get_dpi_for_window(hwnd),
+                    None => device_dpi(hwnd),
+                }
+            };
+            if dpi == 0 {
+                Some(1.0)
+            } else {
+                Some(dpi as f32 / USER_DEFAULT_SCREEN_DPI as f32)
+            }
+        }
+        _ => Some(1.0),
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) type GetDpiForWindowFn = unsafe extern "system" fn(winapi::shared::windef::HWND) -> u32;
+
+/// `GetDpiForWindow()` only exists on Windows 10 1607 and up, so it needs to be looked up at
+/// runtime. Linking to it directly would prevent the plugin from loading on older versions.
+#[cfg(target_os = "windows")]
+pub(crate) unsafe fn get_dpi_for_window() -> Option<GetDpiForWindowFn> {
+    use winapi::um::libloaderapi::{GetModuleHandleA, GetProcAddress};
+
+    let user32 = GetModuleHandleA(b"user32.dll\0".as_ptr() as *const i8);
+    if user32.is_null() {
+        return None;
+    }
+
+    let function = GetProcAddress(user32, b"GetDpiForWindow\0".as_ptr() as *const i8);
+    if function.is_null() {
+        None
+    } else {
+        Some(std::mem::transmute(function))
+    }
+}
+
+/// The DPI of the display device `hwnd` is on, for Windows versions without
+/// `GetDpiForWindow()`. This is the system DPI rather than the monitor's DPI.
+#[cfg(target_os = "windows")]
+unsafe fn device_dpi(hwnd: winapi::shared::windef::HWND) -> u32 {
+    use winapi::um::wingdi::{GetDeviceCaps, LOGPIXELSX};
+    use winapi::um::winuser::{GetDC, ReleaseDC};
+
+    let hdc = GetDC(hwnd);
+    if hdc.is_null() {
+        return 0;
+    }
+
+    let dpi = GetDeviceCaps(hdc, LOGPIXELSX);
+    ReleaseDC(hwnd, hdc);
+
+    dpi.max(0) as u32
+}
+
+/// On macOS the system scaling factor is always the right one.
+#[cfg(target_os = "macos")]
+pub(crate) fn fallback_scale_factor(_parent: ParentWindowHandle) -> Option<f32> {
+    None
+}
+
+/// We can't get the size of the window when baseview does its own scaling, so if the host does not
+/// set a scale factor we'll try the same sources toolkits use: `GDK_SCALE`, the `Xft.dpi` X
+/// resource, and finally the screen's physical DPI. If none of those are available we should just
+/// use a factor of 1. That may make the GUI tiny but it also prevents it from getting cut off.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub(crate) fn fallback_scale_factor(_parent: ParentWindowHandle) -> Option<f32> {
+    Some(system_scale_factor().unwrap_or(1.0))
+}
+
+/// The desktop-wide scaling factor, if it can be determined. X11 does not have per-monitor
+/// scaling factors.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub(crate) fn system_scale_factor() -> Option<f32> {
+    gdk_scale().or_else(|| unsafe { x11_scale_factor() })
+}
+
+/// The DPI X11 considers to be a scaling factor of 1.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const X11_DEFAULT_DPI: f32 = 96.0;
+
+/// The integer scaling factor GTK applications use, if it has been set.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn gdk_scale() -> Option<f32> {
+    std::env::var("GDK_SCALE")
+        .ok()
+        .and_then(|scale| scale.trim().parse::<f32>().ok())
+        .filter(|&scale| scale > 0.0)
+}
+
+/// Read the scaling factor from the `Xft.dpi` resource, falling back to the default screen's
+/// physical DPI. This opens a short lived connection to the X server.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+unsafe fn x11_scale_factor() -> Option<f32> {
+    use std::ffi::CStr;
+    use x11::xlib;
+
+    let display = crate::x11_isolation::open_display();
+    if display.is_null() {
+        return None;
+    }
+
+    let resources = xlib::XResourceManagerString(display);
+    let xft_dpi = if resources.is_null() {
+        None
+    } else {
+        CStr::from_ptr(resources)
+            .to_string_lossy()
+            .lines()
+            .find_map(|line| line.strip_prefix("Xft.dpi:"))
+            .and_then(|dpi| dpi.trim().parse::<f32>().ok())
+    };
+
+    let scale_factor = match xft_dpi {
+        Some(dpi) if dpi > 0.0 => Some(dpi / X11_DEFAULT_DPI),
+        _ => {
+            let screen = xlib::XDefaultScreen(display);
+            let width_px = xlib::XDisplayWidth(display, screen);
+            let width_mm = xlib::XDisplayWidthMM(display, screen);
+            if width_mm > 0 {
+                // Physical sizes reported by monitors are often slightly off, so this is rounded
+                // to the nearest quarter to avoid blurry scaling factors like 1.04
+                let dpi = width_px as f32 / (width_mm as f32 / 25.4);
+                Some(((dpi / X11_DEFAULT_DPI) * 4.0).round().max(4.0) / 4.0)
+            } else {
+                None
+            }
+        }
+    };
+
+    crate::x11_isolation::close_display(display);
+
+    scale_factor
+}
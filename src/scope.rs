@@ -0,0 +1,240 @@
+//! A ring buffer for streaming raw audio from the audio thread to the editor, for waveform and
+//! oscilloscope displays.
+
+use crossbeam::atomic::AtomicCell;
+use std::sync::atomic::{self, AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::BaseviewState;
+
+/// The editor's side of a ring buffer created using
+/// [`BaseviewState::scope_buffer()`][crate::BaseviewState::scope_buffer()].
+pub struct ScopeReader {
+    ring: Arc<Ring>,
+    /// The total number of samples read or skipped so far.
+    read_pos: u64,
+    /// The number of samples that were overwritten before they could be read since the last call
+    /// to [`ScopeReader::take_overflow()`].
+    overflow: usize,
+}
+
+/// The audio thread's side of a ring buffer created using
+/// [`BaseviewState::scope_buffer()`][crate::BaseviewState::scope_buffer()].
+pub struct ScopeWriter {
+    ring: Arc<Ring>,
+    /// Used to skip writes while the editor is closed.
+    baseview_state: Arc<BaseviewState>,
+}
+
+struct Ring {
+    /// The samples, stored as their bit patterns so they can be atomics. This has one slot more
+    /// than the buffer's capacity for the sample the writer is currently writing.
+    samples: Box<[AtomicU32]>,
+    /// The total number of samples written so far. The writer publishes this after every sample.
+    /// This is 64-bit even on 32-bit platforms, since a 32-bit position would wrap around after a
+    /// day of audio and the slot for a position is only stable as long as it doesn't.
+    write_pos: AtomicU64,
+    sample_rate: AtomicCell<f32>,
+}
+
+/// Create both sides of a ring buffer that holds `capacity` samples.
+pub(crate) fn scope_buffer(
+    capacity: usize,
+    baseview_state: Arc<BaseviewState>,
+) -> (ScopeWriter, ScopeReader) {
+    let ring = Arc::new(Ring {
+        samples: (0..capacity.max(1) + 1)
+            .map(|_| AtomicU32::new(0))
+            .collect(),
+        write_pos: AtomicU64::new(0),
+        sample_rate: AtomicCell::new(44100.0),
+    });
+
+    (
+        ScopeWriter {
+            ring: ring.clone(),
+            baseview_state,
+        },
+        ScopeReader {
+            ring,
+            read_pos: 0,
+            overflow: 0,
+        },
+    )
+}
+
+impl ScopeWriter {
+    /// Set the sample rate the samples are written at, so the editor can compute time scales. Call
+    /// this from the plugin's `initialize()` function.
+    pub fn set_sample_rate(&self, sample_rate: f32) {
+        self.ring.sample_rate.store(sample_rate);
+    }
+
+    /// Append samples to the ring buffer, overwriting the oldest samples if the editor hasn't read
+    /// them yet. For multichannel audio, write a mixdown or use one buffer per channel. This does
+    /// nothing and returns `false` while the editor is closed. This is realtime-safe.
+    pub fn write(&mut self, samples: impl IntoIterator<Item = f32>) -> bool {
+        if !self.baseview_state.is_open() {
+            return false;
+        }
+
+        let mut write_pos = self.ring.write_pos.load(Ordering::Relaxed);
+        for sample in samples {
+            self.ring.samples[self.ring.slot(write_pos)].store(sample.to_bits(), Ordering::Relaxed);
+            write_pos += 1;
+            self.ring.write_pos.store(write_pos, Ordering::Release);
+        }
+
+        true
+    }
+}
+
+impl ScopeReader {
+    /// The sample rate set with [`ScopeWriter::set_sample_rate()`].
+    pub fn sample_rate(&self) -> f32 {
+        self.ring.sample_rate.load()
+    }
+
+    /// The number of samples the ring buffer can hold.
+    pub fn capacity(&self) -> usize {
+        self.ring.samples.len() - 1
+    }
+
+    /// Append the samples written since the last call to `out`, oldest first. If the writer got
+    /// more than [`ScopeReader::capacity()`] samples ahead, the overwritten samples are skipped and
+    /// counted in [`ScopeReader::take_overflow()`]. Returns the number of samples appended.
+    pub fn drain_into(&mut self, out: &mut Vec<f32>) -> usize {
+        let write_pos = self.ring.write_pos.load(Ordering::Acquire);
+        self.skip_overwritten(write_pos);
+
+        let start = out.len();
+        let mut read_pos = self.read_pos;
+        while read_pos != write_pos {
+            let bits = self.ring.samples[self.ring.slot(read_pos)].load(Ordering::Relaxed);
+            out.push(f32::from_bits(bits));
+            read_pos += 1;
+        }
+
+        // The writer may have wrapped around while we were reading, in which case the samples at
+        // the start of what we just read may already be from the next lap. The fence keeps the
+        // sample loads above from being moved past this load.
+        atomic::fence(Ordering::Acquire);
+        let new_write_pos = self.ring.write_pos.load(Ordering::Acquire);
+        let torn = (new_write_pos - self.read_pos)
+            .saturating_sub(self.capacity() as u64)
+            .min((out.len() - start) as u64) as usize;
+        out.drain(start..start + torn);
+        self.overflow += torn;
+        self.read_pos = write_pos;
+
+        out.len() - start
+    }
+
+    /// Return the samples written since the last call. See [`ScopeReader::drain_into()`].
+    pub fn drain(&mut self) -> Vec<f32> {
+        let mut samples = Vec::new();
+        self.drain_into(&mut samples);
+
+        samples
+    }
+
+    /// The number of samples that were overwritten before they could be read since the last call.
+    /// This is nonzero when the editor doesn't read the buffer often enough for its capacity.
+    pub fn take_overflow(&mut self) -> usize {
+        std::mem::take(&mut self.overflow)
+    }
+
+    /// Move the read position past the samples the writer already overwrote.
+    fn skip_overwritten(&mut self, write_pos: u64) {
+        let unread = write_pos - self.read_pos;
+        let capacity = self.capacity() as u64;
+        if unread > capacity {
+            self.overflow += (unread - capacity) as usize;
+            self.read_pos = write_pos - capacity;
+        }
+    }
+}
+
+impl Ring {
+    /// The index of the slot a sample position is stored in.
+    fn slot(&self, position: u64) -> usize {
+        (position % self.samples.len() as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    fn open_scope_buffer(capacity: usize) -> (ScopeWriter, ScopeReader) {
+        let baseview_state = BaseviewState::from_size(400, 300);
+        baseview_state.open.store(true, Ordering::Release);
+
+        scope_buffer(capacity, baseview_state)
+    }
+
+    #[test]
+    fn samples_survive_wrapping_around() {
+        let (mut writer, mut reader) = open_scope_buffer(4);
+
+        let mut next_sample = 0.0;
+        for _ in 0..10 {
+            let samples: Vec<f32> = (0..3).map(|i| next_sample + i as f32).collect();
+            next_sample += 3.0;
+
+            assert!(writer.write(samples.iter().copied()));
+            assert_eq!(reader.drain(), samples);
+            assert_eq!(reader.take_overflow(), 0);
+        }
+    }
+
+    #[test]
+    fn overrun_skips_overwritten_samples() {
+        let (mut writer, mut reader) = open_scope_buffer(4);
+
+        writer.write((0..10).map(|i| i as f32));
+        assert_eq!(reader.drain(), [6.0, 7.0, 8.0, 9.0]);
+        assert_eq!(reader.take_overflow(), 6);
+        assert_eq!(reader.take_overflow(), 0);
+        assert!(reader.drain().is_empty());
+    }
+
+    #[test]
+    fn writes_are_skipped_while_closed() {
+        let (mut writer, mut reader) = open_scope_buffer(4);
+        writer.baseview_state.open.store(false, Ordering::Release);
+
+        assert!(!writer.write([1.0, 2.0]));
+        assert!(reader.drain().is_empty());
+    }
+
+    #[test]
+    fn concurrent_reads_never_return_torn_samples() {
+        const SAMPLES: usize = 200_000;
+
+        let (mut writer, mut reader) = open_scope_buffer(64);
+        let writer_thread = thread::spawn(move || {
+            for chunk in (0..SAMPLES).collect::<Vec<_>>().chunks(17) {
+                writer.write(chunk.iter().map(|&i| i as f32));
+            }
+        });
+
+        // Every sample is either read in order or counted as overwritten
+        let mut expected = 0;
+        let mut samples = Vec::new();
+        while expected < SAMPLES {
+            samples.clear();
+            reader.drain_into(&mut samples);
+            expected += reader.take_overflow();
+            for &sample in &samples {
+                assert_eq!(sample, expected as f32);
+                expected += 1;
+            }
+        }
+        writer_thread.join().unwrap();
+
+        assert_eq!(expected, SAMPLES);
+    }
+}
@@ -0,0 +1,41 @@
+//! Normalizing mouse wheel and trackpad scroll events. Baseview passes the platform's deltas on
+//! as-is, so depending on the OS and the input device the same gesture arrives as whole lines, as
+//! physical pixels, or as logical points, and horizontal scrolling uses opposite signs on macOS.
+//! With normalization enabled using
+//! [`BaseviewState::set_normalize_scrolling()`][crate::BaseviewState::set_normalize_scrolling()],
+//! the window handler only ever receives [`ScrollDelta::Lines`].
+
+use baseview::ScrollDelta;
+use keyboard_types::Modifiers;
+
+/// The number of logical pixels that make up a single line when converting pixel deltas from
+/// trackpads and precision mice. This matches the built-in editors.
+const PIXELS_PER_SCROLL_LINE: f32 = 50.0;
+
+/// Convert a scroll delta to lines, where positive values scroll up and to the right. Pixel deltas
+/// are converted using the window's scaling factor so the same gesture scrolls the same distance
+/// at every scale. The user's natural scrolling setting is already applied by the OS, so that is
+/// left alone. On Windows and Linux, holding Shift turns a vertical wheel into a horizontal one
+/// like it does on macOS.
+pub(crate) fn normalize(
+    delta: ScrollDelta,
+    modifiers: Modifiers,
+    window_scale: f64,
+) -> ScrollDelta {
+    let (x, y) = match delta {
+        ScrollDelta::Lines { x, y } => (x, y),
+        ScrollDelta::Pixels { x, y } => {
+            let pixels_per_line = PIXELS_PER_SCROLL_LINE * window_scale as f32;
+            (x / pixels_per_line, y / pixels_per_line)
+        }
+    };
+
+    // AppKit reports how far the content moves instead of which way the wheel was turned
+    let x = if cfg!(target_os = "macos") { -x } else { x };
+
+    if !cfg!(target_os = "macos") && modifiers.contains(Modifiers::SHIFT) && x == 0.0 {
+        ScrollDelta::Lines { x: y, y: 0.0 }
+    } else {
+        ScrollDelta::Lines { x, y }
+    }
+}
@@ -0,0 +1,234 @@
+//! A Skia surface bound to the editor's OpenGL framebuffer, available with the `skia` feature. The
+//! surface is recreated whenever the window's size or scale changes, and the canvas is scaled so
+//! the handler can draw in logical pixels. The `skia-safe` crate is re-exported from this crate so
+//! its version always matches the surface.
+
+use baseview::gl::GlContext;
+use baseview::{Event, EventStatus, Window, WindowEvent, WindowHandler, WindowInfo};
+use nih_plug::prelude::{nih_log, Editor, GuiContext};
+use skia_safe::gpu::gl::{FramebufferInfo, Interface};
+use skia_safe::gpu::{BackendRenderTarget, DirectContext, SurfaceOrigin};
+use skia_safe::{Canvas, Color, ColorSpace, ColorType, Surface};
+use std::ffi::c_void;
+use std::sync::Arc;
+
+use crate::{present, BaseviewState};
+
+const GL_RGBA8: u32 = 0x8058;
+const GL_FRAMEBUFFER_SRGB: u32 = 0x8DB9;
+
+/// The window's framebuffer has 8 stencil bits and no multisampling, see `editor.rs`.
+const STENCIL_BITS: usize = 8;
+const SAMPLE_COUNT: usize = 0;
+
+type Disable = unsafe extern "system" fn(u32);
+
+/// A handler that draws to a Skia [`Canvas`]. The canvas is scaled so the handler can draw in
+/// logical pixels, matching the mouse events' coordinates.
+pub trait SkiaHandler {
+    /// Draw a frame. The canvas has already been cleared to
+    /// [`SkiaHandler::background_color()`], and it is flushed and presented afterwards.
+    fn draw(&mut self, window: &mut Window, canvas: &mut Canvas);
+
+    /// Handle an event. The surface is resized automatically, so there's no need to handle
+    /// resize events for that.
+    fn on_event(&mut self, window: &mut Window, event: Event) -> EventStatus {
+        let _ = (window, event);
+        EventStatus::Ignored
+    }
+
+    /// The color the canvas is cleared to before every frame.
+    fn background_color(&self) -> Color {
+        Color::BLACK
+    }
+}
+
+/// Create an [`Editor`] that draws to a Skia [`Canvas`]. `build` is called every time the window
+/// is opened to create the handler. The window's context is current while `build` runs, so it can
+/// create GPU resources using the [`DirectContext`].
+pub fn create_skia_editor<T, B, H>(
+    baseview_state: Arc<BaseviewState>,
+    user_state: T,
+    build: B,
+) -> Option<Box<dyn Editor>>
+where
+    T: 'static + Send + Sync,
+    B: Fn(&Window, WindowInfo, &mut DirectContext, Arc<dyn GuiContext>, &mut T) -> H
+        + 'static
+        + Send
+        + Sync,
+    H: SkiaHandler + 'static,
+{
+    crate::create_baseview_editor(
+        baseview_state,
+        user_state,
+        move |window, window_info, context, user_state| {
+            let Some(gl_context) = window.gl_context() else {
+                return SkiaWindowHandler {
+                    skia: None,
+                    inner: None,
+                    window_info,
+                };
+            };
+
+            unsafe { gl_context.make_current() };
+            let mut skia = SkiaState::new(gl_context, window_info);
+            let inner = skia.as_mut().map(|skia| {
+                build(
+                    window,
+                    window_info,
+                    &mut skia.gr_context,
+                    context,
+                    user_state,
+                )
+            });
+            unsafe { gl_context.make_not_current() };
+
+            SkiaWindowHandler {
+                skia,
+                inner,
+                window_info,
+            }
+        },
+    )
+}
+
+/// Skia's GPU context and the surface for the window's default framebuffer.
+struct SkiaState {
+    /// This needs to be dropped before the GPU context.
+    surface: Surface,
+    gr_context: DirectContext,
+    /// Used to turn off the driver's sRGB conversion, since Skia already encodes its output.
+    disable: Disable,
+}
+
+impl SkiaState {
+    /// Create the GPU context and the surface. The context needs to be current.
+    fn new(gl_context: &GlContext, window_info: WindowInfo) -> Option<Self> {
+        let disable = gl_context.get_proc_address("glDisable");
+        if disable.is_null() {
+            nih_log!("Could not load 'glDisable' for Skia");
+            return None;
+        }
+        let disable = unsafe { std::mem::transmute::<*const c_void, Disable>(disable) };
+
+        let Some(interface) =
+            Interface::new_load_with(|symbol| gl_context.get_proc_address(symbol))
+        else {
+            nih_log!("Could not create Skia's OpenGL interface");
+            return None;
+        };
+        let Some(mut gr_context) = DirectContext::new_gl(Some(interface), None) else {
+            nih_log!("Could not create Skia's GPU context");
+            return None;
+        };
+        let surface = create_surface(&mut gr_context, window_info)?;
+
+        Some(Self {
+            surface,
+            gr_context,
+            disable,
+        })
+    }
+}
+
+/// Create a surface for the window's default framebuffer. The surface uses the sRGB color space
+/// with a linear framebuffer format, so Skia does the sRGB encoding.
+fn create_surface(gr_context: &mut DirectContext, window_info: WindowInfo) -> Option<Surface> {
+    let physical_size = window_info.physical_size();
+    let framebuffer_info = FramebufferInfo {
+        fboid: 0,
+        format: GL_RGBA8,
+        ..Default::default()
+    };
+    let render_target = BackendRenderTarget::new_gl(
+        (physical_size.width as i32, physical_size.height as i32),
+        SAMPLE_COUNT,
+        STENCIL_BITS,
+        framebuffer_info,
+    );
+
+    let surface = Surface::from_backend_render_target(
+        gr_context,
+        &render_target,
+        SurfaceOrigin::BottomLeft,
+        ColorType::RGBA8888,
+        ColorSpace::new_srgb(),
+        None,
+    );
+    if surface.is_none() {
+        nih_log!("Could not create the Skia surface");
+    }
+
+    surface
+}
+
+/// Owns Skia's state and calls the [`SkiaHandler`] built by the user. Both are `None` if the
+/// window doesn't have an OpenGL context or if Skia could not be set up.
+struct SkiaWindowHandler<H> {
+    skia: Option<SkiaState>,
+    inner: Option<H>,
+    window_info: WindowInfo,
+}
+
+impl<H: SkiaHandler> WindowHandler for SkiaWindowHandler<H> {
+    fn on_frame(&mut self, window: &mut Window) {
+        let (Some(skia), Some(inner)) = (&mut self.skia, &mut self.inner) else {
+            return;
+        };
+        match window.gl_context() {
+            Some(gl_context) => unsafe { gl_context.make_current() },
+            None => return,
+        }
+
+        // The framebuffer is created with sRGB support, and Skia already encodes to sRGB
+        unsafe { (skia.disable)(GL_FRAMEBUFFER_SRGB) };
+        skia.gr_context.reset(None);
+
+        let scale = self.window_info.scale() as f32;
+        let canvas = skia.surface.canvas();
+        canvas.clear(inner.background_color());
+        canvas.save();
+        canvas.scale((scale, scale));
+        inner.draw(window, canvas);
+        canvas.restore();
+
+        // The handler may have made another context current while drawing
+        if let Some(gl_context) = window.gl_context() {
+            unsafe { gl_context.make_current() };
+            skia.gr_context.flush_and_submit();
+            present::swap_buffers(gl_context);
+            unsafe { gl_context.make_not_current() };
+        }
+    }
+
+    fn on_event(&mut self, window: &mut Window, event: Event) -> EventStatus {
+        if let Event::Window(WindowEvent::Resized(window_info)) = &event {
+            self.window_info = *window_info;
+            if let (Some(skia), Some(gl_context)) = (&mut self.skia, window.gl_context()) {
+                unsafe { gl_context.make_current() };
+                if let Some(surface) = create_surface(&mut skia.gr_context, *window_info) {
+                    skia.surface = surface;
+                }
+                unsafe { gl_context.make_not_current() };
+            }
+        }
+
+        let will_close = matches!(event, Event::Window(WindowEvent::WillClose));
+        let status = match &mut self.inner {
+            Some(inner) => inner.on_event(window, event),
+            None => EventStatus::Ignored,
+        };
+
+        // Skia's GPU resources need to be freed while the context still exists
+        if will_close {
+            if let (Some(skia), Some(gl_context)) = (self.skia.take(), window.gl_context()) {
+                unsafe { gl_context.make_current() };
+                drop(skia);
+                unsafe { gl_context.make_not_current() };
+            }
+        }
+
+        status
+    }
+}
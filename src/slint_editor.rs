@@ -0,0 +1,437 @@
+//! Running Slint GUIs in the editor's window, available with the `slint` feature. This implements
+//! Slint's platform abstraction on top of baseview: input is forwarded to the Slint window, and the
+//! window is drawn with Slint's software renderer and copied to the window's OpenGL context.
+
+use baseview::{
+    Event, EventStatus, MouseButton, MouseEvent, ScrollDelta, Window, WindowEvent, WindowHandler,
+    WindowInfo,
+};
+use glow::HasContext;
+use keyboard_types::{Key, KeyState, KeyboardEvent};
+use nih_plug::prelude::{nih_log, Editor, GuiContext, ParamSetter};
+use parking_lot::RwLock;
+use slint::platform::software_renderer::{
+    MinimalSoftwareWindow, PremultipliedRgbaColor, RepaintBufferType,
+};
+use slint::platform::{
+    Platform, PlatformError, PointerEventButton, WindowAdapter, WindowEvent as SlintWindowEvent,
+};
+use slint::{ComponentHandle, LogicalPosition, PhysicalSize, SharedString};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::{clipboard, present, BaseviewState};
+
+/// How many logical pixels a single line of scrolling moves.
+const PIXELS_PER_SCROLL_LINE: f32 = 50.0;
+
+thread_local! {
+    /// Whether [`BaseviewPlatform`] has been installed on this thread. Slint's platform is set per
+    /// thread, and hosts may open editors on more than one thread.
+    static PLATFORM_INSTALLED: Cell<bool> = Cell::new(false);
+    /// The window the next Slint component created on this thread should use. This is set right
+    /// before calling the user's build function.
+    static NEXT_WINDOW: RefCell<Option<Rc<MinimalSoftwareWindow>>> = RefCell::new(None);
+}
+
+/// Create an [`Editor`] that shows a Slint component. `build` is called every time the window is
+/// opened to create the component, which is where the component's callbacks should be connected
+/// to the plugin's parameters using the [`GuiContext`]. `update` is called every frame, for
+/// instance to copy the parameters' current values to the component's properties. The user state
+/// is kept across reopening the editor.
+pub fn create_slint_editor<T, C, B, U>(
+    baseview_state: Arc<BaseviewState>,
+    user_state: T,
+    build: B,
+    update: U,
+) -> Option<Box<dyn Editor>>
+where
+    T: 'static + Send + Sync,
+    C: ComponentHandle + 'static,
+    B: Fn(Arc<dyn GuiContext>, &mut T) -> C + 'static + Send + Sync,
+    U: Fn(&C, &ParamSetter, &mut T) + 'static + Send + Sync,
+{
+    let update = Arc::new(update);
+
+    crate::create_baseview_editor(
+        baseview_state,
+        Arc::new(RwLock::new(user_state)),
+        move |window, window_info, context, user_state| {
+            install_platform();
+
+            let slint_window = MinimalSoftwareWindow::new(RepaintBufferType::ReusedBuffer);
+            NEXT_WINDOW.with(|next_window| *next_window.borrow_mut() = Some(slint_window.clone()));
+            let component = build(context.clone(), &mut user_state.write());
+            NEXT_WINDOW.with(|next_window| next_window.borrow_mut().take());
+
+            let mut handler = SlintHandler {
+                gui_context: context,
+                user_state: user_state.clone(),
+                update: update.clone(),
+
+                component,
+                slint_window,
+                presenter: Presenter::new(window),
+                pixels: Vec::new(),
+                window_info,
+                cursor_position: LogicalPosition::default(),
+            };
+            handler.update_window_size();
+            if let Err(err) = handler.component.show() {
+                nih_log!("Could not show the Slint component: {err}");
+            }
+
+            handler
+        },
+    )
+}
+
+/// Install [`BaseviewPlatform`] on the current thread if it isn't installed yet.
+fn install_platform() {
+    if PLATFORM_INSTALLED.with(|installed| installed.replace(true)) {
+        return;
+    }
+
+    if let Err(err) = slint::platform::set_platform(Box::new(BaseviewPlatform {
+        start_time: Instant::now(),
+    })) {
+        nih_log!("Could not install the baseview platform for Slint: {err}");
+    }
+}
+
+/// Slint's platform abstraction, implemented on top of baseview.
+struct BaseviewPlatform {
+    start_time: Instant,
+}
+
+impl Platform for BaseviewPlatform {
+    fn create_window_adapter(&self) -> Result<Rc<dyn WindowAdapter>, PlatformError> {
+        // Components created outside of the build function get a window that isn't shown anywhere
+        let window = NEXT_WINDOW
+            .with(|next_window| next_window.borrow_mut().take())
+            .unwrap_or_else(|| MinimalSoftwareWindow::new(RepaintBufferType::NewBuffer));
+
+        Ok(window)
+    }
+
+    fn duration_since_start(&self) -> Duration {
+        self.start_time.elapsed()
+    }
+
+    fn set_clipboard_text(&self, text: &str, clipboard: slint::platform::Clipboard) {
+        if clipboard == slint::platform::Clipboard::DefaultClipboard {
+            clipboard::set_text(text);
+        }
+    }
+
+    fn clipboard_text(&self, clipboard: slint::platform::Clipboard) -> Option<String> {
+        match clipboard {
+            slint::platform::Clipboard::DefaultClipboard => clipboard::get_text(),
+            _ => None,
+        }
+    }
+}
+
+struct SlintHandler<T, C, U> {
+    gui_context: Arc<dyn GuiContext>,
+    user_state: Arc<RwLock<T>>,
+    update: Arc<U>,
+
+    component: C,
+    slint_window: Rc<MinimalSoftwareWindow>,
+    /// This is `None` if the window doesn't have an OpenGL context, in which case nothing is
+    /// drawn.
+    presenter: Option<Presenter>,
+    /// The software renderer's buffer. Only the parts of the window that changed are redrawn, so
+    /// this is kept between frames.
+    pixels: Vec<PremultipliedRgbaColor>,
+    window_info: WindowInfo,
+    cursor_position: LogicalPosition,
+}
+
+impl<T, C: ComponentHandle, U> SlintHandler<T, C, U> {
+    fn update_window_size(&mut self) {
+        let physical_size = self.window_info.physical_size();
+        self.slint_window
+            .dispatch_event(SlintWindowEvent::ScaleFactorChanged {
+                scale_factor: self.window_info.scale() as f32,
+            });
+        self.slint_window
+            .set_size(PhysicalSize::new(physical_size.width, physical_size.height));
+    }
+
+    fn handle_key_event(&mut self, key_event: KeyboardEvent) -> EventStatus {
+        let text = match &key_event.key {
+            Key::Character(text) => SharedString::from(text.as_str()),
+            key => match translate_key(key) {
+                Some(key) => SharedString::from(key),
+                None => return EventStatus::Ignored,
+            },
+        };
+
+        let event = match key_event.state {
+            KeyState::Down if key_event.repeat => SlintWindowEvent::KeyPressRepeated { text },
+            KeyState::Down => SlintWindowEvent::KeyPressed { text },
+            KeyState::Up => SlintWindowEvent::KeyReleased { text },
+        };
+        self.slint_window.dispatch_event(event);
+
+        // Slint doesn't report whether the key was used, so this can't let the host handle it
+        EventStatus::Captured
+    }
+}
+
+impl<T, C, U> WindowHandler for SlintHandler<T, C, U>
+where
+    T: Send + Sync,
+    C: ComponentHandle,
+    U: Fn(&C, &ParamSetter, &mut T) + Send + Sync,
+{
+    fn on_frame(&mut self, window: &mut Window) {
+        slint::platform::update_timers_and_animations();
+
+        let param_setter = ParamSetter::new(self.gui_context.as_ref());
+        (self.update)(&self.component, &param_setter, &mut self.user_state.write());
+
+        let physical_size = self.window_info.physical_size();
+        let (width, height) = (physical_size.width as usize, physical_size.height as usize);
+        let pixels = &mut self.pixels;
+        let redrawn = self.slint_window.draw_if_needed(|renderer| {
+            if pixels.len() != width * height {
+                // The buffer's contents are no longer valid, so the whole window is redrawn
+                *pixels = vec![PremultipliedRgbaColor::default(); width * height];
+                renderer.set_repaint_buffer_type(RepaintBufferType::NewBuffer);
+                renderer.render(pixels, width);
+                renderer.set_repaint_buffer_type(RepaintBufferType::ReusedBuffer);
+            } else {
+                renderer.render(pixels, width);
+            }
+        });
+
+        if let (true, Some(presenter), Some(gl_context)) =
+            (redrawn, &self.presenter, window.gl_context())
+        {
+            unsafe {
+                gl_context.make_current();
+                presenter.present(&self.pixels, physical_size.width, physical_size.height);
+                present::swap_buffers(gl_context);
+                gl_context.make_not_current();
+            }
+        }
+    }
+
+    fn on_event(&mut self, window: &mut Window, event: Event) -> EventStatus {
+        match event {
+            Event::Mouse(MouseEvent::CursorMoved { position, .. }) => {
+                self.cursor_position = LogicalPosition::new(position.x as f32, position.y as f32);
+                self.slint_window
+                    .dispatch_event(SlintWindowEvent::PointerMoved {
+                        position: self.cursor_position,
+                    });
+            }
+            Event::Mouse(MouseEvent::ButtonPressed { button, .. }) => {
+                self.slint_window
+                    .dispatch_event(SlintWindowEvent::PointerPressed {
+                        position: self.cursor_position,
+                        button: translate_mouse_button(button),
+                    });
+            }
+            Event::Mouse(MouseEvent::ButtonReleased { button, .. }) => {
+                self.slint_window
+                    .dispatch_event(SlintWindowEvent::PointerReleased {
+                        position: self.cursor_position,
+                        button: translate_mouse_button(button),
+                    });
+            }
+            Event::Mouse(MouseEvent::WheelScrolled { delta, .. }) => {
+                let (delta_x, delta_y) = match delta {
+                    ScrollDelta::Lines { x, y } => {
+                        (x * PIXELS_PER_SCROLL_LINE, y * PIXELS_PER_SCROLL_LINE)
+                    }
+                    ScrollDelta::Pixels { x, y } => {
+                        let scale = self.window_info.scale() as f32;
+                        (x / scale, y / scale)
+                    }
+                };
+                self.slint_window
+                    .dispatch_event(SlintWindowEvent::PointerScrolled {
+                        position: self.cursor_position,
+                        delta_x,
+                        delta_y,
+                    });
+            }
+            Event::Mouse(MouseEvent::CursorLeft) => {
+                self.slint_window
+                    .dispatch_event(SlintWindowEvent::PointerExited);
+            }
+            Event::Keyboard(key_event) => return self.handle_key_event(key_event),
+            Event::Window(WindowEvent::Resized(window_info)) => {
+                self.window_info = window_info;
+                self.update_window_size();
+            }
+            Event::Window(WindowEvent::Focused) => {
+                self.slint_window
+                    .dispatch_event(SlintWindowEvent::WindowActiveChanged(true));
+            }
+            Event::Window(WindowEvent::Unfocused) => {
+                self.slint_window
+                    .dispatch_event(SlintWindowEvent::WindowActiveChanged(false));
+            }
+            Event::Window(WindowEvent::WillClose) => {
+                // The presenter's GL objects need to be deleted while the context still exists
+                if let (Some(presenter), Some(gl_context)) =
+                    (self.presenter.take(), window.gl_context())
+                {
+                    unsafe { gl_context.make_current() };
+                    presenter.destroy();
+                    unsafe { gl_context.make_not_current() };
+                }
+            }
+            _ => return EventStatus::Ignored,
+        }
+
+        EventStatus::Captured
+    }
+}
+
+/// Copies the software renderer's pixels to the window's default framebuffer.
+struct Presenter {
+    gl: glow::Context,
+    texture: glow::Texture,
+    framebuffer: glow::Framebuffer,
+}
+
+impl Presenter {
+    fn new(window: &Window) -> Option<Self> {
+        let gl_context = window.gl_context()?;
+        unsafe {
+            gl_context.make_current();
+            let gl =
+                glow::Context::from_loader_function(|symbol| gl_context.get_proc_address(symbol));
+            let objects = gl.create_texture().and_then(|texture| {
+                gl.create_framebuffer()
+                    .map(|framebuffer| (texture, framebuffer))
+            });
+            gl_context.make_not_current();
+
+            match objects {
+                Ok((texture, framebuffer)) => Some(Self {
+                    gl,
+                    texture,
+                    framebuffer,
+                }),
+                Err(err) => {
+                    nih_log!("Could not create the Slint presenter: {err}");
+                    None
+                }
+            }
+        }
+    }
+
+    /// Upload `pixels` and copy them to the default framebuffer. The context needs to be current.
+    unsafe fn present(&self, pixels: &[PremultipliedRgbaColor], width: u32, height: u32) {
+        let gl = &self.gl;
+        // `PremultipliedRgbaColor` is four `u8`s in RGBA order
+        let bytes = std::slice::from_raw_parts(pixels.as_ptr() as *const u8, pixels.len() * 4);
+
+        gl.bind_texture(glow::TEXTURE_2D, Some(self.texture));
+        gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 4);
+        gl.tex_image_2d(
+            glow::TEXTURE_2D,
+            0,
+            glow::RGBA8 as i32,
+            width as i32,
+            height as i32,
+            0,
+            glow::RGBA,
+            glow::UNSIGNED_BYTE,
+            Some(bytes),
+        );
+        gl.bind_texture(glow::TEXTURE_2D, None);
+
+        gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(self.framebuffer));
+        gl.framebuffer_texture_2d(
+            glow::READ_FRAMEBUFFER,
+            glow::COLOR_ATTACHMENT0,
+            glow::TEXTURE_2D,
+            Some(self.texture),
+            0,
+        );
+        gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, None);
+        // The software renderer's first row is the top of the window, so the image is flipped
+        gl.blit_framebuffer(
+            0,
+            0,
+            width as i32,
+            height as i32,
+            0,
+            height as i32,
+            width as i32,
+            0,
+            glow::COLOR_BUFFER_BIT,
+            glow::NEAREST,
+        );
+        gl.bind_framebuffer(glow::READ_FRAMEBUFFER, None);
+    }
+
+    /// Delete the GL objects. The context needs to be current.
+    fn destroy(self) {
+        unsafe {
+            self.gl.delete_framebuffer(self.framebuffer);
+            self.gl.delete_texture(self.texture);
+        }
+    }
+}
+
+fn translate_mouse_button(button: MouseButton) -> PointerEventButton {
+    match button {
+        MouseButton::Left => PointerEventButton::Left,
+        MouseButton::Right => PointerEventButton::Right,
+        MouseButton::Middle => PointerEventButton::Middle,
+        _ => PointerEventButton::Other,
+    }
+}
+
+fn translate_key(key: &Key) -> Option<slint::platform::Key> {
+    use slint::platform::Key as SlintKey;
+
+    let key = match key {
+        Key::Backspace => SlintKey::Backspace,
+        Key::Tab => SlintKey::Tab,
+        Key::Enter => SlintKey::Return,
+        Key::Escape => SlintKey::Escape,
+        Key::Delete => SlintKey::Delete,
+        Key::Shift => SlintKey::Shift,
+        Key::Control => SlintKey::Control,
+        Key::Alt => SlintKey::Alt,
+        Key::AltGraph => SlintKey::AltGr,
+        Key::CapsLock => SlintKey::CapsLock,
+        Key::Meta => SlintKey::Meta,
+        Key::ArrowUp => SlintKey::UpArrow,
+        Key::ArrowDown => SlintKey::DownArrow,
+        Key::ArrowLeft => SlintKey::LeftArrow,
+        Key::ArrowRight => SlintKey::RightArrow,
+        Key::Insert => SlintKey::Insert,
+        Key::Home => SlintKey::Home,
+        Key::End => SlintKey::End,
+        Key::PageUp => SlintKey::PageUp,
+        Key::PageDown => SlintKey::PageDown,
+        Key::F1 => SlintKey::F1,
+        Key::F2 => SlintKey::F2,
+        Key::F3 => SlintKey::F3,
+        Key::F4 => SlintKey::F4,
+        Key::F5 => SlintKey::F5,
+        Key::F6 => SlintKey::F6,
+        Key::F7 => SlintKey::F7,
+        Key::F8 => SlintKey::F8,
+        Key::F9 => SlintKey::F9,
+        Key::F10 => SlintKey::F10,
+        Key::F11 => SlintKey::F11,
+        Key::F12 => SlintKey::F12,
+        _ => return None,
+    };
+
+    Some(key)
+}
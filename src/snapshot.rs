@@ -0,0 +1,625 @@
+//! Golden image tests for the editor's rendering. A [`Frame`] holds the pixels of a drawn frame,
+//! and [`Frame::assert_matches()`] compares it against a PNG file checked into the repository.
+//!
+//! The built-in editors capture their frames from the window's framebuffer right before the
+//! buffers are swapped, so the window needs to be visible. Custom handlers can instead draw into an
+//! offscreen framebuffer using [`render_offscreen()`] and hand the result over using
+//! [`submit_frame()`], which also works in a hidden window. Either way an OpenGL context is needed,
+//! so these tests still need a display server, like Xvfb on Linux.
+
+use baseview::gl::GlContext;
+use nih_plug::prelude::nih_log;
+use std::cell::RefCell;
+use std::ffi::c_void;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+const GL_VIEWPORT: u32 = 0x0BA2;
+const GL_PACK_ALIGNMENT: u32 = 0x0D05;
+const GL_RGBA: u32 = 0x1908;
+const GL_UNSIGNED_BYTE: u32 = 0x1401;
+const GL_READ_FRAMEBUFFER: u32 = 0x8CA8;
+const GL_READ_FRAMEBUFFER_BINDING: u32 = 0x8CAA;
+const GL_FRAMEBUFFER: u32 = 0x8D40;
+const GL_FRAMEBUFFER_BINDING: u32 = 0x8CA6;
+const GL_RENDERBUFFER: u32 = 0x8D41;
+const GL_RENDERBUFFER_BINDING: u32 = 0x8CA7;
+const GL_RGBA8: u32 = 0x8058;
+const GL_COLOR_ATTACHMENT0: u32 = 0x8CE0;
+const GL_FRAMEBUFFER_COMPLETE: u32 = 0x8CD5;
+
+/// The largest difference between two color channels that still counts as the same color, since
+/// drivers don't all round the same way.
+const DEFAULT_TOLERANCE: u8 = 2;
+
+type GetIntegerv = unsafe extern "system" fn(u32, *mut i32);
+type PixelStorei = unsafe extern "system" fn(u32, i32);
+type ReadPixels = unsafe extern "system" fn(i32, i32, i32, i32, u32, u32, *mut c_void);
+type BindFramebuffer = unsafe extern "system" fn(u32, u32);
+type GenObjects = unsafe extern "system" fn(i32, *mut u32);
+type DeleteObjects = unsafe extern "system" fn(i32, *const u32);
+type RenderbufferStorage = unsafe extern "system" fn(u32, u32, i32, i32);
+type FramebufferRenderbuffer = unsafe extern "system" fn(u32, u32, u32, u32);
+type CheckFramebufferStatus = unsafe extern "system" fn(u32) -> u32;
+type Viewport = unsafe extern "system" fn(i32, i32, i32, i32);
+
+thread_local! {
+    /// Whether the next buffer swap on this thread should capture the frame, set by the window
+    /// handler while a capture is requested.
+    static CAPTURE_QUEUED: RefCell<bool> = RefCell::new(false);
+    /// The frame captured by the last buffer swap on this thread.
+    static CAPTURED_FRAME: RefCell<Option<Frame>> = RefCell::new(None);
+}
+
+/// The pixels of a frame as non-premultiplied 8-bit RGBA values, row by row starting at the top.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    width: u32,
+    height: u32,
+    rgba: Vec<u8>,
+}
+
+/// The result of comparing two frames using [`Frame::diff()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameDiff {
+    /// Whether the frames have the same size. The other fields are zero if they don't.
+    pub same_size: bool,
+    /// The number of pixels with a color channel that differs by more than the tolerance.
+    pub mismatched_pixels: usize,
+    /// The largest difference between two color channels, regardless of the tolerance.
+    pub max_difference: u8,
+}
+
+impl FrameDiff {
+    /// Whether the frames are the same within the tolerance.
+    pub fn matches(&self) -> bool {
+        self.same_size && self.mismatched_pixels == 0
+    }
+}
+
+impl Frame {
+    /// Create a frame from non-premultiplied 8-bit RGBA pixels, row by row starting at the top.
+    /// Returns `None` if `rgba` doesn't contain exactly `width * height` pixels.
+    pub fn new(width: u32, height: u32, rgba: Vec<u8>) -> Option<Self> {
+        if rgba.len() != width as usize * height as usize * 4 {
+            return None;
+        }
+
+        Some(Self {
+            width,
+            height,
+            rgba,
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn rgba(&self) -> &[u8] {
+        &self.rgba
+    }
+
+    /// The color of the pixel at `(x, y)`, counted from the top left corner.
+    pub fn pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        let offset = (y as usize * self.width as usize + x as usize) * 4;
+        self.rgba[offset..offset + 4].try_into().ok()
+    }
+
+    /// Load a frame from a PNG file. Grayscale, RGB, and 16-bit images are converted to 8-bit
+    /// RGBA.
+    pub fn load_png(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut decoder = png::Decoder::new(BufReader::new(File::open(path)?));
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info().map_err(png_error)?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer).map_err(png_error)?;
+        buffer.truncate(info.buffer_size());
+
+        let rgba = match info.color_type {
+            png::ColorType::Rgba => buffer,
+            png::ColorType::Rgb => buffer
+                .chunks_exact(3)
+                .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+                .collect(),
+            png::ColorType::GrayscaleAlpha => buffer
+                .chunks_exact(2)
+                .flat_map(|gray_alpha| {
+                    let [gray, alpha] = [gray_alpha[0], gray_alpha[1]];
+                    [gray, gray, gray, alpha]
+                })
+                .collect(),
+            png::ColorType::Grayscale => buffer
+                .iter()
+                .flat_map(|&gray| [gray, gray, gray, 255])
+                .collect(),
+            // The transformations expand palettes
+            png::ColorType::Indexed => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Unexpected indexed PNG data",
+                ))
+            }
+        };
+
+        Self::new(info.width, info.height, rgba)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Unexpected PNG buffer size"))
+    }
+
+    /// Write the frame to a PNG file.
+    pub fn save_png(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut encoder =
+            png::Encoder::new(BufWriter::new(File::create(path)?), self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut writer = encoder.write_header().map_err(png_error)?;
+        writer.write_image_data(&self.rgba).map_err(png_error)?;
+        writer.finish().map_err(png_error)
+    }
+
+    /// Compare this frame to `expected`. Pixels where a color channel differs by more than
+    /// `tolerance` count as mismatched.
+    pub fn diff(&self, expected: &Frame, tolerance: u8) -> FrameDiff {
+        if self.width != expected.width || self.height != expected.height {
+            return FrameDiff {
+                same_size: false,
+                mismatched_pixels: 0,
+                max_difference: 0,
+            };
+        }
+
+        let mut mismatched_pixels = 0;
+        let mut max_difference = 0;
+        for (actual, expected) in self.rgba.chunks_exact(4).zip(expected.rgba.chunks_exact(4)) {
+            let difference = channel_difference(actual, expected);
+            max_difference = max_difference.max(difference);
+            if difference > tolerance {
+                mismatched_pixels += 1;
+            }
+        }
+
+        FrameDiff {
+            same_size: true,
+            mismatched_pixels,
+            max_difference,
+        }
+    }
+
+    /// Compare this frame against a golden image, panicking if they differ. Relative paths are
+    /// resolved against the `tests/snapshots` directory of the crate being tested.
+    ///
+    /// A missing golden image is created from this frame. Setting the
+    /// `NIH_PLUG_BASEVIEW_UPDATE_SNAPSHOTS` environment variable to anything other than `0`
+    /// replaces existing golden images instead, after an intentional change to the rendering. When
+    /// the frame doesn't match, it's written next to the golden image along with an image
+    /// highlighting the differences.
+    pub fn assert_matches(&self, golden: impl AsRef<Path>) {
+        self.assert_matches_with_tolerance(golden, DEFAULT_TOLERANCE);
+    }
+
+    /// [`Frame::assert_matches()`], with a custom tolerance for the differences between color
+    /// channels. See [`Frame::diff()`].
+    pub fn assert_matches_with_tolerance(&self, golden: impl AsRef<Path>, tolerance: u8) {
+        let golden = golden_path(golden.as_ref());
+        if update_requested() || !golden.exists() {
+            if let Some(parent) = golden.parent() {
+                std::fs::create_dir_all(parent)
+                    .unwrap_or_else(|err| panic!("Could not create '{}': {err}", parent.display()));
+            }
+            self.save_png(&golden)
+                .unwrap_or_else(|err| panic!("Could not write '{}': {err}", golden.display()));
+            nih_log!("Wrote the golden image '{}'", golden.display());
+            return;
+        }
+
+        let expected = Frame::load_png(&golden)
+            .unwrap_or_else(|err| panic!("Could not read '{}': {err}", golden.display()));
+        let diff = self.diff(&expected, tolerance);
+        if diff.matches() {
+            return;
+        }
+
+        let actual_path = golden.with_extension("actual.png");
+        let diff_path = golden.with_extension("diff.png");
+        let _ = self.save_png(&actual_path);
+        if diff.same_size {
+            let _ = self.diff_image(&expected, tolerance).save_png(&diff_path);
+            panic!(
+                "The frame doesn't match '{}': {} pixels differ, by up to {}. The frame was \
+                 written to '{}' and the differences to '{}'.",
+                golden.display(),
+                diff.mismatched_pixels,
+                diff.max_difference,
+                actual_path.display(),
+                diff_path.display()
+            );
+        } else {
+            panic!(
+                "The frame doesn't match '{}': the frame is {}x{} but the golden image is {}x{}. \
+                 The frame was written to '{}'.",
+                golden.display(),
+                self.width,
+                self.height,
+                expected.width,
+                expected.height,
+                actual_path.display()
+            );
+        }
+    }
+
+    /// An image of `expected` in faded grayscale with the mismatched pixels in red.
+    fn diff_image(&self, expected: &Frame, tolerance: u8) -> Frame {
+        let rgba = self
+            .rgba
+            .chunks_exact(4)
+            .zip(expected.rgba.chunks_exact(4))
+            .flat_map(|(actual, expected)| {
+                if channel_difference(actual, expected) > tolerance {
+                    [255, 0, 0, 255]
+                } else {
+                    let luma =
+                        (expected[0] as u32 * 3 + expected[1] as u32 * 6 + expected[2] as u32) / 10;
+                    let faded = (128 + luma / 2) as u8;
+                    [faded, faded, faded, 255]
+                }
+            })
+            .collect();
+
+        Frame {
+            width: self.width,
+            height: self.height,
+            rgba,
+        }
+    }
+}
+
+/// Read the pixels in the current viewport of the window's framebuffer, for window handlers that
+/// render with OpenGL themselves. The context needs to be current. Call this after drawing the
+/// GUI and before swapping the buffers, and hand the frame over using [`submit_frame()`]. The
+/// built-in editors do this on their own when a frame is requested through
+/// [`BaseviewState::request_frame_capture()`][crate::BaseviewState::request_frame_capture()].
+/// The window needs to be visible for its framebuffer to have any contents, see
+/// [`render_offscreen()`] for hidden windows.
+pub fn capture_current_frame(context: &GlContext) -> Option<Frame> {
+    let load = |name| {
+        let ptr = context.get_proc_address(name);
+        (!ptr.is_null()).then_some(ptr)
+    };
+    let (Some(get_integerv), Some(pixel_storei), Some(read_pixels)) = (
+        load("glGetIntegerv"),
+        load("glPixelStorei"),
+        load("glReadPixels"),
+    ) else {
+        return None;
+    };
+    // This doesn't exist before OpenGL 3.0, in which case there's only the window's framebuffer
+    let bind_framebuffer = load("glBindFramebuffer");
+
+    unsafe {
+        let get_integerv = std::mem::transmute::<*const c_void, GetIntegerv>(get_integerv);
+        let pixel_storei = std::mem::transmute::<*const c_void, PixelStorei>(pixel_storei);
+        let read_pixels = std::mem::transmute::<*const c_void, ReadPixels>(read_pixels);
+        let bind_framebuffer =
+            bind_framebuffer.map(|ptr| std::mem::transmute::<*const c_void, BindFramebuffer>(ptr));
+
+        let mut viewport = [0i32; 4];
+        get_integerv(GL_VIEWPORT, viewport.as_mut_ptr());
+        let [x, y, width, height] = viewport;
+        if width <= 0 || height <= 0 {
+            return None;
+        }
+
+        // Renderers that draw to their own framebuffer have already blitted it to the window's
+        // framebuffer by the time the buffers are swapped
+        let mut old_read_framebuffer = 0;
+        if let Some(bind_framebuffer) = bind_framebuffer {
+            get_integerv(GL_READ_FRAMEBUFFER_BINDING, &mut old_read_framebuffer);
+            bind_framebuffer(GL_READ_FRAMEBUFFER, 0);
+        }
+        let frame = read_frame(
+            get_integerv,
+            pixel_storei,
+            read_pixels,
+            [x, y, width, height],
+        );
+        if let Some(bind_framebuffer) = bind_framebuffer {
+            bind_framebuffer(GL_READ_FRAMEBUFFER, old_read_framebuffer as u32);
+        }
+
+        frame
+    }
+}
+
+/// Call `draw` with an offscreen framebuffer of `width` by `height` physical pixels bound instead
+/// of the window's, and read back what it drew. The context needs to be current. The viewport
+/// covers the offscreen framebuffer while `draw` runs, and the previous framebuffer and viewport
+/// are restored afterwards. Nothing is drawn to the window, so this also works when the window is
+/// hidden, for instance in a [`MockHost`][crate::testing::MockHost] created using
+/// [`MockHost::new_hidden()`][crate::testing::MockHost::new_hidden()]. `draw` needs to draw to
+/// the currently bound framebuffer instead of binding framebuffer 0 itself.
+///
+/// This needs OpenGL 3.0 or `ARB_framebuffer_object`. Returns `None` if the framebuffer could not
+/// be created.
+pub fn render_offscreen(
+    context: &GlContext,
+    width: u32,
+    height: u32,
+    draw: impl FnOnce(),
+) -> Option<Frame> {
+    if width == 0 || height == 0 {
+        return None;
+    }
+
+    let load = |name| {
+        let ptr = context.get_proc_address(name);
+        (!ptr.is_null()).then_some(ptr)
+    };
+    unsafe {
+        use std::mem::transmute;
+
+        let get_integerv = transmute::<*const c_void, GetIntegerv>(load("glGetIntegerv")?);
+        let pixel_storei = transmute::<*const c_void, PixelStorei>(load("glPixelStorei")?);
+        let read_pixels = transmute::<*const c_void, ReadPixels>(load("glReadPixels")?);
+        let viewport = transmute::<*const c_void, Viewport>(load("glViewport")?);
+        let gen_framebuffers = transmute::<*const c_void, GenObjects>(load("glGenFramebuffers")?);
+        let delete_framebuffers =
+            transmute::<*const c_void, DeleteObjects>(load("glDeleteFramebuffers")?);
+        let bind_framebuffer =
+            transmute::<*const c_void, BindFramebuffer>(load("glBindFramebuffer")?);
+        let gen_renderbuffers = transmute::<*const c_void, GenObjects>(load("glGenRenderbuffers")?);
+        let delete_renderbuffers =
+            transmute::<*const c_void, DeleteObjects>(load("glDeleteRenderbuffers")?);
+        let bind_renderbuffer =
+            transmute::<*const c_void, BindFramebuffer>(load("glBindRenderbuffer")?);
+        let renderbuffer_storage =
+            transmute::<*const c_void, RenderbufferStorage>(load("glRenderbufferStorage")?);
+        let framebuffer_renderbuffer =
+            transmute::<*const c_void, FramebufferRenderbuffer>(load("glFramebufferRenderbuffer")?);
+        let check_framebuffer_status =
+            transmute::<*const c_void, CheckFramebufferStatus>(load("glCheckFramebufferStatus")?);
+
+        let mut old_framebuffer = 0;
+        get_integerv(GL_FRAMEBUFFER_BINDING, &mut old_framebuffer);
+        let mut old_renderbuffer = 0;
+        get_integerv(GL_RENDERBUFFER_BINDING, &mut old_renderbuffer);
+        let mut old_viewport = [0i32; 4];
+        get_integerv(GL_VIEWPORT, old_viewport.as_mut_ptr());
+
+        let (width, height) = (width as i32, height as i32);
+        let mut renderbuffer = 0;
+        gen_renderbuffers(1, &mut renderbuffer);
+        bind_renderbuffer(GL_RENDERBUFFER, renderbuffer);
+        renderbuffer_storage(GL_RENDERBUFFER, GL_RGBA8, width, height);
+        let mut framebuffer = 0;
+        gen_framebuffers(1, &mut framebuffer);
+        bind_framebuffer(GL_FRAMEBUFFER, framebuffer);
+        framebuffer_renderbuffer(
+            GL_FRAMEBUFFER,
+            GL_COLOR_ATTACHMENT0,
+            GL_RENDERBUFFER,
+            renderbuffer,
+        );
+
+        let frame = if check_framebuffer_status(GL_FRAMEBUFFER) == GL_FRAMEBUFFER_COMPLETE {
+            viewport(0, 0, width, height);
+            draw();
+
+            // Renderers may leave another framebuffer bound for reading
+            bind_framebuffer(GL_FRAMEBUFFER, framebuffer);
+            read_frame(
+                get_integerv,
+                pixel_storei,
+                read_pixels,
+                [0, 0, width, height],
+            )
+        } else {
+            nih_log!("Could not create an offscreen framebuffer for capturing a frame");
+            None
+        };
+
+        bind_framebuffer(GL_FRAMEBUFFER, old_framebuffer as u32);
+        bind_renderbuffer(GL_RENDERBUFFER, old_renderbuffer as u32);
+        let [x, y, old_width, old_height] = old_viewport;
+        viewport(x, y, old_width, old_height);
+        delete_framebuffers(1, &framebuffer);
+        delete_renderbuffers(1, &renderbuffer);
+
+        frame
+    }
+}
+
+/// Read a rectangle of pixels from the framebuffer bound for reading, as a frame with its rows
+/// starting at the top.
+unsafe fn read_frame(
+    get_integerv: GetIntegerv,
+    pixel_storei: PixelStorei,
+    read_pixels: ReadPixels,
+    [x, y, width, height]: [i32; 4],
+) -> Option<Frame> {
+    let mut old_pack_alignment = 4;
+    get_integerv(GL_PACK_ALIGNMENT, &mut old_pack_alignment);
+    pixel_storei(GL_PACK_ALIGNMENT, 1);
+
+    let mut rgba = vec![0u8; width as usize * height as usize * 4];
+    read_pixels(
+        x,
+        y,
+        width,
+        height,
+        GL_RGBA,
+        GL_UNSIGNED_BYTE,
+        rgba.as_mut_ptr() as *mut c_void,
+    );
+
+    pixel_storei(GL_PACK_ALIGNMENT, old_pack_alignment);
+
+    // OpenGL's rows start at the bottom
+    let rgba = rgba
+        .chunks_exact(width as usize * 4)
+        .rev()
+        .flatten()
+        .copied()
+        .collect();
+    Frame::new(width as u32, height as u32, rgba)
+}
+
+/// Whether the editor asked for the frame that's being drawn on this thread to be captured. Custom
+/// handlers can check this in [`WindowHandler::on_frame()`][baseview::WindowHandler::on_frame()]
+/// and then capture the frame using [`capture_current_frame()`] or [`render_offscreen()`].
+pub fn capture_requested() -> bool {
+    CAPTURE_QUEUED.with(|capture_queued| *capture_queued.borrow())
+}
+
+/// Hand a frame captured by a custom handler to
+/// [`BaseviewState::take_captured_frame()`][crate::BaseviewState::take_captured_frame()]. This
+/// only has an effect while [`capture_requested()`] is true, and only the first frame submitted
+/// during a frame is kept.
+pub fn submit_frame(frame: Frame) {
+    if CAPTURE_QUEUED.with(|capture_queued| capture_queued.replace(false)) {
+        CAPTURED_FRAME.with(|captured_frame| *captured_frame.borrow_mut() = Some(frame));
+    }
+}
+
+/// Set whether the next buffer swap by a built-in editor should capture the frame.
+pub(crate) fn queue_capture(queued: bool) {
+    CAPTURE_QUEUED.with(|capture_queued| *capture_queued.borrow_mut() = queued);
+}
+
+/// Capture the frame if a capture is queued. The context needs to be current.
+pub(crate) fn capture_queued(context: &GlContext) {
+    if CAPTURE_QUEUED.with(|capture_queued| capture_queued.replace(false)) {
+        let frame = capture_current_frame(context);
+        CAPTURED_FRAME.with(|captured_frame| *captured_frame.borrow_mut() = frame);
+    }
+}
+
+/// Take the frame captured by the last buffer swap, if any.
+pub(crate) fn take_captured() -> Option<Frame> {
+    CAPTURED_FRAME.with(|captured_frame| captured_frame.borrow_mut().take())
+}
+
+fn png_error(err: impl std::error::Error + Send + Sync + 'static) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, err)
+}
+
+/// The largest difference between the color channels of two RGBA pixels.
+fn channel_difference(actual: &[u8], expected: &[u8]) -> u8 {
+    actual
+        .iter()
+        .zip(expected)
+        .map(|(actual, expected)| actual.abs_diff(*expected))
+        .max()
+        .unwrap_or(0)
+}
+
+/// Resolve a relative golden image path against the tested crate's `tests/snapshots` directory.
+fn golden_path(golden: &Path) -> PathBuf {
+    if golden.is_absolute() {
+        return golden.to_owned();
+    }
+
+    let manifest_dir = std::env::var_os("CARGO_MANIFEST_DIR").unwrap_or_else(|| ".".into());
+    Path::new(&manifest_dir)
+        .join("tests")
+        .join("snapshots")
+        .join(golden)
+}
+
+/// Whether the golden images should be replaced with the captured frames.
+fn update_requested() -> bool {
+    std::env::var_os("NIH_PLUG_BASEVIEW_UPDATE_SNAPSHOTS")
+        .map_or(false, |value| !value.is_empty() && value != "0")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{self, AssertUnwindSafe};
+
+    use super::*;
+
+    /// A directory for the golden images written by a single test.
+    fn snapshot_dir(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "nih_plug_baseview_snapshot_{}_{test}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn solid(width: u32, height: u32, color: [u8; 4]) -> Frame {
+        Frame::new(
+            width,
+            height,
+            color.repeat(width as usize * height as usize),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn missing_golden_image_is_written() {
+        let golden = snapshot_dir("missing").join("frame.png");
+        let frame = solid(4, 3, [10, 20, 30, 255]);
+
+        frame.assert_matches(&golden);
+        assert_eq!(Frame::load_png(&golden).unwrap(), frame);
+        // The golden image now exists, so this compares against it
+        frame.assert_matches(&golden);
+    }
+
+    #[test]
+    fn differences_within_the_tolerance_match() {
+        let golden = snapshot_dir("tolerance").join("frame.png");
+        solid(4, 3, [10, 20, 30, 255]).assert_matches(&golden);
+
+        solid(4, 3, [12, 18, 30, 255]).assert_matches(&golden);
+    }
+
+    #[test]
+    fn mismatched_frames_panic_and_write_the_differences() {
+        if update_requested() {
+            return;
+        }
+
+        let golden = snapshot_dir("mismatch").join("frame.png");
+        solid(4, 3, [10, 20, 30, 255]).assert_matches(&golden);
+
+        let mut rgba = solid(4, 3, [10, 20, 30, 255]).rgba;
+        rgba[0..4].copy_from_slice(&[200, 20, 30, 255]);
+        let frame = Frame::new(4, 3, rgba).unwrap();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| frame.assert_matches(&golden)));
+        assert!(result.is_err());
+
+        assert_eq!(
+            Frame::load_png(golden.with_extension("actual.png")).unwrap(),
+            frame
+        );
+        let diff = Frame::load_png(golden.with_extension("diff.png")).unwrap();
+        assert_eq!(diff.pixel(0, 0), Some([255, 0, 0, 255]));
+        assert_ne!(diff.pixel(1, 0), Some([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn frames_of_another_size_panic() {
+        if update_requested() {
+            return;
+        }
+
+        let golden = snapshot_dir("size").join("frame.png");
+        solid(4, 3, [10, 20, 30, 255]).assert_matches(&golden);
+
+        let frame = solid(3, 4, [10, 20, 30, 255]);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| frame.assert_matches(&golden)));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,30 @@
+//! A plain frame presented right after the window is created, so hosts don't show a white or
+//! garbage-filled rectangle while the user's build function compiles shaders or loads assets.
+
+use baseview::gl::GlContext;
+use std::ffi::c_void;
+
+const GL_COLOR_BUFFER_BIT: u32 = 0x0000_4000;
+
+type ClearColor = unsafe extern "system" fn(f32, f32, f32, f32);
+type Clear = unsafe extern "system" fn(u32);
+
+/// Clear the window to `color` and present the frame. This leaves no context current.
+pub(crate) fn present(context: &GlContext, [r, g, b, a]: [f32; 4]) {
+    let clear_color = context.get_proc_address("glClearColor");
+    let clear = context.get_proc_address("glClear");
+    if clear_color.is_null() || clear.is_null() {
+        return;
+    }
+
+    unsafe {
+        let clear_color = std::mem::transmute::<*const c_void, ClearColor>(clear_color);
+        let clear = std::mem::transmute::<*const c_void, Clear>(clear);
+
+        context.make_current();
+        clear_color(r, g, b, a);
+        clear(GL_COLOR_BUFFER_BIT);
+        context.swap_buffers();
+        context.make_not_current();
+    }
+}
@@ -0,0 +1,872 @@
+//! Options for the top level window when the plugin runs as a standalone application. Inside of a
+//! plugin host the editor is always embedded in a window owned by the host, so these options only
+//! apply to the window created by NIH-plug's standalone wrapper.
+
+use baseview::Window;
+use nih_plug::prelude::{nih_log, GuiContext, PluginApi};
+
+/// An icon for the standalone application's window, set using
+/// [`BaseviewState::set_window_icon()`][crate::BaseviewState::set_window_icon()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowIcon {
+    /// The icon's pixels as non-premultiplied 8-bit RGBA values, row by row starting at the top.
+    rgba: Vec<u8>,
+    width: u32,
+    height: u32,
+}
+
+impl WindowIcon {
+    /// Create an icon from non-premultiplied 8-bit RGBA pixels, row by row starting at the top.
+    /// Returns `None` if `rgba` doesn't contain exactly `width * height` pixels.
+    pub fn from_rgba(rgba: Vec<u8>, width: u32, height: u32) -> Option<Self> {
+        if width == 0 || height == 0 || rgba.len() != width as usize * height as usize * 4 {
+            return None;
+        }
+
+        Some(Self {
+            rgba,
+            width,
+            height,
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+/// How the standalone application's window is framed, set using
+/// [`BaseviewState::set_window_style()`][crate::BaseviewState::set_window_style()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowStyle {
+    /// Whether the window has a title bar and borders. Borderless windows can still be moved and
+    /// closed by the plugin, but not by the user.
+    pub decorated: bool,
+    /// Whether the user can resize the window by dragging its borders. Borderless windows cannot
+    /// be resized by the user on every platform.
+    pub resizable: bool,
+}
+
+impl Default for WindowStyle {
+    fn default() -> Self {
+        Self {
+            decorated: true,
+            resizable: true,
+        }
+    }
+}
+
+/// The standalone application's top level window, which contains the editor's window.
+pub(crate) struct StandaloneWindow {
+    #[cfg(target_os = "windows")]
+    hwnd: winapi::shared::windef::HWND,
+    /// The icon set on the window, if any. This is destroyed after it's been replaced.
+    #[cfg(target_os = "windows")]
+    icon: winapi::shared::windef::HICON,
+
+    #[cfg(target_os = "macos")]
+    ns_window: cocoa::base::id,
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    display: *mut x11::xlib::Display,
+    /// The top level client window, which may be inside of a frame window created by the window
+    /// manager.
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    window_id: x11::xlib::Window,
+}
+
+impl StandaloneWindow {
+    /// Find the top level window containing `window`. Returns `None` when running inside of a
+    /// plugin host, since the host's windows should be left alone.
+    pub fn new(window: &Window, context: &dyn GuiContext) -> Option<Self> {
+        if context.plugin_api() != PluginApi::Standalone {
+            return None;
+        }
+
+        let standalone_window = Self::find_top_level(window);
+        if standalone_window.is_none() {
+            nih_log!("Could not find the standalone application's window");
+        }
+
+        standalone_window
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl StandaloneWindow {
+    fn find_top_level(window: &Window) -> Option<Self> {
+        use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+        use winapi::shared::windef::HWND;
+        use winapi::um::winuser::{GetAncestor, GA_ROOT};
+
+        let hwnd = match window.raw_window_handle() {
+            RawWindowHandle::Win32(handle) => handle.hwnd as HWND,
+            _ => return None,
+        };
+        let root = unsafe { GetAncestor(hwnd, GA_ROOT) };
+        if root.is_null() {
+            return None;
+        }
+
+        Some(Self {
+            hwnd: root,
+            icon: std::ptr::null_mut(),
+        })
+    }
+
+    /// Set the window's title bar and task bar icon, or restore the default icon.
+    pub fn set_icon(&mut self, icon: Option<&WindowIcon>) {
+        use winapi::shared::minwindef::{LPARAM, WPARAM};
+        use winapi::um::winuser::{DestroyIcon, SendMessageW, ICON_BIG, ICON_SMALL, WM_SETICON};
+
+        unsafe {
+            let new_icon = icon.map_or(std::ptr::null_mut(), |icon| create_icon(icon));
+            SendMessageW(
+                self.hwnd,
+                WM_SETICON,
+                ICON_SMALL as WPARAM,
+                new_icon as LPARAM,
+            );
+            SendMessageW(
+                self.hwnd,
+                WM_SETICON,
+                ICON_BIG as WPARAM,
+                new_icon as LPARAM,
+            );
+
+            if !self.icon.is_null() {
+                DestroyIcon(self.icon);
+            }
+            self.icon = new_icon;
+        }
+    }
+    /// The position of the window's top left corner in physical screen coordinates. Returns
+    /// `None` while the window is minimized.
+    pub fn position(&self) -> Option<(i32, i32)> {
+        use winapi::um::winuser::{GetWindowRect, IsIconic};
+
+        unsafe {
+            if IsIconic(self.hwnd) != 0 {
+                return None;
+            }
+
+            let mut rect = std::mem::zeroed();
+            if GetWindowRect(self.hwnd, &mut rect) == 0 {
+                return None;
+            }
+
+            Some((rect.left, rect.top))
+        }
+    }
+
+    /// Move the window's top left corner to `position`. The window is left where it is if it
+    /// would not overlap any of the connected monitors at that position.
+    pub fn set_position(&mut self, (x, y): (i32, i32)) {
+        use winapi::shared::windef::RECT;
+        use winapi::um::winuser::{
+            GetWindowRect, MonitorFromRect, SetWindowPos, MONITOR_DEFAULTTONULL, SWP_NOACTIVATE,
+            SWP_NOSIZE, SWP_NOZORDER,
+        };
+
+        unsafe {
+            let mut rect: RECT = std::mem::zeroed();
+            if GetWindowRect(self.hwnd, &mut rect) == 0 {
+                return;
+            }
+
+            let moved_rect = RECT {
+                left: x,
+                top: y,
+                right: x + (rect.right - rect.left),
+                bottom: y + (rect.bottom - rect.top),
+            };
+            if MonitorFromRect(&moved_rect, MONITOR_DEFAULTTONULL).is_null() {
+                return;
+            }
+
+            SetWindowPos(
+                self.hwnd,
+                std::ptr::null_mut(),
+                x,
+                y,
+                0,
+                0,
+                SWP_NOACTIVATE | SWP_NOSIZE | SWP_NOZORDER,
+            );
+        }
+    }
+    /// Keep the window above all other windows that aren't always on top.
+    pub fn set_always_on_top(&mut self, always_on_top: bool) {
+        use winapi::um::winuser::{
+            SetWindowPos, HWND_NOTOPMOST, HWND_TOPMOST, SWP_NOACTIVATE, SWP_NOMOVE, SWP_NOSIZE,
+        };
+
+        unsafe {
+            SetWindowPos(
+                self.hwnd,
+                if always_on_top {
+                    HWND_TOPMOST
+                } else {
+                    HWND_NOTOPMOST
+                },
+                0,
+                0,
+                0,
+                0,
+                SWP_NOACTIVATE | SWP_NOMOVE | SWP_NOSIZE,
+            );
+        }
+    }
+    /// Change the window's frame. The client area keeps its size, so the outer size of the window
+    /// changes when decorations are added or removed.
+    pub fn set_style(&mut self, style: WindowStyle) {
+        use winapi::shared::basetsd::LONG_PTR;
+        use winapi::shared::windef::RECT;
+        use winapi::um::winuser::{
+            AdjustWindowRectEx, GetClientRect, GetWindowLongPtrW, SetWindowLongPtrW, SetWindowPos,
+            GWL_EXSTYLE, GWL_STYLE, SWP_FRAMECHANGED, SWP_NOACTIVATE, SWP_NOMOVE, SWP_NOZORDER,
+            WS_CAPTION, WS_MAXIMIZEBOX, WS_MINIMIZEBOX, WS_POPUP, WS_SYSMENU, WS_THICKFRAME,
+        };
+
+        unsafe {
+            let old_style = GetWindowLongPtrW(self.hwnd, GWL_STYLE) as u32;
+            let mut new_style = old_style
+                & !(WS_CAPTION | WS_SYSMENU | WS_MINIMIZEBOX | WS_MAXIMIZEBOX | WS_THICKFRAME)
+                & !WS_POPUP;
+            if style.decorated {
+                new_style |= WS_CAPTION | WS_SYSMENU | WS_MINIMIZEBOX;
+                if style.resizable {
+                    new_style |= WS_THICKFRAME | WS_MAXIMIZEBOX;
+                }
+            } else {
+                new_style |= WS_POPUP;
+            }
+            if new_style == old_style {
+                return;
+            }
+
+            let mut rect: RECT = std::mem::zeroed();
+            if GetClientRect(self.hwnd, &mut rect) == 0 {
+                return;
+            }
+            let ex_style = GetWindowLongPtrW(self.hwnd, GWL_EXSTYLE) as u32;
+            AdjustWindowRectEx(&mut rect, new_style, 0, ex_style);
+
+            SetWindowLongPtrW(self.hwnd, GWL_STYLE, new_style as LONG_PTR);
+            SetWindowPos(
+                self.hwnd,
+                std::ptr::null_mut(),
+                0,
+                0,
+                rect.right - rect.left,
+                rect.bottom - rect.top,
+                SWP_FRAMECHANGED | SWP_NOACTIVATE | SWP_NOMOVE | SWP_NOZORDER,
+            );
+        }
+    }
+}
+
+/// Create a Win32 icon from the RGBA pixels. Returns a null pointer if that fails.
+#[cfg(target_os = "windows")]
+unsafe fn create_icon(icon: &WindowIcon) -> winapi::shared::windef::HICON {
+    use winapi::shared::minwindef::TRUE;
+    use winapi::um::wingdi::{
+        CreateBitmap, CreateDIBSection, DeleteObject, BITMAPINFO, BITMAPINFOHEADER, BI_RGB,
+        DIB_RGB_COLORS,
+    };
+    use winapi::um::winuser::{CreateIconIndirect, GetDC, ReleaseDC, ICONINFO};
+
+    let mut bitmap_info: BITMAPINFO = std::mem::zeroed();
+    bitmap_info.bmiHeader.biSize = std::mem::size_of::<BITMAPINFOHEADER>() as u32;
+    bitmap_info.bmiHeader.biWidth = icon.width as i32;
+    // A negative height makes this a top-down bitmap
+    bitmap_info.bmiHeader.biHeight = -(icon.height as i32);
+    bitmap_info.bmiHeader.biPlanes = 1;
+    bitmap_info.bmiHeader.biBitCount = 32;
+    bitmap_info.bmiHeader.biCompression = BI_RGB;
+
+    let mut bits = std::ptr::null_mut();
+    let hdc = GetDC(std::ptr::null_mut());
+    let color = CreateDIBSection(
+        hdc,
+        &bitmap_info,
+        DIB_RGB_COLORS,
+        &mut bits,
+        std::ptr::null_mut(),
+        0,
+    );
+    ReleaseDC(std::ptr::null_mut(), hdc);
+    if color.is_null() || bits.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    // The bitmap is stored as BGRA
+    let pixels = std::slice::from_raw_parts_mut(bits as *mut u8, icon.rgba.len());
+    for (dst, src) in pixels.chunks_exact_mut(4).zip(icon.rgba.chunks_exact(4)) {
+        dst.copy_from_slice(&[src[2], src[1], src[0], src[3]]);
+    }
+
+    // The color bitmap's alpha channel is used instead of the mask, but the mask is still required
+    let mask = CreateBitmap(
+        icon.width as i32,
+        icon.height as i32,
+        1,
+        1,
+        std::ptr::null(),
+    );
+    let mut icon_info = ICONINFO {
+        fIcon: TRUE,
+        xHotspot: 0,
+        yHotspot: 0,
+        hbmMask: mask,
+        hbmColor: color,
+    };
+    let hicon = CreateIconIndirect(&mut icon_info);
+    DeleteObject(mask as _);
+    DeleteObject(color as _);
+
+    hicon
+}
+
+#[cfg(target_os = "windows")]
+impl Drop for StandaloneWindow {
+    fn drop(&mut self) {
+        if !self.icon.is_null() {
+            self.set_icon(None);
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl StandaloneWindow {
+    fn find_top_level(window: &Window) -> Option<Self> {
+        use cocoa::base::{id, nil};
+        use objc::{msg_send, sel, sel_impl};
+        use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+
+        let ns_view = match window.raw_window_handle() {
+            RawWindowHandle::AppKit(handle) => handle.ns_view as id,
+            _ => return None,
+        };
+        let ns_window: id = unsafe { msg_send![ns_view, window] };
+        if ns_window == nil {
+            return None;
+        }
+
+        Some(Self { ns_window })
+    }
+
+    /// macOS windows don't have their own icons, so this sets the application's Dock icon and the
+    /// image shown while the window is minimized instead. `None` restores the application bundle's
+    /// icon.
+    pub fn set_icon(&mut self, icon: Option<&WindowIcon>) {
+        use cocoa::base::{id, nil, NO, YES};
+        use cocoa::foundation::{NSInteger, NSSize, NSString};
+        use objc::{class, msg_send, sel, sel_impl};
+
+        /// `NSBitmapFormatAlphaNonpremultiplied`
+        const ALPHA_NONPREMULTIPLIED: NSInteger = 1 << 1;
+
+        unsafe {
+            let app: id = msg_send![class!(NSApplication), sharedApplication];
+            let Some(icon) = icon else {
+                let () = msg_send![app, setApplicationIconImage: nil];
+                let () = msg_send![self.ns_window, setMiniwindowImage: nil];
+                return;
+            };
+
+            let color_space = NSString::alloc(nil).init_str("NSDeviceRGBColorSpace");
+            let rep: id = msg_send![class!(NSBitmapImageRep), alloc];
+            let rep: id = msg_send![rep,
+                initWithBitmapDataPlanes: std::ptr::null_mut::<*mut u8>()
+                pixelsWide: icon.width as NSInteger
+                pixelsHigh: icon.height as NSInteger
+                bitsPerSample: 8 as NSInteger
+                samplesPerPixel: 4 as NSInteger
+                hasAlpha: YES
+                isPlanar: NO
+                colorSpaceName: color_space
+                bitmapFormat: ALPHA_NONPREMULTIPLIED
+                bytesPerRow: (icon.width * 4) as NSInteger
+                bitsPerPixel: 32 as NSInteger];
+            let () = msg_send![color_space, release];
+            if rep == nil {
+                return;
+            }
+
+            let data: *mut u8 = msg_send![rep, bitmapData];
+            std::ptr::copy_nonoverlapping(icon.rgba.as_ptr(), data, icon.rgba.len());
+
+            let image: id = msg_send![class!(NSImage), alloc];
+            let image: id = msg_send![image,
+                initWithSize: NSSize::new(icon.width as f64, icon.height as f64)];
+            let () = msg_send![image, addRepresentation: rep];
+            let () = msg_send![app, setApplicationIconImage: image];
+            let () = msg_send![self.ns_window, setMiniwindowImage: image];
+            let () = msg_send![image, release];
+            let () = msg_send![rep, release];
+        }
+    }
+    /// The position of the window's top left corner in points, in Cocoa's screen coordinates
+    /// where the y-axis points up. Returns `None` while the window is minimized.
+    pub fn position(&self) -> Option<(i32, i32)> {
+        use cocoa::base::NO;
+        use cocoa::foundation::NSRect;
+        use objc::runtime::BOOL;
+        use objc::{msg_send, sel, sel_impl};
+
+        unsafe {
+            let minimized: BOOL = msg_send![self.ns_window, isMiniaturized];
+            if minimized != NO {
+                return None;
+            }
+
+            let frame: NSRect = msg_send![self.ns_window, frame];
+            Some((
+                frame.origin.x.round() as i32,
+                (frame.origin.y + frame.size.height).round() as i32,
+            ))
+        }
+    }
+
+    /// Move the window's top left corner to `position`. The window is left where it is if it
+    /// would not overlap any of the connected screens at that position.
+    pub fn set_position(&mut self, (x, y): (i32, i32)) {
+        use cocoa::appkit::NSScreen;
+        use cocoa::base::nil;
+        use cocoa::foundation::{NSArray, NSPoint, NSRect};
+        use objc::{msg_send, sel, sel_impl};
+
+        unsafe {
+            let frame: NSRect = msg_send![self.ns_window, frame];
+            let (left, top) = (x as f64, y as f64);
+            let (right, bottom) = (left + frame.size.width, top - frame.size.height);
+
+            let screens = NSScreen::screens(nil);
+            if screens == nil {
+                return;
+            }
+            let on_screen = (0..NSArray::count(screens)).any(|i| {
+                let screen = NSScreen::frame(NSArray::objectAtIndex(screens, i));
+                left < screen.origin.x + screen.size.width
+                    && right > screen.origin.x
+                    && top > screen.origin.y
+                    && bottom < screen.origin.y + screen.size.height
+            });
+            if !on_screen {
+                return;
+            }
+
+            let () = msg_send![self.ns_window, setFrameTopLeftPoint: NSPoint::new(left, top)];
+        }
+    }
+    /// Keep the window above all normal windows, like a utility panel.
+    pub fn set_always_on_top(&mut self, always_on_top: bool) {
+        use cocoa::foundation::NSInteger;
+        use objc::{msg_send, sel, sel_impl};
+
+        /// `NSNormalWindowLevel` and `NSFloatingWindowLevel`
+        const NORMAL_WINDOW_LEVEL: NSInteger = 0;
+        const FLOATING_WINDOW_LEVEL: NSInteger = 3;
+
+        let level = if always_on_top {
+            FLOATING_WINDOW_LEVEL
+        } else {
+            NORMAL_WINDOW_LEVEL
+        };
+        unsafe {
+            let () = msg_send![self.ns_window, setLevel: level];
+        }
+    }
+    /// Change the window's style mask. The content view keeps its size, so the window's frame
+    /// changes when the title bar is added or removed.
+    pub fn set_style(&mut self, style: WindowStyle) {
+        use cocoa::base::YES;
+        use cocoa::foundation::{NSRect, NSUInteger};
+        use objc::{msg_send, sel, sel_impl};
+
+        /// `NSWindowStyleMaskTitled`, `NSWindowStyleMaskClosable`,
+        /// `NSWindowStyleMaskMiniaturizable`, and `NSWindowStyleMaskResizable`
+        const TITLED: NSUInteger = 1 << 0;
+        const CLOSABLE: NSUInteger = 1 << 1;
+        const MINIATURIZABLE: NSUInteger = 1 << 2;
+        const RESIZABLE: NSUInteger = 1 << 3;
+
+        unsafe {
+            let old_mask: NSUInteger = msg_send![self.ns_window, styleMask];
+            let mut new_mask = old_mask & !(TITLED | CLOSABLE | MINIATURIZABLE | RESIZABLE);
+            if style.decorated {
+                new_mask |= TITLED | CLOSABLE | MINIATURIZABLE;
+            }
+            if style.resizable {
+                new_mask |= RESIZABLE;
+            }
+            if new_mask == old_mask {
+                return;
+            }
+
+            let frame: NSRect = msg_send![self.ns_window, frame];
+            let content_rect: NSRect = msg_send![self.ns_window, contentRectForFrameRect: frame];
+            let () = msg_send![self.ns_window, setStyleMask: new_mask];
+            let new_frame: NSRect =
+                msg_send![self.ns_window, frameRectForContentRect: content_rect];
+            let () = msg_send![self.ns_window, setFrame: new_frame display: YES];
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+impl StandaloneWindow {
+    fn find_top_level(window: &Window) -> Option<Self> {
+        use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+        use std::os::raw::c_ulong;
+
+        let window_id = match window.raw_window_handle() {
+            RawWindowHandle::Xcb(handle) => handle.window as c_ulong,
+            RawWindowHandle::Xlib(handle) => handle.window,
+            _ => return None,
+        };
+
+        let display = unsafe { crate::x11_isolation::open_display() };
+        if display.is_null() {
+            return None;
+        }
+
+        match unsafe { top_level_window(display, window_id) } {
+            Some(window_id) => Some(Self { display, window_id }),
+            None => {
+                unsafe { crate::x11_isolation::close_display(display) };
+                None
+            }
+        }
+    }
+
+    /// Set the window's `_NET_WM_ICON` property, or remove it to restore the default icon.
+    pub fn set_icon(&mut self, icon: Option<&WindowIcon>) {
+        use std::os::raw::c_ulong;
+        use x11::xlib;
+
+        unsafe {
+            let atom = intern_atom(self.display, "_NET_WM_ICON");
+            match icon {
+                Some(icon) => {
+                    // The property is the width and height followed by the pixels as ARGB values,
+                    // with every value stored in a C long
+                    let mut data: Vec<c_ulong> = Vec::with_capacity(icon.rgba.len() / 4 + 2);
+                    data.push(icon.width as c_ulong);
+                    data.push(icon.height as c_ulong);
+                    data.extend(icon.rgba.chunks_exact(4).map(|pixel| {
+                        ((pixel[3] as c_ulong) << 24)
+                            | ((pixel[0] as c_ulong) << 16)
+                            | ((pixel[1] as c_ulong) << 8)
+                            | pixel[2] as c_ulong
+                    }));
+
+                    xlib::XChangeProperty(
+                        self.display,
+                        self.window_id,
+                        atom,
+                        xlib::XA_CARDINAL,
+                        32,
+                        xlib::PropModeReplace,
+                        data.as_ptr() as *const u8,
+                        data.len() as i32,
+                    );
+                }
+                None => {
+                    xlib::XDeleteProperty(self.display, self.window_id, atom);
+                }
+            }
+            xlib::XFlush(self.display);
+        }
+    }
+    /// The position of the window manager's frame around the window in physical pixels, relative
+    /// to the root window. Returns `None` while the window is not shown.
+    pub fn position(&self) -> Option<(i32, i32)> {
+        use x11::xlib;
+
+        unsafe {
+            let frame = frame_window(self.display, self.window_id)?;
+            let mut attributes: xlib::XWindowAttributes = std::mem::zeroed();
+            if xlib::XGetWindowAttributes(self.display, frame, &mut attributes) == 0
+                || attributes.map_state != xlib::IsViewable
+            {
+                return None;
+            }
+
+            Some((attributes.x, attributes.y))
+        }
+    }
+
+    /// Move the window manager's frame to `position`. The window is left where it is if it would
+    /// not overlap any of the connected monitors at that position.
+    pub fn set_position(&mut self, (x, y): (i32, i32)) {
+        use std::os::raw::c_int;
+        use x11::{xlib, xrandr};
+
+        unsafe {
+            let Some(frame) = frame_window(self.display, self.window_id) else {
+                return;
+            };
+            let mut attributes: xlib::XWindowAttributes = std::mem::zeroed();
+            if xlib::XGetWindowAttributes(self.display, frame, &mut attributes) == 0 {
+                return;
+            }
+            let (right, bottom) = (x + attributes.width, y + attributes.height);
+
+            let mut num_monitors: c_int = 0;
+            let monitor_infos = xrandr::XRRGetMonitors(
+                self.display,
+                attributes.root,
+                xlib::True,
+                &mut num_monitors,
+            );
+            if monitor_infos.is_null() {
+                return;
+            }
+            let on_screen = std::slice::from_raw_parts(monitor_infos, num_monitors.max(0) as usize)
+                .iter()
+                .any(|info| {
+                    x < info.x + info.width
+                        && right > info.x
+                        && y < info.y + info.height
+                        && bottom > info.y
+                });
+            xrandr::XRRFreeMonitors(monitor_infos);
+            if !on_screen {
+                return;
+            }
+
+            // With the default north west gravity, the window manager places its frame at this
+            // position
+            xlib::XMoveWindow(self.display, self.window_id, x, y);
+            xlib::XFlush(self.display);
+        }
+    }
+    /// Ask the window manager to keep the window above other windows using the
+    /// `_NET_WM_STATE_ABOVE` state.
+    pub fn set_always_on_top(&mut self, always_on_top: bool) {
+        unsafe {
+            set_net_wm_state(
+                self.display,
+                self.window_id,
+                "_NET_WM_STATE_ABOVE",
+                always_on_top,
+            )
+        };
+    }
+    /// Ask the window manager to add or remove the window's decorations through the Motif hints
+    /// most window managers still understand, and fix the window's size through its size hints
+    /// when it shouldn't be resizable.
+    pub fn set_style(&mut self, style: WindowStyle) {
+        use std::os::raw::c_ulong;
+        use x11::xlib;
+
+        /// `MWM_HINTS_DECORATIONS`
+        const HINTS_DECORATIONS: c_ulong = 1 << 1;
+
+        unsafe {
+            // The flags, functions, decorations, input mode, and status fields
+            let motif_hints: [c_ulong; 5] =
+                [HINTS_DECORATIONS, 0, style.decorated as c_ulong, 0, 0];
+            let motif_hints_atom = intern_atom(self.display, "_MOTIF_WM_HINTS");
+            xlib::XChangeProperty(
+                self.display,
+                self.window_id,
+                motif_hints_atom,
+                motif_hints_atom,
+                32,
+                xlib::PropModeReplace,
+                motif_hints.as_ptr() as *const u8,
+                motif_hints.len() as i32,
+            );
+
+            let mut attributes: xlib::XWindowAttributes = std::mem::zeroed();
+            let size_hints = xlib::XAllocSizeHints();
+            if !size_hints.is_null()
+                && xlib::XGetWindowAttributes(self.display, self.window_id, &mut attributes) != 0
+            {
+                let mut supplied = 0;
+                xlib::XGetWMNormalHints(self.display, self.window_id, size_hints, &mut supplied);
+                if style.resizable {
+                    (*size_hints).flags &= !(xlib::PMinSize | xlib::PMaxSize);
+                } else {
+                    (*size_hints).flags |= xlib::PMinSize | xlib::PMaxSize;
+                    (*size_hints).min_width = attributes.width;
+                    (*size_hints).min_height = attributes.height;
+                    (*size_hints).max_width = attributes.width;
+                    (*size_hints).max_height = attributes.height;
+                }
+                xlib::XSetWMNormalHints(self.display, self.window_id, size_hints);
+            }
+            if !size_hints.is_null() {
+                xlib::XFree(size_hints as *mut _);
+            }
+
+            xlib::XFlush(self.display);
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+impl Drop for StandaloneWindow {
+    fn drop(&mut self) {
+        unsafe { crate::x11_isolation::close_display(self.display) };
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+unsafe fn intern_atom(display: *mut x11::xlib::Display, name: &str) -> x11::xlib::Atom {
+    let name = std::ffi::CString::new(name).unwrap();
+    x11::xlib::XInternAtom(display, name.as_ptr(), x11::xlib::False)
+}
+
+/// Add or remove one of a mapped window's `_NET_WM_STATE` states by sending a request to the
+/// window manager, as described in the EWMH specification.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+unsafe fn set_net_wm_state(
+    display: *mut x11::xlib::Display,
+    window: x11::xlib::Window,
+    state: &str,
+    enabled: bool,
+) {
+    use std::os::raw::c_long;
+    use x11::xlib;
+
+    /// `_NET_WM_STATE_REMOVE` and `_NET_WM_STATE_ADD`
+    const REMOVE: c_long = 0;
+    const ADD: c_long = 1;
+    /// Marks the request as coming from a normal application
+    const SOURCE_APPLICATION: c_long = 1;
+
+    let mut event: xlib::XClientMessageEvent = std::mem::zeroed();
+    event.type_ = xlib::ClientMessage;
+    event.window = window;
+    event.message_type = intern_atom(display, "_NET_WM_STATE");
+    event.format = 32;
+    event.data.set_long(0, if enabled { ADD } else { REMOVE });
+    event
+        .data
+        .set_long(1, intern_atom(display, state) as c_long);
+    event.data.set_long(2, 0);
+    event.data.set_long(3, SOURCE_APPLICATION);
+
+    let mut event = xlib::XEvent::from(event);
+    xlib::XSendEvent(
+        display,
+        xlib::XDefaultRootWindow(display),
+        xlib::False,
+        xlib::SubstructureRedirectMask | xlib::SubstructureNotifyMask,
+        &mut event,
+    );
+    xlib::XFlush(display);
+}
+
+/// Find the top level client window containing `window`. Window managers set `WM_STATE` on the
+/// client windows they manage, which may be inside of a frame window created by the window
+/// manager. If no window has that property, the ancestor that's a direct child of the root window
+/// is used.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+unsafe fn top_level_window(
+    display: *mut x11::xlib::Display,
+    window: x11::xlib::Window,
+) -> Option<x11::xlib::Window> {
+    use std::os::raw::{c_int, c_uchar, c_uint, c_ulong};
+    use x11::xlib;
+
+    let wm_state = intern_atom(display, "WM_STATE");
+    let mut current = window;
+    loop {
+        let mut actual_type = 0;
+        let mut actual_format: c_int = 0;
+        let mut num_items: c_ulong = 0;
+        let mut bytes_after: c_ulong = 0;
+        let mut data: *mut c_uchar = std::ptr::null_mut();
+        let status = xlib::XGetWindowProperty(
+            display,
+            current,
+            wm_state,
+            0,
+            0,
+            xlib::False,
+            xlib::AnyPropertyType as c_ulong,
+            &mut actual_type,
+            &mut actual_format,
+            &mut num_items,
+            &mut bytes_after,
+            &mut data,
+        );
+        if !data.is_null() {
+            xlib::XFree(data as *mut _);
+        }
+        if status == xlib::Success as c_int && actual_type != 0 {
+            return Some(current);
+        }
+
+        let mut root = 0;
+        let mut parent = 0;
+        let mut children: *mut xlib::Window = std::ptr::null_mut();
+        let mut num_children: c_uint = 0;
+        if xlib::XQueryTree(
+            display,
+            current,
+            &mut root,
+            &mut parent,
+            &mut children,
+            &mut num_children,
+        ) == 0
+        {
+            return None;
+        }
+        if !children.is_null() {
+            xlib::XFree(children as *mut _);
+        }
+
+        if parent == root || parent == 0 {
+            return Some(current);
+        }
+        current = parent;
+    }
+}
+
+/// Find the ancestor of `window` that's a direct child of the root window. With a reparenting
+/// window manager this is the frame containing the window's decorations.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+unsafe fn frame_window(
+    display: *mut x11::xlib::Display,
+    window: x11::xlib::Window,
+) -> Option<x11::xlib::Window> {
+    use std::os::raw::c_uint;
+    use x11::xlib;
+
+    let mut current = window;
+    loop {
+        let mut root = 0;
+        let mut parent = 0;
+        let mut children: *mut xlib::Window = std::ptr::null_mut();
+        let mut num_children: c_uint = 0;
+        if xlib::XQueryTree(
+            display,
+            current,
+            &mut root,
+            &mut parent,
+            &mut children,
+            &mut num_children,
+        ) == 0
+        {
+            return None;
+        }
+        if !children.is_null() {
+            xlib::XFree(children as *mut _);
+        }
+
+        if parent == root || parent == 0 {
+            return Some(current);
+        }
+        current = parent;
+    }
+}
@@ -0,0 +1,99 @@
+//! Running closures and futures on the editor's GUI thread from other threads.
+
+use crossbeam::queue::SegQueue;
+use parking_lot::Mutex;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Wake, Waker};
+
+use crate::profile::profile_scope;
+
+/// A closure passed to [`GuiTaskProxy::run()`].
+type GuiTask = Box<dyn FnOnce() + Send>;
+
+/// The closures waiting to run on the GUI thread.
+#[derive(Debug, Default)]
+pub(crate) struct GuiTaskQueue {
+    tasks: SegQueue<GuiTask>,
+}
+
+impl GuiTaskQueue {
+    /// Run the closures that were queued before this call. Closures queued by these closures run
+    /// before the next frame instead.
+    pub fn run_queued(&self) {
+        profile_scope!("gui_tasks");
+        for _ in 0..self.tasks.len() {
+            match self.tasks.pop() {
+                Some(task) => task(),
+                None => break,
+            }
+        }
+    }
+}
+
+/// Runs closures on the editor's GUI thread. Get one using
+/// [`BaseviewState::gui_task_proxy()`][crate::BaseviewState::gui_task_proxy()]. This can be cloned
+/// and moved to background threads, like a thread that loads files, so they can hand their results
+/// to the GUI.
+#[derive(Debug, Clone)]
+pub struct GuiTaskProxy {
+    queue: Arc<GuiTaskQueue>,
+}
+
+impl GuiTaskProxy {
+    pub(crate) fn new(queue: Arc<GuiTaskQueue>) -> Self {
+        Self { queue }
+    }
+
+    /// Run `task` on the GUI thread before the next frame. If the editor is closed, the task runs
+    /// once it is opened again.
+    pub fn run(&self, task: impl FnOnce() + Send + 'static) {
+        self.queue.tasks.push(Box::new(task));
+    }
+
+    /// Drive `future` to completion on the GUI thread. The future is polled before the next frame,
+    /// and again before the frame after it is woken. This works with any async runtime's futures,
+    /// as long as that runtime's reactor runs on another thread. Awaiting a background thread's
+    /// result this way lets the rest of the future safely use the GUI's state.
+    pub fn spawn(&self, future: impl Future<Output = ()> + Send + 'static) {
+        let task = Arc::new(FutureTask {
+            future: Mutex::new(Some(Box::pin(future))),
+            queue: self.queue.clone(),
+        });
+        task.schedule();
+    }
+}
+
+/// A future spawned using [`GuiTaskProxy::spawn()`].
+struct FutureTask {
+    /// This is `None` once the future has completed.
+    future: Mutex<Option<Pin<Box<dyn Future<Output = ()> + Send>>>>,
+    queue: Arc<GuiTaskQueue>,
+}
+
+impl FutureTask {
+    /// Poll the future on the GUI thread before the next frame.
+    fn schedule(self: Arc<Self>) {
+        let queue = self.queue.clone();
+        queue.tasks.push(Box::new(move || self.poll()));
+    }
+
+    fn poll(self: Arc<Self>) {
+        let waker = Waker::from(self.clone());
+        let mut context = Context::from_waker(&waker);
+
+        let mut future = self.future.lock();
+        if let Some(pinned_future) = future.as_mut() {
+            if pinned_future.as_mut().poll(&mut context).is_ready() {
+                *future = None;
+            }
+        }
+    }
+}
+
+impl Wake for FutureTask {
+    fn wake(self: Arc<Self>) {
+        self.schedule();
+    }
+}
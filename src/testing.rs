@@ -0,0 +1,353 @@
+//! Utilities for testing editors and widgets, enabled with the `testing` feature.
+//!
+//! The events built here can be injected into an open editor using
+//! [`BaseviewState::inject_event()`][crate::BaseviewState::inject_event()]. They take the same path
+//! through the bridge as baseview's own events, so modifier tracking, click counting, pointer
+//! locking, scroll normalization, and the keyboard policy all apply to them. Injecting a resize
+//! event only changes what the handler and the bridge believe the window's size to be, it doesn't
+//! resize the actual window.
+//!
+//! Injected events are handled right before the editor's next frame, so they need an open window.
+//! [`EventHarness`] runs without one. It passes the same events to an [`EventHandler`], the part of
+//! a window handler that reacts to input without touching the window, so widget interaction tests
+//! can run without a window server.
+//!
+//! [`MockHost`] takes the host's place, so the way the editor reacts to being opened, closed,
+//! rescaled, and resized in the orders real hosts use can be tested in `cargo test`. The
+//! [`MockGuiContext`] it passes to the editor records the parameter gestures and resize requests
+//! the editor sends to the host, and it can also be used on its own with a
+//! [`ParamSetter`][nih_plug::prelude::ParamSetter] to unit test widgets.
+//!
+//! [`MockHost::assert_frame_matches()`] compares the editor's rendering against a golden image, so
+//! changes to custom widgets' visuals show up as test failures.
+
+use baseview::{
+    Event, EventStatus, MouseButton, MouseEvent, Point, ScrollDelta, Size, WindowEvent, WindowInfo,
+};
+use keyboard_types::{Code, Key, KeyState, KeyboardEvent, Modifiers};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use crate::click::ClickTracker;
+use crate::window;
+use crate::{scroll, BaseviewState, KeyboardPolicy};
+
+pub use crate::mock_host::{scripts, ContextCall, HostCall, MockGuiContext, MockHost};
+pub use crate::snapshot::{
+    capture_current_frame, capture_requested, render_offscreen, submit_frame, Frame, FrameDiff,
+};
+
+/// The cursor moved to `position`, in logical pixels relative to the window's top left corner.
+pub fn cursor_moved(position: Point, modifiers: Modifiers) -> Event {
+    Event::Mouse(MouseEvent::CursorMoved {
+        position,
+        modifiers,
+    })
+}
+
+/// A mouse button was pressed at the cursor's current position.
+pub fn button_pressed(button: MouseButton, modifiers: Modifiers) -> Event {
+    Event::Mouse(MouseEvent::ButtonPressed { button, modifiers })
+}
+
+/// A mouse button was released at the cursor's current position.
+pub fn button_released(button: MouseButton, modifiers: Modifiers) -> Event {
+    Event::Mouse(MouseEvent::ButtonReleased { button, modifiers })
+}
+
+/// Move the cursor to `position` and click `button` there.
+pub fn click(position: Point, button: MouseButton, modifiers: Modifiers) -> [Event; 3] {
+    [
+        cursor_moved(position, modifiers),
+        button_pressed(button, modifiers),
+        button_released(button, modifiers),
+    ]
+}
+
+/// Press `button` at `from`, move the cursor to `to` in `steps` equal steps, and release the
+/// button there.
+pub fn drag(
+    from: Point,
+    to: Point,
+    steps: usize,
+    button: MouseButton,
+    modifiers: Modifiers,
+) -> Vec<Event> {
+    let steps = steps.max(1);
+    let mut events = vec![
+        cursor_moved(from, modifiers),
+        button_pressed(button, modifiers),
+    ];
+    for step in 1..=steps {
+        let t = step as f64 / steps as f64;
+        let position = Point::new(from.x + (to.x - from.x) * t, from.y + (to.y - from.y) * t);
+        events.push(cursor_moved(position, modifiers));
+    }
+    events.push(button_released(button, modifiers));
+
+    events
+}
+
+/// The mouse wheel was scrolled by a number of lines. Positive values scroll up and to the right.
+pub fn wheel_scrolled(lines_x: f32, lines_y: f32, modifiers: Modifiers) -> Event {
+    Event::Mouse(MouseEvent::WheelScrolled {
+        delta: ScrollDelta::Lines {
+            x: lines_x,
+            y: lines_y,
+        },
+        modifiers,
+    })
+}
+
+/// A key was pressed. `key` is the key's meaning in the current layout, and `code` its physical
+/// location.
+pub fn key_down(key: Key, code: Code, modifiers: Modifiers) -> Event {
+    Event::Keyboard(KeyboardEvent {
+        state: KeyState::Down,
+        key,
+        code,
+        modifiers,
+        ..KeyboardEvent::default()
+    })
+}
+
+/// A key was released.
+pub fn key_up(key: Key, code: Code, modifiers: Modifiers) -> Event {
+    Event::Keyboard(KeyboardEvent {
+        state: KeyState::Up,
+        key,
+        code,
+        modifiers,
+        ..KeyboardEvent::default()
+    })
+}
+
+/// Press and release a key.
+pub fn key_press(key: Key, code: Code, modifiers: Modifiers) -> [Event; 2] {
+    [
+        key_down(key.clone(), code, modifiers),
+        key_up(key, code, modifiers),
+    ]
+}
+
+/// Type `text` one character at a time. The characters don't have a physical location, which
+/// is fine for text input but means a [`KeyboardPolicy::PassthroughList`] never matches them.
+///
+/// [`KeyboardPolicy::PassthroughList`]: crate::KeyboardPolicy::PassthroughList
+pub fn type_text(text: &str) -> Vec<Event> {
+    text.chars()
+        .flat_map(|c| {
+            key_press(
+                Key::Character(c.to_string()),
+                Code::Unidentified,
+                Modifiers::empty(),
+            )
+        })
+        .collect()
+}
+
+/// The window was resized to `logical_size` at a scaling factor of `scale`.
+pub fn resized(logical_size: Size, scale: f64) -> Event {
+    Event::Window(WindowEvent::Resized(WindowInfo::from_logical_size(
+        logical_size,
+        scale,
+    )))
+}
+
+/// The window gained keyboard focus.
+pub fn focused() -> Event {
+    Event::Window(WindowEvent::Focused)
+}
+
+/// The window lost keyboard focus.
+pub fn unfocused() -> Event {
+    Event::Window(WindowEvent::Unfocused)
+}
+
+/// The part of a window handler that reacts to input without needing the
+/// [`Window`][baseview::Window], so it can be driven by an [`EventHarness`]. A window handler can
+/// implement this for its input handling and call it from [`WindowHandler::on_event()`].
+///
+/// [`WindowHandler::on_event()`]: baseview::WindowHandler::on_event()
+pub trait EventHandler {
+    fn on_event(&mut self, event: Event) -> EventStatus;
+}
+
+impl<F: FnMut(Event) -> EventStatus> EventHandler for F {
+    fn on_event(&mut self, event: Event) -> EventStatus {
+        self(event)
+    }
+}
+
+/// Sends synthetic events to an [`EventHandler`] as if they came from baseview, without opening a
+/// window. The events go through the parts of the bridge that don't need a window: scroll
+/// normalization, modifier, focus, and size tracking, click counting, and the keyboard policy.
+/// Key events the policy would pass on to the host are not forwarded anywhere. The
+/// [`BaseviewState`] is updated like it would be for an open editor, so its
+/// [`modifiers()`][BaseviewState::modifiers()], [`click_count()`][BaseviewState::click_count()],
+/// and so on can be asserted on.
+pub struct EventHarness<H> {
+    baseview_state: Arc<BaseviewState>,
+    handler: H,
+    click_tracker: ClickTracker,
+    cursor_position: Point,
+    window_scale: f64,
+}
+
+impl<H: EventHandler> EventHarness<H> {
+    /// Drive `handler` using the editor's state. The window's scaling factor starts out at 1.0,
+    /// send a [`resized()`] event to change it.
+    pub fn new(baseview_state: Arc<BaseviewState>, handler: H) -> Self {
+        Self {
+            baseview_state,
+            handler,
+            click_tracker: ClickTracker::default(),
+            cursor_position: Point::new(0.0, 0.0),
+            window_scale: 1.0,
+        }
+    }
+
+    pub fn handler(&self) -> &H {
+        &self.handler
+    }
+
+    pub fn handler_mut(&mut self) -> &mut H {
+        &mut self.handler
+    }
+
+    /// Handle a single event. Returns whether the event was captured, either by the handler or
+    /// because the keyboard policy kept it from the host.
+    pub fn send(&mut self, mut event: Event) -> EventStatus {
+        let baseview_state = &self.baseview_state;
+        if baseview_state.normalize_scrolling() {
+            if let Event::Mouse(MouseEvent::WheelScrolled { delta, modifiers }) = &mut event {
+                *delta = scroll::normalize(*delta, *modifiers, self.window_scale);
+            }
+        }
+        window::track_input_state(baseview_state, &event);
+
+        match &event {
+            Event::Window(WindowEvent::Resized(window_info)) => {
+                self.window_scale = window_info.scale();
+                window::track_window_size(baseview_state, window_info);
+            }
+            Event::Mouse(MouseEvent::CursorMoved { position, .. }) => {
+                self.cursor_position = *position;
+            }
+            Event::Mouse(MouseEvent::ButtonPressed { button, .. }) => {
+                let click_count = self.click_tracker.press(
+                    *button,
+                    self.cursor_position,
+                    baseview_state.double_click_threshold(),
+                );
+                baseview_state.click_count.store(click_count);
+                baseview_state.button_presses.fetch_add(1, Ordering::AcqRel);
+            }
+            _ => (),
+        }
+
+        let key_event = match event {
+            Event::Keyboard(key_event) => key_event,
+            event => return self.handler.on_event(event),
+        };
+        match baseview_state.keyboard_policy() {
+            KeyboardPolicy::CaptureAll => {
+                self.handler.on_event(Event::Keyboard(key_event));
+                EventStatus::Captured
+            }
+            KeyboardPolicy::PassthroughUnhandled => {
+                self.handler.on_event(Event::Keyboard(key_event))
+            }
+            KeyboardPolicy::PassthroughList(codes) => {
+                if codes.contains(&key_event.code) {
+                    EventStatus::Ignored
+                } else {
+                    self.handler.on_event(Event::Keyboard(key_event));
+                    EventStatus::Captured
+                }
+            }
+        }
+    }
+
+    /// Handle several events in order, for instance the ones returned by [`click()`] or
+    /// [`drag()`].
+    pub fn send_all(&mut self, events: impl IntoIterator<Item = Event>) {
+        for event in events {
+            self.send(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records the events it receives, capturing everything except key events.
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<Event>,
+    }
+
+    impl EventHandler for Recorder {
+        fn on_event(&mut self, event: Event) -> EventStatus {
+            let status = match event {
+                Event::Keyboard(_) => EventStatus::Ignored,
+                _ => EventStatus::Captured,
+            };
+            self.events.push(event);
+
+            status
+        }
+    }
+
+    #[test]
+    fn clicks_are_counted_and_passed_on() {
+        let baseview_state = BaseviewState::from_size(400, 300);
+        let mut harness = EventHarness::new(baseview_state.clone(), Recorder::default());
+
+        let position = Point::new(10.0, 20.0);
+        harness.send_all(click(position, MouseButton::Left, Modifiers::SHIFT));
+        assert_eq!(baseview_state.click_count(), 1);
+        harness.send_all(click(position, MouseButton::Left, Modifiers::SHIFT));
+        assert_eq!(baseview_state.click_count(), 2);
+
+        assert_eq!(harness.handler().events.len(), 6);
+        assert_eq!(baseview_state.modifiers(), Modifiers::SHIFT);
+    }
+
+    #[test]
+    fn focus_and_size_are_tracked() {
+        let baseview_state = BaseviewState::from_size(400, 300);
+        let mut harness = EventHarness::new(baseview_state.clone(), Recorder::default());
+
+        harness.send(focused());
+        assert!(baseview_state.is_focused());
+        harness.send(resized(Size::new(640.0, 480.0), 2.0));
+        assert_eq!(baseview_state.size(), (640, 480));
+
+        harness.send(key_down(Key::Shift, Code::ShiftLeft, Modifiers::empty()));
+        assert_eq!(baseview_state.modifiers(), Modifiers::SHIFT);
+        // Keys held while the window loses focus are never released
+        harness.send(unfocused());
+        assert!(!baseview_state.is_focused());
+        assert_eq!(baseview_state.modifiers(), Modifiers::empty());
+    }
+
+    #[test]
+    fn keyboard_policy_applies() {
+        let baseview_state = BaseviewState::from_size(400, 300);
+        let mut harness = EventHarness::new(baseview_state.clone(), Recorder::default());
+        let space = key_down(Key::Character(" ".into()), Code::Space, Modifiers::empty());
+
+        assert!(matches!(harness.send(space.clone()), EventStatus::Ignored));
+        assert_eq!(harness.handler().events.len(), 1);
+
+        baseview_state.set_keyboard_policy(KeyboardPolicy::CaptureAll);
+        assert!(matches!(harness.send(space.clone()), EventStatus::Captured));
+        assert_eq!(harness.handler().events.len(), 2);
+
+        baseview_state.set_keyboard_policy(KeyboardPolicy::PassthroughList(vec![Code::Space]));
+        assert!(matches!(harness.send(space), EventStatus::Ignored));
+        assert_eq!(harness.handler().events.len(), 2);
+    }
+}
@@ -0,0 +1,267 @@
+//! Uploading RGBA images to OpenGL textures on the editor's context, so plugins don't each need
+//! their own unsafe GL boilerplate for this.
+
+use baseview::gl::GlContext;
+use crossbeam::queue::SegQueue;
+use nih_plug::prelude::nih_debug_assert;
+use std::ffi::c_void;
+use std::fmt;
+
+use crate::profile::profile_scope;
+
+const GL_TEXTURE_2D: u32 = 0x0DE1;
+const GL_TEXTURE_BINDING_2D: u32 = 0x8069;
+const GL_TEXTURE_MIN_FILTER: u32 = 0x2801;
+const GL_TEXTURE_MAG_FILTER: u32 = 0x2800;
+const GL_TEXTURE_WRAP_S: u32 = 0x2802;
+const GL_TEXTURE_WRAP_T: u32 = 0x2803;
+const GL_UNPACK_ALIGNMENT: u32 = 0x0CF5;
+const GL_CLAMP_TO_EDGE: i32 = 0x812F;
+const GL_NEAREST: i32 = 0x2600;
+const GL_LINEAR: i32 = 0x2601;
+const GL_LINEAR_MIPMAP_LINEAR: i32 = 0x2703;
+const GL_RGBA: u32 = 0x1908;
+const GL_RGBA8: i32 = 0x8058;
+const GL_SRGB8_ALPHA8: i32 = 0x8C43;
+const GL_UNSIGNED_BYTE: u32 = 0x1401;
+
+/// How a texture should be sampled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextureOptions {
+    /// Generate mipmaps and use trilinear filtering when the texture is scaled down.
+    pub mipmaps: bool,
+    /// Store the texture in the sRGB color space, so sampling it returns linear values. Use this
+    /// for regular images when rendering to an sRGB framebuffer.
+    pub srgb: bool,
+    /// Use nearest neighbor filtering instead of linear filtering, for pixel art.
+    pub nearest: bool,
+}
+
+impl Default for TextureOptions {
+    fn default() -> Self {
+        Self {
+            mipmaps: true,
+            srgb: true,
+            nearest: false,
+        }
+    }
+}
+
+/// An OpenGL texture created using [`GlTexture::upload()`] or
+/// [`BaseviewState::upload_texture()`][crate::BaseviewState::upload_texture()]. This is only the
+/// texture's name, the texture is not deleted when this is dropped. Use [`GlTexture::delete()`]
+/// for that.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GlTexture {
+    id: u32,
+    width: u32,
+    height: u32,
+}
+
+type GenTextures = unsafe extern "system" fn(i32, *mut u32);
+type DeleteTextures = unsafe extern "system" fn(i32, *const u32);
+type BindTexture = unsafe extern "system" fn(u32, u32);
+type GetIntegerv = unsafe extern "system" fn(u32, *mut i32);
+type PixelStorei = unsafe extern "system" fn(u32, i32);
+type TexImage2D = unsafe extern "system" fn(u32, i32, i32, i32, i32, i32, u32, u32, *const c_void);
+type TexParameteri = unsafe extern "system" fn(u32, u32, i32);
+type GenerateMipmap = unsafe extern "system" fn(u32);
+
+/// The GL functions needed to upload a texture, loaded from the context.
+struct GlFunctions {
+    gen_textures: GenTextures,
+    delete_textures: DeleteTextures,
+    bind_texture: BindTexture,
+    get_integerv: GetIntegerv,
+    pixel_storei: PixelStorei,
+    tex_image_2d: TexImage2D,
+    tex_parameteri: TexParameteri,
+    generate_mipmap: GenerateMipmap,
+}
+
+impl GlFunctions {
+    fn load(context: &GlContext) -> Option<Self> {
+        macro_rules! load {
+            ($name:literal as $ty:ty) => {{
+                let ptr = context.get_proc_address($name);
+                if ptr.is_null() {
+                    return None;
+                }
+
+                unsafe { std::mem::transmute::<*const c_void, $ty>(ptr) }
+            }};
+        }
+
+        Some(Self {
+            gen_textures: load!("glGenTextures" as GenTextures),
+            delete_textures: load!("glDeleteTextures" as DeleteTextures),
+            bind_texture: load!("glBindTexture" as BindTexture),
+            get_integerv: load!("glGetIntegerv" as GetIntegerv),
+            pixel_storei: load!("glPixelStorei" as PixelStorei),
+            tex_image_2d: load!("glTexImage2D" as TexImage2D),
+            tex_parameteri: load!("glTexParameteri" as TexParameteri),
+            generate_mipmap: load!("glGenerateMipmap" as GenerateMipmap),
+        })
+    }
+}
+
+impl GlTexture {
+    /// Upload tightly packed 8-bit RGBA pixels to a new texture. The context needs to be current,
+    /// so call `context.make_current()` first when using this from the build function. The
+    /// previously bound texture is bound again afterwards. Returns `None` if `rgba` doesn't match
+    /// the size or if the context is missing the required functions.
+    pub fn upload(
+        context: &GlContext,
+        rgba: &[u8],
+        width: u32,
+        height: u32,
+        options: TextureOptions,
+    ) -> Option<Self> {
+        if rgba.len() != width as usize * height as usize * 4 {
+            nih_debug_assert!(false, "The RGBA data does not match the texture's size");
+            return None;
+        }
+        let gl = GlFunctions::load(context)?;
+
+        let min_filter = match (options.mipmaps, options.nearest) {
+            (true, _) => GL_LINEAR_MIPMAP_LINEAR,
+            (false, true) => GL_NEAREST,
+            (false, false) => GL_LINEAR,
+        };
+        let mag_filter = if options.nearest {
+            GL_NEAREST
+        } else {
+            GL_LINEAR
+        };
+        let internal_format = if options.srgb {
+            GL_SRGB8_ALPHA8
+        } else {
+            GL_RGBA8
+        };
+
+        unsafe {
+            let mut previous_binding = 0;
+            (gl.get_integerv)(GL_TEXTURE_BINDING_2D, &mut previous_binding);
+
+            let mut id = 0;
+            (gl.gen_textures)(1, &mut id);
+            (gl.bind_texture)(GL_TEXTURE_2D, id);
+
+            // Rows of RGBA pixels are always 4-byte aligned, but the context may use another value
+            (gl.pixel_storei)(GL_UNPACK_ALIGNMENT, 4);
+            (gl.tex_image_2d)(
+                GL_TEXTURE_2D,
+                0,
+                internal_format,
+                width as i32,
+                height as i32,
+                0,
+                GL_RGBA,
+                GL_UNSIGNED_BYTE,
+                rgba.as_ptr() as *const c_void,
+            );
+            (gl.tex_parameteri)(GL_TEXTURE_2D, GL_TEXTURE_MIN_FILTER, min_filter);
+            (gl.tex_parameteri)(GL_TEXTURE_2D, GL_TEXTURE_MAG_FILTER, mag_filter);
+            (gl.tex_parameteri)(GL_TEXTURE_2D, GL_TEXTURE_WRAP_S, GL_CLAMP_TO_EDGE);
+            (gl.tex_parameteri)(GL_TEXTURE_2D, GL_TEXTURE_WRAP_T, GL_CLAMP_TO_EDGE);
+            if options.mipmaps {
+                (gl.generate_mipmap)(GL_TEXTURE_2D);
+            }
+
+            (gl.bind_texture)(GL_TEXTURE_2D, previous_binding as u32);
+
+            Some(Self { id, width, height })
+        }
+    }
+
+    /// Delete the texture. The context needs to be current.
+    pub fn delete(self, context: &GlContext) {
+        if let Some(gl) = GlFunctions::load(context) {
+            unsafe { (gl.delete_textures)(1, &self.id) };
+        }
+    }
+
+    /// The texture's OpenGL name, for binding it with your own GL bindings.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+/// A texture upload queued using
+/// [`BaseviewState::upload_texture()`][crate::BaseviewState::upload_texture()].
+struct TextureUpload {
+    rgba: Vec<u8>,
+    width: u32,
+    height: u32,
+    options: TextureOptions,
+    on_uploaded: Box<dyn FnOnce(Option<GlTexture>) + Send>,
+}
+
+/// Texture uploads waiting for the window's context.
+#[derive(Default)]
+pub(crate) struct TextureUploads {
+    uploads: SegQueue<TextureUpload>,
+}
+
+impl fmt::Debug for TextureUploads {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TextureUploads")
+            .field("uploads", &self.uploads.len())
+            .finish()
+    }
+}
+
+impl TextureUploads {
+    pub fn push(
+        &self,
+        rgba: Vec<u8>,
+        width: u32,
+        height: u32,
+        options: TextureOptions,
+        on_uploaded: impl FnOnce(Option<GlTexture>) + Send + 'static,
+    ) {
+        self.uploads.push(TextureUpload {
+            rgba,
+            width,
+            height,
+            options,
+            on_uploaded: Box::new(on_uploaded),
+        });
+    }
+
+    /// Perform the queued uploads on the window's context. Called on the GUI thread before every
+    /// frame.
+    pub fn upload_queued(&self, context: &GlContext) {
+        if self.uploads.is_empty() {
+            return;
+        }
+        profile_scope!("texture_uploads");
+
+        unsafe { context.make_current() };
+        let mut uploaded = Vec::new();
+        while let Some(upload) = self.uploads.pop() {
+            let texture = GlTexture::upload(
+                context,
+                &upload.rgba,
+                upload.width,
+                upload.height,
+                upload.options,
+            );
+            uploaded.push((upload.on_uploaded, texture));
+        }
+        unsafe { context.make_not_current() };
+
+        // The callbacks may want to use the context themselves
+        for (on_uploaded, texture) in uploaded {
+            on_uploaded(texture);
+        }
+    }
+}
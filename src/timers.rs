@@ -0,0 +1,121 @@
+//! One-shot and repeating timers that run on the GUI thread, for things like blinking text cursors
+//! and delayed tooltips.
+
+use parking_lot::Mutex;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::profile::profile_scope;
+
+/// Identifies a timer started using
+/// [`BaseviewState::set_timer()`][crate::BaseviewState::set_timer()] or
+/// [`BaseviewState::set_repeating_timer()`][crate::BaseviewState::set_repeating_timer()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerId(u64);
+
+enum TimerCallback {
+    Once(Box<dyn FnOnce() + Send>),
+    Repeating(Box<dyn FnMut() + Send>, Duration),
+}
+
+struct Timer {
+    id: TimerId,
+    deadline: Instant,
+    callback: TimerCallback,
+}
+
+/// The timers that have not fired yet.
+#[derive(Default)]
+pub(crate) struct Timers {
+    next_id: AtomicU64,
+    timers: Mutex<Vec<Timer>>,
+    /// Repeating timers that were cancelled from their own callback, while they were not in
+    /// `timers`.
+    cancelled_while_running: Mutex<Vec<TimerId>>,
+}
+
+impl fmt::Debug for Timers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Timers")
+            .field("timers", &self.timers.lock().len())
+            .finish()
+    }
+}
+
+impl Timers {
+    pub fn add_once(&self, delay: Duration, callback: impl FnOnce() + Send + 'static) -> TimerId {
+        self.add(delay, TimerCallback::Once(Box::new(callback)))
+    }
+
+    pub fn add_repeating(
+        &self,
+        interval: Duration,
+        callback: impl FnMut() + Send + 'static,
+    ) -> TimerId {
+        self.add(
+            interval,
+            TimerCallback::Repeating(Box::new(callback), interval),
+        )
+    }
+
+    fn add(&self, delay: Duration, callback: TimerCallback) -> TimerId {
+        let id = TimerId(self.next_id.fetch_add(1, Ordering::Relaxed));
+        self.timers.lock().push(Timer {
+            id,
+            deadline: Instant::now() + delay,
+            callback,
+        });
+
+        id
+    }
+
+    pub fn cancel(&self, id: TimerId) {
+        let mut timers = self.timers.lock();
+        let num_timers = timers.len();
+        timers.retain(|timer| timer.id != id);
+        if timers.len() == num_timers {
+            self.cancelled_while_running.lock().push(id);
+        }
+    }
+
+    /// Run the callbacks of the timers whose deadlines have passed. Called on the GUI thread before
+    /// every frame.
+    pub fn run_due(&self) {
+        profile_scope!("timers");
+        let now = Instant::now();
+
+        // The callbacks may add or cancel timers, so they can't be called while holding the lock
+        let due: Vec<Timer> = {
+            let mut timers = self.timers.lock();
+            let (due, pending) = std::mem::take(&mut *timers)
+                .into_iter()
+                .partition(|timer| timer.deadline <= now);
+            *timers = pending;
+
+            due
+        };
+
+        let mut rescheduled = Vec::new();
+        for timer in due {
+            match timer.callback {
+                TimerCallback::Once(callback) => callback(),
+                TimerCallback::Repeating(mut callback, interval) => {
+                    callback();
+
+                    // Frames may be late, in which case the timer shouldn't try to catch up
+                    let deadline = (timer.deadline + interval).max(now);
+                    rescheduled.push(Timer {
+                        id: timer.id,
+                        deadline,
+                        callback: TimerCallback::Repeating(callback, interval),
+                    });
+                }
+            }
+        }
+
+        let cancelled = std::mem::take(&mut *self.cancelled_while_running.lock());
+        rescheduled.retain(|timer| !cancelled.contains(&timer.id));
+        self.timers.lock().extend(rescheduled);
+    }
+}
@@ -0,0 +1,301 @@
+//! Touch input from touch screens. Baseview only reports touches as the mouse events the OS
+//! synthesizes for them, so the bridge collects the actual touch points itself.
+
+use baseview::{Point, Window};
+
+/// A touch point's change reported by [`BaseviewState::take_touch_events()`].
+///
+/// [`BaseviewState::take_touch_events()`]: crate::BaseviewState::take_touch_events()
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TouchEvent {
+    /// Identifies the touch point from the moment it starts until it ends. IDs may be reused for
+    /// later touches.
+    pub id: u64,
+    pub phase: TouchPhase,
+    /// The touch point's position in logical pixels.
+    pub position: Point,
+}
+
+/// The stage of a touch point's life cycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TouchPhase {
+    /// A finger touched the screen.
+    Started,
+    /// The finger moved.
+    Moved,
+    /// The finger was lifted.
+    Ended,
+}
+
+/// Collects the touches for a window, with their positions in physical pixels relative to the
+/// window.
+pub(crate) struct TouchInput {
+    #[cfg(target_os = "windows")]
+    hwnd: winapi::shared::windef::HWND,
+    /// The touches received by the window's subclass procedure since the last call to
+    /// [`TouchInput::take_touches()`]. This is boxed so it has a stable address.
+    #[cfg(target_os = "windows")]
+    touches: Box<std::cell::RefCell<Vec<(u64, TouchPhase, f64, f64)>>>,
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    display: *mut x11::xlib::Display,
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    xinput_opcode: std::os::raw::c_int,
+}
+
+/// Identifies our subclass procedure on the window.
+#[cfg(target_os = "windows")]
+const SUBCLASS_ID: usize = 0x6e69_6874;
+
+#[cfg(target_os = "windows")]
+impl TouchInput {
+    /// Start receiving touch input for the window. Returns `None` if this is not supported.
+    pub fn new(window: &Window) -> Option<Self> {
+        use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+        use std::cell::RefCell;
+        use winapi::shared::windef::HWND;
+        use winapi::um::commctrl::{RemoveWindowSubclass, SetWindowSubclass};
+        use winapi::um::winuser::RegisterTouchWindow;
+
+        let hwnd = match window.raw_window_handle() {
+            RawWindowHandle::Win32(handle) => handle.hwnd as HWND,
+            _ => return None,
+        };
+
+        unsafe {
+            let touches = Box::new(RefCell::new(Vec::new()));
+            if SetWindowSubclass(
+                hwnd,
+                Some(subclass_proc),
+                SUBCLASS_ID,
+                &*touches as *const RefCell<_> as usize,
+            ) == 0
+            {
+                return None;
+            }
+
+            if RegisterTouchWindow(hwnd, 0) == 0 {
+                RemoveWindowSubclass(hwnd, Some(subclass_proc), SUBCLASS_ID);
+                return None;
+            }
+
+            Some(Self { hwnd, touches })
+        }
+    }
+
+    /// Take the touches received since the last call, with their positions in physical pixels
+    /// relative to the window.
+    pub fn take_touches(&mut self) -> Vec<(u64, TouchPhase, f64, f64)> {
+        self.touches.take()
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Drop for TouchInput {
+    fn drop(&mut self) {
+        use winapi::um::commctrl::RemoveWindowSubclass;
+        use winapi::um::winuser::UnregisterTouchWindow;
+
+        unsafe {
+            UnregisterTouchWindow(self.hwnd);
+            RemoveWindowSubclass(self.hwnd, Some(subclass_proc), SUBCLASS_ID);
+        }
+    }
+}
+
+/// Collects the touch points from `WM_TOUCH` messages. All other messages are passed on to
+/// baseview's window procedure.
+#[cfg(target_os = "windows")]
+unsafe extern "system" fn subclass_proc(
+    hwnd: winapi::shared::windef::HWND,
+    msg: winapi::shared::minwindef::UINT,
+    wparam: winapi::shared::minwindef::WPARAM,
+    lparam: winapi::shared::minwindef::LPARAM,
+    _id: winapi::shared::basetsd::UINT_PTR,
+    ref_data: winapi::shared::basetsd::DWORD_PTR,
+) -> winapi::shared::minwindef::LRESULT {
+    use std::cell::RefCell;
+    use winapi::shared::windef::POINT;
+    use winapi::um::commctrl::DefSubclassProc;
+    use winapi::um::winuser::{
+        CloseTouchInputHandle, GetTouchInputInfo, ScreenToClient, HTOUCHINPUT, TOUCHEVENTF_DOWN,
+        TOUCHEVENTF_MOVE, TOUCHEVENTF_UP, TOUCHINPUT, WM_TOUCH,
+    };
+
+    if msg != WM_TOUCH {
+        return DefSubclassProc(hwnd, msg, wparam, lparam);
+    }
+
+    let num_inputs = (wparam & 0xffff) as usize;
+    let mut inputs: Vec<TOUCHINPUT> = vec![std::mem::zeroed(); num_inputs];
+    if GetTouchInputInfo(
+        lparam as HTOUCHINPUT,
+        num_inputs as u32,
+        inputs.as_mut_ptr(),
+        std::mem::size_of::<TOUCHINPUT>() as i32,
+    ) == 0
+    {
+        return DefSubclassProc(hwnd, msg, wparam, lparam);
+    }
+
+    let touches = &*(ref_data as *const RefCell<Vec<(u64, TouchPhase, f64, f64)>>);
+    for input in inputs {
+        let phase = if input.dwFlags & TOUCHEVENTF_DOWN != 0 {
+            TouchPhase::Started
+        } else if input.dwFlags & TOUCHEVENTF_UP != 0 {
+            TouchPhase::Ended
+        } else if input.dwFlags & TOUCHEVENTF_MOVE != 0 {
+            TouchPhase::Moved
+        } else {
+            continue;
+        };
+
+        // Touch positions are in hundredths of a physical pixel in screen coordinates
+        let mut point = POINT {
+            x: input.x / 100,
+            y: input.y / 100,
+        };
+        ScreenToClient(hwnd, &mut point);
+        let (fraction_x, fraction_y) = (
+            (input.x % 100) as f64 / 100.0,
+            (input.y % 100) as f64 / 100.0,
+        );
+
+        touches.borrow_mut().push((
+            input.dwID as u64,
+            phase,
+            point.x as f64 + fraction_x,
+            point.y as f64 + fraction_y,
+        ));
+    }
+    CloseTouchInputHandle(lparam as HTOUCHINPUT);
+
+    0
+}
+
+#[cfg(target_os = "macos")]
+impl TouchInput {
+    /// Start receiving touch input for the window. Macs don't have touch screens, so this always
+    /// returns `None`.
+    pub fn new(_window: &Window) -> Option<Self> {
+        None
+    }
+
+    /// Take the touches received since the last call, with their positions in physical pixels
+    /// relative to the window.
+    pub fn take_touches(&mut self) -> Vec<(u64, TouchPhase, f64, f64)> {
+        Vec::new()
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+impl TouchInput {
+    /// Start receiving touch input for the window. Returns `None` if this is not supported. This
+    /// needs XInput 2.2.
+    pub fn new(window: &Window) -> Option<Self> {
+        use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+        use std::ffi::CString;
+        use std::os::raw::{c_int, c_ulong};
+        use x11::{xinput2, xlib};
+
+        let window_id = match window.raw_window_handle() {
+            RawWindowHandle::Xcb(handle) => handle.window as c_ulong,
+            RawWindowHandle::Xlib(handle) => handle.window,
+            _ => return None,
+        };
+
+        unsafe {
+            let display = crate::x11_isolation::open_display();
+            if display.is_null() {
+                return None;
+            }
+
+            let extension_name = CString::new("XInputExtension").unwrap();
+            let (mut xinput_opcode, mut first_event, mut first_error): (c_int, c_int, c_int) =
+                (0, 0, 0);
+            let (mut major_version, mut minor_version): (c_int, c_int) = (2, 2);
+            if xlib::XQueryExtension(
+                display,
+                extension_name.as_ptr(),
+                &mut xinput_opcode,
+                &mut first_event,
+                &mut first_error,
+            ) == 0
+                || xinput2::XIQueryVersion(display, &mut major_version, &mut minor_version)
+                    != xlib::Success as c_int
+                || (major_version, minor_version) < (2, 2)
+            {
+                crate::x11_isolation::close_display(display);
+                return None;
+            }
+
+            // Events are delivered to the connection that selected them, so this doesn't take
+            // anything away from baseview's own connection
+            let mut mask = [0u8; (xinput2::XI_LASTEVENT as usize >> 3) + 1];
+            xinput2::XISetMask(&mut mask, xinput2::XI_TouchBegin);
+            xinput2::XISetMask(&mut mask, xinput2::XI_TouchUpdate);
+            xinput2::XISetMask(&mut mask, xinput2::XI_TouchEnd);
+            let mut event_mask = xinput2::XIEventMask {
+                deviceid: xinput2::XIAllMasterDevices,
+                mask_len: mask.len() as c_int,
+                mask: mask.as_mut_ptr(),
+            };
+            xinput2::XISelectEvents(display, window_id, &mut event_mask, 1);
+            xlib::XFlush(display);
+
+            Some(Self {
+                display,
+                xinput_opcode,
+            })
+        }
+    }
+
+    /// Take the touches received since the last call, with their positions in physical pixels
+    /// relative to the window.
+    pub fn take_touches(&mut self) -> Vec<(u64, TouchPhase, f64, f64)> {
+        use x11::{xinput2, xlib};
+
+        let mut touches = Vec::new();
+        unsafe {
+            let mut event: xlib::XEvent = std::mem::zeroed();
+            while xlib::XPending(self.display) > 0 {
+                xlib::XNextEvent(self.display, &mut event);
+
+                let cookie = &mut event.generic_event_cookie;
+                if cookie.type_ != xlib::GenericEvent
+                    || cookie.extension != self.xinput_opcode
+                    || xlib::XGetEventData(self.display, cookie) == 0
+                {
+                    continue;
+                }
+
+                let phase = match cookie.evtype {
+                    xinput2::XI_TouchBegin => Some(TouchPhase::Started),
+                    xinput2::XI_TouchUpdate => Some(TouchPhase::Moved),
+                    xinput2::XI_TouchEnd => Some(TouchPhase::Ended),
+                    _ => None,
+                };
+                if let Some(phase) = phase {
+                    let device_event = &*(cookie.data as *const xinput2::XIDeviceEvent);
+                    touches.push((
+                        device_event.detail as u64,
+                        phase,
+                        device_event.event_x,
+                        device_event.event_y,
+                    ));
+                }
+
+                xlib::XFreeEventData(self.display, cookie);
+            }
+        }
+
+        touches
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+impl Drop for TouchInput {
+    fn drop(&mut self) {
+        unsafe { crate::x11_isolation::close_display(self.display) };
+    }
+}
@@ -0,0 +1,94 @@
+//! Letting the desktop or the host show through the parts of the editor that are drawn with an
+//! alpha below 1, for editors with non-rectangular or partially transparent GUIs. The framebuffer
+//! always has an alpha channel, but the window system ignores it unless it's told otherwise.
+
+use baseview::Window;
+use nih_plug::prelude::{GuiContext, PluginApi};
+
+/// Make the window's OpenGL surface use its alpha channel. On macOS this works for embedded
+/// editors as well, but on Windows only the standalone application's top level window can be made
+/// transparent. On X11 this depends on the window having been created with an ARGB visual and on a
+/// compositor running, which the bridge cannot influence.
+#[cfg(target_os = "windows")]
+pub(crate) fn enable(window: &Window, context: &dyn GuiContext) {
+    use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+    use winapi::shared::minwindef::{FALSE, TRUE};
+    use winapi::shared::windef::HWND;
+    use winapi::um::dwmapi::{
+        DwmEnableBlurBehindWindow, DWM_BB_BLURREGION, DWM_BB_ENABLE, DWM_BLURBEHIND,
+    };
+    use winapi::um::wingdi::{CreateRectRgn, DeleteObject};
+    use winapi::um::winuser::{GetAncestor, GA_ROOT};
+
+    // Child windows are composited into the host's window, which we shouldn't touch
+    if context.plugin_api() != PluginApi::Standalone {
+        return;
+    }
+    let hwnd = match window.raw_window_handle() {
+        RawWindowHandle::Win32(handle) => handle.hwnd as HWND,
+        _ => return,
+    };
+
+    unsafe {
+        let root = GetAncestor(hwnd, GA_ROOT);
+        if root.is_null() {
+            return;
+        }
+
+        // Blurring behind an empty region makes the DWM use the window's alpha channel without
+        // actually blurring anything
+        let region = CreateRectRgn(0, 0, -1, -1);
+        let blur_behind = DWM_BLURBEHIND {
+            dwFlags: DWM_BB_ENABLE | DWM_BB_BLURREGION,
+            fEnable: TRUE,
+            hRgnBlur: region,
+            fTransitionOnMaximized: FALSE,
+        };
+        DwmEnableBlurBehindWindow(root, &blur_behind);
+        DeleteObject(region as _);
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub(crate) fn enable(window: &Window, _context: &dyn GuiContext) {
+    use cocoa::base::{id, nil, NO};
+    use cocoa::foundation::{NSArray, NSInteger};
+    use objc::runtime::BOOL;
+    use objc::{msg_send, sel, sel_impl};
+    use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+
+    /// `NSOpenGLContextParameterSurfaceOpacity`
+    const SURFACE_OPACITY: NSInteger = 236;
+
+    let ns_view = match window.raw_window_handle() {
+        RawWindowHandle::AppKit(handle) => handle.ns_view as id,
+        _ => return,
+    };
+
+    unsafe {
+        // Baseview draws through an OpenGL view inside of the window's view
+        let subviews: id = msg_send![ns_view, subviews];
+        if subviews == nil {
+            return;
+        }
+
+        for i in 0..NSArray::count(subviews) {
+            let view = NSArray::objectAtIndex(subviews, i);
+            let is_gl_view: BOOL = msg_send![view, respondsToSelector: sel!(openGLContext)];
+            if is_gl_view == NO {
+                continue;
+            }
+
+            let gl_context: id = msg_send![view, openGLContext];
+            if gl_context != nil {
+                let opacity: i32 = 0;
+                let () = msg_send![gl_context,
+                    setValues: &opacity as *const i32
+                    forParameter: SURFACE_OPACITY];
+            }
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub(crate) fn enable(_window: &Window, _context: &dyn GuiContext) {}
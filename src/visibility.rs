@@ -0,0 +1,127 @@
+//! Detecting when the editor's window is hidden, for instance because the host minimized its
+//! window or switched to another plugin's tab, so rendering can be paused.
+
+use baseview::Window;
+
+/// Checks whether a window is currently visible on the screen.
+pub(crate) struct VisibilityChecker {
+    #[cfg(target_os = "windows")]
+    hwnd: winapi::shared::windef::HWND,
+
+    #[cfg(target_os = "macos")]
+    ns_view: cocoa::base::id,
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    display: *mut x11::xlib::Display,
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    window_id: x11::xlib::Window,
+}
+
+#[cfg(target_os = "windows")]
+impl VisibilityChecker {
+    pub fn new(window: &Window) -> Option<Self> {
+        use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+        use winapi::shared::windef::HWND;
+
+        match window.raw_window_handle() {
+            RawWindowHandle::Win32(handle) => Some(Self {
+                hwnd: handle.hwnd as HWND,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Whether the window is shown. The window is hidden if it or one of its parents is hidden,
+    /// or if the host's top level window is minimized.
+    pub fn is_visible(&mut self) -> bool {
+        use winapi::um::winuser::{GetAncestor, IsIconic, IsWindowVisible, GA_ROOT};
+
+        unsafe { IsWindowVisible(self.hwnd) != 0 && IsIconic(GetAncestor(self.hwnd, GA_ROOT)) == 0 }
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl VisibilityChecker {
+    pub fn new(window: &Window) -> Option<Self> {
+        use cocoa::base::id;
+        use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+
+        match window.raw_window_handle() {
+            RawWindowHandle::AppKit(handle) => Some(Self {
+                ns_view: handle.ns_view as id,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Whether the window is shown. The window is hidden if it or one of its parents is hidden,
+    /// or if its window is fully covered by other windows or minimized.
+    pub fn is_visible(&mut self) -> bool {
+        use cocoa::base::{id, nil, NO};
+        use cocoa::foundation::NSUInteger;
+        use objc::runtime::BOOL;
+        use objc::{msg_send, sel, sel_impl};
+
+        /// `NSWindowOcclusionStateVisible`
+        const OCCLUSION_STATE_VISIBLE: NSUInteger = 1 << 1;
+
+        unsafe {
+            let hidden: BOOL = msg_send![self.ns_view, isHiddenOrHasHiddenAncestor];
+            if hidden != NO {
+                return false;
+            }
+
+            let ns_window: id = msg_send![self.ns_view, window];
+            if ns_window == nil {
+                return false;
+            }
+
+            let occlusion_state: NSUInteger = msg_send![ns_window, occlusionState];
+            occlusion_state & OCCLUSION_STATE_VISIBLE != 0
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+impl VisibilityChecker {
+    pub fn new(window: &Window) -> Option<Self> {
+        use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+        use std::os::raw::c_ulong;
+
+        let window_id = match window.raw_window_handle() {
+            RawWindowHandle::Xcb(handle) => handle.window as c_ulong,
+            RawWindowHandle::Xlib(handle) => handle.window,
+            _ => return None,
+        };
+
+        let display = unsafe { crate::x11_isolation::open_display() };
+        if display.is_null() {
+            return None;
+        }
+
+        Some(Self { display, window_id })
+    }
+
+    /// Whether the window is shown. The window is hidden if it or one of its parents is unmapped,
+    /// which is also what happens when the host's window is minimized. X11 does not track whether
+    /// a window is covered by other windows.
+    pub fn is_visible(&mut self) -> bool {
+        use x11::xlib;
+
+        unsafe {
+            let mut attributes: xlib::XWindowAttributes = std::mem::zeroed();
+            if xlib::XGetWindowAttributes(self.display, self.window_id, &mut attributes) == 0 {
+                return true;
+            }
+
+            attributes.map_state == xlib::IsViewable
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+impl Drop for VisibilityChecker {
+    fn drop(&mut self) {
+        unsafe { crate::x11_isolation::close_display(self.display) };
+    }
+}
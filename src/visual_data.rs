@@ -0,0 +1,193 @@
+//! Triple buffers for passing visualization data like spectra and envelopes from the audio thread
+//! to the editor. Neither side ever waits for the other, and the editor always sees the most recent
+//! complete value.
+
+use std::cell::UnsafeCell;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+
+use crate::BaseviewState;
+
+/// Set in [`Buffers::middle`] when the middle buffer contains a value the reader hasn't seen yet.
+const DIRTY: u8 = 0b100;
+/// Masks the buffer index in [`Buffers::middle`].
+const INDEX_MASK: u8 = 0b011;
+
+/// The editor's side of a triple buffer created using
+/// [`BaseviewState::visual_data()`][crate::BaseviewState::visual_data()].
+pub struct VisualData<T> {
+    buffers: Arc<Buffers<T>>,
+    /// The index of the buffer only the reader uses.
+    front: u8,
+}
+
+/// The audio thread's side of a triple buffer created using
+/// [`BaseviewState::visual_data()`][crate::BaseviewState::visual_data()].
+pub struct VisualDataWriter<T> {
+    buffers: Arc<Buffers<T>>,
+    /// The index of the buffer only the writer uses.
+    back: u8,
+    /// Used to skip writes while the editor is closed.
+    baseview_state: Arc<BaseviewState>,
+}
+
+struct Buffers<T> {
+    buffers: [UnsafeCell<T>; 3],
+    /// The index of the buffer that is exchanged between the reader and the writer, with the
+    /// [`DIRTY`] bit set if it was written since the last read.
+    middle: AtomicU8,
+}
+
+// The reader and the writer each have exclusive access to their own buffer, and only exchange
+// buffers using the atomic middle index
+unsafe impl<T: Send> Sync for Buffers<T> {}
+
+/// Create both sides of a triple buffer with every buffer set to `initial`.
+pub(crate) fn visual_data<T: Clone>(
+    initial: T,
+    baseview_state: Arc<BaseviewState>,
+) -> (VisualDataWriter<T>, VisualData<T>) {
+    let buffers = Arc::new(Buffers {
+        buffers: [
+            UnsafeCell::new(initial.clone()),
+            UnsafeCell::new(initial.clone()),
+            UnsafeCell::new(initial),
+        ],
+        middle: AtomicU8::new(1),
+    });
+
+    (
+        VisualDataWriter {
+            buffers: buffers.clone(),
+            back: 0,
+            baseview_state,
+        },
+        VisualData { buffers, front: 2 },
+    )
+}
+
+impl<T> VisualData<T> {
+    /// Whether the writer published a new value since the last call to [`VisualData::read()`].
+    pub fn has_update(&self) -> bool {
+        self.buffers.middle.load(Ordering::Acquire) & DIRTY != 0
+    }
+
+    /// Get the most recently published value. This never blocks.
+    pub fn read(&mut self) -> &T {
+        if self.has_update() {
+            let middle = self.buffers.middle.swap(self.front, Ordering::AcqRel);
+            self.front = middle & INDEX_MASK;
+        }
+
+        unsafe { &*self.buffers.buffers[self.front as usize].get() }
+    }
+}
+
+impl<T> VisualDataWriter<T> {
+    /// Publish a new value. This does nothing and returns `false` while the editor is closed, so
+    /// the audio thread can skip computing the value entirely when [`BaseviewState::is_open()`]
+    /// returns `false`. This never blocks and doesn't allocate unless dropping the old value does.
+    pub fn write(&mut self, value: T) -> bool {
+        self.write_with(|buffer| *buffer = value)
+    }
+
+    /// Publish a new value by modifying a buffer in place, to avoid allocations for values like
+    /// `Vec`s. The buffer contains an older value, not necessarily the last one that was
+    /// published, so `f` should overwrite all of it. See [`VisualDataWriter::write()`].
+    pub fn write_with(&mut self, f: impl FnOnce(&mut T)) -> bool {
+        if !self.baseview_state.is_open() {
+            return false;
+        }
+
+        f(unsafe { &mut *self.buffers.buffers[self.back as usize].get() });
+        let middle = self
+            .buffers
+            .middle
+            .swap(self.back | DIRTY, Ordering::AcqRel);
+        self.back = middle & INDEX_MASK;
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    fn open_visual_data<T: Clone>(initial: T) -> (VisualDataWriter<T>, VisualData<T>) {
+        let baseview_state = BaseviewState::from_size(400, 300);
+        baseview_state.open.store(true, Ordering::Release);
+
+        visual_data(initial, baseview_state)
+    }
+
+    #[test]
+    fn reads_the_latest_value() {
+        let (mut writer, mut reader) = open_visual_data(0);
+        assert!(!reader.has_update());
+        assert_eq!(*reader.read(), 0);
+
+        assert!(writer.write(1));
+        assert!(writer.write(2));
+        assert!(reader.has_update());
+        assert_eq!(*reader.read(), 2);
+        assert!(!reader.has_update());
+    }
+
+    #[test]
+    fn reading_again_keeps_the_value() {
+        let (mut writer, mut reader) = open_visual_data(0);
+
+        for value in 1..10 {
+            writer.write(value);
+            assert_eq!(*reader.read(), value);
+            // Without a new write the reader must not swap in the writer's older buffer
+            assert!(!reader.has_update());
+            assert_eq!(*reader.read(), value);
+        }
+    }
+
+    #[test]
+    fn write_with_reuses_buffers() {
+        let (mut writer, mut reader) = open_visual_data(Vec::new());
+
+        writer.write_with(|buffer| {
+            buffer.clear();
+            buffer.extend([1, 2, 3]);
+        });
+        assert_eq!(reader.read(), &[1, 2, 3]);
+    }
+
+    #[test]
+    fn writes_are_skipped_while_closed() {
+        let (mut writer, mut reader) = open_visual_data(0);
+        writer.baseview_state.open.store(false, Ordering::Release);
+
+        assert!(!writer.write(1));
+        assert!(!reader.has_update());
+        assert_eq!(*reader.read(), 0);
+    }
+
+    #[test]
+    fn concurrent_reads_see_whole_values_in_order() {
+        const WRITES: u64 = 100_000;
+
+        let (mut writer, mut reader) = open_visual_data([0u64; 16]);
+        let writer_thread = thread::spawn(move || {
+            for value in 1..=WRITES {
+                writer.write([value; 16]);
+            }
+        });
+
+        let mut last_value = 0;
+        while last_value != WRITES {
+            let values = *reader.read();
+            assert!(values.iter().all(|&value| value == values[0]), "torn read");
+            assert!(values[0] >= last_value, "stale read");
+            last_value = values[0];
+        }
+        writer_thread.join().unwrap();
+    }
+}
@@ -0,0 +1,121 @@
+//! A watchdog that notices when the user's handler takes too long to return from a callback. A
+//! hung editor blocks the host's GUI thread along with it, and without this it's hard to tell
+//! whether the plugin or the host is at fault.
+//!
+//! The watchdog only observes the GUI thread. It can't interrupt a callback, so a callback that
+//! never returns is logged once and keeps blocking the host until the host gives up on it. The
+//! GUI thread's stack can't be sampled from another thread without platform specific debugger
+//! APIs, so the log messages name the callback and how long it ran instead of showing a
+//! backtrace.
+
+use crossbeam::atomic::AtomicCell;
+use crossbeam::channel::{self, RecvTimeoutError, Sender};
+use nih_plug::prelude::nih_log;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Configures the GUI thread watchdog. Enable it using
+/// [`BaseviewState::set_watchdog()`][crate::BaseviewState::set_watchdog()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatchdogConfig {
+    /// How long the handler's `on_frame()` or `on_event()`, or one of the plugin's GUI tasks or
+    /// timers, may run before it counts as hung.
+    pub timeout: Duration,
+    /// Close the editor once a hung callback returns. The callback can't be interrupted while it
+    /// is running, so this only prevents it from hanging again.
+    pub close_window: bool,
+}
+
+/// Watches the callbacks into the user's handler from a separate thread.
+pub(crate) struct Watchdog {
+    config: WatchdogConfig,
+    shared: Arc<Shared>,
+    /// Dropping this stops the watchdog thread.
+    stop_sender: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+struct Shared {
+    /// When the current callback started and what it is, or `None` outside of callbacks.
+    callback_started: AtomicCell<Option<(Instant, &'static str)>>,
+    /// Set by the watchdog thread when the current callback exceeded the timeout.
+    tripped: AtomicBool,
+}
+
+impl Watchdog {
+    pub fn new(config: WatchdogConfig) -> Self {
+        let shared = Arc::new(Shared {
+            callback_started: AtomicCell::new(None),
+            tripped: AtomicBool::new(false),
+        });
+        let (stop_sender, stop_receiver) = channel::bounded::<()>(0);
+
+        // Checking a couple of times per timeout is precise enough for a diagnostic
+        let poll_interval = (config.timeout / 4).max(Duration::from_millis(1));
+        let thread = thread::Builder::new()
+            .name(String::from("baseview-watchdog"))
+            .spawn({
+                let shared = shared.clone();
+                move || loop {
+                    match stop_receiver.recv_timeout(poll_interval) {
+                        Err(RecvTimeoutError::Timeout) => (),
+                        _ => break,
+                    }
+
+                    if let Some((started, callback)) = shared.callback_started.load() {
+                        let elapsed = started.elapsed();
+                        if elapsed >= config.timeout && !shared.tripped.swap(true, Ordering::AcqRel)
+                        {
+                            nih_log!(
+                                "{callback} has been blocking the editor's GUI thread for {} ms",
+                                elapsed.as_millis()
+                            );
+                        }
+                    }
+                }
+            })
+            .ok();
+
+        Self {
+            config,
+            shared,
+            stop_sender: Some(stop_sender),
+            thread,
+        }
+    }
+
+    pub fn config(&self) -> WatchdogConfig {
+        self.config
+    }
+
+    /// Called right before calling into the user's code. `callback` describes the code in log
+    /// messages.
+    pub fn begin(&self, callback: &'static str) {
+        self.shared.tripped.store(false, Ordering::Release);
+        self.shared
+            .callback_started
+            .store(Some((Instant::now(), callback)));
+    }
+
+    /// Called right after the user's handler returned. Returns how long the callback took if it
+    /// exceeded the timeout.
+    pub fn end(&self) -> Option<Duration> {
+        let started = self.shared.callback_started.take();
+        if self.shared.tripped.swap(false, Ordering::AcqRel) {
+            started.map(|(started, _)| started.elapsed())
+        } else {
+            None
+        }
+    }
+}
+
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        self.stop_sender.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
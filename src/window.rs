@@ -0,0 +1,1074 @@
+//! The [`WindowHandler`] that sits between baseview and the user's handler. This is where the
+//! bridge does its own bookkeeping before passing events on.
+
+use baseview::{
+    Event, EventStatus, MouseButton, MouseCursor, MouseEvent, Point, Size, Window, WindowEvent,
+    WindowHandler, WindowInfo,
+};
+use crossbeam::channel::{Receiver, TryRecvError};
+use keyboard_types::{Code, KeyState, KeyboardEvent, Modifiers};
+use nih_plug::prelude::{nih_log, GuiContext, ParamSetter};
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::capture;
+use crate::click::ClickTracker;
+use crate::debug_overlay;
+use crate::diagnostics::DiagnosticsCounter;
+use crate::dpi::DpiWatcher;
+use crate::event_trace::trace_event;
+use crate::fallback::{self, ErrorView};
+use crate::focus::FocusHandshake;
+use crate::gesture::GestureInput;
+use crate::handle::HandleRegistration;
+use crate::keyboard::{self, KeyForwarder, KeyboardPolicy};
+use crate::pointer::PointerLock;
+use crate::profile::{self, profile_scope};
+use crate::raw_mouse::RawMouseInput;
+use crate::recording::Replay;
+use crate::reparent::ParentTracker;
+use crate::scroll;
+#[cfg(feature = "testing")]
+use crate::snapshot;
+use crate::standalone::StandaloneWindow;
+use crate::touch::{TouchEvent, TouchInput};
+use crate::visibility::VisibilityChecker;
+use crate::watchdog::Watchdog;
+use crate::x11_isolation::{DisplayRegistration, ErrorIsolation};
+use crate::xembed::XEmbedClient;
+use crate::{editor, BaseviewState, ScalePolicy};
+
+/// The user's update function with the user state already bound, see
+/// [`create_baseview_editor_with_update()`][crate::create_baseview_editor_with_update()].
+pub(crate) type FrameUpdate = Arc<dyn Fn(&ParamSetter) + Send + Sync>;
+
+/// Wraps the user's [`WindowHandler`] so the bridge can act on the window from the GUI thread.
+pub(crate) struct BaseviewWindowHandler<H> {
+    baseview_state: Arc<BaseviewState>,
+    context: Arc<dyn GuiContext>,
+    /// Disconnects when the editor handle is dropped on a thread other than the one the window was
+    /// opened on, in which case the handler closes the window itself.
+    close_receiver: Receiver<()>,
+    /// The handler returned by the user's build function. This is `None` if the window's OpenGL
+    /// context could not be created, in which case an error message is shown instead.
+    inner: Option<H>,
+    /// Called before the handler's frame, if the editor has an update function.
+    update: Option<FrameUpdate>,
+    /// Set when the user's build function or handler panicked. The handler may be in an
+    /// inconsistent state after that, so it is no longer called and the window shows the panic's
+    /// message instead.
+    panicked: bool,
+
+    /// The ratio between the window's physical and logical pixels.
+    window_scale: f64,
+    /// The last known cursor position in logical pixels.
+    cursor_position: Point,
+    /// The number of mouse buttons that are currently held down. The mouse is captured while this
+    /// is nonzero.
+    buttons_held: u32,
+    /// Whether the window currently captures the mouse.
+    mouse_captured: bool,
+    /// Passes key events the editor doesn't use on to the host.
+    key_forwarder: KeyForwarder,
+    /// Counts consecutive clicks for [`BaseviewState::click_count()`].
+    click_tracker: ClickTracker,
+    /// The cursor position and window size at the start of a resize grip drag, if the user is
+    /// currently dragging the resize grip.
+    resize_grip_drag: Option<(Point, (f64, f64))>,
+    /// Set when the bridge changed the window's size before opening it, for instance because the
+    /// persisted size did not fit on the screen. The host is then asked to resize its container
+    /// before the first frame.
+    needs_host_resize: bool,
+    /// The mouse cursor that was last set on the window.
+    mouse_cursor: MouseCursor,
+    /// The active pointer lock, along with the logical cursor position where it started and the
+    /// virtual cursor position passed to the user's handler.
+    pointer_lock: Option<(PointerLock, Point, Point)>,
+    /// Collects raw mouse movement while it is enabled.
+    raw_mouse_input: Option<RawMouseInput>,
+    /// Collects touch input while it is enabled.
+    touch_input: Option<TouchInput>,
+    /// Collects pinch and rotate gestures while they are enabled.
+    gesture_input: Option<GestureInput>,
+    /// Used to pause rendering while the window is hidden. This is `None` if the window's
+    /// visibility can't be determined.
+    visibility_checker: Option<VisibilityChecker>,
+    /// Shows an error message in the window when there is no handler.
+    error_view: Option<ErrorView>,
+    /// Watches the calls into the user's handler while the watchdog is enabled.
+    watchdog: Option<Watchdog>,
+    /// The application's window when running as a standalone application. This is `None` inside
+    /// of a plugin host.
+    standalone_window: Option<StandaloneWindow>,
+    /// Notices when the host moves the window to another parent. This is `None` for windows
+    /// without a parent.
+    parent_tracker: Option<ParentTracker>,
+    /// Notices when the window is moved to a monitor with a different DPI. This is only used on
+    /// Windows.
+    dpi_watcher: Option<DpiWatcher>,
+    /// The host's scaling factor on macOS when the host scales the editor's view itself. Mouse
+    /// positions are reported in the scaled view's points, so they're divided by this.
+    host_view_scale: Option<f64>,
+    /// Takes care of the XEmbed handshake on X11. This is `None` on the other platforms and for
+    /// top level windows.
+    xembed: Option<XEmbedClient>,
+    /// Moves keyboard focus between the editor and the host while the focus handshake is enabled.
+    focus_handshake: Option<FocusHandshake>,
+    /// Counts frames and events for [`BaseviewState::diagnostics()`].
+    diagnostics_counter: DiagnosticsCounter,
+    /// The recording passed to [`BaseviewState::replay_recording()`] that's being replayed.
+    replay: Option<Replay>,
+    /// Set while a replayed event is being handled, so it isn't recorded or ignored like the
+    /// window's own input.
+    handling_replayed_event: bool,
+    /// Set when the window could not be rebuilt for a new zoom factor, so it isn't tried again
+    /// every frame. The zoom factor is then applied the next time the editor opens.
+    zoom_rebuild_failed: bool,
+    /// Keeps errors on baseview's X11 connection away from the host's error handler while X11
+    /// isolation is enabled. This is `None` otherwise and on the other platforms.
+    x11_display: Option<DisplayRegistration>,
+    /// Keeps the isolating error handler installed while this window is open. The handler is
+    /// shared between all windows and restored once the last of them is closed.
+    _x11_isolation: Option<ErrorIsolation>,
+    /// Lets the bridge's helpers look up the window's handle on this thread while it's open.
+    _handle_registration: HandleRegistration,
+
+    /// This needs to be the last field so it is dropped after the user's handler.
+    _alive_guard: AliveGuard,
+}
+
+/// Counts the window handlers for a spawned editor that have not been destroyed yet, so the
+/// editor handle can wait for the teardown to finish on the window's own thread.
+struct AliveGuard(Arc<AtomicUsize>);
+
+impl AliveGuard {
+    fn new(alive_handlers: Arc<AtomicUsize>) -> Self {
+        alive_handlers.fetch_add(1, Ordering::AcqRel);
+        Self(alive_handlers)
+    }
+}
+
+impl Drop for AliveGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl<H> BaseviewWindowHandler<H> {
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        window: &Window,
+        handle_registration: HandleRegistration,
+        baseview_state: Arc<BaseviewState>,
+        context: Arc<dyn GuiContext>,
+        close_receiver: Receiver<()>,
+        alive_handlers: Arc<AtomicUsize>,
+        inner: Option<H>,
+        update: Option<FrameUpdate>,
+        window_scale: f64,
+        needs_host_resize: bool,
+    ) -> Self {
+        trace_event!("Opened the window with a scale of {window_scale}");
+        profile::register_thread();
+        let x11_isolation = baseview_state.x11_isolation().then(ErrorIsolation::install);
+        let x11_display = if baseview_state.x11_isolation() {
+            DisplayRegistration::new(window)
+        } else {
+            None
+        };
+
+        // The panic message is cleared before the window is opened, so this is only set if the
+        // build function panicked
+        let build_panic = baseview_state.panic_message();
+        let error_view = match (&inner, &build_panic) {
+            (Some(_), _) => None,
+            (None, Some(message)) => {
+                ErrorView::new(window, &fallback::panic_error_message(message))
+            }
+            (None, None) => ErrorView::new(window, fallback::GL_ERROR_MESSAGE),
+        };
+        let standalone_window = StandaloneWindow::new(window, context.as_ref());
+        let host_view_scale = if cfg!(target_os = "macos")
+            && baseview_state.macos_host_scaling()
+            && baseview_state.scale_policy() == ScalePolicy::Host
+        {
+            baseview_state.scale_factor().map(f64::from)
+        } else {
+            None
+        };
+
+        let mut handler = Self {
+            baseview_state,
+            context,
+            close_receiver,
+            inner,
+            update,
+            panicked: build_panic.is_some(),
+
+            window_scale,
+            cursor_position: Point::new(0.0, 0.0),
+            buttons_held: 0,
+            mouse_captured: false,
+            key_forwarder: KeyForwarder::new(),
+            click_tracker: ClickTracker::default(),
+            resize_grip_drag: None,
+            needs_host_resize,
+            mouse_cursor: MouseCursor::Default,
+            pointer_lock: None,
+            raw_mouse_input: None,
+            touch_input: None,
+            gesture_input: None,
+            visibility_checker: VisibilityChecker::new(window),
+            error_view,
+            watchdog: None,
+            standalone_window,
+            parent_tracker: ParentTracker::new(window),
+            dpi_watcher: DpiWatcher::new(window),
+            host_view_scale,
+            xembed: XEmbedClient::new(window),
+            focus_handshake: FocusHandshake::new(window),
+            diagnostics_counter: DiagnosticsCounter::default(),
+            replay: None,
+            handling_replayed_event: false,
+            zoom_rebuild_failed: false,
+            x11_display,
+            _x11_isolation: x11_isolation,
+            _handle_registration: handle_registration,
+
+            _alive_guard: AliveGuard::new(alive_handlers),
+        };
+        handler.apply_standalone_window_options();
+        if let (Some(standalone_window), Some(position)) = (
+            &mut handler.standalone_window,
+            handler.baseview_state.window_position(),
+        ) {
+            standalone_window.set_position(position);
+        }
+
+        handler
+    }
+
+    /// Ask the host to resize the window to a new logical size. The host queries the editor's size
+    /// during `request_resize()`, so the editor reports the new size for the duration of that call.
+    /// The size is only stored in the state once the host has accepted it.
+    fn apply_requested_size(&mut self, window: &mut Window, width: f64, height: f64) {
+        if self.baseview_state.size.load() == (width, height) {
+            return;
+        }
+
+        self.baseview_state
+            .negotiated_size
+            .store(Some((width, height)));
+        let accepted = self.context.request_resize();
+        self.baseview_state.negotiated_size.store(None);
+        trace_event!("GuiContext::request_resize() for {width}x{height} -> {accepted}");
+        if accepted {
+            self.baseview_state.size.store((width, height));
+            window.resize(Size::new(width, height));
+        }
+    }
+
+    /// Whether the cursor is currently over the resize grip, if it is enabled.
+    fn cursor_over_resize_grip(&self) -> bool {
+        match self.baseview_state.resize_grip() {
+            Some(grip_size) => {
+                let (width, height) = self.baseview_state.logical_size();
+                self.cursor_position.x >= width - grip_size as f64
+                    && self.cursor_position.y >= height - grip_size as f64
+            }
+            None => false,
+        }
+    }
+
+    /// Set the window's mouse cursor to the one requested through
+    /// [`BaseviewState::set_mouse_cursor()`], or to a resize cursor while the resize grip is
+    /// hovered or dragged.
+    fn update_mouse_cursor(&mut self, window: &mut Window) {
+        let mouse_cursor = if self.pointer_lock.is_some() {
+            MouseCursor::Hidden
+        } else if self.resize_grip_drag.is_some() || self.cursor_over_resize_grip() {
+            MouseCursor::SeResize
+        } else {
+            self.baseview_state.mouse_cursor()
+        };
+
+        if mouse_cursor != self.mouse_cursor {
+            window.set_mouse_cursor(mouse_cursor);
+            self.mouse_cursor = mouse_cursor;
+        }
+    }
+
+    /// Lock or unlock the pointer to match [`BaseviewState::pointer_locked()`]. When the lock is
+    /// released the cursor is moved back to where it started.
+    fn update_pointer_lock(&mut self) {
+        let locked = self.baseview_state.pointer_locked();
+        if locked && self.pointer_lock.is_none() {
+            match PointerLock::new() {
+                Some(lock) => {
+                    self.pointer_lock = Some((lock, self.cursor_position, self.cursor_position))
+                }
+                None => self.baseview_state.set_pointer_locked(false),
+            }
+        } else if !locked {
+            if let Some((lock, _, _)) = self.pointer_lock.take() {
+                lock.warp_to_anchor();
+            }
+        }
+    }
+
+    /// Capture the mouse while a button is held or while the handler asked for it using
+    /// [`BaseviewState::capture_mouse()`], and release it otherwise.
+    fn update_mouse_capture(&mut self, window: &Window) {
+        let captured = self.buttons_held > 0 || self.baseview_state.is_mouse_captured();
+        if captured != self.mouse_captured {
+            capture::set_mouse_capture(window, captured);
+            self.mouse_captured = captured;
+        }
+    }
+
+    /// Start or stop collecting raw mouse input to match [`BaseviewState::raw_mouse_input()`].
+    fn update_raw_mouse_input(&mut self, window: &Window) {
+        let enabled = self.baseview_state.raw_mouse_input();
+        if enabled && self.raw_mouse_input.is_none() {
+            match RawMouseInput::new(window) {
+                Some(raw_mouse_input) => self.raw_mouse_input = Some(raw_mouse_input),
+                None => self.baseview_state.set_raw_mouse_input(false),
+            }
+        } else if !enabled {
+            self.raw_mouse_input = None;
+            self.baseview_state.raw_mouse_delta.store(None);
+        }
+    }
+
+    /// Start or stop collecting touch input to match [`BaseviewState::touch_input()`], and pass
+    /// the touches received since the last call on to the state.
+    fn update_touch_input(&mut self, window: &Window) {
+        let enabled = self.baseview_state.touch_input();
+        if enabled && self.touch_input.is_none() {
+            match TouchInput::new(window) {
+                Some(touch_input) => self.touch_input = Some(touch_input),
+                None => self.baseview_state.set_touch_input(false),
+            }
+        } else if !enabled {
+            self.touch_input = None;
+            self.baseview_state.touch_events.lock().clear();
+        }
+
+        if let Some(touch_input) = &mut self.touch_input {
+            let touches = touch_input.take_touches();
+            if !touches.is_empty() {
+                let window_scale = self.window_scale;
+                self.baseview_state
+                    .touch_events
+                    .lock()
+                    .extend(touches.into_iter().map(|(id, phase, x, y)| TouchEvent {
+                        id,
+                        phase,
+                        position: Point::new(x / window_scale, y / window_scale),
+                    }));
+            }
+        }
+    }
+
+    /// Start or stop collecting gestures to match [`BaseviewState::gesture_input()`], and pass the
+    /// gestures received since the last call on to the state.
+    fn update_gesture_input(&mut self, window: &Window) {
+        let enabled = self.baseview_state.gesture_input();
+        if enabled && self.gesture_input.is_none() {
+            match GestureInput::new(window) {
+                Some(gesture_input) => self.gesture_input = Some(gesture_input),
+                None => self.baseview_state.set_gesture_input(false),
+            }
+        } else if !enabled {
+            self.gesture_input = None;
+            self.baseview_state.gesture_events.lock().clear();
+        }
+
+        if let Some(gesture_input) = &mut self.gesture_input {
+            let gestures = gesture_input.take_gestures();
+            if !gestures.is_empty() {
+                let cursor_position = self.cursor_position;
+                self.baseview_state.gesture_events.lock().extend(
+                    gestures
+                        .into_iter()
+                        .map(|gesture| gesture.with_position(cursor_position)),
+                );
+            }
+        }
+    }
+
+    /// Apply the standalone application window's options if they changed since the last frame, and
+    /// keep track of its position so it can be persisted.
+    fn update_standalone_window(&mut self) {
+        if let Some(position) = self
+            .standalone_window
+            .as_ref()
+            .and_then(StandaloneWindow::position)
+        {
+            self.baseview_state.window_position.store(Some(position));
+        }
+
+        if self
+            .baseview_state
+            .standalone_window_changed
+            .swap(false, Ordering::AcqRel)
+        {
+            self.apply_standalone_window_options();
+        }
+    }
+
+    /// Redraw the window and tell the host about its size again after the host moved it to another
+    /// parent window. Otherwise the editor would stay black or frozen until something else causes
+    /// a redraw.
+    fn update_parent(&mut self) {
+        let reparented = self
+            .parent_tracker
+            .as_mut()
+            .map_or(false, ParentTracker::update);
+        if reparented {
+            nih_log!("The host moved the editor to another parent window");
+            self.needs_host_resize = true;
+            self.baseview_state
+                .repaint_requested
+                .store(true, Ordering::Release);
+        }
+    }
+
+    /// Start replaying a recording passed to [`BaseviewState::replay_recording()`] and handle the
+    /// replayed events that are due.
+    fn replay_events(&mut self, window: &mut Window) {
+        if let Some(recording) = self.baseview_state.pending_replay.lock().take() {
+            trace_event!("Replaying {} recorded events", recording.events().len());
+            self.replay = Some(Replay::new(recording));
+            self.baseview_state.replaying.store(true, Ordering::Release);
+        }
+
+        while let Some(event) = self.replay.as_mut().and_then(Replay::next_due) {
+            self.handling_replayed_event = true;
+            self.on_event(window, event);
+            self.handling_replayed_event = false;
+        }
+        if self.replay.as_ref().map_or(false, Replay::is_finished) {
+            self.replay = None;
+            self.baseview_state
+                .replaying
+                .store(false, Ordering::Release);
+        }
+    }
+
+    /// Bring the bridge's own bookkeeping up to date with the window and the plugin's requests
+    /// before every frame.
+    fn update_bridge_state(&mut self, window: &mut Window) {
+        profile_scope!("update_bridge_state");
+
+        self.update_pointer_lock();
+        self.update_raw_mouse_input(window);
+        self.update_touch_input(window);
+        self.update_gesture_input(window);
+        self.update_mouse_capture(window);
+        self.update_mouse_cursor(window);
+        self.update_watchdog();
+        self.update_standalone_window();
+        self.update_parent();
+        self.update_dpi();
+        self.update_focus();
+    }
+
+    /// Give keyboard focus back to the host when the user clicks somewhere else in the host's
+    /// window while the editor has focus.
+    fn update_focus(&mut self) {
+        if !self.baseview_state.focus_handshake() || !self.baseview_state.is_focused() {
+            return;
+        }
+
+        if let Some(focus_handshake) = &mut self.focus_handshake {
+            if focus_handshake.clicked_outside() {
+                focus_handshake.release();
+            }
+        }
+    }
+
+    /// Rebuild the window at the new scale when it was moved to a monitor with a different DPI and
+    /// the host didn't already take care of that. The new window's handler is built with the new
+    /// scaling factor, so it doesn't need to handle this itself.
+    fn update_dpi(&mut self) {
+        let Some(dpi_factor) = self
+            .dpi_watcher
+            .as_mut()
+            .and_then(DpiWatcher::take_scale_factor)
+        else {
+            return;
+        };
+        let zoom = self.baseview_state.zoom.load();
+        if ((dpi_factor * zoom) as f64 - self.window_scale).abs() < 1e-3 {
+            return;
+        }
+
+        // Hosts that report the new factor themselves have already rebuilt the window by now
+        trace_event!("The window's DPI changed to a scaling factor of {dpi_factor}");
+        let factor = match self.baseview_state.scale_policy() {
+            ScalePolicy::Host if self.baseview_state.scale_factor() == Some(dpi_factor) => return,
+            ScalePolicy::Host => Some(dpi_factor),
+            ScalePolicy::System => None,
+            ScalePolicy::Fixed(_) => return,
+        };
+
+        nih_log!("The editor's DPI changed, rebuilding the window at the new scale");
+        editor::rebuild_window(&self.baseview_state, factor);
+    }
+
+    fn apply_standalone_window_options(&mut self) {
+        let Some(standalone_window) = &mut self.standalone_window else {
+            return;
+        };
+
+        standalone_window.set_icon(self.baseview_state.window_icon.lock().as_ref());
+        standalone_window.set_always_on_top(self.baseview_state.always_on_top());
+        if let Some(style) = self.baseview_state.window_style() {
+            standalone_window.set_style(style);
+        }
+    }
+
+    /// Start, stop, or reconfigure the watchdog to match [`BaseviewState::watchdog()`].
+    fn update_watchdog(&mut self) {
+        let config = self.baseview_state.watchdog();
+        if config != self.watchdog.as_ref().map(Watchdog::config) {
+            self.watchdog = config.map(Watchdog::new);
+        }
+    }
+
+    /// Turn a cursor movement into a movement of the virtual cursor while the pointer is locked,
+    /// and move the real cursor back to where the lock started. Returns `None` for the movements
+    /// caused by warping the cursor, which should not reach the user's handler.
+    fn translate_locked_cursor_movement(&mut self, event: MouseEvent) -> Option<MouseEvent> {
+        let (lock, anchor, virtual_position) = match &mut self.pointer_lock {
+            Some(pointer_lock) => pointer_lock,
+            None => return Some(event),
+        };
+
+        match event {
+            MouseEvent::CursorMoved {
+                position,
+                modifiers,
+            } => {
+                let (delta_x, delta_y) = (position.x - anchor.x, position.y - anchor.y);
+                if delta_x == 0.0 && delta_y == 0.0 {
+                    return None;
+                }
+
+                virtual_position.x += delta_x;
+                virtual_position.y += delta_y;
+                lock.warp_to_anchor();
+
+                Some(MouseEvent::CursorMoved {
+                    position: *virtual_position,
+                    modifiers,
+                })
+            }
+            event => Some(event),
+        }
+    }
+
+    /// Handle mouse events for the resize grip in the bottom right corner of the window. Returns
+    /// `true` if the event was consumed by the grip and should not be passed on to the user's
+    /// handler.
+    fn handle_resize_grip(&mut self, event: &MouseEvent) -> bool {
+        if self.baseview_state.resize_grip().is_none() {
+            self.resize_grip_drag = None;
+            return false;
+        }
+
+        match event {
+            MouseEvent::ButtonPressed {
+                button: MouseButton::Left,
+                ..
+            } => {
+                if self.cursor_over_resize_grip() {
+                    self.resize_grip_drag =
+                        Some((self.cursor_position, self.baseview_state.logical_size()));
+                    return true;
+                }
+            }
+            MouseEvent::CursorMoved { .. } => {
+                if let Some((start_position, (start_width, start_height))) = self.resize_grip_drag {
+                    let new_width = start_width + (self.cursor_position.x - start_position.x);
+                    let new_height = start_height + (self.cursor_position.y - start_position.y);
+                    self.baseview_state
+                        .set_logical_size(new_width.max(1.0), new_height.max(1.0));
+
+                    return true;
+                }
+            }
+            MouseEvent::ButtonReleased {
+                button: MouseButton::Left,
+                ..
+            } => {
+                if self.resize_grip_drag.take().is_some() {
+                    return true;
+                }
+            }
+            _ => (),
+        }
+
+        false
+    }
+}
+
+impl<H: WindowHandler> BaseviewWindowHandler<H> {
+    /// Call the user's handler, catching any panics so they don't unwind into the host. After a
+    /// panic the handler is never called again, the window shows the panic's message, and
+    /// `default` is returned instead.
+    fn call_inner<R>(
+        &mut self,
+        window: &mut Window,
+        default: R,
+        f: impl FnOnce(&mut H, &mut Window) -> R,
+    ) -> R {
+        if self.inner.is_none() {
+            return default;
+        }
+
+        profile_scope!("handler");
+        self.guard_callback(window, "The editor's window handler", |handler, window| {
+            handler.inner.as_mut().map(|inner| f(inner, window))
+        })
+        .flatten()
+        .unwrap_or(default)
+    }
+
+    /// Run code that calls into the plugin, like the handler or the GUI tasks and timers the
+    /// plugin queued, under the watchdog and with the same panic handling as
+    /// [`call_inner()`][Self::call_inner()]. `callback` describes the code in log messages.
+    /// Returns `None` if `f` panicked or if anything panicked before, in which case `f` isn't
+    /// called at all.
+    fn guard_callback<R>(
+        &mut self,
+        window: &mut Window,
+        callback: &'static str,
+        f: impl FnOnce(&mut Self, &mut Window) -> R,
+    ) -> Option<R> {
+        if self.panicked {
+            return None;
+        }
+
+        if let Some(watchdog) = &self.watchdog {
+            watchdog.begin(callback);
+        }
+        let result = panic::catch_unwind(AssertUnwindSafe(|| f(self, window)));
+        if let Some(watchdog) = &self.watchdog {
+            if let Some(elapsed) = watchdog.end() {
+                nih_log!("{callback} returned after {} ms", elapsed.as_millis());
+                if watchdog.config().close_window {
+                    self.baseview_state.request_close();
+                }
+            }
+        }
+
+        match result {
+            Ok(result) => Some(result),
+            Err(payload) => {
+                let message = panic_message(&*payload);
+                nih_log!("{callback} panicked and the editor will no longer be called: {message}");
+
+                self.panicked = true;
+                self.error_view = ErrorView::new(window, &fallback::panic_error_message(&message));
+                *self.baseview_state.panic_message.lock() = Some(message);
+
+                None
+            }
+        }
+    }
+
+    /// Pass a key event to the handler, the host, or both depending on the keyboard policy. Keys
+    /// the editor doesn't use should still reach the host, so that things like Space for starting
+    /// playback keep working while the editor has keyboard focus. Baseview already passes ignored
+    /// key events on to the host on macOS, so there we only need to return the right status.
+    fn handle_key_event(&mut self, window: &mut Window, key_event: KeyboardEvent) -> EventStatus {
+        if let Some(keyboard_midi) = self.baseview_state.keyboard_midi() {
+            if keyboard_midi.handle_key_event(&key_event) {
+                return EventStatus::Captured;
+            }
+        }
+
+        // The handler may change the policy, so the lock can't be held while calling it
+        let policy = self.baseview_state.keyboard_policy();
+        match policy {
+            KeyboardPolicy::CaptureAll => {
+                self.call_inner(window, EventStatus::Ignored, |inner, window| {
+                    inner.on_event(window, Event::Keyboard(key_event))
+                });
+                EventStatus::Captured
+            }
+            KeyboardPolicy::PassthroughUnhandled => {
+                let status = self.call_inner(window, EventStatus::Ignored, |inner, window| {
+                    inner.on_event(window, Event::Keyboard(key_event.clone()))
+                });
+                if let EventStatus::Ignored = status {
+                    self.key_forwarder.forward(window, &key_event);
+                }
+
+                status
+            }
+            KeyboardPolicy::PassthroughList(codes) => {
+                if codes.contains(&key_event.code) {
+                    self.key_forwarder.forward(window, &key_event);
+                    EventStatus::Ignored
+                } else {
+                    self.call_inner(window, EventStatus::Ignored, |inner, window| {
+                        inner.on_event(window, Event::Keyboard(key_event))
+                    });
+                    EventStatus::Captured
+                }
+            }
+        }
+    }
+}
+
+impl<H: WindowHandler> WindowHandler for BaseviewWindowHandler<H> {
+    fn on_frame(&mut self, window: &mut Window) {
+        profile_scope!("on_frame");
+        let frame_start = Instant::now();
+        if let Err(TryRecvError::Disconnected) = self.close_receiver.try_recv() {
+            trace_event!("Closing the window because its handle was dropped");
+            window.close();
+            return;
+        }
+        if self
+            .baseview_state
+            .close_requested
+            .swap(false, Ordering::AcqRel)
+        {
+            trace_event!("Closing the window at the plugin's request");
+            self.baseview_state.editor_closed();
+            window.close();
+            return;
+        }
+
+        if self.needs_host_resize {
+            self.needs_host_resize = false;
+            let accepted = self.context.request_resize();
+            trace_event!("GuiContext::request_resize() -> {accepted}");
+        }
+        if let Some((width, height)) = self.baseview_state.requested_size.take() {
+            self.apply_requested_size(window, width, height);
+        }
+        // Rebuilding the window applies the new zoom factor and asks the host to resize the
+        // window. This isn't possible on X11, where the zoom factor is applied when the editor is
+        // opened again.
+        if !self.zoom_rebuild_failed
+            && self.baseview_state.requested_zoom.load().is_some()
+            && !editor::rebuild_window(&self.baseview_state, None)
+        {
+            nih_log!("The window can't be rebuilt, the new zoom factor is used after reopening it");
+            self.zoom_rebuild_failed = true;
+        }
+        self.update_bridge_state(window);
+        #[cfg(feature = "testing")]
+        while let Some(event) = self.baseview_state.injected_events.pop() {
+            trace_event!("Injecting {event:?}");
+            self.on_event(window, event);
+        }
+        self.replay_events(window);
+        self.baseview_state.flush_recording();
+        // These all run the plugin's own closures, so a panic in them is treated like a panic in
+        // the handler
+        self.guard_callback(window, "A frame hook", |handler, _| {
+            handler.baseview_state.run_frame_hooks()
+        });
+        self.guard_callback(window, "A GUI task", |handler, _| {
+            handler.baseview_state.gui_tasks.run_queued()
+        });
+        self.guard_callback(window, "A timer callback", |handler, _| {
+            handler.baseview_state.timers.run_due()
+        });
+        self.guard_callback(window, "A texture upload callback", |handler, window| {
+            if let Some(context) = window.gl_context() {
+                handler
+                    .baseview_state
+                    .texture_uploads
+                    .upload_queued(context);
+            }
+        });
+
+        let visible = self
+            .visibility_checker
+            .as_mut()
+            .map_or(true, |checker| checker.is_visible());
+        self.baseview_state
+            .visible
+            .store(visible, Ordering::Release);
+        let repaint_requested = self
+            .baseview_state
+            .repaint_requested
+            .swap(false, Ordering::AcqRel);
+        if !visible
+            && self.baseview_state.pause_when_hidden()
+            && !self.baseview_state.host_quirks().unreliable_visibility
+            && !repaint_requested
+        {
+            self.baseview_state.frame_timer.lock().skip();
+            return;
+        }
+
+        if let Some(error_view) = &mut self.error_view {
+            error_view.update();
+        }
+        let diagnostics = self.diagnostics_counter.count_frame(
+            window.gl_context(),
+            self.baseview_state.frame_stats(),
+            self.baseview_state.size.load(),
+            self.window_scale,
+        );
+        if debug_overlay::enabled() {
+            debug_overlay::queue(Some(diagnostics.to_string()));
+        }
+        *self.baseview_state.diagnostics.lock() = diagnostics;
+
+        #[cfg(feature = "testing")]
+        snapshot::queue_capture(
+            self.baseview_state
+                .frame_capture_requested
+                .load(Ordering::Acquire),
+        );
+
+        let update = self.update.clone();
+        let context = self.context.clone();
+        self.call_inner(window, (), |inner, window| {
+            // This also runs under the watchdog and the panic handler, and it's skipped along
+            // with the handler when there is none
+            if let Some(update) = update {
+                update(&ParamSetter::new(context.as_ref()));
+            }
+            inner.on_frame(window)
+        });
+        // Custom handlers never take the queued text, so it shouldn't linger until the next frame
+        debug_overlay::queue(None);
+        #[cfg(feature = "testing")]
+        {
+            snapshot::queue_capture(false);
+            if let Some(frame) = snapshot::take_captured() {
+                *self.baseview_state.captured_frame.lock() = Some(frame);
+                self.baseview_state
+                    .frame_capture_requested
+                    .store(false, Ordering::Release);
+            }
+        }
+        self.baseview_state.frame_timer.lock().record(frame_start);
+        profile::finish_frame();
+    }
+
+    fn on_event(&mut self, window: &mut Window, mut event: Event) -> EventStatus {
+        profile_scope!("on_event");
+        trace_event!("on_event: {event:?}");
+        self.diagnostics_counter.count_event();
+        if !self.handling_replayed_event {
+            if self.replay.is_some() && matches!(event, Event::Mouse(_) | Event::Keyboard(_)) {
+                return EventStatus::Ignored;
+            }
+            self.baseview_state.record_event(&event);
+        }
+        self.update_touch_input(window);
+        self.update_gesture_input(window);
+
+        if let (Some(scale), Event::Mouse(MouseEvent::CursorMoved { position, .. })) =
+            (self.host_view_scale, &mut event)
+        {
+            position.x /= scale;
+            position.y /= scale;
+        }
+        if self.baseview_state.normalize_scrolling() {
+            if let Event::Mouse(MouseEvent::WheelScrolled { delta, modifiers }) = &mut event {
+                *delta = scroll::normalize(*delta, *modifiers, self.window_scale);
+            }
+        }
+
+        track_input_state(&self.baseview_state, &event);
+
+        match &event {
+            Event::Window(WindowEvent::Resized(window_info)) => {
+                self.window_scale = window_info.scale();
+                track_window_size(&self.baseview_state, window_info);
+            }
+            // Nothing releases the lock if the button release happens outside of the window
+            Event::Window(WindowEvent::Unfocused) => {
+                self.baseview_state.set_pointer_locked(false);
+                self.update_pointer_lock();
+
+                // The button releases may never arrive when focus moves elsewhere mid-drag
+                self.buttons_held = 0;
+                self.update_mouse_capture(window);
+            }
+            Event::Window(WindowEvent::WillClose) => {
+                self.baseview_state.focused.store(false, Ordering::Release);
+                self.baseview_state.visible.store(false, Ordering::Release);
+                self.baseview_state.set_pointer_locked(false);
+                self.update_pointer_lock();
+
+                if let Some(keyboard_midi) = self.baseview_state.keyboard_midi() {
+                    keyboard_midi.release_all();
+                }
+            }
+            Event::Mouse(mouse_event) => {
+                if let MouseEvent::ButtonReleased { .. } = mouse_event {
+                    self.baseview_state.set_pointer_locked(false);
+                }
+                self.update_pointer_lock();
+                self.update_raw_mouse_input(window);
+                if let (Some(raw_mouse_input), MouseEvent::CursorMoved { .. }) =
+                    (&mut self.raw_mouse_input, mouse_event)
+                {
+                    self.baseview_state
+                        .raw_mouse_delta
+                        .store(Some(raw_mouse_input.take_delta()));
+                }
+
+                match mouse_event {
+                    MouseEvent::ButtonPressed { .. } => self.buttons_held += 1,
+                    MouseEvent::ButtonReleased { .. } => {
+                        self.buttons_held = self.buttons_held.saturating_sub(1)
+                    }
+                    _ => (),
+                }
+                self.update_mouse_capture(window);
+
+                if let MouseEvent::ButtonPressed { button, .. } = mouse_event {
+                    let click_count = self.click_tracker.press(
+                        *button,
+                        self.cursor_position,
+                        self.baseview_state.double_click_threshold(),
+                    );
+                    self.baseview_state.click_count.store(click_count);
+                    self.baseview_state
+                        .button_presses
+                        .fetch_add(1, Ordering::AcqRel);
+
+                    // Hosts expect the editor to take keyboard focus when it's clicked, and XEmbed
+                    // embedders only forward keyboard input after the client asked for it
+                    if !self.baseview_state.is_focused() {
+                        if self.baseview_state.focus_handshake() {
+                            if let Some(focus_handshake) = &mut self.focus_handshake {
+                                focus_handshake.claim();
+                            }
+                        }
+                        if let Some(xembed) = &mut self.xembed {
+                            xembed.request_focus();
+                        }
+                    }
+                }
+
+                // The real cursor stays where the lock started, so only the virtual position moves
+                if self.pointer_lock.is_none() {
+                    if let MouseEvent::CursorMoved { position, .. } = mouse_event {
+                        self.cursor_position = *position;
+                    }
+                }
+
+                let handled_by_grip = self.handle_resize_grip(mouse_event);
+                self.update_mouse_cursor(window);
+                if handled_by_grip {
+                    return EventStatus::Captured;
+                }
+            }
+            _ => (),
+        }
+
+        // Modal dialogs block input to the editor, but not to the host. Clicking the editor brings
+        // the dialog back to the front instead.
+        if self.baseview_state.has_modal_dialog() {
+            match &event {
+                Event::Mouse(MouseEvent::ButtonPressed { .. }) => {
+                    self.baseview_state
+                        .modal_focus_requested
+                        .store(true, Ordering::Release);
+                    return EventStatus::Captured;
+                }
+                Event::Mouse(_) => return EventStatus::Captured,
+                Event::Keyboard(_) => return EventStatus::Ignored,
+                _ => (),
+            }
+        }
+
+        if let Event::Mouse(mouse_event) = event {
+            match self.translate_locked_cursor_movement(mouse_event) {
+                Some(mouse_event) => event = Event::Mouse(mouse_event),
+                None => return EventStatus::Captured,
+            }
+        }
+
+        match event {
+            Event::Keyboard(key_event) => {
+                let releases_focus = self.baseview_state.focus_handshake()
+                    && key_event.state == KeyState::Down
+                    && key_event.code == Code::Escape;
+                let status = self.handle_key_event(window, key_event);
+                if releases_focus && matches!(status, EventStatus::Ignored) {
+                    if let Some(focus_handshake) = &mut self.focus_handshake {
+                        focus_handshake.release();
+                    }
+                }
+
+                status
+            }
+            event => self.call_inner(window, EventStatus::Ignored, |inner, window| {
+                inner.on_event(window, event)
+            }),
+        }
+    }
+}
+
+/// Keep track of the held modifiers and the window's focus so they can be queried at any time.
+/// This doesn't need the window, so the testing feature's `EventHarness` does the same.
+pub(crate) fn track_input_state(baseview_state: &BaseviewState, event: &Event) {
+    match event {
+        Event::Keyboard(key_event) => {
+            baseview_state
+                .modifiers
+                .store(keyboard::modifiers_after(key_event));
+        }
+        Event::Mouse(
+            MouseEvent::CursorMoved { modifiers, .. }
+            | MouseEvent::ButtonPressed { modifiers, .. }
+            | MouseEvent::ButtonReleased { modifiers, .. }
+            | MouseEvent::WheelScrolled { modifiers, .. },
+        ) => baseview_state.modifiers.store(*modifiers),
+        Event::Window(WindowEvent::Focused) => {
+            baseview_state.focused.store(true, Ordering::Release)
+        }
+        // Release events for keys held while the window loses focus never arrive
+        Event::Window(WindowEvent::Unfocused) => {
+            baseview_state.focused.store(false, Ordering::Release);
+            baseview_state.modifiers.store(Modifiers::empty());
+        }
+        _ => (),
+    }
+}
+
+/// Whether the host or the user resized the window, the persisted size and the size reported
+/// through `Editor::size()` should follow it.
+pub(crate) fn track_window_size(baseview_state: &BaseviewState, window_info: &WindowInfo) {
+    let logical_size = window_info.logical_size();
+    let new_size = (logical_size.width, logical_size.height);
+    baseview_state.size.store(new_size);
+
+    // Hosts that allow freely resizing the window don't know about our size limits, so we'll ask
+    // for a size within those limits instead
+    let clamped_size = baseview_state.clamp_size(new_size);
+    if clamped_size != new_size {
+        baseview_state.requested_size.store(Some(clamped_size));
+    }
+}
+
+/// Get the message from a panic's payload. This is a `&str` or a `String` for panics created with
+/// `panic!()`.
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        String::from("unknown panic payload")
+    }
+}
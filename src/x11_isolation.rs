@@ -0,0 +1,204 @@
+//! Keeping the editor's X11 connections out of the host's Xlib error handling. Xlib only has a
+//! single, process wide error handler. Many hosts install one that aborts on any error, and other
+//! plugins sometimes install their own without restoring the previous one, so a harmless error on
+//! one of the editor's connections, like querying a window the host just destroyed, can take down
+//! the whole host. Baseview already runs the editor's event loop on its own thread with its own
+//! connection, and the bridge's helpers open separate connections as well. When isolation is
+//! enabled using
+//! [`BaseviewState::set_x11_isolation()`][crate::BaseviewState::set_x11_isolation()], errors on
+//! those connections are logged and ignored while the editor's thread is running, and errors on
+//! any other connection are passed on to the handler that was installed before. Windows and macOS
+//! don't have an equivalent.
+
+/// Installs the isolating error handler for as long as this is alive. Every open window with
+/// isolation enabled holds one of these, so the handler is only swapped when the first window
+/// opens and the last one closes.
+pub(crate) struct ErrorIsolation {
+    _private: (),
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod imp {
+    use parking_lot::Mutex;
+    use std::os::raw::c_int;
+    use x11::xlib;
+
+    pub type ErrorHandler =
+        unsafe extern "C" fn(*mut xlib::Display, *mut xlib::XErrorEvent) -> c_int;
+
+    /// The state shared between the editors in this process.
+    pub struct Isolation {
+        /// The connections opened by the bridge and baseview, stored as addresses.
+        pub displays: Vec<usize>,
+        /// The number of live [`ErrorIsolation`][super::ErrorIsolation] guards. The handler is
+        /// installed by the first one and removed by the last one.
+        pub guards: usize,
+        /// The error handler that was installed before ours.
+        pub previous_handler: Option<ErrorHandler>,
+    }
+
+    lazy_static::lazy_static! {
+        pub static ref ISOLATION: Mutex<Isolation> = Mutex::new(Isolation {
+            displays: Vec::new(),
+            guards: 0,
+            previous_handler: None,
+        });
+    }
+
+    /// Whether `handler` is [`error_handler()`]. A function isn't guaranteed to have a single
+    /// address, which is what the `unpredictable_function_pointer_comparisons` lint warns about,
+    /// but this one isn't generic or inlined so comparing the addresses is fine.
+    pub fn is_ours(handler: Option<ErrorHandler>) -> bool {
+        handler.map_or(false, |handler| handler as usize == error_handler as usize)
+    }
+
+    pub unsafe extern "C" fn error_handler(
+        display: *mut xlib::Display,
+        event: *mut xlib::XErrorEvent,
+    ) -> c_int {
+        let previous_handler = {
+            let isolation = ISOLATION.lock();
+            if isolation.displays.contains(&(display as usize)) {
+                nih_plug::nih_log!(
+                    "Ignoring X11 error {} for request {}.{} on resource {:#x}",
+                    (*event).error_code,
+                    (*event).request_code,
+                    (*event).minor_code,
+                    (*event).resourceid
+                );
+                return 0;
+            }
+
+            isolation.previous_handler
+        };
+
+        match previous_handler {
+            Some(handler) => handler(display, event),
+            None => 0,
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+impl ErrorIsolation {
+    /// Install the isolating error handler, unless another editor already did.
+    pub fn install() -> Self {
+        let mut isolation = imp::ISOLATION.lock();
+        if isolation.guards == 0 {
+            let previous_handler = unsafe { x11::xlib::XSetErrorHandler(Some(imp::error_handler)) };
+            // Another editor's guard may have been dropped while a plugin replaced the handler
+            if !imp::is_ours(previous_handler) {
+                isolation.previous_handler = previous_handler;
+            }
+        }
+        isolation.guards += 1;
+
+        Self { _private: () }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+impl Drop for ErrorIsolation {
+    fn drop(&mut self) {
+        let mut isolation = imp::ISOLATION.lock();
+        isolation.guards -= 1;
+        if isolation.guards > 0 {
+            return;
+        }
+
+        // If someone else replaced our handler in the meantime, theirs is left in place
+        unsafe {
+            let current_handler = x11::xlib::XSetErrorHandler(isolation.previous_handler);
+            if !imp::is_ours(current_handler) {
+                x11::xlib::XSetErrorHandler(current_handler);
+            }
+        }
+    }
+}
+
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+impl ErrorIsolation {
+    pub fn install() -> Self {
+        Self { _private: () }
+    }
+}
+
+/// Open a new connection to the X server for one of the bridge's helpers. Errors on this
+/// connection are isolated from the host's error handler while an [`ErrorIsolation`] guard is
+/// alive. The connection must be closed with [`close_display()`].
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub(crate) unsafe fn open_display() -> *mut x11::xlib::Display {
+    let display = x11::xlib::XOpenDisplay(std::ptr::null());
+    if !display.is_null() {
+        register_display(display);
+    }
+
+    display
+}
+
+/// Close a connection opened with [`open_display()`].
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub(crate) unsafe fn close_display(display: *mut x11::xlib::Display) {
+    // Errors for the last requests only arrive once the connection is flushed
+    x11::xlib::XSync(display, x11::xlib::False);
+    unregister_display(display);
+    x11::xlib::XCloseDisplay(display);
+}
+
+/// Treat errors on a connection the bridge didn't open itself, like baseview's, as the editor's
+/// own. This needs to be undone with [`unregister_display()`] before the connection is closed.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub(crate) fn register_display(display: *mut x11::xlib::Display) {
+    imp::ISOLATION.lock().displays.push(display as usize);
+}
+
+/// Undo [`register_display()`].
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub(crate) fn unregister_display(display: *mut x11::xlib::Display) {
+    let mut isolation = imp::ISOLATION.lock();
+    if let Some(idx) = isolation
+        .displays
+        .iter()
+        .position(|&address| address == display as usize)
+    {
+        isolation.displays.swap_remove(idx);
+    }
+}
+
+/// Registers the connection baseview uses for a window with [`register_display()`] for as long
+/// as this is alive.
+pub(crate) struct DisplayRegistration {
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    display: *mut x11::xlib::Display,
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+impl DisplayRegistration {
+    pub fn new(window: &baseview::Window) -> Option<Self> {
+        use raw_window_handle::{HasRawDisplayHandle, RawDisplayHandle};
+
+        let display = match window.raw_display_handle() {
+            RawDisplayHandle::Xlib(handle) if !handle.display.is_null() => {
+                handle.display as *mut x11::xlib::Display
+            }
+            _ => return None,
+        };
+        register_display(display);
+
+        Some(Self { display })
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+impl Drop for DisplayRegistration {
+    fn drop(&mut self) {
+        unregister_display(self.display);
+    }
+}
+
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+impl DisplayRegistration {
+    pub fn new(_window: &baseview::Window) -> Option<Self> {
+        None
+    }
+}
@@ -0,0 +1,129 @@
+//! The client side of the XEmbed protocol for hosts that embed the editor in an XEmbed socket, like
+//! GTK and Qt based hosts. Embedders that follow the protocol only map the editor's window once it
+//! advertises itself as an XEmbed client, and they only forward keyboard input to it after it has
+//! asked for focus. Baseview receives the embedder's messages on its own connection and turns the
+//! focus changes into regular focus events, so the bridge only needs to take care of the client's
+//! half of the handshake. Windows and macOS don't have an equivalent.
+
+use baseview::Window;
+
+/// Implements the XEmbed client protocol for the editor's window.
+pub(crate) struct XEmbedClient {
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    display: *mut x11::xlib::Display,
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    window_id: x11::xlib::Window,
+    /// The `_XEMBED` message type.
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    xembed_atom: x11::xlib::Atom,
+}
+
+/// The version of the XEmbed protocol we implement.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const XEMBED_VERSION: std::os::raw::c_long = 0;
+/// The `_XEMBED_INFO` flag asking the embedder to map the window.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const XEMBED_MAPPED: std::os::raw::c_long = 1 << 0;
+/// The `_XEMBED` message asking the embedder to move keyboard focus to the client.
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const XEMBED_REQUEST_FOCUS: std::os::raw::c_long = 3;
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+impl XEmbedClient {
+    /// Advertise the window as an XEmbed client. Embedders that don't implement XEmbed ignore the
+    /// property, so this is done for every embedded window. Returns `None` for top level windows.
+    pub fn new(window: &Window) -> Option<Self> {
+        use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+        use std::os::raw::{c_long, c_ulong};
+        use x11::xlib;
+
+        let window_id = match window.raw_window_handle() {
+            RawWindowHandle::Xcb(handle) => handle.window as c_ulong,
+            RawWindowHandle::Xlib(handle) => handle.window,
+            _ => return None,
+        };
+
+        unsafe {
+            let display = crate::x11_isolation::open_display();
+            if display.is_null() {
+                return None;
+            }
+
+            let parent = crate::reparent::query_parent(display, window_id);
+            if parent == 0 || parent == xlib::XDefaultRootWindow(display) {
+                crate::x11_isolation::close_display(display);
+                return None;
+            }
+
+            let info_atom = intern_atom(display, "_XEMBED_INFO");
+            let info: [c_long; 2] = [XEMBED_VERSION, XEMBED_MAPPED];
+            xlib::XChangeProperty(
+                display,
+                window_id,
+                info_atom,
+                info_atom,
+                32,
+                xlib::PropModeReplace,
+                info.as_ptr() as *const u8,
+                info.len() as i32,
+            );
+            xlib::XFlush(display);
+
+            Some(Self {
+                display,
+                window_id,
+                xembed_atom: intern_atom(display, "_XEMBED"),
+            })
+        }
+    }
+
+    /// Ask the embedder for keyboard focus. This should be done when the user clicks on the
+    /// editor, since XEmbed embedders keep the X11 input focus to themselves and only forward key
+    /// events to the client they consider focused.
+    pub fn request_focus(&mut self) {
+        use x11::xlib;
+
+        unsafe {
+            let parent = crate::reparent::query_parent(self.display, self.window_id);
+            if parent == 0 {
+                return;
+            }
+
+            let mut event: xlib::XClientMessageEvent = std::mem::zeroed();
+            event.type_ = xlib::ClientMessage;
+            event.window = parent;
+            event.message_type = self.xembed_atom;
+            event.format = 32;
+            event
+                .data
+                .set_long(0, xlib::CurrentTime as std::os::raw::c_long);
+            event.data.set_long(1, XEMBED_REQUEST_FOCUS);
+
+            let mut event = xlib::XEvent::from(event);
+            xlib::XSendEvent(self.display, parent, xlib::False, 0, &mut event);
+            xlib::XFlush(self.display);
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+impl Drop for XEmbedClient {
+    fn drop(&mut self) {
+        unsafe { crate::x11_isolation::close_display(self.display) };
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+unsafe fn intern_atom(display: *mut x11::xlib::Display, name: &str) -> x11::xlib::Atom {
+    let name = std::ffi::CString::new(name).unwrap();
+    x11::xlib::XInternAtom(display, name.as_ptr(), x11::xlib::False)
+}
+
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+impl XEmbedClient {
+    pub fn new(_window: &Window) -> Option<Self> {
+        None
+    }
+
+    pub fn request_focus(&mut self) {}
+}